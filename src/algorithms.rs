@@ -0,0 +1,27 @@
+//! 算法合集，对照 README 中的 TheAlgorithms/Rust
+//! - graph：通用的邻接表 Graph<W>，以及 Dijkstra、Bellman-Ford、Kruskal(并查集)、Tarjan 强连通分量
+//! - dp：最长公共子序列、0/1 背包、编辑距离
+//! - strings：KMP 和 Aho–Corasick 多模式匹配
+//!
+//! 每个子模块都有自己的练习函数，用随机数据和朴素实现(Floyd–Warshall、暴力枚举、逐位置比较)的结果对照
+
+pub mod dp;
+pub mod graph;
+pub mod strings;
+
+use crate::hasher_bench::XorShift;
+
+pub fn algorithms_practice() {
+    graph::graph_practice();
+    dp::dp_practice();
+    strings::strings_practice();
+    println!("algorithms: all checks passed");
+}
+
+// 随机测试用：长度不超过 max_len，字符取自前 alphabet 个小写字母，字母表小才容易出现重复和匹配
+fn random_bytes(rng: &mut XorShift, max_len: usize, alphabet: u8) -> Vec<u8> {
+    let len = rng.next_u64() as usize % (max_len + 1);
+    (0..len)
+        .map(|_| b'a' + (rng.next_u64() % alphabet as u64) as u8)
+        .collect()
+}
@@ -0,0 +1,312 @@
+//! 动态规划：最长公共子序列、0/1 背包、编辑距离
+//! 都是二维表格 dp，只需要最优值时滚动数组只保留一行，需要回溯出方案时保留整张表
+
+use super::random_bytes;
+use crate::hasher_bench::XorShift;
+
+/* ---------------------------- 最长公共子序列 ---------------------------- */
+
+pub fn lcs_len<T: PartialEq>(a: &[T], b: &[T]) -> usize {
+    // row[j] 是 a[..i] 和 b[..j] 的 LCS 长度
+    let mut row = vec![0; b.len() + 1];
+    for x in a {
+        let mut diag = 0;
+        for (j, y) in b.iter().enumerate() {
+            let up = row[j + 1];
+            row[j + 1] = if x == y { diag + 1 } else { up.max(row[j]) };
+            diag = up;
+        }
+    }
+    row[b.len()]
+}
+
+// 返回一个最长公共子序列；有多个时由回溯的方向决定(两边相等时跳过 a 的元素)
+pub fn lcs<T: PartialEq + Clone>(a: &[T], b: &[T]) -> Vec<T> {
+    let (n, m) = (a.len(), b.len());
+    // table[i][j] 是 a[i..] 和 b[j..] 的 LCS 长度，从后往前填，回溯时就能从前往后走
+    let mut table = vec![vec![0; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            table[i][j] = if a[i] == b[j] {
+                table[i + 1][j + 1] + 1
+            } else {
+                table[i + 1][j].max(table[i][j + 1])
+            };
+        }
+    }
+    let mut out = Vec::with_capacity(table[0][0]);
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if a[i] == b[j] {
+            out.push(a[i].clone());
+            i += 1;
+            j += 1;
+        } else if table[i][j + 1] > table[i + 1][j] {
+            j += 1;
+        } else {
+            i += 1;
+        }
+    }
+    out
+}
+
+/* ---------------------------- 0/1 背包 ---------------------------- */
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Item {
+    pub weight: usize,
+    pub value: u64,
+}
+
+// 返回最大价值和选中的物品下标(升序)
+pub fn knapsack(items: &[Item], capacity: usize) -> (u64, Vec<usize>) {
+    // best[i][c] 是只用前 i 个物品、容量 c 时的最大价值
+    let mut best = vec![vec![0u64; capacity + 1]; items.len() + 1];
+    for (i, item) in items.iter().enumerate() {
+        for c in 0..=capacity {
+            let skip = best[i][c];
+            best[i + 1][c] = match c.checked_sub(item.weight) {
+                Some(rest) => skip.max(best[i][rest] + item.value),
+                None => skip,
+            };
+        }
+    }
+    let mut chosen = Vec::new();
+    let mut c = capacity;
+    for i in (0..items.len()).rev() {
+        if best[i + 1][c] != best[i][c] {
+            chosen.push(i);
+            c -= items[i].weight;
+        }
+    }
+    chosen.reverse();
+    (best[items.len()][capacity], chosen)
+}
+
+/* ---------------------------- 编辑距离 ---------------------------- */
+
+// Levenshtein 距离：插入、删除、替换各算一步
+pub fn edit_distance<T: PartialEq>(a: &[T], b: &[T]) -> usize {
+    // 让 b 是较短的一个，滚动数组的长度是 min(len) + 1
+    if a.len() < b.len() {
+        return edit_distance(b, a);
+    }
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, x) in a.iter().enumerate() {
+        let mut diag = row[0];
+        row[0] = i + 1;
+        for (j, y) in b.iter().enumerate() {
+            let up = row[j + 1];
+            row[j + 1] = if x == y {
+                diag
+            } else {
+                1 + diag.min(up).min(row[j])
+            };
+            diag = up;
+        }
+    }
+    row[b.len()]
+}
+
+pub fn edit_distance_str(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    edit_distance(&a, &b)
+}
+
+/* ---------------------------- 练习 ---------------------------- */
+
+fn is_subsequence<T: PartialEq>(sub: &[T], of: &[T]) -> bool {
+    let mut it = of.iter();
+    sub.iter().all(|x| it.any(|y| y == x))
+}
+
+// 参照实现：直接递归
+fn edit_distance_naive(a: &[u8], b: &[u8]) -> usize {
+    match (a.split_first(), b.split_first()) {
+        (None, _) => b.len(),
+        (_, None) => a.len(),
+        (Some((x, ra)), Some((y, rb))) if x == y => edit_distance_naive(ra, rb),
+        (Some((_, ra)), Some((_, rb))) => {
+            1 + edit_distance_naive(ra, rb)
+                .min(edit_distance_naive(ra, b))
+                .min(edit_distance_naive(a, rb))
+        }
+    }
+}
+
+pub fn dp_practice() {
+    let mut rng = XorShift::new(4901);
+    for _ in 0..500 {
+        let a = random_bytes(&mut rng, 7, 3);
+        let b = random_bytes(&mut rng, 7, 3);
+        let common = lcs(&a, &b);
+        assert_eq!(common.len(), lcs_len(&a, &b));
+        assert!(is_subsequence(&common, &a) && is_subsequence(&common, &b));
+        // 参照：枚举 a 的所有子序列，最长的那个同时是 b 的子序列
+        let brute = (0u32..1 << a.len())
+            .filter_map(|mask| {
+                let sub: Vec<u8> = (0..a.len())
+                    .filter(|i| mask >> i & 1 == 1)
+                    .map(|i| a[i])
+                    .collect();
+                is_subsequence(&sub, &b).then_some(sub.len())
+            })
+            .max()
+            .unwrap_or(0);
+        assert_eq!(common.len(), brute);
+
+        let d = edit_distance(&a, &b);
+        assert_eq!(d, edit_distance_naive(&a, &b));
+        assert_eq!(d, edit_distance(&b, &a));
+        assert!(d >= a.len().abs_diff(b.len()) && d <= a.len().max(b.len()));
+        // 编辑距离不超过删掉非公共部分再插入的步数
+        assert!(d <= a.len() + b.len() - 2 * common.len());
+    }
+    assert_eq!(lcs(b"ABCBDAB", b"BDCABA"), b"BDAB");
+    assert_eq!(lcs_len(b"AGGTAB", b"GXTXAYB"), 4);
+    assert_eq!(edit_distance_str("kitten", "sitting"), 3);
+    assert_eq!(edit_distance_str("", "abc"), 3);
+    assert_eq!(edit_distance_str("你好世界", "你好"), 2);
+
+    for _ in 0..300 {
+        let n = rng.next_u64() as usize % 10;
+        let items: Vec<Item> = (0..n)
+            .map(|_| Item {
+                weight: 1 + rng.next_u64() as usize % 10,
+                value: rng.next_u64() % 50,
+            })
+            .collect();
+        let capacity = rng.next_u64() as usize % 30;
+        let (best, chosen) = knapsack(&items, capacity);
+        assert!(chosen.iter().map(|&i| items[i].weight).sum::<usize>() <= capacity);
+        assert_eq!(chosen.iter().map(|&i| items[i].value).sum::<u64>(), best);
+        // 参照：枚举所有子集
+        let brute = (0u32..1 << n)
+            .filter_map(|mask| {
+                let picked = (0..n).filter(|i| mask >> i & 1 == 1);
+                let weight: usize = picked.clone().map(|i| items[i].weight).sum();
+                (weight <= capacity).then(|| picked.map(|i| items[i].value).sum::<u64>())
+            })
+            .max()
+            .unwrap_or(0);
+        assert_eq!(best, brute);
+    }
+    let items = [
+        Item {
+            weight: 1,
+            value: 1,
+        },
+        Item {
+            weight: 3,
+            value: 4,
+        },
+        Item {
+            weight: 4,
+            value: 5,
+        },
+        Item {
+            weight: 5,
+            value: 7,
+        },
+    ];
+    assert_eq!(knapsack(&items, 7), (9, vec![1, 2]));
+    assert_eq!(knapsack(&items, 0), (0, vec![]));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lcs_examples() {
+        assert_eq!(lcs(b"ABCBDAB", b"BDCABA"), b"BDAB");
+        assert_eq!(lcs_len(b"AGGTAB", b"GXTXAYB"), 4);
+        assert_eq!(lcs::<u8>(b"", b"abc"), b"");
+        assert_eq!(lcs_len(b"abc", b"def"), 0);
+        assert_eq!(lcs(&[1, 2, 3], &[1, 2, 3]), [1, 2, 3]);
+    }
+
+    #[test]
+    fn lcs_is_a_longest_common_subsequence() {
+        let mut rng = XorShift::new(1);
+        for _ in 0..300 {
+            let a = random_bytes(&mut rng, 8, 3);
+            let b = random_bytes(&mut rng, 8, 3);
+            let common = lcs(&a, &b);
+            assert!(is_subsequence(&common, &a) && is_subsequence(&common, &b));
+            assert_eq!(common.len(), lcs_len(&a, &b));
+            assert_eq!(lcs_len(&a, &b), lcs_len(&b, &a));
+        }
+    }
+
+    #[test]
+    fn knapsack_examples() {
+        let items = [
+            Item {
+                weight: 1,
+                value: 1,
+            },
+            Item {
+                weight: 3,
+                value: 4,
+            },
+            Item {
+                weight: 4,
+                value: 5,
+            },
+            Item {
+                weight: 5,
+                value: 7,
+            },
+        ];
+        assert_eq!(knapsack(&items, 7), (9, vec![1, 2]));
+        assert_eq!(knapsack(&items, 0), (0, vec![]));
+        assert_eq!(knapsack(&items, 100), (17, vec![0, 1, 2, 3]));
+        assert_eq!(knapsack(&[], 10), (0, vec![]));
+    }
+
+    #[test]
+    fn knapsack_matches_brute_force() {
+        let mut rng = XorShift::new(2);
+        for _ in 0..200 {
+            let n = rng.next_u64() as usize % 9;
+            let items: Vec<Item> = (0..n)
+                .map(|_| Item {
+                    weight: 1 + rng.next_u64() as usize % 8,
+                    value: rng.next_u64() % 30,
+                })
+                .collect();
+            let capacity = rng.next_u64() as usize % 20;
+            let brute = (0u32..1 << n)
+                .filter_map(|mask| {
+                    let picked = (0..n).filter(|i| mask >> i & 1 == 1);
+                    let weight: usize = picked.clone().map(|i| items[i].weight).sum();
+                    (weight <= capacity).then(|| picked.map(|i| items[i].value).sum::<u64>())
+                })
+                .max()
+                .unwrap_or(0);
+            let (best, chosen) = knapsack(&items, capacity);
+            assert_eq!(best, brute);
+            assert_eq!(chosen.iter().map(|&i| items[i].value).sum::<u64>(), best);
+        }
+    }
+
+    #[test]
+    fn edit_distance_examples() {
+        assert_eq!(edit_distance_str("kitten", "sitting"), 3);
+        assert_eq!(edit_distance_str("", "abc"), 3);
+        assert_eq!(edit_distance_str("abc", "abc"), 0);
+        assert_eq!(edit_distance_str("你好世界", "你好"), 2);
+    }
+
+    #[test]
+    fn edit_distance_matches_naive_recursion() {
+        let mut rng = XorShift::new(3);
+        for _ in 0..300 {
+            let a = random_bytes(&mut rng, 6, 3);
+            let b = random_bytes(&mut rng, 6, 3);
+            assert_eq!(edit_distance(&a, &b), edit_distance_naive(&a, &b));
+        }
+    }
+}
@@ -0,0 +1,712 @@
+//! 带权图和图算法
+//! rc_structs::Graph 用 Rc/Weak 演示所有权，节点不好编号；算法里统一用下标表示节点、邻接表保存边，
+//! 有向图和无向图共用一个 Graph<W>，无向边在两个端点的邻接表中各存一份，边表中只存一份
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fmt;
+use std::ops::Add;
+
+use crate::hasher_bench::XorShift;
+
+// 边权：可以相加、可以比较，有零值
+pub trait Weight: Copy + Ord + Add<Output = Self> + fmt::Debug {
+    const ZERO: Self;
+}
+
+macro_rules! weight {
+    ($($t:ty),*) => {$(
+        impl Weight for $t {
+            const ZERO: Self = 0;
+        }
+    )*};
+}
+
+weight!(i32, i64, u32, u64, usize);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Edge<W> {
+    pub from: usize,
+    pub to: usize,
+    pub weight: W,
+}
+
+#[derive(Debug, Clone)]
+pub struct Graph<W> {
+    directed: bool,
+    adj: Vec<Vec<Edge<W>>>,
+    edges: Vec<Edge<W>>,
+}
+
+impl<W: Copy> Graph<W> {
+    pub fn directed(nodes: usize) -> Self {
+        Graph {
+            directed: true,
+            adj: vec![Vec::new(); nodes],
+            edges: Vec::new(),
+        }
+    }
+
+    pub fn undirected(nodes: usize) -> Self {
+        Graph {
+            directed: false,
+            ..Graph::directed(nodes)
+        }
+    }
+
+    pub fn is_directed(&self) -> bool {
+        self.directed
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.adj.len()
+    }
+
+    pub fn add_node(&mut self) -> usize {
+        self.adj.push(Vec::new());
+        self.adj.len() - 1
+    }
+
+    pub fn add_edge(&mut self, from: usize, to: usize, weight: W) {
+        assert!(
+            from < self.adj.len() && to < self.adj.len(),
+            "edge {} -> {} out of range for {} nodes",
+            from,
+            to,
+            self.adj.len()
+        );
+        let edge = Edge { from, to, weight };
+        self.adj[from].push(edge);
+        if !self.directed && from != to {
+            self.adj[to].push(Edge {
+                from: to,
+                to: from,
+                weight,
+            });
+        }
+        self.edges.push(edge);
+    }
+
+    // 从 from 出发的边(无向图包括以 from 为另一端的边)
+    pub fn neighbors(&self, from: usize) -> &[Edge<W>] {
+        &self.adj[from]
+    }
+
+    // 每条边一次，无向边按加入时的方向
+    pub fn edges(&self) -> &[Edge<W>] {
+        &self.edges
+    }
+}
+
+impl Graph<()> {
+    // 无权有向图
+    pub fn from_pairs(nodes: usize, pairs: &[(usize, usize)]) -> Self {
+        let mut g = Graph::directed(nodes);
+        for &(from, to) in pairs {
+            g.add_edge(from, to, ());
+        }
+        g
+    }
+}
+
+/* ---------------------------- 最短路 ---------------------------- */
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShortestPaths<W> {
+    pub source: usize,
+    // None 表示不可达
+    pub dist: Vec<Option<W>>,
+    pub prev: Vec<Option<usize>>,
+}
+
+impl<W> ShortestPaths<W> {
+    fn new(source: usize, nodes: usize, zero: W) -> Self {
+        let mut dist: Vec<Option<W>> = (0..nodes).map(|_| None).collect();
+        dist[source] = Some(zero);
+        ShortestPaths {
+            source,
+            dist,
+            prev: vec![None; nodes],
+        }
+    }
+
+    // 从起点到 to 的节点序列
+    pub fn path_to(&self, to: usize) -> Option<Vec<usize>> {
+        self.dist[to].as_ref()?;
+        let mut path = vec![to];
+        let mut cur = to;
+        while let Some(p) = self.prev[cur] {
+            path.push(p);
+            cur = p;
+        }
+        path.reverse();
+        Some(path)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PathError {
+    NegativeWeight(usize, usize),
+    // 从起点可达的负权环，按环上的顺序
+    NegativeCycle(Vec<usize>),
+}
+
+impl fmt::Display for PathError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PathError::NegativeWeight(u, v) => write!(f, "negative weight on edge {} -> {}", u, v),
+            PathError::NegativeCycle(cycle) => write!(f, "negative cycle through {:?}", cycle),
+        }
+    }
+}
+
+impl std::error::Error for PathError {}
+
+impl<W: Weight> Graph<W> {
+    // 二叉堆实现，O((V + E) log V)；遇到负权边返回错误
+    pub fn dijkstra(&self, source: usize) -> Result<ShortestPaths<W>, PathError> {
+        if let Some(e) = self.edges.iter().find(|e| e.weight < W::ZERO) {
+            return Err(PathError::NegativeWeight(e.from, e.to));
+        }
+        let mut paths = ShortestPaths::new(source, self.node_count(), W::ZERO);
+        let mut heap = BinaryHeap::new();
+        heap.push(Reverse((W::ZERO, source)));
+        while let Some(Reverse((d, u))) = heap.pop() {
+            // 同一个节点可能入堆多次，只处理距离最新的那一次
+            if paths.dist[u].is_some_and(|best| d > best) {
+                continue;
+            }
+            for e in &self.adj[u] {
+                let nd = d + e.weight;
+                if paths.dist[e.to].is_none_or(|old| nd < old) {
+                    paths.dist[e.to] = Some(nd);
+                    paths.prev[e.to] = Some(u);
+                    heap.push(Reverse((nd, e.to)));
+                }
+            }
+        }
+        Ok(paths)
+    }
+
+    // 允许负权，O(VE)；第 V 轮还能松弛说明存在负权环
+    pub fn bellman_ford(&self, source: usize) -> Result<ShortestPaths<W>, PathError> {
+        let n = self.node_count();
+        let mut paths = ShortestPaths::new(source, n, W::ZERO);
+        // 无向图的每条边两个方向都能松弛
+        let arcs: Vec<Edge<W>> = self.adj.iter().flatten().copied().collect();
+        let mut changed = None;
+        for _ in 0..n {
+            changed = None;
+            for e in &arcs {
+                let Some(d) = paths.dist[e.from] else {
+                    continue;
+                };
+                let nd = d + e.weight;
+                if paths.dist[e.to].is_none_or(|old| nd < old) {
+                    paths.dist[e.to] = Some(nd);
+                    paths.prev[e.to] = Some(e.from);
+                    changed = Some(e.to);
+                }
+            }
+            if changed.is_none() {
+                return Ok(paths);
+            }
+        }
+        // 沿 prev 回退 n 步一定进入环内，再绕一圈取出整个环
+        let mut v = changed.expect("relaxed in the last round");
+        for _ in 0..n {
+            v = paths.prev[v].expect("relaxed node has a predecessor");
+        }
+        let mut cycle = vec![v];
+        let mut u = paths.prev[v].expect("on cycle");
+        while u != v {
+            cycle.push(u);
+            u = paths.prev[u].expect("on cycle");
+        }
+        cycle.reverse();
+        Err(PathError::NegativeCycle(cycle))
+    }
+
+    /* ---------------------------- 最小生成树 ---------------------------- */
+
+    // Kruskal：按权重从小到大加边，用并查集跳过成环的边；图不连通时得到最小生成森林
+    pub fn kruskal(&self) -> (W, Vec<Edge<W>>) {
+        let mut edges = self.edges.clone();
+        edges.sort_by_key(|e| e.weight);
+        let mut sets = UnionFind::new(self.node_count());
+        let mut total = W::ZERO;
+        let mut tree = Vec::new();
+        for e in edges {
+            if sets.union(e.from, e.to) {
+                total = total + e.weight;
+                tree.push(e);
+            }
+        }
+        (total, tree)
+    }
+}
+
+// 按大小合并 + 路径压缩
+#[derive(Debug, Clone)]
+pub struct UnionFind {
+    parent: Vec<usize>,
+    size: Vec<usize>,
+    sets: usize,
+}
+
+impl UnionFind {
+    pub fn new(n: usize) -> Self {
+        UnionFind {
+            parent: (0..n).collect(),
+            size: vec![1; n],
+            sets: n,
+        }
+    }
+
+    pub fn find(&mut self, x: usize) -> usize {
+        let mut root = x;
+        while self.parent[root] != root {
+            root = self.parent[root];
+        }
+        let mut x = x;
+        while self.parent[x] != root {
+            x = std::mem::replace(&mut self.parent[x], root);
+        }
+        root
+    }
+
+    // 原来不在同一个集合时合并并返回 true
+    pub fn union(&mut self, a: usize, b: usize) -> bool {
+        let (mut a, mut b) = (self.find(a), self.find(b));
+        if a == b {
+            return false;
+        }
+        if self.size[a] < self.size[b] {
+            std::mem::swap(&mut a, &mut b);
+        }
+        self.parent[b] = a;
+        self.size[a] += self.size[b];
+        self.sets -= 1;
+        true
+    }
+
+    pub fn sets(&self) -> usize {
+        self.sets
+    }
+}
+
+/* ---------------------------- 强连通分量 ---------------------------- */
+
+impl<W> Graph<W> {
+    // Tarjan，用显式栈代替递归，长链也不会栈溢出；分量按逆拓扑序返回(先返回的分量没有指向后面分量的边)
+    pub fn tarjan_scc(&self) -> Vec<Vec<usize>> {
+        const UNVISITED: usize = usize::MAX;
+        let n = self.adj.len();
+        let mut index = vec![UNVISITED; n];
+        let mut low = vec![0; n];
+        let mut on_stack = vec![false; n];
+        let mut stack = Vec::new();
+        let mut next_index = 0;
+        let mut components = Vec::new();
+        // (节点, 下一条要看的边)
+        let mut call: Vec<(usize, usize)> = Vec::new();
+
+        for root in 0..n {
+            if index[root] != UNVISITED {
+                continue;
+            }
+            call.push((root, 0));
+            while let Some(&mut (v, ref mut edge)) = call.last_mut() {
+                if *edge == 0 {
+                    index[v] = next_index;
+                    low[v] = next_index;
+                    next_index += 1;
+                    stack.push(v);
+                    on_stack[v] = true;
+                }
+                if let Some(e) = self.adj[v].get(*edge) {
+                    *edge += 1;
+                    let w = e.to;
+                    if index[w] == UNVISITED {
+                        call.push((w, 0));
+                    } else if on_stack[w] {
+                        low[v] = low[v].min(index[w]);
+                    }
+                    continue;
+                }
+                // v 的边都处理完了
+                call.pop();
+                if let Some(&(parent, _)) = call.last() {
+                    low[parent] = low[parent].min(low[v]);
+                }
+                if low[v] == index[v] {
+                    let mut component = Vec::new();
+                    loop {
+                        let w = stack.pop().expect("v is on the stack");
+                        on_stack[w] = false;
+                        component.push(w);
+                        if w == v {
+                            break;
+                        }
+                    }
+                    component.sort_unstable();
+                    components.push(component);
+                }
+            }
+        }
+        components
+    }
+}
+
+/* ---------------------------- 练习 ---------------------------- */
+
+// 参照实现：Floyd–Warshall 全源最短路
+fn floyd_warshall(g: &Graph<i64>) -> Vec<Vec<Option<i64>>> {
+    let n = g.node_count();
+    let mut d = vec![vec![None; n]; n];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[i] = Some(0);
+    }
+    for e in g.adj.iter().flatten() {
+        let cur = &mut d[e.from][e.to];
+        if cur.is_none_or(|c| e.weight < c) {
+            *cur = Some(e.weight);
+        }
+    }
+    for k in 0..n {
+        for i in 0..n {
+            for j in 0..n {
+                if let (Some(a), Some(b)) = (d[i][k], d[k][j]) {
+                    if d[i][j].is_none_or(|c| a + b < c) {
+                        d[i][j] = Some(a + b);
+                    }
+                }
+            }
+        }
+    }
+    d
+}
+
+// 参照实现：O(V^2) 的 Prim，只求总权重
+fn prim_total(g: &Graph<i64>) -> i64 {
+    let n = g.node_count();
+    let mut in_tree = vec![false; n];
+    let mut best: Vec<Option<i64>> = vec![None; n];
+    let mut total = 0;
+    for _ in 0..n {
+        // 优先选已有候选边的节点，否则开始一棵新树
+        let next = (0..n)
+            .filter(|&v| !in_tree[v])
+            .min_by_key(|&v| (best[v].is_none(), best[v]))
+            .expect("a node remains");
+        in_tree[next] = true;
+        total += best[next].unwrap_or(0);
+        for e in g.neighbors(next) {
+            if !in_tree[e.to] && best[e.to].is_none_or(|b| e.weight < b) {
+                best[e.to] = Some(e.weight);
+            }
+        }
+    }
+    total
+}
+
+fn random_graph(rng: &mut XorShift, directed: bool, min_weight: i64) -> Graph<i64> {
+    let n = 1 + rng.next_u64() as usize % 12;
+    let mut g = if directed {
+        Graph::directed(n)
+    } else {
+        Graph::undirected(n)
+    };
+    for _ in 0..rng.next_u64() % (3 * n as u64) {
+        let (u, v) = (rng.next_u64() as usize % n, rng.next_u64() as usize % n);
+        let w = min_weight + (rng.next_u64() % 20) as i64;
+        g.add_edge(u, v, w);
+    }
+    g
+}
+
+fn check_paths(g: &Graph<i64>, paths: &ShortestPaths<i64>, expected: &[Option<i64>]) {
+    assert_eq!(paths.dist, expected);
+    // 沿 prev 重建的路径权重之和等于最短距离
+    for (v, d) in expected.iter().enumerate() {
+        let Some(path) = paths.path_to(v) else {
+            assert!(d.is_none());
+            continue;
+        };
+        assert_eq!(path[0], paths.source);
+        let len: i64 = path
+            .windows(2)
+            .map(|w| {
+                g.neighbors(w[0])
+                    .iter()
+                    .filter(|e| e.to == w[1])
+                    .map(|e| e.weight)
+                    .min()
+                    .expect("path uses an existing edge")
+            })
+            .sum();
+        assert_eq!(Some(len), *d);
+    }
+}
+
+fn shortest_path_practice() {
+    let mut rng = XorShift::new(49);
+    for round in 0..400 {
+        // 非负权：Dijkstra 和 Bellman-Ford 都要和 Floyd–Warshall 一致
+        let g = random_graph(&mut rng, round % 2 == 0, 0);
+        let all = floyd_warshall(&g);
+        let s = rng.next_u64() as usize % g.node_count();
+        check_paths(&g, &g.dijkstra(s).unwrap(), &all[s]);
+        check_paths(&g, &g.bellman_ford(s).unwrap(), &all[s]);
+
+        // 有负权的有向图：没有负环时和 Floyd–Warshall 一致，有负环时返回的环权重之和确实为负
+        let g = random_graph(&mut rng, true, -3);
+        assert!(g.edges().iter().all(|e| e.weight >= 0) || g.dijkstra(0).is_err());
+        match g.bellman_ford(s.min(g.node_count() - 1)) {
+            Ok(paths) => {
+                let all = floyd_warshall(&g);
+                check_paths(&g, &paths, &all[paths.source]);
+            }
+            Err(PathError::NegativeCycle(cycle)) => {
+                let weight: i64 = cycle
+                    .iter()
+                    .zip(cycle.iter().cycle().skip(1))
+                    .map(|(&u, &v)| {
+                        g.neighbors(u)
+                            .iter()
+                            .filter(|e| e.to == v)
+                            .map(|e| e.weight)
+                            .min()
+                            .expect("cycle uses existing edges")
+                    })
+                    .sum();
+                assert!(weight < 0, "cycle {:?} has weight {}", cycle, weight);
+            }
+            Err(e) => panic!("unexpected {}", e),
+        }
+    }
+
+    // 书上的例子
+    let mut g = Graph::directed(5);
+    for (u, v, w) in [
+        (0, 1, 10),
+        (0, 3, 5),
+        (1, 2, 1),
+        (3, 1, 3),
+        (3, 2, 9),
+        (2, 4, 4),
+        (3, 4, 2),
+    ] {
+        g.add_edge(u, v, w);
+    }
+    let paths = g.dijkstra(0).unwrap();
+    assert_eq!(paths.dist, [Some(0), Some(8), Some(9), Some(5), Some(7)]);
+    assert_eq!(paths.path_to(2), Some(vec![0, 3, 1, 2]));
+    g.add_edge(4, 3, -9);
+    assert_eq!(g.dijkstra(0).unwrap_err(), PathError::NegativeWeight(4, 3));
+    assert_eq!(
+        g.bellman_ford(0).unwrap_err().to_string(),
+        "negative cycle through [3, 4]"
+    );
+}
+
+fn mst_practice() {
+    let mut rng = XorShift::new(490);
+    for _ in 0..300 {
+        let g = random_graph(&mut rng, false, -5);
+        let (total, tree) = g.kruskal();
+        assert_eq!(total, prim_total(&g));
+        // 森林的边数 = 节点数 - 连通分量数
+        let mut sets = UnionFind::new(g.node_count());
+        for e in g.edges() {
+            sets.union(e.from, e.to);
+        }
+        assert_eq!(tree.len(), g.node_count() - sets.sets());
+        assert_eq!(total, tree.iter().map(|e| e.weight).sum::<i64>());
+    }
+    let mut g = Graph::undirected(4);
+    for (u, v, w) in [(0, 1, 1u32), (1, 2, 2), (0, 2, 3), (2, 3, 4), (1, 3, 5)] {
+        g.add_edge(u, v, w);
+    }
+    let (total, tree) = g.kruskal();
+    assert_eq!(total, 7);
+    assert_eq!(
+        tree.iter().map(|e| (e.from, e.to)).collect::<Vec<_>>(),
+        [(0, 1), (1, 2), (2, 3)]
+    );
+    assert!(!g.is_directed());
+}
+
+fn scc_practice() {
+    let mut rng = XorShift::new(4900);
+    for _ in 0..300 {
+        let g = random_graph(&mut rng, true, 0);
+        let n = g.node_count();
+        // 参照：u、v 互相可达时属于同一个分量
+        let reach = floyd_warshall(&g);
+        let components = g.tarjan_scc();
+        let mut which = vec![usize::MAX; n];
+        for (c, comp) in components.iter().enumerate() {
+            for &v in comp {
+                assert_eq!(which[v], usize::MAX, "node {} in two components", v);
+                which[v] = c;
+            }
+        }
+        for u in 0..n {
+            for v in 0..n {
+                let mutual = reach[u][v].is_some() && reach[v][u].is_some();
+                assert_eq!(which[u] == which[v], mutual);
+                // 逆拓扑序：边只能从后面的分量指向前面的分量
+                if reach[u][v].is_some() {
+                    assert!(which[u] >= which[v]);
+                }
+            }
+        }
+    }
+    let g = Graph::from_pairs(6, &[(0, 1), (1, 2), (2, 0), (2, 3), (3, 4), (4, 3), (5, 4)]);
+    assert_eq!(g.tarjan_scc(), [vec![3, 4], vec![0, 1, 2], vec![5]]);
+
+    // 十万个节点的长链，递归实现会栈溢出
+    let n = 100_000;
+    let mut chain = Graph::directed(0);
+    for _ in 0..n {
+        chain.add_node();
+    }
+    for i in 1..n {
+        chain.add_edge(i - 1, i, ());
+    }
+    chain.add_edge(n - 1, 0, ());
+    assert_eq!(chain.tarjan_scc().len(), 1);
+}
+
+pub fn graph_practice() {
+    shortest_path_practice();
+    mst_practice();
+    scc_practice();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn weighted(directed: bool, n: usize, edges: &[(usize, usize, i64)]) -> Graph<i64> {
+        let mut g = if directed {
+            Graph::directed(n)
+        } else {
+            Graph::undirected(n)
+        };
+        for &(u, v, w) in edges {
+            g.add_edge(u, v, w);
+        }
+        g
+    }
+
+    #[test]
+    fn dijkstra_matches_floyd_warshall() {
+        let mut rng = XorShift::new(1);
+        for round in 0..200 {
+            let g = random_graph(&mut rng, round % 2 == 0, 0);
+            let all = floyd_warshall(&g);
+            for (s, expected) in all.iter().enumerate() {
+                check_paths(&g, &g.dijkstra(s).unwrap(), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn dijkstra_rejects_negative_weights() {
+        let g = weighted(true, 3, &[(0, 1, 2), (1, 2, -1)]);
+        assert_eq!(g.dijkstra(0).unwrap_err(), PathError::NegativeWeight(1, 2));
+    }
+
+    #[test]
+    fn unreachable_nodes_have_no_distance() {
+        let g = weighted(true, 3, &[(1, 0, 1)]);
+        let paths = g.dijkstra(0).unwrap();
+        assert_eq!(paths.dist, [Some(0), None, None]);
+        assert_eq!(paths.path_to(0), Some(vec![0]));
+        assert_eq!(paths.path_to(1), None);
+    }
+
+    #[test]
+    fn bellman_ford_uses_negative_edges() {
+        let g = weighted(true, 3, &[(0, 1, 4), (0, 2, 5), (2, 1, -3)]);
+        let paths = g.bellman_ford(0).unwrap();
+        assert_eq!(paths.dist, [Some(0), Some(2), Some(5)]);
+        assert_eq!(paths.path_to(1), Some(vec![0, 2, 1]));
+    }
+
+    #[test]
+    fn bellman_ford_matches_floyd_warshall_without_negative_cycles() {
+        let mut rng = XorShift::new(2);
+        let mut checked = 0;
+        for _ in 0..300 {
+            let g = random_graph(&mut rng, true, -2);
+            if let Ok(paths) = g.bellman_ford(0) {
+                check_paths(&g, &paths, &floyd_warshall(&g)[0]);
+                checked += 1;
+            }
+        }
+        assert!(checked > 50);
+    }
+
+    #[test]
+    fn bellman_ford_reports_negative_cycle() {
+        let g = weighted(true, 4, &[(0, 1, 1), (1, 2, 1), (2, 3, -4), (3, 1, 2)]);
+        match g.bellman_ford(0) {
+            Err(PathError::NegativeCycle(mut cycle)) => {
+                cycle.sort_unstable();
+                assert_eq!(cycle, [1, 2, 3]);
+            }
+            other => panic!("expected a negative cycle, got {:?}", other.map(|p| p.dist)),
+        }
+    }
+
+    #[test]
+    fn kruskal_matches_prim() {
+        let mut rng = XorShift::new(3);
+        for _ in 0..200 {
+            let g = random_graph(&mut rng, false, -5);
+            assert_eq!(g.kruskal().0, prim_total(&g));
+        }
+    }
+
+    #[test]
+    fn kruskal_spans_each_component() {
+        let g = weighted(false, 5, &[(0, 1, 3), (1, 2, 1), (0, 2, 2), (3, 4, 7)]);
+        let (total, tree) = g.kruskal();
+        assert_eq!(total, 10);
+        assert_eq!(tree.len(), 3);
+    }
+
+    #[test]
+    fn union_find_counts_sets() {
+        let mut sets = UnionFind::new(5);
+        assert_eq!(sets.sets(), 5);
+        assert!(sets.union(0, 1));
+        assert!(sets.union(3, 4));
+        assert!(!sets.union(1, 0));
+        assert!(sets.union(1, 4));
+        assert_eq!(sets.find(0), sets.find(3));
+        assert_ne!(sets.find(0), sets.find(2));
+        assert_eq!(sets.sets(), 2);
+    }
+
+    #[test]
+    fn tarjan_scc_in_reverse_topological_order() {
+        let g = Graph::from_pairs(6, &[(0, 1), (1, 2), (2, 0), (2, 3), (3, 4), (4, 3), (5, 4)]);
+        assert_eq!(g.tarjan_scc(), [vec![3, 4], vec![0, 1, 2], vec![5]]);
+        assert!(Graph::from_pairs(0, &[]).tarjan_scc().is_empty());
+        assert_eq!(
+            Graph::from_pairs(2, &[(0, 0)]).tarjan_scc(),
+            [vec![0], vec![1]]
+        );
+    }
+
+    #[test]
+    fn tarjan_scc_handles_long_chains() {
+        let n = 100_000;
+        let pairs: Vec<(usize, usize)> = (1..n).map(|i| (i - 1, i)).collect();
+        assert_eq!(Graph::from_pairs(n, &pairs).tarjan_scc().len(), n);
+    }
+}
@@ -0,0 +1,351 @@
+//! 字符串匹配：KMP 单模式、Aho–Corasick 多模式
+//! KMP 对任意 T: PartialEq 的切片工作；Aho–Corasick 按字节构建自动机，&str 和 &[u8] 都可以用
+
+use std::collections::VecDeque;
+
+use super::random_bytes;
+use crate::hasher_bench::XorShift;
+
+/* ---------------------------- KMP ---------------------------- */
+
+pub struct Kmp<'p, T> {
+    pattern: &'p [T],
+    // fail[i] 是 pattern[..=i] 最长的相等真前缀和真后缀的长度
+    fail: Vec<usize>,
+}
+
+impl<'p, T: PartialEq> Kmp<'p, T> {
+    pub fn new(pattern: &'p [T]) -> Self {
+        let mut fail = vec![0; pattern.len()];
+        let mut k = 0;
+        for i in 1..pattern.len() {
+            while k > 0 && pattern[i] != pattern[k] {
+                k = fail[k - 1];
+            }
+            if pattern[i] == pattern[k] {
+                k += 1;
+            }
+            fail[i] = k;
+        }
+        Kmp { pattern, fail }
+    }
+
+    pub fn fail_table(&self) -> &[usize] {
+        &self.fail
+    }
+
+    // 所有匹配的起始位置，包括互相重叠的；空模式在每个位置都匹配
+    pub fn find_all(&self, text: &[T]) -> Vec<usize> {
+        let m = self.pattern.len();
+        if m == 0 {
+            return (0..=text.len()).collect();
+        }
+        let mut found = Vec::new();
+        let mut k = 0;
+        for (i, x) in text.iter().enumerate() {
+            while k > 0 && *x != self.pattern[k] {
+                k = self.fail[k - 1];
+            }
+            if *x == self.pattern[k] {
+                k += 1;
+            }
+            if k == m {
+                found.push(i + 1 - m);
+                k = self.fail[k - 1];
+            }
+        }
+        found
+    }
+
+    pub fn find(&self, text: &[T]) -> Option<usize> {
+        self.find_all(text).first().copied()
+    }
+}
+
+/* ---------------------------- Aho–Corasick ---------------------------- */
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Match {
+    pub pattern: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+/*
+ * 先把所有模式插入字典树，再按 BFS 顺序计算失配指针，同时把缺失的转移补全成 DFA：
+ * 匹配时每个字节只走一步，不需要沿失配链回退。
+ * 每个状态的输出包括自己结束的模式和失配链上所有状态结束的模式(构建时合并)
+ */
+pub struct AhoCorasick {
+    next: Vec<[u32; 256]>,
+    fail: Vec<u32>,
+    output: Vec<Vec<usize>>,
+    lengths: Vec<usize>,
+}
+
+impl AhoCorasick {
+    pub fn new<P: AsRef<[u8]>>(patterns: &[P]) -> Self {
+        let mut ac = AhoCorasick {
+            next: vec![[0; 256]],
+            fail: vec![0],
+            output: vec![Vec::new()],
+            lengths: patterns.iter().map(|p| p.as_ref().len()).collect(),
+        };
+        // 0 既是根，也表示“还没有转移”(根之外没有状态会转移回根的子树之外)
+        for (id, p) in patterns.iter().enumerate() {
+            let mut state = 0;
+            for &b in p.as_ref() {
+                let slot = ac.next[state][b as usize];
+                state = if slot == 0 {
+                    ac.next.push([0; 256]);
+                    ac.fail.push(0);
+                    ac.output.push(Vec::new());
+                    let new = ac.next.len() - 1;
+                    ac.next[state][b as usize] = new as u32;
+                    new
+                } else {
+                    slot as usize
+                };
+            }
+            ac.output[state].push(id);
+        }
+
+        let mut queue: VecDeque<usize> = ac.next[0]
+            .iter()
+            .filter(|&&s| s != 0)
+            .map(|&s| s as usize)
+            .collect();
+        while let Some(state) = queue.pop_front() {
+            let inherited = ac.output[ac.fail[state] as usize].clone();
+            ac.output[state].extend(inherited);
+            for b in 0..256 {
+                let child = ac.next[state][b] as usize;
+                let via_fail = ac.next[ac.fail[state] as usize][b];
+                if child == 0 {
+                    ac.next[state][b] = via_fail;
+                } else {
+                    ac.fail[child] = via_fail;
+                    queue.push_back(child);
+                }
+            }
+        }
+        ac
+    }
+
+    pub fn states(&self) -> usize {
+        self.next.len()
+    }
+
+    // 所有(可能重叠的)匹配，按结束位置排序，同一位置按模式编号排序。
+    // 和 Kmp 一样，空模式在每个位置都匹配(包括文本开头和结尾)
+    pub fn find_all(&self, text: impl AsRef<[u8]>) -> Vec<Match> {
+        // 根的输出只可能是空模式，其余位置的空匹配由失配链合并到每个状态的输出中
+        let mut found: Vec<Match> = self.output[0]
+            .iter()
+            .map(|&pattern| Match {
+                pattern,
+                start: 0,
+                end: 0,
+            })
+            .collect();
+        let mut state = 0;
+        for (i, &b) in text.as_ref().iter().enumerate() {
+            state = self.next[state][b as usize] as usize;
+            let mut here: Vec<Match> = self.output[state]
+                .iter()
+                .map(|&pattern| Match {
+                    pattern,
+                    start: i + 1 - self.lengths[pattern],
+                    end: i + 1,
+                })
+                .collect();
+            here.sort_by_key(|m| m.pattern);
+            found.extend(here);
+        }
+        found
+    }
+
+    // 文本中是否出现任意一个模式；有空模式时总是 true
+    pub fn is_match(&self, text: impl AsRef<[u8]>) -> bool {
+        if !self.output[0].is_empty() {
+            return true;
+        }
+        let mut state = 0;
+        text.as_ref().iter().any(|&b| {
+            state = self.next[state][b as usize] as usize;
+            !self.output[state].is_empty()
+        })
+    }
+}
+
+/* ---------------------------- 练习 ---------------------------- */
+
+fn naive_find_all(text: &[u8], pattern: &[u8]) -> Vec<usize> {
+    if pattern.is_empty() {
+        return (0..=text.len()).collect();
+    }
+    text.windows(pattern.len())
+        .enumerate()
+        .filter(|(_, w)| *w == pattern)
+        .map(|(i, _)| i)
+        .collect()
+}
+
+pub fn strings_practice() {
+    let kmp = Kmp::new(b"ababaca".as_slice());
+    assert_eq!(kmp.fail_table(), [0, 0, 1, 2, 3, 0, 1]);
+    assert_eq!(Kmp::new(b"aa".as_slice()).find_all(b"aaaa"), [0, 1, 2]);
+    assert_eq!(Kmp::new(&[3, 1]).find(&[1, 3, 1, 3, 1]), Some(1));
+    let words: Vec<&str> = "to be or not to be".split(' ').collect();
+    assert_eq!(Kmp::new(&["to", "be"]).find_all(&words), [0, 4]);
+
+    let mut rng = XorShift::new(4902);
+    for _ in 0..1000 {
+        let text = random_bytes(&mut rng, 60, 3);
+        let pattern = random_bytes(&mut rng, 4, 3);
+        assert_eq!(
+            Kmp::new(&pattern[..]).find_all(&text),
+            naive_find_all(&text, &pattern)
+        );
+
+        let patterns: Vec<Vec<u8>> = (0..1 + rng.next_u64() % 5)
+            .map(|_| random_bytes(&mut rng, 4, 3))
+            .collect();
+        let ac = AhoCorasick::new(&patterns);
+        let mut expected: Vec<Match> = patterns
+            .iter()
+            .enumerate()
+            .flat_map(|(id, p)| {
+                naive_find_all(&text, p)
+                    .into_iter()
+                    .map(move |start| Match {
+                        pattern: id,
+                        start,
+                        end: start + p.len(),
+                    })
+            })
+            .collect();
+        expected.sort_by_key(|m| (m.end, m.pattern));
+        assert_eq!(ac.find_all(&text), expected);
+        assert_eq!(ac.is_match(&text), !expected.is_empty());
+    }
+
+    // 经典例子：he、she、his、hers
+    let ac = AhoCorasick::new(&["he", "she", "his", "hers"]);
+    let found: Vec<(usize, usize)> = ac
+        .find_all("ushers")
+        .iter()
+        .map(|m| (m.pattern, m.start))
+        .collect();
+    assert_eq!(found, [(0, 2), (1, 1), (3, 2)]);
+    assert_eq!(ac.states(), 10);
+    assert!(!ac.is_match("hi"));
+    // 空模式在每个位置都匹配，和 Kmp::find_all 一致
+    let ac = AhoCorasick::new(&["", "b"]);
+    let found: Vec<(usize, usize)> = ac
+        .find_all("ab")
+        .iter()
+        .map(|m| (m.pattern, m.start))
+        .collect();
+    assert_eq!(found, [(0, 0), (0, 1), (0, 2), (1, 1)]);
+    assert!(ac.is_match("") && ac.is_match("xyz"));
+    assert_eq!(AhoCorasick::new(&[""]).find_all("").len(), 1);
+    // 按字节匹配，UTF-8 文本中的多字节字符也可以
+    let ac = AhoCorasick::new(&["世界", "界"]);
+    let found: Vec<Match> = ac.find_all("你好，世界");
+    assert_eq!(found.len(), 2);
+    assert_eq!((found[0].start, found[0].end), (9, 15));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kmp_fail_table() {
+        assert_eq!(
+            Kmp::new(b"ababaca".as_slice()).fail_table(),
+            [0, 0, 1, 2, 3, 0, 1]
+        );
+        assert_eq!(Kmp::new(b"aaaa".as_slice()).fail_table(), [0, 1, 2, 3]);
+        assert!(Kmp::new(b"".as_slice()).fail_table().is_empty());
+    }
+
+    #[test]
+    fn kmp_finds_overlapping_matches() {
+        assert_eq!(Kmp::new(b"aa".as_slice()).find_all(b"aaaa"), [0, 1, 2]);
+        assert_eq!(Kmp::new(b"abc".as_slice()).find(b"ababd"), None);
+        assert_eq!(Kmp::new(b"".as_slice()).find_all(b"ab"), [0, 1, 2]);
+        assert_eq!(Kmp::new(&[3, 1]).find(&[1, 3, 1, 3, 1]), Some(1));
+    }
+
+    #[test]
+    fn kmp_matches_naive_search() {
+        let mut rng = XorShift::new(1);
+        for _ in 0..500 {
+            let text = random_bytes(&mut rng, 40, 2);
+            let pattern = random_bytes(&mut rng, 4, 2);
+            assert_eq!(
+                Kmp::new(&pattern[..]).find_all(&text),
+                naive_find_all(&text, &pattern)
+            );
+        }
+    }
+
+    #[test]
+    fn aho_corasick_classic_example() {
+        let ac = AhoCorasick::new(&["he", "she", "his", "hers"]);
+        let found: Vec<(usize, usize, usize)> = ac
+            .find_all("ushers")
+            .iter()
+            .map(|m| (m.pattern, m.start, m.end))
+            .collect();
+        assert_eq!(found, [(0, 2, 4), (1, 1, 4), (3, 2, 6)]);
+        assert!(!ac.is_match("hi"));
+    }
+
+    #[test]
+    fn aho_corasick_empty_patterns_match_everywhere() {
+        let ac = AhoCorasick::new(&[""]);
+        let starts: Vec<usize> = ac.find_all("abc").iter().map(|m| m.start).collect();
+        assert_eq!(starts, [0, 1, 2, 3]);
+        assert!(ac.is_match(""));
+        let none = AhoCorasick::new::<&str>(&[]);
+        assert!(none.find_all("abc").is_empty() && !none.is_match("abc"));
+    }
+
+    #[test]
+    fn aho_corasick_matches_naive_search() {
+        let mut rng = XorShift::new(2);
+        for _ in 0..500 {
+            let text = random_bytes(&mut rng, 40, 3);
+            let patterns: Vec<Vec<u8>> = (0..1 + rng.next_u64() % 4)
+                .map(|_| random_bytes(&mut rng, 3, 3))
+                .collect();
+            let mut expected: Vec<Match> = patterns
+                .iter()
+                .enumerate()
+                .flat_map(|(id, p)| {
+                    naive_find_all(&text, p)
+                        .into_iter()
+                        .map(move |start| Match {
+                            pattern: id,
+                            start,
+                            end: start + p.len(),
+                        })
+                })
+                .collect();
+            expected.sort_by_key(|m| (m.end, m.pattern));
+            let ac = AhoCorasick::new(&patterns);
+            assert_eq!(ac.find_all(&text), expected);
+            assert_eq!(ac.is_match(&text), !expected.is_empty());
+        }
+    }
+
+    #[test]
+    fn aho_corasick_works_on_utf8_bytes() {
+        let found = AhoCorasick::new(&["世界", "界"]).find_all("你好，世界");
+        let spans: Vec<(usize, usize)> = found.iter().map(|m| (m.start, m.end)).collect();
+        assert_eq!(spans, [(9, 15), (12, 15)]);
+    }
+}
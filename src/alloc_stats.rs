@@ -0,0 +1,669 @@
+//! 带统计的全局分配器和 bump 分配器
+//! auto_ptr 比较了栈上 `[0; 1000]` 的拷贝和 `Box::new([0; 1000])` 的移动，但没有看到堆分配到底发生了几次。
+//! - Tracking：包装任意 GlobalAlloc，记录分配次数、字节数、峰值和按大小分组的直方图，
+//!   全局计数用原子变量，另外每个线程单独计数，measure 只统计当前线程，不受 tokio 工作线程干扰
+//! - Arena：bump 分配器，从大块内存中顺序切分，整体释放，适合生命周期相同的一批小对象
+//! - chapter_report：逐个运行 main.rs 中的章节函数，输出每章的堆分配情况
+//!   cargo run --release --features track-allocs -- allocs [章节名...]
+//!
+//! 统计会给每次分配都加上原子操作，所以 Tracking 只在打开 track-allocs 特性时才安装为全局分配器，
+//! 否则 measure 和 global 返回的统计全为 0
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::{Cell, RefCell};
+use std::fmt;
+use std::mem::align_of;
+use std::ptr::NonNull;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::frame::{Align, Table};
+
+#[cfg_attr(feature = "track-allocs", global_allocator)]
+static GLOBAL: Tracking<System> = Tracking::new(System);
+
+/* ---------------------------- 统计 ---------------------------- */
+
+// Tracking 是否安装为全局分配器
+pub const ENABLED: bool = cfg!(feature = "track-allocs");
+
+// 直方图的桶：第 i 个桶统计大小在 (2^(i+2), 2^(i+3)] 之间的分配，第一个桶包括 0..=8，最后一个桶包括所有更大的
+pub const BUCKETS: usize = 16;
+
+fn bucket(size: usize) -> usize {
+    let bits = usize::BITS - size.saturating_sub(1).leading_zeros();
+    (bits.saturating_sub(3) as usize).min(BUCKETS - 1)
+}
+
+// 桶的上界，用作直方图的标签
+pub fn bucket_label(i: usize) -> String {
+    let upper = 8u64 << i;
+    let human = |n: u64| {
+        if n >= 1024 {
+            format!("{}K", n / 1024)
+        } else {
+            n.to_string()
+        }
+    };
+    if i == BUCKETS - 1 {
+        format!(">{}", human(upper / 2))
+    } else {
+        format!("<={}", human(upper))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AllocStats {
+    pub allocs: u64,
+    pub deallocs: u64,
+    pub reallocs: u64,
+    // 累计申请的字节数，realloc 变大时只计增加的部分
+    pub bytes: u64,
+    // 当前仍未释放的字节数，measure 返回的是这段时间的净变化
+    pub live: i64,
+    pub peak: u64,
+    pub histogram: [u64; BUCKETS],
+}
+
+impl AllocStats {
+    fn since(&self, before: &AllocStats) -> AllocStats {
+        let mut histogram = [0; BUCKETS];
+        for (i, h) in histogram.iter_mut().enumerate() {
+            *h = self.histogram[i] - before.histogram[i];
+        }
+        AllocStats {
+            allocs: self.allocs - before.allocs,
+            deallocs: self.deallocs - before.deallocs,
+            reallocs: self.reallocs - before.reallocs,
+            bytes: self.bytes - before.bytes,
+            live: self.live - before.live,
+            // 峰值在 measure 开始时被重置为当时的 live，这里换算成相对开始时的增量
+            peak: (self.peak as i64 - before.live).max(0) as u64,
+            histogram,
+        }
+    }
+}
+
+impl fmt::Display for AllocStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "allocs {}, deallocs {}, reallocs {}, bytes {}, live {}, peak {}",
+            self.allocs, self.deallocs, self.reallocs, self.bytes, self.live, self.peak
+        )?;
+        for (i, &n) in self.histogram.iter().enumerate().filter(|(_, &n)| n > 0) {
+            writeln!(f, "  {:>7} {:>8}", bucket_label(i), n)?;
+        }
+        Ok(())
+    }
+}
+
+/* ---------------------------- 全局分配器 ---------------------------- */
+
+struct Counters {
+    allocs: AtomicU64,
+    deallocs: AtomicU64,
+    reallocs: AtomicU64,
+    bytes: AtomicU64,
+    live: AtomicU64,
+    peak: AtomicU64,
+    histogram: [AtomicU64; BUCKETS],
+}
+
+impl Counters {
+    const fn new() -> Self {
+        Counters {
+            allocs: AtomicU64::new(0),
+            deallocs: AtomicU64::new(0),
+            reallocs: AtomicU64::new(0),
+            bytes: AtomicU64::new(0),
+            live: AtomicU64::new(0),
+            peak: AtomicU64::new(0),
+            histogram: [const { AtomicU64::new(0) }; BUCKETS],
+        }
+    }
+}
+
+// 每个线程的计数器：const 初始化、没有析构函数，访问时不会再触发分配
+struct Local {
+    allocs: Cell<u64>,
+    deallocs: Cell<u64>,
+    reallocs: Cell<u64>,
+    bytes: Cell<u64>,
+    live: Cell<i64>,
+    peak: Cell<i64>,
+    histogram: [Cell<u64>; BUCKETS],
+}
+
+thread_local! {
+    static LOCAL: Local = const {
+        Local {
+            allocs: Cell::new(0),
+            deallocs: Cell::new(0),
+            reallocs: Cell::new(0),
+            bytes: Cell::new(0),
+            live: Cell::new(0),
+            peak: Cell::new(0),
+            histogram: [const { Cell::new(0) }; BUCKETS],
+        }
+    };
+}
+
+fn bump(cell: &Cell<u64>, n: u64) {
+    cell.set(cell.get() + n);
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Event {
+    Alloc,
+    Dealloc,
+    Realloc,
+}
+
+pub struct Tracking<A> {
+    inner: A,
+    counters: Counters,
+}
+
+impl<A> Tracking<A> {
+    pub const fn new(inner: A) -> Self {
+        Tracking {
+            inner,
+            counters: Counters::new(),
+        }
+    }
+
+    // grow 是 live 的变化量
+    fn record(&self, event: Event, size: usize, grow: i64) {
+        let c = &self.counters;
+        match event {
+            Event::Alloc => c.allocs.fetch_add(1, Ordering::Relaxed),
+            Event::Dealloc => c.deallocs.fetch_add(1, Ordering::Relaxed),
+            Event::Realloc => c.reallocs.fetch_add(1, Ordering::Relaxed),
+        };
+        if event != Event::Dealloc {
+            c.histogram[bucket(size)].fetch_add(1, Ordering::Relaxed);
+        }
+        if grow > 0 {
+            c.bytes.fetch_add(grow as u64, Ordering::Relaxed);
+            let live = c.live.fetch_add(grow as u64, Ordering::Relaxed) + grow as u64;
+            c.peak.fetch_max(live, Ordering::Relaxed);
+        } else {
+            c.live.fetch_sub(grow.unsigned_abs(), Ordering::Relaxed);
+        }
+        // 线程退出后 LOCAL 已经销毁，这时的分配只计入全局
+        let _ = LOCAL.try_with(|l| {
+            match event {
+                Event::Alloc => bump(&l.allocs, 1),
+                Event::Dealloc => bump(&l.deallocs, 1),
+                Event::Realloc => bump(&l.reallocs, 1),
+            }
+            if event != Event::Dealloc {
+                bump(&l.histogram[bucket(size)], 1);
+            }
+            if grow > 0 {
+                bump(&l.bytes, grow as u64);
+            }
+            l.live.set(l.live.get() + grow);
+            l.peak.set(l.peak.get().max(l.live.get()));
+        });
+    }
+
+    // 进程启动以来所有线程的统计
+    pub fn global(&self) -> AllocStats {
+        let c = &self.counters;
+        let mut histogram = [0; BUCKETS];
+        for (h, a) in histogram.iter_mut().zip(&c.histogram) {
+            *h = a.load(Ordering::Relaxed);
+        }
+        AllocStats {
+            allocs: c.allocs.load(Ordering::Relaxed),
+            deallocs: c.deallocs.load(Ordering::Relaxed),
+            reallocs: c.reallocs.load(Ordering::Relaxed),
+            bytes: c.bytes.load(Ordering::Relaxed),
+            live: c.live.load(Ordering::Relaxed) as i64,
+            peak: c.peak.load(Ordering::Relaxed),
+            histogram,
+        }
+    }
+}
+
+unsafe impl<A: GlobalAlloc> GlobalAlloc for Tracking<A> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let p = self.inner.alloc(layout);
+        if !p.is_null() {
+            self.record(Event::Alloc, layout.size(), layout.size() as i64);
+        }
+        p
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let p = self.inner.alloc_zeroed(layout);
+        if !p.is_null() {
+            self.record(Event::Alloc, layout.size(), layout.size() as i64);
+        }
+        p
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        self.inner.dealloc(ptr, layout);
+        self.record(Event::Dealloc, layout.size(), -(layout.size() as i64));
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let p = self.inner.realloc(ptr, layout, new_size);
+        if !p.is_null() {
+            self.record(
+                Event::Realloc,
+                new_size,
+                new_size as i64 - layout.size() as i64,
+            );
+        }
+        p
+    }
+}
+
+fn local() -> AllocStats {
+    LOCAL.with(|l| {
+        let mut histogram = [0; BUCKETS];
+        for (h, c) in histogram.iter_mut().zip(&l.histogram) {
+            *h = c.get();
+        }
+        AllocStats {
+            allocs: l.allocs.get(),
+            deallocs: l.deallocs.get(),
+            reallocs: l.reallocs.get(),
+            bytes: l.bytes.get(),
+            live: l.live.get(),
+            peak: l.peak.get().max(0) as u64,
+            histogram,
+        }
+    })
+}
+
+pub fn global() -> AllocStats {
+    GLOBAL.global()
+}
+
+// 运行 f，返回当前线程在这期间的分配统计；f 中创建的其他线程的分配不计入
+pub fn measure<R>(f: impl FnOnce() -> R) -> (R, AllocStats) {
+    let before = LOCAL.with(|l| {
+        l.peak.set(l.live.get());
+        local()
+    });
+    let result = f();
+    (result, local().since(&before))
+}
+
+/* ---------------------------- bump 分配器 ---------------------------- */
+
+const MIN_CHUNK: usize = 4096;
+const CHUNK_ALIGN: usize = 16;
+
+// 一块从 System 申请的内存，只通过裸指针访问，已经分出去的引用不会被后续的借用失效
+struct Chunk {
+    ptr: NonNull<u8>,
+    layout: Layout,
+}
+
+impl Chunk {
+    fn new(size: usize, align: usize) -> Chunk {
+        let layout = Layout::from_size_align(size, align.max(CHUNK_ALIGN)).expect("chunk layout");
+        // SAFETY: size 至少是 MIN_CHUNK，不为 0
+        let ptr = unsafe { std::alloc::alloc(layout) };
+        let ptr = NonNull::new(ptr).unwrap_or_else(|| std::alloc::handle_alloc_error(layout));
+        Chunk { ptr, layout }
+    }
+
+    // 在 used 之后找一个满足 layout 的位置，返回起始偏移
+    fn fit(&self, used: usize, layout: Layout) -> Option<usize> {
+        let addr = self.ptr.as_ptr().addr() + used;
+        let start = used + (addr.next_multiple_of(layout.align()) - addr);
+        (start + layout.size() <= self.layout.size()).then_some(start)
+    }
+}
+
+impl Drop for Chunk {
+    fn drop(&mut self) {
+        // SAFETY: ptr 由同一个 layout 分配
+        unsafe { std::alloc::dealloc(self.ptr.as_ptr(), self.layout) }
+    }
+}
+
+/*
+ * 分配只是把偏移向后移动，不能单独释放，Arena 销毁或 reset 时整体回收。
+ * 和 bumpalo 一样不会运行放进来的值的析构函数，放 String、Vec 这类持有堆内存的值会泄漏它们的缓冲区。
+ * 返回的引用借用 &self，所以 reset 需要 &mut self，编译器保证之前分出去的引用都已经失效
+ */
+pub struct Arena {
+    chunks: RefCell<Vec<Chunk>>,
+    used: Cell<usize>,
+    allocated: Cell<usize>,
+}
+
+impl Arena {
+    pub fn new() -> Self {
+        Arena::with_capacity(0)
+    }
+
+    pub fn with_capacity(bytes: usize) -> Self {
+        let arena = Arena {
+            chunks: RefCell::new(Vec::new()),
+            used: Cell::new(0),
+            allocated: Cell::new(0),
+        };
+        if bytes > 0 {
+            arena
+                .chunks
+                .borrow_mut()
+                .push(Chunk::new(bytes, CHUNK_ALIGN));
+        }
+        arena
+    }
+
+    fn alloc_layout(&self, layout: Layout) -> NonNull<u8> {
+        let mut chunks = self.chunks.borrow_mut();
+        let used = self.used.get();
+        let (chunk, start) = match chunks.last().and_then(|c| Some((c, c.fit(used, layout)?))) {
+            Some(found) => found,
+            None => {
+                // 新块的大小翻倍，至少能放下这次的请求
+                let last = chunks.last().map_or(0, |c| c.layout.size());
+                let size = (last * 2).max(MIN_CHUNK).max(layout.size());
+                chunks.push(Chunk::new(size, layout.align()));
+                (chunks.last().expect("just pushed"), 0)
+            }
+        };
+        self.used.set(start + layout.size());
+        self.allocated.set(self.allocated.get() + layout.size());
+        // SAFETY: fit 保证 [start, start + size) 在块内
+        unsafe { chunk.ptr.add(start) }
+    }
+
+    // 每次调用返回互不重叠的新区域，从 &self 得到 &mut 是安全的(和 bumpalo 相同)
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc<T>(&self, value: T) -> &mut T {
+        let p = self.alloc_layout(Layout::new::<T>()).cast::<T>();
+        // SAFETY: p 对齐、大小足够，且不和任何已分出的区域重叠
+        unsafe {
+            p.as_ptr().write(value);
+            &mut *p.as_ptr()
+        }
+    }
+
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc_slice_copy<T: Copy>(&self, values: &[T]) -> &mut [T] {
+        let layout = Layout::array::<T>(values.len()).expect("slice layout");
+        let p = self.alloc_layout(layout).cast::<T>();
+        // SAFETY: 同 alloc，T: Copy 所以按位复制是合法的
+        unsafe {
+            std::ptr::copy_nonoverlapping(values.as_ptr(), p.as_ptr(), values.len());
+            std::slice::from_raw_parts_mut(p.as_ptr(), values.len())
+        }
+    }
+
+    pub fn alloc_str(&self, s: &str) -> &str {
+        let bytes = self.alloc_slice_copy(s.as_bytes());
+        // SAFETY: 复制自合法的 UTF-8
+        unsafe { std::str::from_utf8_unchecked(bytes) }
+    }
+
+    // 已分出的字节数(不含对齐填充)
+    pub fn allocated(&self) -> usize {
+        self.allocated.get()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.chunks.borrow().iter().map(|c| c.layout.size()).sum()
+    }
+
+    pub fn chunks(&self) -> usize {
+        self.chunks.borrow().len()
+    }
+
+    // 只保留最大的一块，下一轮从头开始使用
+    pub fn reset(&mut self) {
+        let chunks = self.chunks.get_mut();
+        if chunks.len() > 1 {
+            let last = chunks.pop().expect("more than one chunk");
+            chunks.clear();
+            chunks.push(last);
+        }
+        self.used.set(0);
+        self.allocated.set(0);
+    }
+}
+
+impl Default for Arena {
+    fn default() -> Self {
+        Arena::new()
+    }
+}
+
+/* ---------------------------- 章节报告 ---------------------------- */
+
+pub type Chapter = (&'static str, fn());
+
+pub struct ChapterRow {
+    pub name: &'static str,
+    pub local: AllocStats,
+    // 包括章节中创建的线程和同时运行的其他线程
+    pub global: AllocStats,
+}
+
+pub fn run_chapters(chapters: &[Chapter]) -> Vec<ChapterRow> {
+    chapters
+        .iter()
+        .map(|&(name, f)| {
+            let before = global();
+            let ((), local) = measure(f);
+            ChapterRow {
+                name,
+                local,
+                global: global().since(&before),
+            }
+        })
+        .collect()
+}
+
+pub fn chapter_table(rows: &[ChapterRow]) -> Table {
+    let mut table = Table::new(&[
+        &"chapter",
+        &"allocs",
+        &"reallocs",
+        &"bytes",
+        &"peak",
+        &"live",
+        &"all threads",
+    ]);
+    table = (1..7).fold(table, |t, col| t.align(col, Align::Right));
+    for r in rows {
+        table.row(&[
+            &r.name,
+            &r.local.allocs,
+            &r.local.reallocs,
+            &r.local.bytes,
+            &r.local.peak,
+            &r.local.live,
+            &r.global.allocs,
+        ]);
+    }
+    table
+}
+
+// cargo run --release --features track-allocs -- allocs [章节名...]
+pub fn chapter_report(
+    chapters: &[Chapter],
+    args: &[String],
+) -> Result<(), Box<dyn std::error::Error>> {
+    if !ENABLED {
+        return Err("allocation tracking is disabled, rebuild with --features track-allocs".into());
+    }
+    let selected: Vec<Chapter> = if args.is_empty() {
+        chapters.to_vec()
+    } else {
+        args.iter()
+            .map(|a| {
+                chapters
+                    .iter()
+                    .find(|(name, _)| name == a)
+                    .copied()
+                    .ok_or_else(|| format!("unknown chapter: {}", a))
+            })
+            .collect::<Result<_, _>>()?
+    };
+    let rows = run_chapters(&selected);
+    println!();
+    println!("{}", chapter_table(&rows));
+    let mut total = AllocStats::default();
+    for r in &rows {
+        for (t, h) in total.histogram.iter_mut().zip(&r.local.histogram) {
+            *t += h;
+        }
+    }
+    println!("allocation sizes");
+    for (i, &n) in total.histogram.iter().enumerate().filter(|(_, &n)| n > 0) {
+        println!("  {:>7} {:>8}", bucket_label(i), n);
+    }
+    Ok(())
+}
+
+/* ---------------------------- 练习 ---------------------------- */
+
+fn box_vs_array() -> usize {
+    // 对应 auto_ptr：栈上数组的移动是内存拷贝，没有堆分配
+    let arr = [0u8; 1000];
+    let arr1 = arr;
+    std::hint::black_box(&arr1);
+    // Box::new 分配一次 1000 字节，移动 Box 只拷贝指针
+    let boxed = Box::new([0u8; 1000]);
+    let moved = boxed;
+    moved.len()
+}
+
+pub fn alloc_practice() {
+    assert_eq!(bucket(0), 0);
+    assert_eq!(bucket(8), 0);
+    assert_eq!(bucket(9), 1);
+    assert_eq!(bucket(16), 1);
+    assert_eq!(bucket(1000), 7);
+    assert_eq!(bucket(usize::MAX), BUCKETS - 1);
+    assert_eq!(
+        (bucket_label(0), bucket_label(7)),
+        ("<=8".into(), "<=1K".into())
+    );
+    assert_eq!(bucket_label(BUCKETS - 1), ">128K");
+
+    // 对齐和跨块：大对象直接开一个足够大的新块，reset 后只留最后一块
+    let mut arena = Arena::with_capacity(64);
+    let a = arena.alloc(1u8);
+    *a += 1;
+    #[repr(align(64))]
+    struct Wide([u8; 64]);
+    let wide = arena.alloc(Wide([3; 64]));
+    assert_eq!((wide as *const Wide).addr() % 64, 0);
+    let big = arena.alloc_slice_copy(&[9u32; 5000]);
+    assert_eq!((big.len(), big[4999], *a, wide.0[63]), (5000, 9, 2, 3));
+    assert_eq!(arena.chunks(), 3);
+    assert_eq!(align_of::<Wide>(), 64);
+    arena.reset();
+    assert_eq!((arena.chunks(), arena.allocated()), (1, 0));
+    let zst: &mut () = arena.alloc(());
+    assert_eq!(std::mem::size_of_val(zst), 0);
+    assert_eq!(Arena::default().capacity(), 0);
+
+    // 下面的检查依赖 Tracking 统计到的分配
+    if !ENABLED {
+        println!(
+            "allocation tracking is disabled, run with --features track-allocs to see the counts"
+        );
+        return;
+    }
+
+    let (len, stats) = measure(box_vs_array);
+    assert_eq!(len, 1000);
+    assert_eq!((stats.allocs, stats.deallocs, stats.bytes), (1, 1, 1000));
+    assert_eq!((stats.peak, stats.live), (1000, 0));
+    assert_eq!(stats.histogram[bucket(1000)], 1);
+
+    let (array, stats) = measure(|| {
+        let arr = [7u32; 256];
+        arr.iter().sum::<u32>()
+    });
+    assert_eq!((array, stats.allocs), (1792, 0));
+
+    // Vec 逐个 push：第一次是 alloc，之后容量不够时 realloc，增长策略由标准库决定，这里只检查上下界
+    let (v, stats) = measure(|| (0..1000u32).collect::<Vec<_>>());
+    assert!(v.capacity() >= 1000);
+    assert_eq!(stats.allocs + stats.reallocs, 1);
+    let (v, stats) = measure(|| {
+        let mut v = Vec::new();
+        for i in 0..1000u32 {
+            v.push(i);
+        }
+        v
+    });
+    assert_eq!(stats.allocs, 1);
+    // 至少扩容一次，且按倍数增长时扩容次数不超过 log2(1000)
+    assert!((1..=10).contains(&stats.reallocs));
+    let bytes = (v.capacity() * 4) as u64;
+    assert_eq!((stats.live as u64, stats.peak), (bytes, bytes));
+    // 预先分配容量则只有一次 alloc
+    let (_, stats) = measure(|| {
+        let mut v = Vec::with_capacity(1000);
+        v.extend(0..1000u32);
+        v
+    });
+    assert_eq!((stats.allocs, stats.reallocs, stats.live), (1, 0, 4000));
+    print!("Vec push x1000: {}", stats);
+
+    // 返回值在 measure 结束后释放，不影响统计
+    let (s, stats) = measure(|| "x".repeat(100));
+    assert_eq!((stats.live, s.len()), (100, 100));
+    drop(s);
+
+    // 峰值：先分配 4K 释放后再分配 1K，峰值是 4K，最终 live 为 0
+    let (_, stats) = measure(|| {
+        drop(vec![0u8; 4096]);
+        drop(vec![0u8; 1024]);
+    });
+    assert_eq!((stats.peak, stats.live, stats.bytes), (4096, 0, 5120));
+
+    // 其他线程的分配不计入当前线程，但计入全局
+    let before = global();
+    let (_, stats) = measure(|| {
+        std::thread::spawn(|| vec![0u8; 10_000].len())
+            .join()
+            .unwrap();
+    });
+    let all = global().since(&before);
+    assert!(stats.bytes < 10_000);
+    assert!(all.bytes >= 10_000 + stats.bytes);
+
+    // bump 分配器：一批小对象只需要几次大块分配
+    let (total, stats) = measure(|| {
+        let arena = Arena::new();
+        let mut refs = Vec::with_capacity(1000);
+        for i in 0..1000u64 {
+            refs.push(&*arena.alloc(i));
+        }
+        let name = arena.alloc_str("arena");
+        assert_eq!(name, "arena");
+        assert_eq!(arena.allocated(), 8000 + 5);
+        assert!(arena.capacity() >= arena.allocated());
+        refs.iter().map(|&&x| x).sum::<u64>()
+    });
+    assert_eq!(total, 499_500);
+    // Vec<&u64> 一次 + 记录块的 Vec 一次 + 4K、8K 两个块
+    assert_eq!(stats.allocs, 4);
+    assert_eq!(stats.live, 0);
+    let (_, boxed) = measure(|| (0..1000u64).map(Box::new).collect::<Vec<_>>());
+    assert_eq!(boxed.allocs, 1001);
+    println!(
+        "1000 u64: Box {} allocs, Arena {} allocs",
+        boxed.allocs, stats.allocs
+    );
+
+    let g = global();
+    assert!(g.allocs >= g.deallocs && g.peak as i64 >= g.live);
+    print!("process so far: {}", g);
+}
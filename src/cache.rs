@@ -0,0 +1,673 @@
+//! 记忆化缓存
+//! closure 一章注释掉的 Cacher<T> 只缓存第一次的结果，之后无论参数是什么都返回同一个值
+//! 这里按参数缓存，支持 LRU、TTL 和按大小限制三种淘汰策略，并提供 RwLock 线程安全版本和合并并发请求的异步版本
+
+use std::collections::{BTreeMap, HashMap};
+use std::future::Future;
+use std::hash::Hash;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+use futures::future::{BoxFuture, FutureExt, Shared};
+
+pub type Clock = Arc<dyn Fn() -> Instant + Send + Sync>;
+
+// 计算单个条目的大小
+pub type Weigher<K, V> = fn(&K, &V) -> usize;
+
+/* ---------------------------- 淘汰策略 ---------------------------- */
+
+// 三种限制可以同时使用，超出容量或总大小时淘汰最久未使用的条目
+pub struct Policy<K, V> {
+    pub capacity: Option<usize>,
+    pub ttl: Option<Duration>,
+    // (总大小上限, 计算大小的函数)
+    pub max_weight: Option<(usize, Weigher<K, V>)>,
+}
+
+impl<K, V> Policy<K, V> {
+    pub fn unbounded() -> Self {
+        Policy {
+            capacity: None,
+            ttl: None,
+            max_weight: None,
+        }
+    }
+
+    pub fn lru(mut self, capacity: usize) -> Self {
+        self.capacity = Some(capacity);
+        self
+    }
+
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    pub fn max_weight(mut self, limit: usize, weigh: Weigher<K, V>) -> Self {
+        self.max_weight = Some((limit, weigh));
+        self
+    }
+}
+
+impl<K, V> Default for Policy<K, V> {
+    fn default() -> Self {
+        Self::unbounded()
+    }
+}
+
+/* ---------------------------- 统计 ---------------------------- */
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Stats {
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+    pub expirations: u64,
+    // 异步版本中等待其他请求加载结果的次数
+    pub coalesced: u64,
+}
+
+impl Stats {
+    pub fn hit_rate(&self) -> f64 {
+        let total = self.hits + self.misses;
+        if total == 0 {
+            0.0
+        } else {
+            self.hits as f64 / total as f64
+        }
+    }
+}
+
+// 命中只持有读锁，计数用原子变量
+#[derive(Default)]
+struct Counters {
+    hits: AtomicU64,
+    misses: AtomicU64,
+    evictions: AtomicU64,
+    expirations: AtomicU64,
+}
+
+fn bump(counter: &AtomicU64) {
+    counter.fetch_add(1, Ordering::Relaxed);
+}
+
+/* ---------------------------- 缓存 ---------------------------- */
+
+struct Entry<V> {
+    value: V,
+    // 最近一次使用的时刻，读取时只更新这里
+    used: AtomicU64,
+    // 在 order 中登记的时刻，淘汰时才与 used 同步
+    indexed: u64,
+    // 插入的时刻，用来区分同一个键先后插入的条目
+    born: u64,
+    expires: Option<Instant>,
+    weight: usize,
+}
+
+/*
+ * 用逻辑时钟 tick 记录使用顺序，order 按 tick 排列
+ * get 只需要 &self：命中时把新的 tick 写进条目的 used，不移动 order
+ * 淘汰时从 order 最小的一端取，如果条目的 used 已经变化说明后来被用过，按新的 tick 重新登记后继续，
+ * 遇到的第一个没有变化的条目就是最久未使用的
+ */
+pub struct Cache<K, V> {
+    map: HashMap<K, Entry<V>>,
+    order: BTreeMap<u64, K>,
+    // 过期时间相同时按插入时刻区分
+    expiry: BTreeMap<(Instant, u64), K>,
+    tick: AtomicU64,
+    weight: usize,
+    policy: Policy<K, V>,
+    clock: Clock,
+    counters: Counters,
+}
+
+impl<K: Hash + Eq + Clone, V> Cache<K, V> {
+    pub fn new(policy: Policy<K, V>) -> Self {
+        Self::with_clock(policy, Arc::new(Instant::now))
+    }
+
+    // 测试 TTL 时可以传入手动推进的时钟
+    pub fn with_clock(policy: Policy<K, V>, clock: Clock) -> Self {
+        Cache {
+            map: HashMap::new(),
+            order: BTreeMap::new(),
+            expiry: BTreeMap::new(),
+            tick: AtomicU64::new(0),
+            weight: 0,
+            policy,
+            clock,
+            counters: Counters::default(),
+        }
+    }
+
+    fn next_tick(&self) -> u64 {
+        self.tick.fetch_add(1, Ordering::Relaxed)
+    }
+
+    fn live(&self, k: &K) -> Option<&Entry<V>> {
+        let now = (self.clock)();
+        self.map
+            .get(k)
+            .filter(|e| e.expires.is_none_or(|t| t > now))
+    }
+
+    // 过期的条目视为不存在，留到下一次写入时清理
+    pub fn get(&self, k: &K) -> Option<&V> {
+        match self.live(k) {
+            Some(e) => {
+                e.used.store(self.next_tick(), Ordering::Relaxed);
+                bump(&self.counters.hits);
+                Some(&e.value)
+            }
+            None => {
+                bump(&self.counters.misses);
+                None
+            }
+        }
+    }
+
+    // 不计入统计，也不更新使用顺序
+    pub fn peek(&self, k: &K) -> Option<&V> {
+        self.live(k).map(|e| &e.value)
+    }
+
+    // 容量为 0 或单个条目超过总大小上限时不缓存，返回 false
+    pub fn insert(&mut self, k: K, v: V) -> bool {
+        self.purge();
+        self.remove(&k);
+        let weight = self.policy.max_weight.map_or(0, |(_, weigh)| weigh(&k, &v));
+        if self.policy.capacity == Some(0)
+            || self
+                .policy
+                .max_weight
+                .is_some_and(|(limit, _)| weight > limit)
+        {
+            return false;
+        }
+        let tick = self.next_tick();
+        let expires = self.policy.ttl.map(|ttl| (self.clock)() + ttl);
+        if let Some(t) = expires {
+            self.expiry.insert((t, tick), k.clone());
+        }
+        self.order.insert(tick, k.clone());
+        self.weight += weight;
+        self.map.insert(
+            k,
+            Entry {
+                value: v,
+                used: AtomicU64::new(tick),
+                indexed: tick,
+                born: tick,
+                expires,
+                weight,
+            },
+        );
+        while self.over_budget() {
+            self.evict_lru();
+        }
+        true
+    }
+
+    fn over_budget(&self) -> bool {
+        self.policy.capacity.is_some_and(|c| self.map.len() > c)
+            || self
+                .policy
+                .max_weight
+                .is_some_and(|(limit, _)| self.weight > limit)
+    }
+
+    fn evict_lru(&mut self) {
+        while let Some((tick, k)) = self.order.pop_first() {
+            let entry = self.map.get_mut(&k).expect("order and map out of sync");
+            let used = *entry.used.get_mut();
+            if used != tick {
+                entry.indexed = used;
+                self.order.insert(used, k);
+                continue;
+            }
+            self.remove(&k);
+            bump(&self.counters.evictions);
+            return;
+        }
+    }
+
+    pub fn remove(&mut self, k: &K) -> Option<V> {
+        let entry = self.map.remove(k)?;
+        self.order.remove(&entry.indexed);
+        if let Some(t) = entry.expires {
+            self.expiry.remove(&(t, entry.born));
+        }
+        self.weight -= entry.weight;
+        Some(entry.value)
+    }
+
+    // 删除所有过期的条目
+    pub fn purge(&mut self) {
+        let now = (self.clock)();
+        while let Some(entry) = self.expiry.first_entry() {
+            if entry.key().0 > now {
+                break;
+            }
+            let k = entry.remove();
+            self.remove(&k);
+            bump(&self.counters.expirations);
+        }
+    }
+
+    // 包含已经过期但还没有清理的条目
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    pub fn weight(&self) -> usize {
+        self.weight
+    }
+
+    pub fn clear(&mut self) {
+        self.map.clear();
+        self.order.clear();
+        self.expiry.clear();
+        self.weight = 0;
+    }
+
+    pub fn stats(&self) -> Stats {
+        let load = |c: &AtomicU64| c.load(Ordering::Relaxed);
+        Stats {
+            hits: load(&self.counters.hits),
+            misses: load(&self.counters.misses),
+            evictions: load(&self.counters.evictions),
+            expirations: load(&self.counters.expirations),
+            coalesced: 0,
+        }
+    }
+
+    // 按最近使用的先后排列，用于检查淘汰顺序
+    pub fn keys_by_recency(&self) -> Vec<&K> {
+        let mut keys: Vec<(u64, &K)> = self
+            .map
+            .iter()
+            .map(|(k, e)| (e.used.load(Ordering::Relaxed), k))
+            .collect();
+        keys.sort_by_key(|&(used, _)| std::cmp::Reverse(used));
+        keys.into_iter().map(|(_, k)| k).collect()
+    }
+}
+
+/* ---------------------------- 记忆化 ---------------------------- */
+
+// 按参数缓存 query 的结果
+pub struct Cacher<F, A, R> {
+    query: F,
+    cache: Cache<A, R>,
+}
+
+impl<F, A, R> Cacher<F, A, R>
+where
+    F: Fn(A) -> R,
+    A: Hash + Eq + Clone,
+    R: Clone,
+{
+    pub fn new(query: F) -> Self {
+        Self::with_policy(query, Policy::unbounded())
+    }
+
+    pub fn with_policy(query: F, policy: Policy<A, R>) -> Self {
+        Cacher {
+            query,
+            cache: Cache::new(policy),
+        }
+    }
+
+    // 先查询缓存，若不存在，则调用 query 加载
+    pub fn value(&mut self, arg: A) -> R {
+        if let Some(v) = self.cache.get(&arg) {
+            return v.clone();
+        }
+        let v = (self.query)(arg.clone());
+        self.cache.insert(arg, v.clone());
+        v
+    }
+
+    pub fn cache(&self) -> &Cache<A, R> {
+        &self.cache
+    }
+
+    pub fn stats(&self) -> Stats {
+        self.cache.stats()
+    }
+}
+
+// 命中只需要读锁；未命中时在锁外计算，多个线程可能同时计算同一个参数
+pub struct SyncCacher<F, A, R> {
+    query: F,
+    cache: RwLock<Cache<A, R>>,
+}
+
+impl<F, A, R> SyncCacher<F, A, R>
+where
+    F: Fn(A) -> R,
+    A: Hash + Eq + Clone,
+    R: Clone,
+{
+    pub fn new(query: F, policy: Policy<A, R>) -> Self {
+        SyncCacher {
+            query,
+            cache: RwLock::new(Cache::new(policy)),
+        }
+    }
+
+    pub fn value(&self, arg: A) -> R {
+        if let Some(v) = self.cache.read().unwrap().get(&arg) {
+            return v.clone();
+        }
+        let v = (self.query)(arg.clone());
+        self.cache.write().unwrap().insert(arg, v.clone());
+        v
+    }
+
+    pub fn stats(&self) -> Stats {
+        self.cache.read().unwrap().stats()
+    }
+}
+
+/*
+ * 异步加载：同一个参数同时只有一个加载任务，其余请求等待同一个 Shared future
+ * 第一个拿到结果的请求负责写入缓存并删除 inflight 中的记录，
+ * 之后这个参数可能已经开始了新一轮加载，其余请求醒来时不能删除新的记录，
+ * 如果发起加载的请求被取消，等待中的请求仍会继续推动加载完成
+ */
+pub struct AsyncCacher<F, A, R> {
+    loader: F,
+    cache: RwLock<Cache<A, R>>,
+    inflight: Mutex<HashMap<A, Shared<BoxFuture<'static, R>>>>,
+    coalesced: AtomicU64,
+}
+
+impl<F, Fut, A, R> AsyncCacher<F, A, R>
+where
+    F: Fn(A) -> Fut,
+    Fut: Future<Output = R> + Send + 'static,
+    A: Hash + Eq + Clone,
+    R: Clone,
+{
+    pub fn new(loader: F, policy: Policy<A, R>) -> Self {
+        AsyncCacher {
+            loader,
+            cache: RwLock::new(Cache::new(policy)),
+            inflight: Mutex::new(HashMap::new()),
+            coalesced: AtomicU64::new(0),
+        }
+    }
+
+    pub async fn value(&self, arg: A) -> R {
+        if let Some(v) = self.cache.read().unwrap().get(&arg) {
+            return v.clone();
+        }
+        let load = {
+            let mut inflight = self.inflight.lock().unwrap();
+            // 持有 inflight 锁时再查一次，结果可能刚刚写入缓存
+            if let Some(v) = self.cache.read().unwrap().peek(&arg) {
+                return v.clone();
+            }
+            match inflight.get(&arg) {
+                Some(load) => {
+                    bump(&self.coalesced);
+                    load.clone()
+                }
+                None => {
+                    let load = (self.loader)(arg.clone()).boxed().shared();
+                    inflight.insert(arg.clone(), load.clone());
+                    load
+                }
+            }
+        };
+        let v = load.clone().await;
+        let mut inflight = self.inflight.lock().unwrap();
+        if inflight
+            .get(&arg)
+            .is_some_and(|current| current.ptr_eq(&load))
+        {
+            inflight.remove(&arg);
+            self.cache.write().unwrap().insert(arg, v.clone());
+        }
+        v
+    }
+
+    pub fn stats(&self) -> Stats {
+        Stats {
+            coalesced: self.coalesced.load(Ordering::Relaxed),
+            ..self.cache.read().unwrap().stats()
+        }
+    }
+}
+
+/* ---------------------------- 练习 ---------------------------- */
+
+// 手动推进的时钟
+fn manual_clock() -> (Clock, Arc<AtomicU64>) {
+    let base = Instant::now();
+    let millis = Arc::new(AtomicU64::new(0));
+    let m = millis.clone();
+    let clock: Clock = Arc::new(move || base + Duration::from_millis(m.load(Ordering::Relaxed)));
+    (clock, millis)
+}
+
+pub fn cache_practice() {
+    // 原来的 Cacher 对任何参数都返回第一次的结果，这里按参数缓存
+    let calls = AtomicU64::new(0);
+    let mut square = Cacher::new(|x: u32| {
+        bump(&calls);
+        x * x
+    });
+    assert_eq!(square.value(2), 4);
+    assert_eq!(square.value(3), 9);
+    assert_eq!(square.value(2), 4);
+    assert_eq!(calls.load(Ordering::Relaxed), 2);
+    let stats = square.stats();
+    assert_eq!((stats.hits, stats.misses), (1, 2));
+    assert!((stats.hit_rate() - 1.0 / 3.0).abs() < 1e-9);
+    assert_eq!(square.cache().len(), 2);
+
+    // LRU：读取会刷新顺序
+    let mut lru = Cache::new(Policy::unbounded().lru(3));
+    for k in 1..=3 {
+        lru.insert(k, k * 10);
+    }
+    assert_eq!(lru.get(&1), Some(&10));
+    lru.insert(4, 40);
+    assert_eq!(lru.peek(&2), None);
+    assert_eq!(lru.keys_by_recency(), [&4, &1, &3]);
+    lru.get(&3);
+    lru.insert(5, 50);
+    lru.insert(6, 60);
+    assert_eq!(lru.keys_by_recency(), [&6, &5, &3]);
+    assert_eq!(lru.stats().evictions, 3);
+    // 覆盖已有的键不会淘汰其他条目
+    lru.insert(5, 55);
+    assert_eq!(lru.len(), 3);
+    assert_eq!(lru.remove(&5), Some(55));
+    assert_eq!(lru.len(), 2);
+
+    // TTL
+    let (clock, millis) = manual_clock();
+    let mut ttl = Cache::with_clock(Policy::unbounded().ttl(Duration::from_secs(1)), clock);
+    ttl.insert("a", 1);
+    millis.store(600, Ordering::Relaxed);
+    ttl.insert("b", 2);
+    assert_eq!(ttl.get(&"a"), Some(&1));
+    millis.store(1000, Ordering::Relaxed);
+    assert_eq!(ttl.get(&"a"), None);
+    assert_eq!(ttl.get(&"b"), Some(&2));
+    assert_eq!(ttl.len(), 2);
+    ttl.purge();
+    assert_eq!(ttl.len(), 1);
+    // 重新插入会刷新过期时间
+    ttl.insert("b", 3);
+    millis.store(1700, Ordering::Relaxed);
+    assert_eq!(ttl.get(&"b"), Some(&3));
+    millis.store(2600, Ordering::Relaxed);
+    ttl.purge();
+    assert!(ttl.is_empty());
+    assert_eq!(ttl.stats().expirations, 2);
+
+    // 按大小限制
+    let policy = Policy::unbounded().max_weight(10, |_: &u32, v: &String| v.len());
+    let mut sized = Cache::new(policy);
+    sized.insert(1, "abcd".to_string());
+    sized.insert(2, "efgh".to_string());
+    sized.get(&1);
+    sized.insert(3, "ijk".to_string());
+    assert_eq!(sized.peek(&2), None);
+    assert_eq!(sized.weight(), 7);
+    assert!(!sized.insert(4, "x".repeat(11)));
+    assert_eq!(sized.len(), 2);
+    sized.clear();
+    assert_eq!((sized.len(), sized.weight()), (0, 0));
+
+    // 与哈希表逐步比较，检查淘汰总是选中最久未使用的条目
+    let mut rng = crate::hasher_bench::XorShift::new(43);
+    let mut cache = Cache::new(Policy::unbounded().lru(8));
+    let mut model: Vec<u64> = Vec::new();
+    for _ in 0..5000 {
+        let k = rng.next_u64() % 16;
+        if rng.next_u64().is_multiple_of(2) {
+            let hit = cache.get(&k).is_some();
+            assert_eq!(hit, model.contains(&k));
+            if hit {
+                model.retain(|&m| m != k);
+                model.push(k);
+            }
+        } else {
+            cache.insert(k, k);
+            model.retain(|&m| m != k);
+            model.push(k);
+            if model.len() > 8 {
+                model.remove(0);
+            }
+        }
+        let expect: Vec<&u64> = model.iter().rev().collect();
+        assert_eq!(cache.keys_by_recency(), expect);
+    }
+
+    // 多线程
+    let calls = AtomicU64::new(0);
+    let shared = SyncCacher::new(
+        |n: u64| {
+            bump(&calls);
+            (1..=n).product::<u64>()
+        },
+        Policy::unbounded().lru(64),
+    );
+    std::thread::scope(|s| {
+        for t in 0..4 {
+            let shared = &shared;
+            s.spawn(move || {
+                for i in 0..200u64 {
+                    assert_eq!(shared.value((i + t) % 20), (1..=(i + t) % 20).product());
+                }
+            });
+        }
+    });
+    let stats = shared.stats();
+    assert_eq!(stats.hits + stats.misses, 800);
+    assert!(calls.load(Ordering::Relaxed) >= 20);
+    assert_eq!(stats.misses, calls.load(Ordering::Relaxed));
+
+    // 异步：同时请求同一个参数只加载一次
+    let loads = Arc::new(AtomicU64::new(0));
+    let (open, gate) = futures::channel::oneshot::channel::<()>();
+    let gate = gate.shared();
+    let l = loads.clone();
+    let users = AsyncCacher::new(
+        move |id: u32| {
+            bump(&l);
+            let gate = gate.clone();
+            async move {
+                let _ = gate.await;
+                format!("user-{}", id)
+            }
+        },
+        Policy::unbounded(),
+    );
+    let (a, b, c, ()) = futures::executor::block_on(async {
+        futures::join!(users.value(1), users.value(1), users.value(2), async {
+            open.send(()).unwrap();
+        })
+    });
+    assert_eq!(
+        (a.as_str(), b.as_str(), c.as_str()),
+        ("user-1", "user-1", "user-2")
+    );
+    assert_eq!(loads.load(Ordering::Relaxed), 2);
+    assert_eq!(futures::executor::block_on(users.value(1)), "user-1");
+    let stats = users.stats();
+    assert_eq!(loads.load(Ordering::Relaxed), 2);
+    assert_eq!((stats.hits, stats.misses, stats.coalesced), (1, 3, 1));
+
+    println!("{:?}", shared.stats());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::channel::oneshot;
+
+    #[test]
+    fn zero_capacity_caches_nothing() {
+        let mut cache = Cache::new(Policy::unbounded().lru(0));
+        assert!(!cache.insert(1, 10));
+        assert_eq!((cache.len(), cache.peek(&1)), (0, None));
+        assert_eq!(cache.stats().evictions, 0);
+    }
+
+    #[test]
+    fn late_waiter_keeps_the_next_load() {
+        // 每次加载返回一个由测试控制何时完成的 future
+        let senders = Arc::new(Mutex::new(Vec::new()));
+        let loader = {
+            let senders = senders.clone();
+            move |_: u32| {
+                let (tx, rx) = oneshot::channel::<u32>();
+                senders.lock().unwrap().push(tx);
+                async move { rx.await.unwrap() }
+            }
+        };
+        let complete = |i: usize, v: u32| {
+            let tx = std::mem::replace(&mut senders.lock().unwrap()[i], oneshot::channel().0);
+            tx.send(v).unwrap();
+        };
+        let cacher = AsyncCacher::new(loader, Policy::unbounded().lru(1));
+
+        // a 发起第一轮加载，b 等待同一个加载
+        let mut a = Box::pin(cacher.value(1));
+        let mut b = Box::pin(cacher.value(1));
+        assert_eq!((&mut a).now_or_never(), None);
+        assert_eq!((&mut b).now_or_never(), None);
+        complete(0, 10);
+        assert_eq!(a.now_or_never(), Some(10));
+
+        // 1 被淘汰后 c 发起第二轮加载，这时 b 才醒来
+        let mut two = Box::pin(cacher.value(2));
+        assert_eq!((&mut two).now_or_never(), None);
+        complete(1, 20);
+        assert_eq!(two.now_or_never(), Some(20));
+        let mut c = Box::pin(cacher.value(1));
+        assert_eq!((&mut c).now_or_never(), None);
+        assert_eq!(b.now_or_never(), Some(10));
+
+        // b 没有删除第二轮的记录，第二轮的结果写入缓存
+        assert_eq!(cacher.inflight.lock().unwrap().len(), 1);
+        complete(2, 11);
+        assert_eq!(c.now_or_never(), Some(11));
+        assert_eq!(cacher.cache.read().unwrap().peek(&1), Some(&11));
+        assert!(cacher.inflight.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,1996 @@
+//! 表达式计算器
+//! 对应 doc/表达式小作业.md：字面量表达式、路径表达式、块表达式和操作符优先级
+
+use std::collections::HashMap;
+use std::fmt;
+use std::io::BufRead;
+
+/*
+ * 操作符优先级(从高到低)，和 Rust 参考手册一致：
+ *   方法调用       x.pow(2)
+ *   一元运算       -  !
+ *   as             左结合
+ *   * / %          左结合
+ *   + -            左结合
+ *   << >>          左结合
+ *   &              左结合
+ *   ^              左结合
+ *   |              左结合
+ *   == != < > <= >= 需要括号，不能连写
+ *   &&             左结合
+ *   ||             左结合
+ *
+ * 溢出策略对应 int_overflow 中的四类方法：
+ *   Checked      checked_*，溢出即报错
+ *   Wrapping     wrapping_*，静默按补码回绕
+ *   Saturating   saturating_*，静默限制在最大/最小值
+ *   Overflowing  overflowing_*，回绕并记录一条警告
+ */
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    fn to(self, other: Span) -> Span {
+        Span {
+            start: self.start,
+            end: other.end,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Level {
+    Error,
+    Warning,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub level: Level,
+    pub message: String,
+    pub span: Span,
+}
+
+impl Diagnostic {
+    fn error(message: impl Into<String>, span: Span) -> Self {
+        Diagnostic {
+            level: Level::Error,
+            message: message.into(),
+            span,
+        }
+    }
+
+    // 仿照 rustc 的格式，用 ^^^ 标出出错的位置
+    pub fn render(&self, src: &str) -> String {
+        let level = match self.level {
+            Level::Error => "error",
+            Level::Warning => "warning",
+        };
+        let mut line_start = 0;
+        let mut line_no = 1;
+        for (i, c) in src.char_indices() {
+            if i >= self.span.start {
+                break;
+            }
+            if c == '\n' {
+                line_start = i + 1;
+                line_no += 1;
+            }
+        }
+        let line_end = src[line_start..]
+            .find('\n')
+            .map_or(src.len(), |i| line_start + i);
+        let line = &src[line_start..line_end];
+        let end = self.span.end.clamp(self.span.start, line_end);
+        let pad = src[line_start..self.span.start].chars().count();
+        let width = src[self.span.start..end].chars().count().max(1);
+        let gutter = " ".repeat(line_no.to_string().len());
+        format!(
+            "{}: {}\n{} --> {}:{}\n{} |\n{} | {}\n{} | {}{}",
+            level,
+            self.message,
+            gutter,
+            line_no,
+            pad + 1,
+            gutter,
+            line_no,
+            line,
+            gutter,
+            " ".repeat(pad),
+            "^".repeat(width)
+        )
+    }
+}
+
+/* ---------------------------- 类型和值 ---------------------------- */
+
+// 根据类型在运行时选择具体的原生整数类型
+macro_rules! with_int_ty {
+    ($ty:expr, $t:ident => $e:expr) => {
+        match $ty {
+            IntTy::I8 => {
+                type $t = i8;
+                $e
+            }
+            IntTy::I16 => {
+                type $t = i16;
+                $e
+            }
+            IntTy::I32 => {
+                type $t = i32;
+                $e
+            }
+            IntTy::I64 => {
+                type $t = i64;
+                $e
+            }
+            IntTy::U8 => {
+                type $t = u8;
+                $e
+            }
+            IntTy::U16 => {
+                type $t = u16;
+                $e
+            }
+            IntTy::U32 => {
+                type $t = u32;
+                $e
+            }
+            IntTy::U64 => {
+                type $t = u64;
+                $e
+            }
+        }
+    };
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IntTy {
+    I8,
+    I16,
+    I32,
+    I64,
+    U8,
+    U16,
+    U32,
+    U64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FloatTy {
+    F32,
+    F64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Ty {
+    Int(IntTy),
+    Float(FloatTy),
+    Bool,
+}
+
+impl IntTy {
+    pub fn name(self) -> &'static str {
+        match self {
+            IntTy::I8 => "i8",
+            IntTy::I16 => "i16",
+            IntTy::I32 => "i32",
+            IntTy::I64 => "i64",
+            IntTy::U8 => "u8",
+            IntTy::U16 => "u16",
+            IntTy::U32 => "u32",
+            IntTy::U64 => "u64",
+        }
+    }
+
+    fn min(self) -> i128 {
+        with_int_ty!(self, T => T::MIN as i128)
+    }
+
+    fn max(self) -> i128 {
+        with_int_ty!(self, T => T::MAX as i128)
+    }
+}
+
+impl FloatTy {
+    pub fn name(self) -> &'static str {
+        match self {
+            FloatTy::F32 => "f32",
+            FloatTy::F64 => "f64",
+        }
+    }
+}
+
+impl Ty {
+    pub fn parse(name: &str) -> Option<Ty> {
+        Some(match name {
+            "i8" => Ty::Int(IntTy::I8),
+            "i16" => Ty::Int(IntTy::I16),
+            "i32" => Ty::Int(IntTy::I32),
+            "i64" => Ty::Int(IntTy::I64),
+            "u8" => Ty::Int(IntTy::U8),
+            "u16" => Ty::Int(IntTy::U16),
+            "u32" => Ty::Int(IntTy::U32),
+            "u64" => Ty::Int(IntTy::U64),
+            "f32" => Ty::Float(FloatTy::F32),
+            "f64" => Ty::Float(FloatTy::F64),
+            "bool" => Ty::Bool,
+            _ => return None,
+        })
+    }
+}
+
+impl fmt::Display for Ty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Ty::Int(t) => write!(f, "{}", t.name()),
+            Ty::Float(t) => write!(f, "{}", t.name()),
+            Ty::Bool => write!(f, "bool"),
+        }
+    }
+}
+
+/*
+ * 没有后缀的字面量(比如 1 或 0.5)是"待推断"的，和另一边有确定类型的操作数运算时采用对方的类型，
+ * 两边都待推断时使用会话的默认类型
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Value {
+    Int {
+        value: i128,
+        ty: IntTy,
+        inferred: bool,
+    },
+    Float {
+        value: f64,
+        ty: FloatTy,
+        inferred: bool,
+    },
+    Bool(bool),
+}
+
+impl Value {
+    pub fn ty(&self) -> Ty {
+        match self {
+            Value::Int { ty, .. } => Ty::Int(*ty),
+            Value::Float { ty, .. } => Ty::Float(*ty),
+            Value::Bool(_) => Ty::Bool,
+        }
+    }
+
+    fn concrete(self) -> Value {
+        match self {
+            Value::Int { value, ty, .. } => Value::Int {
+                value,
+                ty,
+                inferred: false,
+            },
+            Value::Float { value, ty, .. } => Value::Float {
+                value,
+                ty,
+                inferred: false,
+            },
+            v => v,
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Int { value, ty, .. } => write!(f, "{}{}", value, ty.name()),
+            Value::Float { value, ty, .. } => match ty {
+                FloatTy::F32 => write!(f, "{:?}{}", *value as f32, ty.name()),
+                FloatTy::F64 => write!(f, "{:?}{}", value, ty.name()),
+            },
+            Value::Bool(b) => write!(f, "{}", b),
+        }
+    }
+}
+
+/* ---------------------------- 溢出策略 ---------------------------- */
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    Checked,
+    Wrapping,
+    Saturating,
+    Overflowing,
+}
+
+impl OverflowPolicy {
+    pub fn parse(name: &str) -> Option<OverflowPolicy> {
+        match name {
+            "checked" => Some(OverflowPolicy::Checked),
+            "wrapping" => Some(OverflowPolicy::Wrapping),
+            "saturating" => Some(OverflowPolicy::Saturating),
+            "overflowing" => Some(OverflowPolicy::Overflowing),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ArithError {
+    // 溢出，附带 rustc 风格的描述
+    Overflow(&'static str),
+    DivideByZero,
+    RemainderByZero,
+    // 该类型没有对应运算，比如对无符号数取负
+    Unsupported,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    Shl,
+    Shr,
+    BitAnd,
+    BitXor,
+    BitOr,
+    Eq,
+    Ne,
+    Lt,
+    Gt,
+    Le,
+    Ge,
+    And,
+    Or,
+}
+
+impl BinOp {
+    fn symbol(self) -> &'static str {
+        match self {
+            BinOp::Add => "+",
+            BinOp::Sub => "-",
+            BinOp::Mul => "*",
+            BinOp::Div => "/",
+            BinOp::Rem => "%",
+            BinOp::Shl => "<<",
+            BinOp::Shr => ">>",
+            BinOp::BitAnd => "&",
+            BinOp::BitXor => "^",
+            BinOp::BitOr => "|",
+            BinOp::Eq => "==",
+            BinOp::Ne => "!=",
+            BinOp::Lt => "<",
+            BinOp::Gt => ">",
+            BinOp::Le => "<=",
+            BinOp::Ge => ">=",
+            BinOp::And => "&&",
+            BinOp::Or => "||",
+        }
+    }
+
+    fn is_comparison(self) -> bool {
+        matches!(
+            self,
+            BinOp::Eq | BinOp::Ne | BinOp::Lt | BinOp::Gt | BinOp::Le | BinOp::Ge
+        )
+    }
+}
+
+// 原生整数的溢出运算族，由宏为每个整数类型实现
+trait PrimInt: Copy + PartialOrd + Into<i128> {
+    const SIGNED: bool;
+
+    fn from_i128(v: i128) -> Self;
+    // 返回回绕后的结果和是否溢出
+    fn overflowing(op: BinOp, a: Self, b: Self) -> Result<(Self, bool), ArithError>;
+    // 没有 saturating 形式的运算返回 None，退回到 checked 行为
+    fn saturating(op: BinOp, a: Self, b: Self) -> Option<Self>;
+    fn overflowing_shift(op: BinOp, a: Self, rhs: u32) -> (Self, bool);
+    fn overflowing_pow(self, exp: u32) -> (Self, bool);
+    fn saturating_pow(self, exp: u32) -> Self;
+    fn overflowing_neg(self) -> (Self, bool);
+    fn saturating_neg(self) -> Self;
+    fn overflowing_abs(self) -> (Self, bool);
+    fn saturating_abs(self) -> Self;
+    fn not(self) -> Self;
+}
+
+macro_rules! prim_int {
+    ($signed:expr; $($t:ty),*) => {$(
+        impl PrimInt for $t {
+            const SIGNED: bool = $signed;
+
+            fn from_i128(v: i128) -> Self {
+                v as $t
+            }
+
+            fn overflowing(op: BinOp, a: Self, b: Self) -> Result<(Self, bool), ArithError> {
+                Ok(match op {
+                    BinOp::Add => a.overflowing_add(b),
+                    BinOp::Sub => a.overflowing_sub(b),
+                    BinOp::Mul => a.overflowing_mul(b),
+                    BinOp::Div if b == 0 => return Err(ArithError::DivideByZero),
+                    BinOp::Div => a.overflowing_div(b),
+                    BinOp::Rem if b == 0 => return Err(ArithError::RemainderByZero),
+                    BinOp::Rem => a.overflowing_rem(b),
+                    BinOp::BitAnd => (a & b, false),
+                    BinOp::BitXor => (a ^ b, false),
+                    BinOp::BitOr => (a | b, false),
+                    _ => return Err(ArithError::Unsupported),
+                })
+            }
+
+            fn saturating(op: BinOp, a: Self, b: Self) -> Option<Self> {
+                match op {
+                    BinOp::Add => Some(a.saturating_add(b)),
+                    BinOp::Sub => Some(a.saturating_sub(b)),
+                    BinOp::Mul => Some(a.saturating_mul(b)),
+                    BinOp::Div if b != 0 => Some(a.saturating_div(b)),
+                    _ => None,
+                }
+            }
+
+            fn overflowing_shift(op: BinOp, a: Self, rhs: u32) -> (Self, bool) {
+                if op == BinOp::Shl {
+                    a.overflowing_shl(rhs)
+                } else {
+                    a.overflowing_shr(rhs)
+                }
+            }
+
+            fn overflowing_pow(self, exp: u32) -> (Self, bool) {
+                <$t>::overflowing_pow(self, exp)
+            }
+
+            fn saturating_pow(self, exp: u32) -> Self {
+                <$t>::saturating_pow(self, exp)
+            }
+
+            fn overflowing_neg(self) -> (Self, bool) {
+                <$t>::overflowing_neg(self)
+            }
+
+            fn saturating_neg(self) -> Self {
+                (0 as $t).saturating_sub(self)
+            }
+
+            fn overflowing_abs(self) -> (Self, bool) {
+                if self < (0 as $t) {
+                    self.overflowing_neg()
+                } else {
+                    (self, false)
+                }
+            }
+
+            fn saturating_abs(self) -> Self {
+                if self < (0 as $t) {
+                    PrimInt::saturating_neg(self)
+                } else {
+                    self
+                }
+            }
+
+            fn not(self) -> Self {
+                !self
+            }
+        }
+    )*};
+}
+
+prim_int!(true; i8, i16, i32, i64);
+prim_int!(false; u8, u16, u32, u64);
+
+// 根据策略把 (回绕结果, 是否溢出, 饱和结果) 合成最终结果
+fn apply_policy<T: PrimInt>(
+    policy: OverflowPolicy,
+    wrapped: (T, bool),
+    saturated: Option<T>,
+    what: &'static str,
+) -> Result<(T, bool), ArithError> {
+    let (value, overflow) = wrapped;
+    if !overflow {
+        return Ok((value, false));
+    }
+    match policy {
+        OverflowPolicy::Checked => Err(ArithError::Overflow(what)),
+        OverflowPolicy::Wrapping => Ok((value, false)),
+        OverflowPolicy::Overflowing => Ok((value, true)),
+        OverflowPolicy::Saturating => saturated
+            .map(|v| (v, false))
+            .ok_or(ArithError::Overflow(what)),
+    }
+}
+
+fn int_binary<T: PrimInt>(
+    op: BinOp,
+    a: i128,
+    b: i128,
+    policy: OverflowPolicy,
+) -> Result<(i128, bool), ArithError> {
+    let (a, b) = (T::from_i128(a), T::from_i128(b));
+    let what = match op {
+        BinOp::Add => "attempt to add with overflow",
+        BinOp::Sub => "attempt to subtract with overflow",
+        BinOp::Mul => "attempt to multiply with overflow",
+        BinOp::Div => "attempt to divide with overflow",
+        _ => "attempt to calculate the remainder with overflow",
+    };
+    let wrapped = T::overflowing(op, a, b)?;
+    let (v, o) = apply_policy(policy, wrapped, T::saturating(op, a, b), what)?;
+    Ok((v.into(), o))
+}
+
+fn int_shift<T: PrimInt>(
+    op: BinOp,
+    a: i128,
+    rhs: i128,
+    policy: OverflowPolicy,
+) -> Result<(i128, bool), ArithError> {
+    let what = if op == BinOp::Shl {
+        "attempt to shift left with overflow"
+    } else {
+        "attempt to shift right with overflow"
+    };
+    // 负数或超出 u32 的移位量一定溢出
+    let wrapped = match u32::try_from(rhs) {
+        Ok(r) => T::overflowing_shift(op, T::from_i128(a), r),
+        Err(_) => (
+            T::overflowing_shift(op, T::from_i128(a), rhs as u32).0,
+            true,
+        ),
+    };
+    // 标准库没有 saturating_shl，饱和模式下移位溢出按 checked 处理
+    let (v, o) = apply_policy(policy, wrapped, None, what)?;
+    Ok((v.into(), o))
+}
+
+fn int_unary<T: PrimInt>(
+    op: &str,
+    a: i128,
+    arg: Option<u32>,
+    policy: OverflowPolicy,
+) -> Result<(i128, bool), ArithError> {
+    let a = T::from_i128(a);
+    let (v, o) = match op {
+        "neg" if T::SIGNED => apply_policy(
+            policy,
+            a.overflowing_neg(),
+            Some(PrimInt::saturating_neg(a)),
+            "attempt to negate with overflow",
+        )?,
+        "abs" if T::SIGNED => apply_policy(
+            policy,
+            a.overflowing_abs(),
+            Some(a.saturating_abs()),
+            "attempt to negate with overflow",
+        )?,
+        "pow" => {
+            let exp = arg.unwrap_or(0);
+            apply_policy(
+                policy,
+                a.overflowing_pow(exp),
+                Some(a.saturating_pow(exp)),
+                "attempt to multiply with overflow",
+            )?
+        }
+        "not" => (a.not(), false),
+        _ => return Err(ArithError::Unsupported),
+    };
+    Ok((v.into(), o))
+}
+
+/* ---------------------------- 词法分析 ---------------------------- */
+
+#[derive(Debug, Clone, PartialEq)]
+enum Tok {
+    Int(u128, Option<Ty>),
+    Float(f64, Option<FloatTy>),
+    Ident(String),
+    Op(&'static str),
+    Eof,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Token {
+    tok: Tok,
+    span: Span,
+}
+
+// 多字符的操作符要排在前面，保证最长匹配
+const PUNCTS: [&str; 28] = [
+    "<<", ">>", "==", "!=", "<=", ">=", "&&", "||", "+", "-", "*", "/", "%", "&", "^", "|", "<",
+    ">", "!", "(", ")", "{", "}", ";", ":", ",", ".", "=",
+];
+
+fn lex(src: &str) -> Result<Vec<Token>, Diagnostic> {
+    let bytes = src.as_bytes();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let c = bytes[i];
+        if c.is_ascii_whitespace() {
+            i += 1;
+            continue;
+        }
+        // 行注释
+        if src[i..].starts_with("//") {
+            while i < bytes.len() && bytes[i] != b'\n' {
+                i += 1;
+            }
+            continue;
+        }
+        let start = i;
+        if c.is_ascii_digit() {
+            tokens.push(lex_number(src, &mut i)?);
+            continue;
+        }
+        if c.is_ascii_alphabetic() || c == b'_' {
+            while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_') {
+                i += 1;
+            }
+            tokens.push(Token {
+                tok: Tok::Ident(src[start..i].to_string()),
+                span: Span { start, end: i },
+            });
+            continue;
+        }
+        match PUNCTS.iter().find(|p| src[i..].starts_with(**p)) {
+            Some(p) => {
+                i += p.len();
+                tokens.push(Token {
+                    tok: Tok::Op(p),
+                    span: Span { start, end: i },
+                });
+            }
+            None => {
+                let len = src[i..].chars().next().map_or(1, char::len_utf8);
+                return Err(Diagnostic::error(
+                    format!("unknown start of token: {}", &src[i..i + len]),
+                    Span {
+                        start,
+                        end: start + len,
+                    },
+                ));
+            }
+        }
+    }
+    tokens.push(Token {
+        tok: Tok::Eof,
+        span: Span {
+            start: src.len(),
+            end: src.len(),
+        },
+    });
+    Ok(tokens)
+}
+
+fn lex_number(src: &str, i: &mut usize) -> Result<Token, Diagnostic> {
+    let bytes = src.as_bytes();
+    let start = *i;
+    let radix = match src.get(start..start + 2) {
+        Some("0x") => 16,
+        Some("0o") => 8,
+        Some("0b") => 2,
+        _ => 10,
+    };
+    if radix != 10 {
+        *i += 2;
+    }
+    let digits_start = *i;
+    while *i < bytes.len()
+        && (bytes[*i].is_ascii_hexdigit() && radix == 16
+            || bytes[*i].is_ascii_digit()
+            || bytes[*i] == b'_')
+    {
+        *i += 1;
+    }
+    let mut is_float = false;
+    // 1.5 是浮点数，但 2.pow(3) 中的 . 是方法调用
+    if radix == 10 && *i + 1 < bytes.len() && bytes[*i] == b'.' && bytes[*i + 1].is_ascii_digit() {
+        is_float = true;
+        *i += 1;
+        while *i < bytes.len() && (bytes[*i].is_ascii_digit() || bytes[*i] == b'_') {
+            *i += 1;
+        }
+    }
+    if radix == 10 && *i < bytes.len() && (bytes[*i] == b'e' || bytes[*i] == b'E') {
+        let mut j = *i + 1;
+        if j < bytes.len() && (bytes[j] == b'+' || bytes[j] == b'-') {
+            j += 1;
+        }
+        if j < bytes.len() && bytes[j].is_ascii_digit() {
+            is_float = true;
+            *i = j;
+            while *i < bytes.len() && bytes[*i].is_ascii_digit() {
+                *i += 1;
+            }
+        }
+    }
+    let body: String = src[digits_start..*i]
+        .chars()
+        .filter(|c| *c != '_')
+        .collect();
+    // 后缀：u8、i64、f32 ...
+    let suffix_start = *i;
+    while *i < bytes.len() && (bytes[*i].is_ascii_alphanumeric() || bytes[*i] == b'_') {
+        *i += 1;
+    }
+    let span = Span { start, end: *i };
+    let suffix = src[suffix_start..*i].trim_start_matches('_');
+    let suffix = if suffix.is_empty() {
+        None
+    } else {
+        match Ty::parse(suffix) {
+            Some(Ty::Bool) | None => {
+                return Err(Diagnostic::error(
+                    format!("invalid suffix `{}` for number literal", suffix),
+                    span,
+                ))
+            }
+            ty => ty,
+        }
+    };
+    let invalid = || Diagnostic::error("invalid number literal", span);
+    if is_float || matches!(suffix, Some(Ty::Float(_))) {
+        if radix != 10 {
+            return Err(invalid());
+        }
+        let value: f64 = body.parse().map_err(|_| invalid())?;
+        let fty = match suffix {
+            Some(Ty::Float(t)) => Some(t),
+            None => None,
+            Some(_) => return Err(invalid()),
+        };
+        Ok(Token {
+            tok: Tok::Float(value, fty),
+            span,
+        })
+    } else {
+        let value = u128::from_str_radix(&body, radix).map_err(|_| invalid())?;
+        Ok(Token {
+            tok: Tok::Int(value, suffix),
+            span,
+        })
+    }
+}
+
+/* ---------------------------- 语法树 ---------------------------- */
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExprKind {
+    // 字面量表达式，负号在解析时直接并入字面量，这样 -128i8 不会溢出
+    Int {
+        value: u128,
+        negative: bool,
+        suffix: Option<IntTy>,
+    },
+    Float {
+        value: f64,
+        suffix: Option<FloatTy>,
+    },
+    Bool(bool),
+    // 路径表达式
+    Path(String),
+    Neg(Box<Expr>),
+    Not(Box<Expr>),
+    Binary(BinOp, Box<Expr>, Box<Expr>),
+    Cast(Box<Expr>, Ty),
+    MethodCall(Box<Expr>, String, Vec<Expr>),
+    // 块表达式
+    Block(Block),
+    If(Box<Expr>, Block, Option<Box<Expr>>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expr {
+    pub kind: ExprKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Stmt {
+    Let {
+        name: String,
+        ty: Option<Ty>,
+        init: Expr,
+    },
+    Expr(Expr),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Block {
+    pub stmts: Vec<Stmt>,
+    pub tail: Option<Box<Expr>>,
+}
+
+/* ---------------------------- Pratt 解析 ---------------------------- */
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+// 返回 (左结合力, 右结合力)
+fn infix_binding_power(op: &str) -> Option<(u8, u8, BinOp)> {
+    Some(match op {
+        "||" => (1, 2, BinOp::Or),
+        "&&" => (3, 4, BinOp::And),
+        "==" => (5, 6, BinOp::Eq),
+        "!=" => (5, 6, BinOp::Ne),
+        "<" => (5, 6, BinOp::Lt),
+        ">" => (5, 6, BinOp::Gt),
+        "<=" => (5, 6, BinOp::Le),
+        ">=" => (5, 6, BinOp::Ge),
+        "|" => (7, 8, BinOp::BitOr),
+        "^" => (9, 10, BinOp::BitXor),
+        "&" => (11, 12, BinOp::BitAnd),
+        "<<" => (13, 14, BinOp::Shl),
+        ">>" => (13, 14, BinOp::Shr),
+        "+" => (15, 16, BinOp::Add),
+        "-" => (15, 16, BinOp::Sub),
+        "*" => (17, 18, BinOp::Mul),
+        "/" => (17, 18, BinOp::Div),
+        "%" => (17, 18, BinOp::Rem),
+        _ => return None,
+    })
+}
+
+const AS_BP: u8 = 19;
+const PREFIX_BP: u8 = 21;
+const METHOD_BP: u8 = 23;
+
+impl Parser {
+    fn peek(&self) -> &Token {
+        &self.tokens[self.pos]
+    }
+
+    fn next(&mut self) -> Token {
+        let t = self.tokens[self.pos].clone();
+        if self.pos + 1 < self.tokens.len() {
+            self.pos += 1;
+        }
+        t
+    }
+
+    fn is_op(&self, op: &str) -> bool {
+        self.peek().tok
+            == Tok::Op(match PUNCTS.iter().find(|p| **p == op) {
+                Some(p) => p,
+                None => return false,
+            })
+    }
+
+    fn is_keyword(&self, kw: &str) -> bool {
+        matches!(&self.peek().tok, Tok::Ident(s) if s == kw)
+    }
+
+    fn expect_op(&mut self, op: &str) -> Result<Span, Diagnostic> {
+        if self.is_op(op) {
+            Ok(self.next().span)
+        } else {
+            Err(self.unexpected(&format!("`{}`", op)))
+        }
+    }
+
+    fn unexpected(&self, expected: &str) -> Diagnostic {
+        let t = self.peek();
+        let found = match &t.tok {
+            Tok::Eof => "end of input".to_string(),
+            Tok::Int(..) | Tok::Float(..) => "literal".to_string(),
+            Tok::Ident(s) => format!("`{}`", s),
+            Tok::Op(s) => format!("`{}`", s),
+        };
+        Diagnostic::error(format!("expected {}, found {}", expected, found), t.span)
+    }
+
+    fn ident(&mut self) -> Result<(String, Span), Diagnostic> {
+        match self.peek().tok.clone() {
+            Tok::Ident(s) if !is_keyword(&s) => Ok((s, self.next().span)),
+            _ => Err(self.unexpected("identifier")),
+        }
+    }
+
+    fn ty(&mut self) -> Result<Ty, Diagnostic> {
+        let (name, span) = match self.peek().tok.clone() {
+            Tok::Ident(s) => (s, self.next().span),
+            _ => return Err(self.unexpected("type")),
+        };
+        Ty::parse(&name)
+            .ok_or_else(|| Diagnostic::error(format!("cannot find type `{}`", name), span))
+    }
+
+    // 语句序列，直到遇到 end(`}` 或 Eof)
+    fn block_body(&mut self, end: &Tok) -> Result<Block, Diagnostic> {
+        let mut stmts = Vec::new();
+        loop {
+            if &self.peek().tok == end {
+                return Ok(Block { stmts, tail: None });
+            }
+            if self.is_op(";") {
+                self.next();
+                continue;
+            }
+            if self.is_keyword("let") {
+                self.next();
+                let (name, _) = self.ident()?;
+                let ty = if self.is_op(":") {
+                    self.next();
+                    Some(self.ty()?)
+                } else {
+                    None
+                };
+                self.expect_op("=")?;
+                let init = self.expr(0)?;
+                self.expect_op(";")?;
+                stmts.push(Stmt::Let { name, ty, init });
+                continue;
+            }
+            let expr = self.expr(0)?;
+            if self.is_op(";") {
+                self.next();
+                stmts.push(Stmt::Expr(expr));
+            } else if &self.peek().tok == end {
+                return Ok(Block {
+                    stmts,
+                    tail: Some(Box::new(expr)),
+                });
+            } else if matches!(expr.kind, ExprKind::Block(_) | ExprKind::If(..)) {
+                // 块表达式作为语句时可以省略分号
+                stmts.push(Stmt::Expr(expr));
+            } else {
+                return Err(self.unexpected("`;`"));
+            }
+        }
+    }
+
+    fn block(&mut self) -> Result<(Block, Span), Diagnostic> {
+        let open = self.expect_op("{")?;
+        let block = self.block_body(&Tok::Op("}"))?;
+        let close = self.expect_op("}")?;
+        Ok((block, open.to(close)))
+    }
+
+    fn expr(&mut self, min_bp: u8) -> Result<Expr, Diagnostic> {
+        let mut lhs = self.prefix()?;
+        loop {
+            let op = match &self.peek().tok {
+                Tok::Op(op) => *op,
+                Tok::Ident(s) if s == "as" => "as",
+                _ => break,
+            };
+            if op == "." {
+                if METHOD_BP < min_bp {
+                    break;
+                }
+                self.next();
+                let (name, _) = self.ident()?;
+                self.expect_op("(")?;
+                let mut args = Vec::new();
+                while !self.is_op(")") {
+                    args.push(self.expr(0)?);
+                    if !self.is_op(")") {
+                        self.expect_op(",")?;
+                    }
+                }
+                let close = self.expect_op(")")?;
+                let span = lhs.span.to(close);
+                lhs = Expr {
+                    kind: ExprKind::MethodCall(Box::new(lhs), name, args),
+                    span,
+                };
+                continue;
+            }
+            if op == "as" {
+                if AS_BP < min_bp {
+                    break;
+                }
+                self.next();
+                let ty_span = self.peek().span;
+                let ty = self.ty()?;
+                let span = lhs.span.to(ty_span);
+                lhs = Expr {
+                    kind: ExprKind::Cast(Box::new(lhs), ty),
+                    span,
+                };
+                continue;
+            }
+            let Some((l_bp, r_bp, bin)) = infix_binding_power(op) else {
+                break;
+            };
+            if l_bp < min_bp {
+                break;
+            }
+            let op_span = self.next().span;
+            let rhs = self.expr(r_bp)?;
+            // 比较运算符不能连写：a < b < c
+            if bin.is_comparison() {
+                if let Tok::Op(next) = self.peek().tok {
+                    if matches!(infix_binding_power(next), Some((_, _, b)) if b.is_comparison()) {
+                        return Err(Diagnostic::error(
+                            "comparison operators cannot be chained",
+                            op_span.to(self.peek().span),
+                        ));
+                    }
+                }
+            }
+            let span = lhs.span.to(rhs.span);
+            lhs = Expr {
+                kind: ExprKind::Binary(bin, Box::new(lhs), Box::new(rhs)),
+                span,
+            };
+        }
+        Ok(lhs)
+    }
+
+    fn prefix(&mut self) -> Result<Expr, Diagnostic> {
+        let token = self.peek().clone();
+        let span = token.span;
+        match token.tok {
+            Tok::Int(value, suffix) => {
+                self.next();
+                let suffix = match suffix {
+                    None => None,
+                    Some(Ty::Int(t)) => Some(t),
+                    Some(_) => unreachable!("float suffix is lexed as float"),
+                };
+                Ok(Expr {
+                    kind: ExprKind::Int {
+                        value,
+                        negative: false,
+                        suffix,
+                    },
+                    span,
+                })
+            }
+            Tok::Float(value, suffix) => {
+                self.next();
+                Ok(Expr {
+                    kind: ExprKind::Float { value, suffix },
+                    span,
+                })
+            }
+            Tok::Op("-") => {
+                self.next();
+                let operand = self.expr(PREFIX_BP)?;
+                let span = span.to(operand.span);
+                let kind = match operand.kind {
+                    ExprKind::Int {
+                        value,
+                        negative: false,
+                        suffix,
+                    } => ExprKind::Int {
+                        value,
+                        negative: true,
+                        suffix,
+                    },
+                    ExprKind::Float { value, suffix } => ExprKind::Float {
+                        value: -value,
+                        suffix,
+                    },
+                    _ => ExprKind::Neg(Box::new(operand)),
+                };
+                Ok(Expr { kind, span })
+            }
+            Tok::Op("!") => {
+                self.next();
+                let operand = self.expr(PREFIX_BP)?;
+                let span = span.to(operand.span);
+                Ok(Expr {
+                    kind: ExprKind::Not(Box::new(operand)),
+                    span,
+                })
+            }
+            Tok::Op("(") => {
+                self.next();
+                let mut inner = self.expr(0)?;
+                let close = self.expect_op(")")?;
+                inner.span = span.to(close);
+                Ok(inner)
+            }
+            Tok::Op("{") => {
+                let (block, span) = self.block()?;
+                Ok(Expr {
+                    kind: ExprKind::Block(block),
+                    span,
+                })
+            }
+            Tok::Ident(ref s) if s == "true" || s == "false" => {
+                self.next();
+                Ok(Expr {
+                    kind: ExprKind::Bool(s == "true"),
+                    span,
+                })
+            }
+            Tok::Ident(ref s) if s == "if" => {
+                self.next();
+                let cond = self.expr(0)?;
+                let (then, then_span) = self.block()?;
+                let mut end = then_span;
+                let otherwise = if self.is_keyword("else") {
+                    self.next();
+                    let e = if self.is_keyword("if") {
+                        self.prefix()?
+                    } else {
+                        let (b, s) = self.block()?;
+                        Expr {
+                            kind: ExprKind::Block(b),
+                            span: s,
+                        }
+                    };
+                    end = e.span;
+                    Some(Box::new(e))
+                } else {
+                    None
+                };
+                Ok(Expr {
+                    kind: ExprKind::If(Box::new(cond), then, otherwise),
+                    span: span.to(end),
+                })
+            }
+            Tok::Ident(_) => {
+                let (name, span) = self.ident()?;
+                Ok(Expr {
+                    kind: ExprKind::Path(name),
+                    span,
+                })
+            }
+            _ => Err(self.unexpected("expression")),
+        }
+    }
+}
+
+fn is_keyword(s: &str) -> bool {
+    matches!(s, "let" | "if" | "else" | "as" | "true" | "false")
+}
+
+// 解析一段程序：若干语句加一个可选的结尾表达式
+pub fn parse(src: &str) -> Result<Block, Diagnostic> {
+    let mut parser = Parser {
+        tokens: lex(src)?,
+        pos: 0,
+    };
+    let block = parser.block_body(&Tok::Eof)?;
+    if parser.peek().tok != Tok::Eof {
+        return Err(parser.unexpected("end of input"));
+    }
+    Ok(block)
+}
+
+/* ---------------------------- 求值 ---------------------------- */
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Outcome {
+    pub value: Option<Value>,
+    // Overflowing 策略下记录的溢出位置
+    pub warnings: Vec<Diagnostic>,
+}
+
+// 一次会话：保存溢出策略、默认类型和顶层变量
+pub struct Session {
+    pub policy: OverflowPolicy,
+    pub int_ty: IntTy,
+    pub float_ty: FloatTy,
+    scopes: Vec<HashMap<String, Value>>,
+    warnings: Vec<Diagnostic>,
+}
+
+impl Session {
+    pub fn new(policy: OverflowPolicy) -> Self {
+        Session {
+            policy,
+            int_ty: IntTy::I32,
+            float_ty: FloatTy::F64,
+            scopes: vec![HashMap::new()],
+            warnings: Vec::new(),
+        }
+    }
+
+    pub fn eval(&mut self, src: &str) -> Result<Outcome, Diagnostic> {
+        let program = parse(src)?;
+        self.warnings.clear();
+        // 出错时丢弃本次执行中创建的内部作用域
+        let depth = self.scopes.len();
+        let result = self.eval_stmts(&program);
+        self.scopes.truncate(depth);
+        let span = Span {
+            start: 0,
+            end: src.len(),
+        };
+        let value = result?.map(|v| self.settle(v, span)).transpose()?;
+        Ok(Outcome {
+            value,
+            warnings: std::mem::take(&mut self.warnings),
+        })
+    }
+
+    fn eval_stmts(&mut self, block: &Block) -> Result<Option<Value>, Diagnostic> {
+        for stmt in &block.stmts {
+            match stmt {
+                Stmt::Let { name, ty, init } => {
+                    let v = self.eval_expr(init)?;
+                    let v = match ty {
+                        Some(ty) => self.coerce(v, *ty, init.span)?,
+                        None => self.settle(v, init.span)?,
+                    };
+                    self.scopes.last_mut().unwrap().insert(name.clone(), v);
+                }
+                Stmt::Expr(e) => {
+                    self.eval_expr(e)?;
+                }
+            }
+        }
+        block.tail.as_ref().map(|e| self.eval_expr(e)).transpose()
+    }
+
+    fn eval_block(&mut self, block: &Block) -> Result<Option<Value>, Diagnostic> {
+        self.scopes.push(HashMap::new());
+        let v = self.eval_stmts(block);
+        self.scopes.pop();
+        v
+    }
+
+    // 单独出现的待推断字面量落地为会话默认类型，超出范围时按 Rust 的 lint 报错
+    fn settle(&self, v: Value, span: Span) -> Result<Value, Diagnostic> {
+        self.coerce(v, v.ty(), span)
+    }
+
+    // 把待推断的字面量转换为期望类型，已确定类型的值必须完全一致
+    fn coerce(&self, v: Value, ty: Ty, span: Span) -> Result<Value, Diagnostic> {
+        match (v, ty) {
+            (
+                Value::Int {
+                    value,
+                    inferred: true,
+                    ..
+                },
+                Ty::Int(t),
+            ) => {
+                if value < t.min() || value > t.max() {
+                    return Err(Diagnostic::error(
+                        format!("literal out of range for `{}`", t.name()),
+                        span,
+                    ));
+                }
+                Ok(Value::Int {
+                    value,
+                    ty: t,
+                    inferred: false,
+                })
+            }
+            (
+                Value::Float {
+                    value,
+                    inferred: true,
+                    ..
+                },
+                Ty::Float(t),
+            ) => Ok(Value::Float {
+                value: round_float(value, t),
+                ty: t,
+                inferred: false,
+            }),
+            (v, ty) if v.ty() == ty => Ok(v.concrete()),
+            (v, ty) => Err(Diagnostic::error(
+                format!("mismatched types: expected `{}`, found `{}`", ty, v.ty()),
+                span,
+            )),
+        }
+    }
+
+    fn lookup(&self, name: &str, span: Span) -> Result<Value, Diagnostic> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|s| s.get(name))
+            .copied()
+            .ok_or_else(|| {
+                Diagnostic::error(format!("cannot find value `{}` in this scope", name), span)
+            })
+    }
+
+    fn eval_expr(&mut self, expr: &Expr) -> Result<Value, Diagnostic> {
+        let span = expr.span;
+        match &expr.kind {
+            ExprKind::Int {
+                value,
+                negative,
+                suffix,
+            } => {
+                let v = i128::try_from(*value)
+                    .map_err(|_| Diagnostic::error("integer literal is too large", span))?;
+                let v = if *negative { -v } else { v };
+                let lit = Value::Int {
+                    value: v,
+                    ty: self.int_ty,
+                    inferred: true,
+                };
+                match suffix {
+                    Some(t) if *negative && t.min() == 0 => Err(Diagnostic::error(
+                        format!("cannot apply unary operator `-` to type `{}`", t.name()),
+                        span,
+                    )),
+                    Some(t) => self.coerce(lit, Ty::Int(*t), span),
+                    None => Ok(lit),
+                }
+            }
+            ExprKind::Float { value, suffix } => {
+                let lit = Value::Float {
+                    value: *value,
+                    ty: self.float_ty,
+                    inferred: true,
+                };
+                match suffix {
+                    Some(t) => self.coerce(lit, Ty::Float(*t), span),
+                    None => Ok(lit),
+                }
+            }
+            ExprKind::Bool(b) => Ok(Value::Bool(*b)),
+            ExprKind::Path(name) => self.lookup(name, span),
+            ExprKind::Block(block) => self
+                .eval_block(block)?
+                .ok_or_else(|| Diagnostic::error("block has no value", span)),
+            ExprKind::If(cond, then, otherwise) => {
+                let c = match self.eval_expr(cond)? {
+                    Value::Bool(b) => b,
+                    v => {
+                        return Err(Diagnostic::error(
+                            format!("mismatched types: expected `bool`, found `{}`", v.ty()),
+                            cond.span,
+                        ))
+                    }
+                };
+                let v = if c {
+                    self.eval_block(then)?
+                } else {
+                    match otherwise {
+                        Some(e) => Some(self.eval_expr(e)?),
+                        None => None,
+                    }
+                };
+                v.ok_or_else(|| Diagnostic::error("`if` has no value", span))
+            }
+            ExprKind::Neg(e) => {
+                let v = self.eval_expr(e)?;
+                match self.settle(v, e.span)? {
+                    Value::Int { value, ty, .. } => self.int_unary(ty, "neg", value, None, span),
+                    Value::Float { value, ty, .. } => Ok(Value::Float {
+                        value: -value,
+                        ty,
+                        inferred: false,
+                    }),
+                    Value::Bool(_) => Err(Diagnostic::error(
+                        "cannot apply unary operator `-` to type `bool`",
+                        span,
+                    )),
+                }
+            }
+            ExprKind::Not(e) => match self.eval_expr(e).and_then(|v| self.settle(v, e.span))? {
+                Value::Bool(b) => Ok(Value::Bool(!b)),
+                Value::Int { value, ty, .. } => self.int_unary(ty, "not", value, None, span),
+                v => Err(Diagnostic::error(
+                    format!("cannot apply unary operator `!` to type `{}`", v.ty()),
+                    span,
+                )),
+            },
+            ExprKind::Cast(e, ty) => {
+                let v = self.eval_expr(e)?;
+                cast(self.settle(v, e.span)?, *ty, span)
+            }
+            ExprKind::MethodCall(recv, name, args) => {
+                let recv_v = self.eval_expr(recv)?;
+                let args = args
+                    .iter()
+                    .map(|a| Ok((self.eval_expr(a)?, a.span)))
+                    .collect::<Result<Vec<_>, Diagnostic>>()?;
+                self.method(recv_v, name, &args, span)
+            }
+            ExprKind::Binary(op, l, r) => self.binary(*op, l, r, span),
+        }
+    }
+
+    fn binary(&mut self, op: BinOp, l: &Expr, r: &Expr, span: Span) -> Result<Value, Diagnostic> {
+        // && 和 || 短路求值
+        if matches!(op, BinOp::And | BinOp::Or) {
+            let lv = self.expect_bool(l)?;
+            if (op == BinOp::And) != lv {
+                return Ok(Value::Bool(lv));
+            }
+            return Ok(Value::Bool(self.expect_bool(r)?));
+        }
+        let lv = self.eval_expr(l)?;
+        let rv = self.eval_expr(r)?;
+        // 移位运算的右操作数可以是任意整数类型
+        if matches!(op, BinOp::Shl | BinOp::Shr) {
+            return match (self.settle(lv, l.span)?, rv) {
+                (Value::Int { value, ty, .. }, Value::Int { value: amount, .. }) => {
+                    let res = with_int_ty!(ty, T => int_shift::<T>(op, value, amount, self.policy));
+                    self.int_result(res, ty, span)
+                }
+                (a, b) => Err(type_error(op, a, b, span)),
+            };
+        }
+        let (lv, rv) = self.unify(lv, rv, l.span, r.span)?;
+        match (lv, rv) {
+            (Value::Int { value: a, ty, .. }, Value::Int { value: b, .. }) => {
+                if op.is_comparison() {
+                    return Ok(Value::Bool(compare(op, &a, &b)));
+                }
+                let res = with_int_ty!(ty, T => int_binary::<T>(op, a, b, self.policy));
+                self.int_result(res, ty, span)
+            }
+            (Value::Float { value: a, ty, .. }, Value::Float { value: b, .. }) => {
+                if op.is_comparison() {
+                    return Ok(Value::Bool(compare(op, &a, &b)));
+                }
+                let v = match op {
+                    BinOp::Add => a + b,
+                    BinOp::Sub => a - b,
+                    BinOp::Mul => a * b,
+                    BinOp::Div => a / b,
+                    BinOp::Rem => a % b,
+                    _ => return Err(type_error(op, lv, rv, span)),
+                };
+                Ok(Value::Float {
+                    value: round_float(v, ty),
+                    ty,
+                    inferred: false,
+                })
+            }
+            (Value::Bool(a), Value::Bool(b)) => match op {
+                BinOp::BitAnd => Ok(Value::Bool(a & b)),
+                BinOp::BitOr => Ok(Value::Bool(a | b)),
+                BinOp::BitXor => Ok(Value::Bool(a ^ b)),
+                op if op.is_comparison() => Ok(Value::Bool(compare(op, &a, &b))),
+                _ => Err(type_error(op, lv, rv, span)),
+            },
+            _ => Err(type_error(op, lv, rv, span)),
+        }
+    }
+
+    fn expect_bool(&mut self, e: &Expr) -> Result<bool, Diagnostic> {
+        match self.eval_expr(e)? {
+            Value::Bool(b) => Ok(b),
+            v => Err(Diagnostic::error(
+                format!("mismatched types: expected `bool`, found `{}`", v.ty()),
+                e.span,
+            )),
+        }
+    }
+
+    // 两个操作数统一为相同类型
+    fn unify(
+        &self,
+        l: Value,
+        r: Value,
+        lspan: Span,
+        rspan: Span,
+    ) -> Result<(Value, Value), Diagnostic> {
+        let l_inferred = matches!(
+            l,
+            Value::Int { inferred: true, .. } | Value::Float { inferred: true, .. }
+        );
+        let r_inferred = matches!(
+            r,
+            Value::Int { inferred: true, .. } | Value::Float { inferred: true, .. }
+        );
+        match (l_inferred, r_inferred) {
+            (true, false) => Ok((self.coerce(l, r.ty(), lspan)?, r)),
+            (false, true) => Ok((l, self.coerce(r, l.ty(), rspan)?)),
+            _ => {
+                let (l, r) = (self.settle(l, lspan)?, self.settle(r, rspan)?);
+                if l.ty() != r.ty() {
+                    return Err(Diagnostic::error(
+                        format!(
+                            "mismatched types: expected `{}`, found `{}`",
+                            l.ty(),
+                            r.ty()
+                        ),
+                        rspan,
+                    ));
+                }
+                Ok((l, r))
+            }
+        }
+    }
+
+    fn int_result(
+        &mut self,
+        res: Result<(i128, bool), ArithError>,
+        ty: IntTy,
+        span: Span,
+    ) -> Result<Value, Diagnostic> {
+        match res {
+            Ok((value, overflowed)) => {
+                if overflowed {
+                    self.warnings.push(Diagnostic {
+                        level: Level::Warning,
+                        message: format!("this arithmetic operation overflowed `{}`", ty.name()),
+                        span,
+                    });
+                }
+                Ok(Value::Int {
+                    value,
+                    ty,
+                    inferred: false,
+                })
+            }
+            Err(ArithError::Overflow(what)) => Err(Diagnostic::error(what, span)),
+            Err(ArithError::DivideByZero) => {
+                Err(Diagnostic::error("attempt to divide by zero", span))
+            }
+            Err(ArithError::RemainderByZero) => Err(Diagnostic::error(
+                "attempt to calculate the remainder with a divisor of zero",
+                span,
+            )),
+            Err(ArithError::Unsupported) => Err(Diagnostic::error(
+                format!("operation is not supported for `{}`", ty.name()),
+                span,
+            )),
+        }
+    }
+
+    fn int_unary(
+        &mut self,
+        ty: IntTy,
+        op: &str,
+        value: i128,
+        arg: Option<u32>,
+        span: Span,
+    ) -> Result<Value, Diagnostic> {
+        let res = with_int_ty!(ty, T => int_unary::<T>(op, value, arg, self.policy));
+        if res == Err(ArithError::Unsupported) && op == "neg" {
+            return Err(Diagnostic::error(
+                format!("cannot apply unary operator `-` to type `{}`", ty.name()),
+                span,
+            ));
+        }
+        self.int_result(res, ty, span)
+    }
+
+    fn method(
+        &mut self,
+        recv: Value,
+        name: &str,
+        args: &[(Value, Span)],
+        span: Span,
+    ) -> Result<Value, Diagnostic> {
+        let arity = |n: usize| -> Result<(), Diagnostic> {
+            if args.len() == n {
+                Ok(())
+            } else {
+                Err(Diagnostic::error(
+                    format!(
+                        "this method takes {} argument(s) but {} were supplied",
+                        n,
+                        args.len()
+                    ),
+                    span,
+                ))
+            }
+        };
+        let recv = self.settle(recv, span)?;
+        match (recv, name) {
+            (Value::Int { value, ty, .. }, "pow") => {
+                arity(1)?;
+                let exp = self.coerce(args[0].0, Ty::Int(IntTy::U32), args[0].1)?;
+                let Value::Int { value: exp, .. } = exp else {
+                    unreachable!()
+                };
+                self.int_unary(ty, "pow", value, Some(exp as u32), span)
+            }
+            (Value::Int { value, ty, .. }, "abs") => {
+                arity(0)?;
+                self.int_unary(ty, "abs", value, None, span)
+            }
+            (Value::Float { value, ty, .. }, "abs" | "sqrt") => {
+                arity(0)?;
+                let v = if name == "abs" {
+                    value.abs()
+                } else {
+                    value.sqrt()
+                };
+                Ok(Value::Float {
+                    value: round_float(v, ty),
+                    ty,
+                    inferred: false,
+                })
+            }
+            (Value::Float { value, ty, .. }, "powi") => {
+                arity(1)?;
+                let exp = self.coerce(args[0].0, Ty::Int(IntTy::I32), args[0].1)?;
+                let Value::Int { value: exp, .. } = exp else {
+                    unreachable!()
+                };
+                Ok(Value::Float {
+                    value: round_float(value.powi(exp as i32), ty),
+                    ty,
+                    inferred: false,
+                })
+            }
+            (v, "min" | "max") if !matches!(v, Value::Bool(_)) => {
+                arity(1)?;
+                let other = self.coerce(args[0].0, v.ty(), args[0].1)?;
+                let pick_self = match (v, other) {
+                    (Value::Int { value: a, .. }, Value::Int { value: b, .. }) => {
+                        (a <= b) == (name == "min")
+                    }
+                    (Value::Float { value: a, .. }, Value::Float { value: b, .. }) => {
+                        (a <= b) == (name == "min")
+                    }
+                    _ => unreachable!(),
+                };
+                Ok(if pick_self { v } else { other })
+            }
+            (v, _) => Err(Diagnostic::error(
+                format!("no method named `{}` found for `{}`", name, v.ty()),
+                span,
+            )),
+        }
+    }
+}
+
+fn round_float(v: f64, ty: FloatTy) -> f64 {
+    match ty {
+        FloatTy::F32 => v as f32 as f64,
+        FloatTy::F64 => v,
+    }
+}
+
+fn compare<T: PartialOrd>(op: BinOp, a: &T, b: &T) -> bool {
+    match op {
+        BinOp::Eq => a == b,
+        BinOp::Ne => a != b,
+        BinOp::Lt => a < b,
+        BinOp::Gt => a > b,
+        BinOp::Le => a <= b,
+        _ => a >= b,
+    }
+}
+
+fn type_error(op: BinOp, l: Value, r: Value, span: Span) -> Diagnostic {
+    Diagnostic::error(
+        format!(
+            "cannot apply `{}` to `{}` and `{}`",
+            op.symbol(),
+            l.ty(),
+            r.ty()
+        ),
+        span,
+    )
+}
+
+// 按 Rust 的 as 语义转换：整数截断，浮点转整数饱和，NaN 转为 0
+fn cast(v: Value, ty: Ty, span: Span) -> Result<Value, Diagnostic> {
+    let int = |value: i128, t: IntTy| Value::Int {
+        value: with_int_ty!(t, T => value as T as i128),
+        ty: t,
+        inferred: false,
+    };
+    Ok(match (v, ty) {
+        (Value::Int { value, .. }, Ty::Int(t)) => int(value, t),
+        (Value::Int { value, .. }, Ty::Float(t)) => Value::Float {
+            value: round_float(value as f64, t),
+            ty: t,
+            inferred: false,
+        },
+        (Value::Float { value, .. }, Ty::Int(t)) => Value::Int {
+            value: with_int_ty!(t, T => value as T as i128),
+            ty: t,
+            inferred: false,
+        },
+        (Value::Float { value, .. }, Ty::Float(t)) => Value::Float {
+            value: round_float(value, t),
+            ty: t,
+            inferred: false,
+        },
+        (Value::Bool(b), Ty::Int(t)) => int(b as i128, t),
+        (v, ty) => {
+            return Err(Diagnostic::error(
+                format!("non-primitive cast: `{}` as `{}`", v.ty(), ty),
+                span,
+            ))
+        }
+    })
+}
+
+/* ---------------------------- 交互界面 ---------------------------- */
+
+// cargo run -- calc [checked|wrapping|saturating|overflowing] [默认整数类型]
+// 输入 :policy wrapping 或 :type u8 可以在会话中切换
+pub fn repl(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let mut session = Session::new(OverflowPolicy::Checked);
+    for arg in args {
+        configure(&mut session, arg)?;
+    }
+    println!(
+        "policy = {:?}, default int = {}",
+        session.policy,
+        session.int_ty.name()
+    );
+    let stdin = std::io::stdin();
+    for line in stdin.lock().lines() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(cmd) = line.strip_prefix(':') {
+            let arg = cmd.split_whitespace().nth(1).unwrap_or("");
+            match configure(&mut session, arg) {
+                Ok(()) => println!(
+                    "policy = {:?}, default int = {}",
+                    session.policy,
+                    session.int_ty.name()
+                ),
+                Err(e) => eprintln!("{}", e),
+            }
+            continue;
+        }
+        match session.eval(line) {
+            Ok(outcome) => {
+                for w in &outcome.warnings {
+                    println!("{}", w.render(line));
+                }
+                if let Some(v) = outcome.value {
+                    println!("{}", v);
+                }
+            }
+            Err(d) => println!("{}", d.render(line)),
+        }
+    }
+    Ok(())
+}
+
+fn configure(session: &mut Session, arg: &str) -> Result<(), String> {
+    if let Some(p) = OverflowPolicy::parse(arg) {
+        session.policy = p;
+        return Ok(());
+    }
+    match Ty::parse(arg) {
+        Some(Ty::Int(t)) => session.int_ty = t,
+        Some(Ty::Float(t)) => session.float_ty = t,
+        _ => return Err(format!("unknown policy or type: {}", arg)),
+    }
+    Ok(())
+}
+
+pub fn calc_practice() {
+    // int_overflow：255u8 + 20 在四种策略下
+    let src = "let a: u8 = 255; a + 20";
+    for policy in ["checked", "wrapping", "saturating", "overflowing"] {
+        let mut session = Session::new(OverflowPolicy::parse(policy).unwrap());
+        match session.eval(src) {
+            Ok(out) => {
+                for w in &out.warnings {
+                    println!("{}", w.render(src));
+                }
+                println!("{:<11} {}", policy, out.value.unwrap());
+            }
+            Err(d) => println!("{:<11} {}", policy, d.render(src)),
+        }
+    }
+
+    // 溢出位置精确到子表达式
+    let src = "1 + (100i8 * 2) - 3";
+    if let Err(d) = Session::new(OverflowPolicy::Checked).eval(src) {
+        println!("{}", d.render(src));
+    }
+
+    // 同一会话内切换默认类型和策略，变量保留
+    let mut session = Session::new(OverflowPolicy::Checked);
+    configure(&mut session, "u8").unwrap();
+    for line in ["let x = 200;", "x + 100", ":policy wrapping", "x + 100"] {
+        if let Some(cmd) = line.strip_prefix(':') {
+            configure(&mut session, cmd.split_whitespace().nth(1).unwrap()).unwrap();
+            continue;
+        }
+        match session.eval(line) {
+            Ok(out) => println!("{:<12} => {:?}", line, out.value.map(|v| v.to_string())),
+            Err(d) => println!("{:<12} => {}", line, d.message),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval(policy: OverflowPolicy, src: &str) -> Result<Outcome, Diagnostic> {
+        Session::new(policy).eval(src)
+    }
+
+    fn value(policy: OverflowPolicy, src: &str) -> String {
+        eval(policy, src).unwrap().value.unwrap().to_string()
+    }
+
+    fn spanned(src: &str, span: Span) -> &str {
+        &src[span.start..span.end]
+    }
+
+    #[test]
+    fn precedence() {
+        assert_eq!(value(OverflowPolicy::Checked, "1 + 2 * 3"), "7i32");
+        assert_eq!(value(OverflowPolicy::Checked, "(1 + 2) * 3"), "9i32");
+        assert_eq!(value(OverflowPolicy::Checked, "1 << 2 + 1"), "8i32");
+        assert_eq!(value(OverflowPolicy::Checked, "6 & 3 ^ 1 | 8"), "11i32");
+        assert_eq!(value(OverflowPolicy::Checked, "-2.pow(2)"), "-4i32");
+        assert_eq!(value(OverflowPolicy::Checked, "10 - 4 - 3"), "3i32");
+        assert_eq!(
+            value(OverflowPolicy::Checked, "1 + 2 == 3 && !false"),
+            "true"
+        );
+        assert_eq!(value(OverflowPolicy::Checked, "-7 % 3"), "-1i32");
+        assert_eq!(value(OverflowPolicy::Checked, "300 as u8"), "44u8");
+        assert_eq!(
+            value(OverflowPolicy::Checked, "-1i32 as u8 as i32"),
+            "255i32"
+        );
+        assert_eq!(value(OverflowPolicy::Checked, "1e3 as u8"), "255u8");
+        assert_eq!(value(OverflowPolicy::Checked, "-128i8"), "-128i8");
+        assert_eq!(value(OverflowPolicy::Checked, "1_000 + 0x_ff"), "1255i32");
+    }
+
+    #[test]
+    fn float_trap() {
+        assert_eq!(
+            value(OverflowPolicy::Checked, "0.1 + 0.2"),
+            "0.30000000000000004f64"
+        );
+        assert_eq!(value(OverflowPolicy::Checked, "0.1f32 + 0.2"), "0.3f32");
+        assert_eq!(value(OverflowPolicy::Checked, "0.1 + 0.2 == 0.3"), "false");
+    }
+
+    #[test]
+    fn literal_path_and_block_expressions() {
+        assert_eq!(
+            value(
+                OverflowPolicy::Checked,
+                "let x = 5; let y = { let x = x * 2; x + 1 }; x + y"
+            ),
+            "16i32"
+        );
+        assert_eq!(
+            value(
+                OverflowPolicy::Checked,
+                "let n: u8 = 7; if n % 2 == 0 { 0 } else if n > 5 { 2 } else { 1 }"
+            ),
+            "2i32"
+        );
+        assert_eq!(value(OverflowPolicy::Checked, "let _x = 1; _x + 1"), "2i32");
+    }
+
+    // int_overflow：255u8 + 20 在四种策略下
+    const OVERFLOW: &str = "let a: u8 = 255; a + 20";
+
+    #[test]
+    fn checked_reports_overflow() {
+        let err = eval(OverflowPolicy::Checked, OVERFLOW).unwrap_err();
+        assert_eq!(err.level, Level::Error);
+        assert_eq!(err.message, "attempt to add with overflow");
+        assert_eq!(spanned(OVERFLOW, err.span), "a + 20");
+        assert_eq!(
+            eval(OverflowPolicy::Checked, "-(-128i8)")
+                .unwrap_err()
+                .message,
+            "attempt to negate with overflow"
+        );
+        assert!(eval(OverflowPolicy::Checked, "2u32.pow(33)").is_err());
+        assert!(eval(OverflowPolicy::Checked, "1u8 << 9").is_err());
+    }
+
+    #[test]
+    fn wrapping_wraps_silently() {
+        let out = eval(OverflowPolicy::Wrapping, OVERFLOW).unwrap();
+        assert_eq!(out.value.unwrap().to_string(), "19u8");
+        assert!(out.warnings.is_empty());
+        assert_eq!(value(OverflowPolicy::Wrapping, "-(-128i8)"), "-128i8");
+        assert_eq!(value(OverflowPolicy::Wrapping, "2u32.pow(33)"), "0u32");
+        assert_eq!(value(OverflowPolicy::Wrapping, "1u8 << 9"), "2u8");
+    }
+
+    #[test]
+    fn saturating_clamps_to_bounds() {
+        let out = eval(OverflowPolicy::Saturating, OVERFLOW).unwrap();
+        assert_eq!(out.value.unwrap().to_string(), "255u8");
+        assert!(out.warnings.is_empty());
+        assert_eq!(value(OverflowPolicy::Saturating, "(-128i8).abs()"), "127i8");
+        assert_eq!(value(OverflowPolicy::Saturating, "-100i8 - 100"), "-128i8");
+        assert_eq!(
+            value(OverflowPolicy::Saturating, "2u32.pow(33)"),
+            "4294967295u32"
+        );
+        // 移位没有饱和版本
+        assert!(eval(OverflowPolicy::Saturating, "1u8 << 9").is_err());
+    }
+
+    #[test]
+    fn overflowing_wraps_and_warns() {
+        let out = eval(OverflowPolicy::Overflowing, OVERFLOW).unwrap();
+        assert_eq!(out.value.unwrap().to_string(), "19u8");
+        assert_eq!(out.warnings.len(), 1);
+        assert_eq!(out.warnings[0].level, Level::Warning);
+        assert_eq!(spanned(OVERFLOW, out.warnings[0].span), "a + 20");
+        // 每一次溢出各记录一条
+        let src = "let b: u8 = 200; (b + b) * 2";
+        let out = eval(OverflowPolicy::Overflowing, src).unwrap();
+        let spans: Vec<&str> = out.warnings.iter().map(|w| spanned(src, w.span)).collect();
+        assert_eq!(spans, ["(b + b)", "(b + b) * 2"]);
+        assert!(eval(OverflowPolicy::Overflowing, "1 + 1")
+            .unwrap()
+            .warnings
+            .is_empty());
+    }
+
+    #[test]
+    fn spans_point_at_the_failing_subexpression() {
+        let src = "1 + (100i8 * 2) - 3";
+        let err = eval(OverflowPolicy::Checked, src).unwrap_err();
+        assert_eq!(spanned(src, err.span), "(100i8 * 2)");
+        assert_eq!(err.message, "attempt to multiply with overflow");
+        assert_eq!(
+            err.render(src),
+            "error: attempt to multiply with overflow\n  --> 1:5\n  |\n1 | 1 + (100i8 * 2) - 3\n  |     ^^^^^^^^^^^"
+        );
+
+        let src = "1 $ 2";
+        let err = eval(OverflowPolicy::Checked, src).unwrap_err();
+        assert_eq!(err.message, "unknown start of token: $");
+        assert_eq!(spanned(src, err.span), "$");
+        let src = "1 +";
+        assert_eq!(
+            eval(OverflowPolicy::Checked, src).unwrap_err().span,
+            Span { start: 3, end: 3 }
+        );
+        let src = "let a: u8 = 256; a";
+        let err = eval(OverflowPolicy::Checked, src).unwrap_err();
+        assert_eq!(spanned(src, err.span), "256");
+    }
+
+    #[test]
+    fn type_and_syntax_errors() {
+        let err = eval(OverflowPolicy::Checked, "1 / 0").unwrap_err();
+        assert_eq!(err.message, "attempt to divide by zero");
+        assert_eq!(
+            eval(OverflowPolicy::Checked, "1 < 2 < 3")
+                .unwrap_err()
+                .message,
+            "comparison operators cannot be chained"
+        );
+        assert_eq!(
+            eval(OverflowPolicy::Checked, "let a: u8 = 256; a")
+                .unwrap_err()
+                .message,
+            "literal out of range for `u8`"
+        );
+        assert_eq!(
+            eval(OverflowPolicy::Checked, "1u8 + 1i32")
+                .unwrap_err()
+                .message,
+            "mismatched types: expected `u8`, found `i32`"
+        );
+        assert_eq!(
+            eval(OverflowPolicy::Checked, "-1u32").unwrap_err().message,
+            "cannot apply unary operator `-` to type `u32`"
+        );
+        assert_eq!(
+            eval(OverflowPolicy::Checked, "1 +").unwrap_err().message,
+            "expected expression, found end of input"
+        );
+    }
+
+    #[test]
+    fn session_keeps_variables_across_policies() {
+        let mut session = Session::new(OverflowPolicy::Checked);
+        session.int_ty = IntTy::U8;
+        session.eval("let x = 200;").unwrap();
+        assert!(session.eval("x + 100").is_err());
+        session.policy = OverflowPolicy::Wrapping;
+        assert_eq!(
+            session.eval("x + 100").unwrap().value.unwrap().to_string(),
+            "44u8"
+        );
+        assert!(configure(&mut session, "float").is_err());
+    }
+}
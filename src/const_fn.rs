@@ -0,0 +1,534 @@
+//! 常量函数练习
+//! 对应 doc/表达式小作业.md 第 2 题：const fn 中的 if/match/loop、const-sha1、ArrayVec
+
+use std::fmt;
+use std::mem::MaybeUninit;
+use std::ops::{Deref, DerefMut};
+
+/*
+ * const fn 可以在编译期求值，也可以像普通函数一样在运行期调用
+ * 在 const fn 中：
+ * - 可以使用 if、match、loop、while，但不能使用 for(for 依赖 Iterator 特征，特征方法不是 const 的)
+ * - 不能使用 println! 等格式化宏，格式化需要调用 Display 特征方法
+ * - panic! 可以使用，在编译期触发会变成编译错误
+ * Vec::new 是常量函数，因为它不分配内存，这样 static 中也可以放一个空的 Vec
+ */
+
+// if
+pub const fn is_prime(n: u32) -> bool {
+    if n < 2 {
+        return false;
+    }
+    let mut i = 2;
+    // i * i 在 n 接近 u32::MAX 时会溢出，改用除法比较
+    while i <= n / i {
+        if n.is_multiple_of(i) {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+// loop
+pub const fn fib(n: u32) -> u64 {
+    let (mut a, mut b) = (0u64, 1u64);
+    let mut i = 0;
+    loop {
+        if i == n {
+            break a;
+        }
+        let t = a + b;
+        a = b;
+        b = t;
+        i += 1;
+    }
+}
+
+// match
+pub const fn gcd(a: u64, b: u64) -> u64 {
+    match (a, b) {
+        (x, 0) => x,
+        (x, y) => gcd(y, x % y),
+    }
+}
+
+/* ---------------------------- ArrayVec ---------------------------- */
+
+/*
+ * 固定容量的 Vec，元素保存在栈上的数组中
+ * display_array_1<T, const N: usize> 中的 const 泛型 N 在这里表示容量
+ * 参考 https://github.com/Michael-F-Bryan/const-arrayvec
+ */
+pub struct ArrayVec<T, const N: usize> {
+    items: [MaybeUninit<T>; N],
+    len: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CapacityError<T>(pub T);
+
+impl<T, const N: usize> ArrayVec<T, N> {
+    pub const fn new() -> Self {
+        ArrayVec {
+            items: [const { MaybeUninit::uninit() }; N],
+            len: 0,
+        }
+    }
+
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub const fn is_full(&self) -> bool {
+        self.len == N
+    }
+
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    // 容量已满时把元素原样还给调用者
+    pub const fn try_push(&mut self, value: T) -> Result<(), CapacityError<T>> {
+        if self.len == N {
+            return Err(CapacityError(value));
+        }
+        self.items[self.len] = MaybeUninit::new(value);
+        self.len += 1;
+        Ok(())
+    }
+
+    // 容量已满时 panic，在常量上下文中就是编译错误
+    pub const fn push(&mut self, value: T) {
+        if self.len == N {
+            panic!("ArrayVec is full");
+        }
+        self.items[self.len] = MaybeUninit::new(value);
+        self.len += 1;
+    }
+
+    pub const fn pop(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        // SAFETY: 下标小于原来的 len，这个位置已经初始化，并且 len 减一后不会再被读取
+        Some(unsafe { self.items[self.len].assume_init_read() })
+    }
+
+    pub const fn get(&self, index: usize) -> Option<&T> {
+        if index >= self.len {
+            return None;
+        }
+        // SAFETY: 下标小于 len 的元素都已初始化
+        Some(unsafe { self.items[index].assume_init_ref() })
+    }
+
+    pub const fn as_slice(&self) -> &[T] {
+        // SAFETY: 前 len 个元素都已初始化，MaybeUninit<T> 和 T 的内存布局相同
+        unsafe { std::slice::from_raw_parts(self.items.as_ptr() as *const T, self.len) }
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        // SAFETY: 同 as_slice
+        unsafe { std::slice::from_raw_parts_mut(self.items.as_mut_ptr() as *mut T, self.len) }
+    }
+
+    pub fn clear(&mut self) {
+        while self.pop().is_some() {}
+    }
+}
+
+impl<T: Copy, const N: usize> ArrayVec<T, N> {
+    // T: Copy 保证复制元素时不需要调用 Clone(特征方法不是 const 的)
+    pub const fn from_slice(slice: &[T]) -> Self {
+        let mut v = Self::new();
+        let mut i = 0;
+        while i < slice.len() {
+            v.push(slice[i]);
+            i += 1;
+        }
+        v
+    }
+
+    // 数组的 [..] 切片在 const fn 中不可用，这里按下标复制
+    pub const fn to_array<const M: usize>(&self, fill: T) -> [T; M] {
+        let mut out = [fill; M];
+        let mut i = 0;
+        while i < self.len && i < M {
+            out[i] = *self.get(i).unwrap();
+            i += 1;
+        }
+        out
+    }
+}
+
+impl<T, const N: usize> Default for ArrayVec<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const N: usize> Drop for ArrayVec<T, N> {
+    fn drop(&mut self) {
+        // SAFETY: 只释放已初始化的前 len 个元素
+        unsafe { std::ptr::drop_in_place(self.as_mut_slice()) }
+    }
+}
+
+impl<T, const N: usize> Deref for ArrayVec<T, N> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        self.as_slice()
+    }
+}
+
+impl<T, const N: usize> DerefMut for ArrayVec<T, N> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        self.as_mut_slice()
+    }
+}
+
+impl<T: Clone, const N: usize> Clone for ArrayVec<T, N> {
+    fn clone(&self) -> Self {
+        let mut v = Self::new();
+        for item in self.iter() {
+            v.push(item.clone());
+        }
+        v
+    }
+}
+
+impl<T: fmt::Debug, const N: usize> fmt::Debug for ArrayVec<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: PartialEq, const N: usize> PartialEq for ArrayVec<T, N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+/* ---------------------------- 查找表 ---------------------------- */
+
+/*
+ * 用闭包风格的语法在编译期生成查找表
+ * const fn 不能接收闭包参数(调用闭包需要 Fn 特征)，所以用宏把循环展开在调用处
+ *   const SQUARES: [u32; 16] = const_table!(16, |i| (i * i) as u32);
+ */
+#[macro_export]
+macro_rules! const_table {
+    ($n:expr, |$i:ident| $body:expr) => {{
+        let mut table = [0; $n];
+        let mut $i = 0;
+        while $i < $n {
+            table[$i] = $body;
+            $i += 1;
+        }
+        table
+    }};
+}
+
+pub const fn popcount_table() -> [u8; 256] {
+    const_table!(256, |i| (i as u8).count_ones() as u8)
+}
+
+pub const POPCOUNT: [u8; 256] = popcount_table();
+
+pub const PRIMES_BELOW_64: u64 = {
+    let mut bits = 0u64;
+    let mut i = 0;
+    while i < 64 {
+        if is_prime(i) {
+            bits |= 1 << i;
+        }
+        i += 1;
+    }
+    bits
+};
+
+/* ---------------------------- CRC32 ---------------------------- */
+
+// IEEE 802.3 多项式(反射形式)，zip、png 使用的 CRC32
+const CRC32_POLY: u32 = 0xEDB8_8320;
+
+pub const fn crc32_table() -> [u32; 256] {
+    const_table!(256, |i| {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ CRC32_POLY
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        crc
+    })
+}
+
+pub const CRC32_TABLE: [u32; 256] = crc32_table();
+
+pub const fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    let mut i = 0;
+    while i < data.len() {
+        crc = CRC32_TABLE[((crc ^ data[i] as u32) & 0xFF) as usize] ^ (crc >> 8);
+        i += 1;
+    }
+    !crc
+}
+
+/* ---------------------------- SHA-1 ---------------------------- */
+
+// 取第 block 个 64 字节分组，最后的分组在这里补位(0x80、若干 0、64 位长度)
+const fn sha1_block(data: &[u8], block: usize) -> [u8; 64] {
+    let mut out = [0u8; 64];
+    let bit_len = (data.len() as u64).wrapping_mul(8);
+    let total_blocks = (data.len() + 8) / 64 + 1;
+    let mut i = 0;
+    while i < 64 {
+        let pos = block * 64 + i;
+        out[i] = if pos < data.len() {
+            data[pos]
+        } else if pos == data.len() {
+            0x80
+        } else if block == total_blocks - 1 && i >= 56 {
+            (bit_len >> ((63 - i) * 8)) as u8
+        } else {
+            0
+        };
+        i += 1;
+    }
+    out
+}
+
+pub const fn sha1(data: &[u8]) -> [u8; 20] {
+    let mut h: [u32; 5] = [
+        0x6745_2301,
+        0xEFCD_AB89,
+        0x98BA_DCFE,
+        0x1032_5476,
+        0xC3D2_E1F0,
+    ];
+    let total_blocks = (data.len() + 8) / 64 + 1;
+    let mut block = 0;
+    while block < total_blocks {
+        let chunk = sha1_block(data, block);
+        let mut w = [0u32; 80];
+        let mut t = 0;
+        while t < 16 {
+            w[t] = u32::from_be_bytes([
+                chunk[t * 4],
+                chunk[t * 4 + 1],
+                chunk[t * 4 + 2],
+                chunk[t * 4 + 3],
+            ]);
+            t += 1;
+        }
+        while t < 80 {
+            w[t] = (w[t - 3] ^ w[t - 8] ^ w[t - 14] ^ w[t - 16]).rotate_left(1);
+            t += 1;
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e] = h;
+        t = 0;
+        while t < 80 {
+            let (f, k) = match t / 20 {
+                0 => ((b & c) | (!b & d), 0x5A82_7999),
+                1 => (b ^ c ^ d, 0x6ED9_EBA1),
+                2 => ((b & c) | (b & d) | (c & d), 0x8F1B_BCDC),
+                _ => (b ^ c ^ d, 0xCA62_C1D6),
+            };
+            let temp = a
+                .rotate_left(5)
+                .wrapping_add(f)
+                .wrapping_add(e)
+                .wrapping_add(k)
+                .wrapping_add(w[t]);
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = temp;
+            t += 1;
+        }
+        h[0] = h[0].wrapping_add(a);
+        h[1] = h[1].wrapping_add(b);
+        h[2] = h[2].wrapping_add(c);
+        h[3] = h[3].wrapping_add(d);
+        h[4] = h[4].wrapping_add(e);
+        block += 1;
+    }
+
+    let mut out = [0u8; 20];
+    let mut i = 0;
+    while i < 5 {
+        let bytes = h[i].to_be_bytes();
+        out[i * 4] = bytes[0];
+        out[i * 4 + 1] = bytes[1];
+        out[i * 4 + 2] = bytes[2];
+        out[i * 4 + 3] = bytes[3];
+        i += 1;
+    }
+    out
+}
+
+// 把摘要编码为小写十六进制
+pub const fn to_hex<const N: usize, const M: usize>(bytes: &[u8; N]) -> [u8; M] {
+    assert!(M == N * 2, "output must be twice the input length");
+    const DIGITS: &[u8; 16] = b"0123456789abcdef";
+    let mut out = [0u8; M];
+    let mut i = 0;
+    while i < N {
+        out[i * 2] = DIGITS[(bytes[i] >> 4) as usize];
+        out[i * 2 + 1] = DIGITS[(bytes[i] & 0xF) as usize];
+        i += 1;
+    }
+    out
+}
+
+// 数组的 == 依赖 PartialEq 特征，在 const fn 中不可用
+pub const fn bytes_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < a.len() {
+        if a[i] != b[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+/* ---------------------------- 编译期断言 ---------------------------- */
+
+// 下面的断言在编译时求值，任何一个不成立都会导致编译失败
+const _: () = {
+    assert!(is_prime(97) && !is_prime(91));
+    assert!(fib(50) == 12_586_269_025);
+    assert!(gcd(1071, 462) == 21);
+    assert!(POPCOUNT[0xFF] == 8 && POPCOUNT[0b1010_0001] == 3);
+    assert!(PRIMES_BELOW_64 & (1 << 61) != 0);
+    assert!(PRIMES_BELOW_64.count_ones() == 18);
+
+    assert!(crc32(b"") == 0);
+    assert!(crc32(b"123456789") == 0xCBF4_3926);
+    assert!(crc32(b"The quick brown fox jumps over the lazy dog") == 0x414F_A339);
+
+    assert!(bytes_eq(
+        &to_hex::<20, 40>(&sha1(b"")),
+        b"da39a3ee5e6b4b0d3255bfef95601890afd80709"
+    ));
+    assert!(bytes_eq(
+        &to_hex::<20, 40>(&sha1(b"abc")),
+        b"a9993e364706816aba3e25717850c26c9cd0d89d"
+    ));
+    // 56 字节的输入需要额外一个分组来放长度
+    assert!(bytes_eq(
+        &to_hex::<20, 40>(&sha1(
+            b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"
+        )),
+        b"84983e441c3bd26ebaae4aa1f95129e5e54670f1"
+    ));
+};
+
+pub const SMALL_PRIMES: ArrayVec<u32, 8> = {
+    let mut v = ArrayVec::new();
+    let mut n = 0;
+    while !v.is_full() {
+        if is_prime(n) {
+            v.push(n);
+        }
+        n += 1;
+    }
+    v
+};
+
+// ArrayVec 实现了 Drop，而析构函数不能在编译期执行，所以常量上下文中用完后要 forget
+const _: () = {
+    let primes = SMALL_PRIMES;
+    assert!(primes.len() == 8);
+    assert!(*primes.as_slice().last().unwrap() == 19);
+    std::mem::forget(primes);
+
+    let mut v: ArrayVec<u8, 2> = ArrayVec::from_slice(b"ab");
+    assert!(v.is_full());
+    match v.try_push(b'c') {
+        Err(CapacityError(c)) => assert!(c == b'c'),
+        Ok(()) => panic!("push into a full ArrayVec"),
+    }
+    assert!(matches!(v.pop(), Some(b'b')));
+    assert!(bytes_eq(v.as_slice(), b"a"));
+    let arr: [u8; 3] = v.to_array(0);
+    assert!(bytes_eq(&arr, b"a\0\0"));
+    // 容量已满时 push 会 panic，放开下面这行会编译失败
+    // v.push(b'x'); v.push(b'y');
+    std::mem::forget(v);
+};
+
+// SHA-1 和 CRC32 作为编译期常量嵌入二进制
+pub const BUILD_DIGEST: [u8; 20] = sha1(b"0xSchnappi/Rust");
+pub const BUILD_CRC: u32 = crc32(b"0xSchnappi/Rust");
+
+// cargo run -- const [文本]
+pub fn const_fn_practice(args: &[String]) {
+    // const fn 同样可以在运行期调用
+    let input = args.first().map_or("abc", String::as_str);
+    let digest = sha1(input.as_bytes());
+    let hex: [u8; 40] = to_hex(&digest);
+    println!("sha1({:?}) = {}", input, String::from_utf8_lossy(&hex));
+    println!("crc32({:?}) = {:08x}", input, crc32(input.as_bytes()));
+    println!(
+        "build digest = {}, crc = {:08x}",
+        String::from_utf8_lossy(&to_hex::<20, 40>(&BUILD_DIGEST)),
+        BUILD_CRC
+    );
+
+    // 运行期使用 ArrayVec，元素有析构函数
+    let mut names: ArrayVec<String, 3> = ArrayVec::new();
+    names.push("中国".to_string());
+    names.push("Rust".to_string());
+    assert_eq!(names.len(), 2);
+    assert_eq!(names.capacity(), 3);
+    names.push("const".to_string());
+    let rejected = names.try_push("overflow".to_string());
+    assert_eq!(rejected, Err(CapacityError("overflow".to_string())));
+    names.sort();
+    assert_eq!(names.as_slice(), ["Rust", "const", "中国"]);
+    let copy = names.clone();
+    assert_eq!(copy, names);
+    assert_eq!(names.pop().as_deref(), Some("中国"));
+    names.clear();
+    assert!(names.is_empty());
+    println!("{:?} {:?}", copy, SMALL_PRIMES);
+
+    let squares: [u32; 8] = const_table!(8, |i| (i * i) as u32);
+    assert_eq!(squares, [0, 1, 4, 9, 16, 25, 36, 49]);
+    assert_eq!(fib(10), 55);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn is_prime_near_u32_max() {
+        // 小于 2^32 的最大素数
+        assert!(is_prime(4_294_967_291));
+        assert!(!is_prime(u32::MAX));
+        assert!(!is_prime(65_521 * 65_537));
+        // 编译期求值同样不会溢出
+        const { assert!(is_prime(4_294_967_291)) };
+    }
+}
@@ -0,0 +1,376 @@
+//! 静态分发与动态分发的基准测试
+//! Screen_1<T: Draw> 的注释说泛型"代码更清晰，性能更好"，这里和特征对象、枚举、函数指针表比较一下
+
+use std::hint::black_box;
+use std::mem::{size_of, size_of_val};
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+use crate::hasher_bench::XorShift;
+use crate::{Button, Draw, Screen, Screen_1, SelectBox};
+
+/*
+ * 四种分发方式，每次调用的都是 Draw::size：
+ *   泛型      Screen_1<T>，编译期确定类型，可以内联，但每个 T 生成一份代码，且只能存放同一种类型
+ *   特征对象  Screen 中的 Box<dyn Draw>，通过虚表间接调用，每个组件单独分配在堆上
+ *   枚举      enum Widget，match 后直接调用，组件连续存放，但新增类型需要修改枚举
+ *   函数指针  (函数指针, 下标) 表，和虚表一样是间接调用，但数据按类型分开连续存放
+ */
+pub enum Widget {
+    Button(Button),
+    Select(SelectBox),
+}
+
+impl Draw for Widget {
+    fn draw(&self) {
+        match self {
+            Widget::Button(b) => b.draw(),
+            Widget::Select(s) => s.draw(),
+        }
+    }
+
+    fn size(&self) -> (u16, u16) {
+        match self {
+            Widget::Button(b) => b.size(),
+            Widget::Select(s) => s.size(),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct Store {
+    buttons: Vec<Button>,
+    selects: Vec<SelectBox>,
+}
+
+type SizeFn = fn(&Store, usize) -> (u16, u16);
+
+fn button_size(store: &Store, i: usize) -> (u16, u16) {
+    store.buttons[i].size()
+}
+
+fn select_size(store: &Store, i: usize) -> (u16, u16) {
+    store.selects[i].size()
+}
+
+#[derive(Default)]
+pub struct FnTable {
+    store: Store,
+    entries: Vec<(SizeFn, usize)>,
+}
+
+fn area((w, h): (u16, u16)) -> u64 {
+    w as u64 * h as u64
+}
+
+// 被测函数禁止内联，既保证测量的是循环本身，也让它们在符号表中可见，便于统计代码大小
+#[inline(never)]
+pub fn static_sum<T: Draw>(screen: &Screen_1<T>) -> u64 {
+    screen.components.iter().map(|c| area(c.size())).sum()
+}
+
+#[inline(never)]
+pub fn dyn_sum(screen: &Screen) -> u64 {
+    screen.components.iter().map(|c| area(c.size())).sum()
+}
+
+#[inline(never)]
+pub fn enum_sum(widgets: &[Widget]) -> u64 {
+    widgets.iter().map(|w| area(w.size())).sum()
+}
+
+#[inline(never)]
+pub fn table_sum(table: &FnTable) -> u64 {
+    table
+        .entries
+        .iter()
+        .map(|(f, i)| area(f(&table.store, *i)))
+        .sum()
+}
+
+/* ---------------------------- 组件列表 ---------------------------- */
+
+// 同一批组件的四种存放方式
+pub struct Lists {
+    // 泛型只能存放一种类型，混合列表时按类型分成两组
+    pub buttons: Screen_1<Button>,
+    pub selects: Screen_1<SelectBox>,
+    pub boxed: Screen,
+    pub widgets: Vec<Widget>,
+    pub table: FnTable,
+}
+
+fn make_button(rng: &mut XorShift) -> Button {
+    Button {
+        width: (rng.next_u64() % 80) as u32,
+        height: (rng.next_u64() % 24) as u32,
+        label: String::new(),
+        on_click: None,
+    }
+}
+
+fn make_select(rng: &mut XorShift) -> SelectBox {
+    SelectBox {
+        width: (rng.next_u64() % 80) as u32,
+        height: (rng.next_u64() % 24) as u32,
+        options: Vec::new(),
+        selected: 0,
+        on_change: None,
+    }
+}
+
+// mixed 为 false 时全部是 Button，否则 Button 和 SelectBox 随机混合，间接调用的目标无法预测
+pub fn make_lists(n: usize, mixed: bool, seed: u64) -> Lists {
+    let mut rng = XorShift::new(seed);
+    let mut lists = Lists {
+        buttons: Screen_1 {
+            components: Vec::new(),
+        },
+        selects: Screen_1 {
+            components: Vec::new(),
+        },
+        boxed: Screen::default(),
+        widgets: Vec::with_capacity(n),
+        table: FnTable::default(),
+    };
+    // 用同样的随机序列生成四份相同的组件
+    for _ in 0..n {
+        let is_select = mixed && rng.next_u64().is_multiple_of(2);
+        let state = rng.next_u64();
+        let r = || XorShift::new(state);
+        if is_select {
+            lists.selects.components.push(make_select(&mut r()));
+            lists.boxed.components.push(Box::new(make_select(&mut r())));
+            lists.widgets.push(Widget::Select(make_select(&mut r())));
+            let t = &mut lists.table;
+            t.entries.push((select_size, t.store.selects.len()));
+            t.store.selects.push(make_select(&mut r()));
+        } else {
+            lists.buttons.components.push(make_button(&mut r()));
+            lists.boxed.components.push(Box::new(make_button(&mut r())));
+            lists.widgets.push(Widget::Button(make_button(&mut r())));
+            let t = &mut lists.table;
+            t.entries.push((button_size, t.store.buttons.len()));
+            t.store.buttons.push(make_button(&mut r()));
+        }
+    }
+    lists
+}
+
+/* ---------------------------- 测量 ---------------------------- */
+
+pub struct Row {
+    pub strategy: &'static str,
+    pub ns_per_call: f64,
+    // 每个组件占用的内存(字节)，包括堆上的部分
+    pub bytes_per_item: usize,
+    pub checksum: u64,
+}
+
+fn best_of(rounds: usize, calls: usize, mut f: impl FnMut() -> u64) -> (f64, u64) {
+    let mut best = Duration::MAX;
+    let mut checksum = 0;
+    for _ in 0..rounds.max(1) {
+        let start = Instant::now();
+        checksum = black_box(f());
+        best = best.min(start.elapsed());
+    }
+    (best.as_nanos() as f64 / calls.max(1) as f64, checksum)
+}
+
+pub fn measure(lists: &Lists, rounds: usize) -> Vec<Row> {
+    let n = lists.widgets.len();
+    let boxed_heap: usize = lists
+        .boxed
+        .components
+        .iter()
+        .map(|c| size_of_val(&**c))
+        .sum();
+
+    let (ns, checksum) = best_of(rounds, n, || {
+        static_sum(black_box(&lists.buttons)) + static_sum(black_box(&lists.selects))
+    });
+    let generic_bytes = lists.buttons.components.len() * size_of::<Button>()
+        + lists.selects.components.len() * size_of::<SelectBox>();
+    let mut rows = vec![Row {
+        strategy: "generic",
+        ns_per_call: ns,
+        bytes_per_item: generic_bytes / n.max(1),
+        checksum,
+    }];
+
+    let (ns, checksum) = best_of(rounds, n, || dyn_sum(black_box(&lists.boxed)));
+    rows.push(Row {
+        strategy: "dyn Draw",
+        ns_per_call: ns,
+        bytes_per_item: size_of::<Box<dyn Draw>>() + boxed_heap / n.max(1),
+        checksum,
+    });
+
+    let (ns, checksum) = best_of(rounds, n, || enum_sum(black_box(&lists.widgets)));
+    rows.push(Row {
+        strategy: "enum",
+        ns_per_call: ns,
+        bytes_per_item: size_of::<Widget>(),
+        checksum,
+    });
+
+    let (ns, checksum) = best_of(rounds, n, || table_sum(black_box(&lists.table)));
+    let store = &lists.table.store;
+    let table_bytes =
+        store.buttons.len() * size_of::<Button>() + store.selects.len() * size_of::<SelectBox>();
+    rows.push(Row {
+        strategy: "fn table",
+        ns_per_call: ns,
+        bytes_per_item: size_of::<(SizeFn, usize)>() + table_bytes / n.max(1),
+        checksum,
+    });
+    rows
+}
+
+pub fn print_rows(title: &str, rows: &[Row]) {
+    println!("{}", title);
+    println!(
+        "  {:<10}{:>12}{:>14}{:>10}",
+        "strategy", "ns/call", "bytes/item", "relative"
+    );
+    let base = rows[0].ns_per_call.max(f64::MIN_POSITIVE);
+    for r in rows {
+        println!(
+            "  {:<10}{:>12.3}{:>14}{:>9.2}x",
+            r.strategy,
+            r.ns_per_call,
+            r.bytes_per_item,
+            r.ns_per_call / base
+        );
+    }
+}
+
+/* ---------------------------- 代码大小 ---------------------------- */
+
+/*
+ * 代码大小通过当前可执行文件的符号表统计(需要 nm，且没有 strip)：
+ * 泛型函数每个类型参数生成一份代码，所以 static_sum 出现两次，其余函数只有一份
+ * 注意：被内联进调用者的函数(比如 release 下的 Draw::size 和闭包)不会出现在符号表中，
+ * 它们的代码计入调用者的大小
+ */
+pub struct CodeSize {
+    pub name: &'static str,
+    pub copies: usize,
+    pub bytes: u64,
+}
+
+pub fn code_sizes() -> Result<Vec<CodeSize>, String> {
+    let exe = std::env::current_exe().map_err(|e| e.to_string())?;
+    let out = Command::new("nm")
+        .args(["-C", "-S", "--size-sort"])
+        .arg(&exe)
+        .output()
+        .map_err(|e| format!("cannot run nm: {}", e))?;
+    if !out.status.success() {
+        return Err(String::from_utf8_lossy(&out.stderr).trim().to_string());
+    }
+    let symbols = String::from_utf8_lossy(&out.stdout);
+    let mut sizes: Vec<CodeSize> = [
+        "static_sum",
+        "dyn_sum",
+        "enum_sum",
+        "table_sum",
+        "button_size",
+        "select_size",
+    ]
+    .iter()
+    .map(|&name| CodeSize {
+        name,
+        copies: 0,
+        bytes: 0,
+    })
+    .collect();
+    for line in symbols.lines() {
+        // 地址 大小 类型 名字
+        let mut parts = line.splitn(4, ' ');
+        let (Some(_), Some(size), Some(_), Some(symbol)) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            continue;
+        };
+        for s in sizes.iter_mut() {
+            let path = format!("dispatch_bench::{}", s.name);
+            let Some(rest) = symbol.find(&path).map(|i| &symbol[i + path.len()..]) else {
+                continue;
+            };
+            // 函数中的闭包是单独的符号，大小计入函数，但不算一份新的拷贝
+            if rest.is_empty() {
+                s.copies += 1;
+            } else if !rest.starts_with("::{{closure}}") {
+                continue;
+            }
+            s.bytes += u64::from_str_radix(size, 16).unwrap_or(0);
+        }
+    }
+    if sizes.iter().all(|s| s.copies == 0) {
+        return Err("no dispatch_bench symbols found (stripped binary?)".to_string());
+    }
+    Ok(sizes)
+}
+
+pub fn print_code_sizes() {
+    match code_sizes() {
+        Ok(sizes) => {
+            println!("code size (from symbol table)");
+            println!("  {:<12}{:>8}{:>10}", "function", "copies", "bytes");
+            for s in sizes {
+                println!("  {:<12}{:>8}{:>10}", s.name, s.copies, s.bytes);
+            }
+        }
+        Err(e) => println!("code size unavailable: {}", e),
+    }
+}
+
+// cargo run --release -- dispatch [--n 1000000] [--rounds 10]
+pub fn dispatch_bench(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let mut n = 1_000_000;
+    let mut rounds = 10;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let value = iter
+            .next()
+            .ok_or_else(|| format!("{} requires a value", arg))?;
+        match arg.as_str() {
+            "--n" => n = value.parse()?,
+            "--rounds" => rounds = value.parse()?,
+            other => return Err(format!("unknown option: {}", other).into()),
+        }
+    }
+    print_rows(
+        "all Button (predictable)",
+        &measure(&make_lists(n, false, 1), rounds),
+    );
+    print_rows(
+        "Button/SelectBox mixed at random (generic: grouped by type)",
+        &measure(&make_lists(n, true, 2), rounds),
+    );
+    print_code_sizes();
+    Ok(())
+}
+
+pub fn dispatch_practice() {
+    for mixed in [false, true] {
+        let lists = make_lists(10_000, mixed, 7);
+        assert_eq!(lists.boxed.components.len(), 10_000);
+        assert_eq!(
+            lists.buttons.components.len() + lists.selects.components.len(),
+            10_000
+        );
+        assert_eq!(lists.selects.components.is_empty(), !mixed);
+        let rows = measure(&lists, 3);
+        // 四种方式计算出的结果相同
+        assert!(rows.iter().all(|r| r.checksum == rows[0].checksum));
+        assert!(rows[0].checksum > 0);
+        print_rows(if mixed { "mixed" } else { "all Button" }, &rows);
+    }
+    // 特征对象是胖指针：数据指针 + 虚表指针
+    assert_eq!(size_of::<Box<dyn Draw>>(), 2 * size_of::<usize>());
+    assert!(size_of::<Widget>() >= size_of::<Button>().max(size_of::<SelectBox>()));
+    print_code_sizes();
+}
@@ -0,0 +1,191 @@
+//! 枚举练习：back_to_enum! 生成的转换和名称，以及 EnumSet、EnumMap
+
+use rust::back_to_enum;
+use rust::enums::{Enum, EnumMap, EnumSet, InvalidValue};
+
+use crate::{Direction, MyEnum, MyEnum1, MyEnum2, PokerSuit};
+
+back_to_enum! {
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    #[repr(u8)]
+    enum Suit {
+        Clubs = 1,
+        Spades => "♠",
+        Diamonds = 10,
+        Hearts => "hearts",
+    }
+}
+
+back_to_enum! {
+    ignore_case;
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    #[repr(i8)]
+    pub enum Level {
+        Low = -1,
+        Mid,
+        High = 100,
+    }
+}
+
+back_to_enum! {
+    #[derive(Debug, PartialEq)]
+    #[repr(u64)]
+    enum Big {
+        Max = u64::MAX,
+        Zero = 0,
+    }
+}
+
+fn count<E: Enum>() -> usize {
+    E::COUNT
+}
+
+pub fn enums_practice() {
+    assert_eq!(Suit::COUNT, 4);
+    assert_eq!(
+        Suit::ALL,
+        [Suit::Clubs, Suit::Spades, Suit::Diamonds, Suit::Hearts]
+    );
+    let discriminants: Vec<u8> = Suit::iter().map(|s| s as u8).collect();
+    assert_eq!(discriminants, [1, 2, 10, 11]);
+    assert_eq!(Suit::Diamonds.index(), 2);
+
+    // 所有整数类型都可以转换，错误中保留原值
+    assert_eq!(Suit::try_from(10u8), Ok(Suit::Diamonds));
+    assert_eq!(Suit::try_from(11i64), Ok(Suit::Hearts));
+    assert_eq!(Suit::try_from(2u128), Ok(Suit::Spades));
+    let err = Suit::try_from(3u16).unwrap_err();
+    assert_eq!(err, InvalidValue::new("Suit", 3u16));
+    assert_eq!(err.to_string(), "3 is not a valid Suit");
+    assert_eq!(Suit::try_from(-1i32).unwrap_err().value, -1);
+    // 257 截断为 u8 后等于 1，但这里先按 i128 比较，不会误判
+    assert!(Suit::try_from(257i32).is_err());
+    assert_eq!(Level::try_from(-1isize), Ok(Level::Low));
+    assert_eq!(Level::try_from(0u8), Ok(Level::Mid));
+    assert_eq!(Level::try_from(255u8).unwrap_err().value, 255);
+    assert_eq!(Big::try_from(u64::MAX), Ok(Big::Max));
+    assert_eq!(Big::try_from(u128::MAX).unwrap_err().value, u128::MAX);
+    assert_eq!(Big::try_from(-1i64).unwrap_err().value, -1);
+
+    // 名称，重命名后只认新名称
+    assert_eq!(Suit::Spades.to_string(), "♠");
+    assert_eq!(format!("[{:>6}]", Suit::Hearts), "[hearts]");
+    assert_eq!(format!("[{:<7}]", Suit::Clubs), "[Clubs  ]");
+    assert_eq!("♠".parse(), Ok(Suit::Spades));
+    assert_eq!("Clubs".parse(), Ok(Suit::Clubs));
+    assert!("Spades".parse::<Suit>().is_err());
+    assert_eq!(
+        "clubs".parse::<Suit>().unwrap_err().to_string(),
+        "unknown Suit variant: \"clubs\""
+    );
+    assert_eq!("hIgH".parse(), Ok(Level::High));
+    assert_eq!("low".parse(), Ok(Level::Low));
+    assert_eq!(Big::Zero.name(), "Zero");
+    for level in Level::iter() {
+        assert_eq!(level.to_string().to_uppercase().parse(), Ok(level));
+    }
+
+    assert_eq!(count::<Suit>(), 4);
+    assert_eq!(count::<Level>(), 3);
+    assert_eq!(<Big as Enum>::ALL.len(), 2);
+    assert_eq!(Enum::name(&Level::Mid), "Mid");
+    assert_eq!(Enum::index(&Level::High), 2);
+
+    containers_practice();
+
+    for suit in Suit::iter() {
+        println!("{:>8} = {}", suit, suit as u8);
+    }
+}
+
+fn names<E: Enum>(set: EnumSet<E>) -> Vec<&'static str> {
+    set.iter().map(E::name).collect()
+}
+
+fn containers_practice() {
+    // 插入顺序不影响遍历顺序
+    let red: EnumSet<PokerSuit> = [PokerSuit::Hearts, PokerSuit::Diamonds].iter().collect();
+    let mut black = EnumSet::new();
+    assert!(black.insert(&PokerSuit::Spades));
+    assert!(!black.insert(&PokerSuit::Spades));
+    black.insert(&PokerSuit::Clubs);
+    assert_eq!(format!("{:?}", red), "{Diamonds, Hearts}");
+    assert_eq!(format!("{:?}", black), "{Clubs, Spades}");
+    assert_eq!(red | black, EnumSet::all());
+    assert_eq!(!red, black);
+    assert!((red & black).is_empty());
+    assert!(red.is_disjoint(&black));
+    assert_eq!(red.len(), 2);
+    assert_eq!(red.bits(), 0b1100);
+
+    let mut high = red;
+    high.remove(&PokerSuit::Diamonds);
+    high |= EnumSet::from_iter([&PokerSuit::Spades]);
+    assert_eq!(names(high), ["Spades", "Hearts"]);
+    assert_eq!(names(red - high), ["Diamonds"]);
+    assert_eq!(names(red ^ high), ["Spades", "Diamonds"]);
+    assert!(EnumSet::from_iter([&PokerSuit::Hearts]).is_subset(&red));
+    assert!(EnumSet::<PokerSuit>::all().is_superset(&high));
+    high -= red;
+    high &= black;
+    assert_eq!(names(high), ["Spades"]);
+    high ^= black;
+    assert_eq!(names(high), ["Clubs"]);
+    high.clear();
+    assert_eq!(high, EnumSet::default());
+
+    // 判别值从 1 开始，序号仍从 0 开始
+    let mut abc: EnumSet<MyEnum> = EnumSet::new();
+    abc.insert(&MyEnum::C);
+    assert_eq!(abc.bits(), 0b100);
+    assert_eq!(format!("{:?}", abc.complement()), "{A, B}");
+    let one: EnumSet<MyEnum1> = [&MyEnum1::B].into_iter().collect();
+    assert_eq!(format!("{:?}", one), "{B}");
+    let all2 = EnumSet::<MyEnum2>::all();
+    assert_eq!(all2.len(), MyEnum2::COUNT);
+    assert!(all2.contains(&MyEnum2::C));
+    let empty: EnumSet<Direction> = EnumSet::new();
+    assert_eq!(format!("{:?}", empty), "{}");
+    assert_eq!(names(!empty), ["East", "West", "North", "South"]);
+
+    // EnumMap 按变体下标存取
+    let mut steps: EnumMap<Direction, i32> = EnumMap::default();
+    for (dir, n) in [
+        (Direction::North, 3),
+        (Direction::East, 2),
+        (Direction::North, 1),
+        (Direction::West, 5),
+    ] {
+        steps[&dir] += n;
+    }
+    assert_eq!(steps[&Direction::North], 4);
+    assert_eq!(
+        format!("{:?}", steps),
+        "{East: 2, West: 5, North: 4, South: 0}"
+    );
+    let x = steps[&Direction::East] - steps[&Direction::West];
+    let y = steps[&Direction::North] - steps[&Direction::South];
+    assert_eq!((x, y), (-3, 4));
+    assert_eq!(steps.insert(&Direction::South, 9), 0);
+    assert_eq!(steps.values().sum::<i32>(), 20);
+    for (_, v) in steps.iter_mut() {
+        *v *= 2;
+    }
+    assert_eq!(*steps.get(&Direction::South), 18);
+    assert_eq!(steps.len(), 4);
+    assert!(!steps.is_empty());
+    assert_eq!(steps.clone(), steps);
+
+    let symbols = EnumMap::<PokerSuit, char>::from_fn(|s| match s {
+        PokerSuit::Clubs => '♣',
+        PokerSuit::Spades => '♠',
+        PokerSuit::Diamonds => '♦',
+        PokerSuit::Hearts => '♥',
+    });
+    let line: String = symbols.iter().map(|(_, c)| *c).collect();
+    assert_eq!(line, "♣♠♦♥");
+    let labels = EnumMap::<MyEnum2, String>::from_fn(|e| format!("{}{}", e, e.index()));
+    assert_eq!(format!("{:?}", labels), r#"{A: "A0", B: "B1", C: "C2"}"#);
+    let counts: EnumMap<Suit, usize> = EnumMap::from_fn(|s: &Suit| s.name().chars().count());
+    assert_eq!(counts[&Suit::Spades], 1);
+}
@@ -0,0 +1,366 @@
+//! 枚举工具
+//! back_to_enum! 宏(定义在 lib.rs)生成的代码用到的错误类型和 Enum 特征，以及以变体为键的 EnumSet 和 EnumMap
+
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+use std::ops;
+
+// 整数不是任何变体的判别值，value 保留被拒绝的值
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InvalidValue<T> {
+    pub enum_name: &'static str,
+    pub value: T,
+}
+
+impl<T> InvalidValue<T> {
+    pub fn new(enum_name: &'static str, value: T) -> Self {
+        InvalidValue { enum_name, value }
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for InvalidValue<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} is not a valid {}", self.value, self.enum_name)
+    }
+}
+
+impl<T: fmt::Debug + fmt::Display> std::error::Error for InvalidValue<T> {}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseEnumError {
+    pub enum_name: &'static str,
+    pub input: String,
+}
+
+impl ParseEnumError {
+    pub fn new(enum_name: &'static str, input: &str) -> Self {
+        ParseEnumError {
+            enum_name,
+            input: input.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for ParseEnumError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "unknown {} variant: {:?}", self.enum_name, self.input)
+    }
+}
+
+impl std::error::Error for ParseEnumError {}
+
+pub fn name_matches(name: &str, input: &str, ignore_case: bool) -> bool {
+    if ignore_case {
+        name.to_lowercase() == input.to_lowercase()
+    } else {
+        name == input
+    }
+}
+
+// 无数据的枚举，ALL 按声明顺序排列，index 是变体在 ALL 中的位置
+pub trait Enum: Sized + 'static {
+    const ALL: &'static [Self];
+    const COUNT: usize = Self::ALL.len();
+
+    fn index(&self) -> usize;
+    fn name(&self) -> &'static str;
+}
+
+/* ---------------------------- EnumSet ---------------------------- */
+
+// 以变体序号为位的集合，最多 128 个变体
+pub struct EnumSet<E: Enum> {
+    bits: u128,
+    _marker: PhantomData<fn() -> E>,
+}
+
+impl<E: Enum> EnumSet<E> {
+    const FULL: u128 = {
+        assert!(E::COUNT <= 128, "EnumSet supports at most 128 variants");
+        if E::COUNT == 128 {
+            u128::MAX
+        } else {
+            (1 << E::COUNT) - 1
+        }
+    };
+
+    fn from_bits(bits: u128) -> Self {
+        EnumSet {
+            bits: bits & Self::FULL,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn new() -> Self {
+        Self::from_bits(0)
+    }
+
+    pub fn all() -> Self {
+        Self::from_bits(Self::FULL)
+    }
+
+    pub fn bits(&self) -> u128 {
+        self.bits
+    }
+
+    // 返回是否是新加入的
+    pub fn insert(&mut self, e: &E) -> bool {
+        let had = self.contains(e);
+        self.bits |= 1 << e.index();
+        !had
+    }
+
+    pub fn remove(&mut self, e: &E) -> bool {
+        let had = self.contains(e);
+        self.bits &= !(1 << e.index());
+        had
+    }
+
+    pub fn contains(&self, e: &E) -> bool {
+        self.bits & (1 << e.index()) != 0
+    }
+
+    pub fn len(&self) -> usize {
+        self.bits.count_ones() as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bits == 0
+    }
+
+    pub fn clear(&mut self) {
+        self.bits = 0;
+    }
+
+    pub fn union(self, other: Self) -> Self {
+        Self::from_bits(self.bits | other.bits)
+    }
+
+    pub fn intersection(self, other: Self) -> Self {
+        Self::from_bits(self.bits & other.bits)
+    }
+
+    pub fn difference(self, other: Self) -> Self {
+        Self::from_bits(self.bits & !other.bits)
+    }
+
+    pub fn symmetric_difference(self, other: Self) -> Self {
+        Self::from_bits(self.bits ^ other.bits)
+    }
+
+    pub fn complement(self) -> Self {
+        Self::from_bits(!self.bits)
+    }
+
+    pub fn is_subset(&self, other: &Self) -> bool {
+        self.bits & !other.bits == 0
+    }
+
+    pub fn is_superset(&self, other: &Self) -> bool {
+        other.is_subset(self)
+    }
+
+    pub fn is_disjoint(&self, other: &Self) -> bool {
+        self.bits & other.bits == 0
+    }
+
+    // 按声明顺序
+    pub fn iter(&self) -> impl Iterator<Item = &'static E> {
+        let bits = self.bits;
+        E::ALL
+            .iter()
+            .enumerate()
+            .filter(move |(i, _)| bits & (1 << i) != 0)
+            .map(|(_, e)| e)
+    }
+}
+
+// 手工实现，派生会要求 E 也实现这些特征
+impl<E: Enum> Clone for EnumSet<E> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<E: Enum> Copy for EnumSet<E> {}
+
+impl<E: Enum> Default for EnumSet<E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<E: Enum> PartialEq for EnumSet<E> {
+    fn eq(&self, other: &Self) -> bool {
+        self.bits == other.bits
+    }
+}
+
+impl<E: Enum> Eq for EnumSet<E> {}
+
+impl<E: Enum> Hash for EnumSet<E> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.bits.hash(state);
+    }
+}
+
+// 输出变体名称，例如 {Clubs, Hearts}
+struct Name(&'static str);
+
+impl fmt::Debug for Name {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.0)
+    }
+}
+
+impl<E: Enum> fmt::Debug for EnumSet<E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_set()
+            .entries(self.iter().map(|e| Name(e.name())))
+            .finish()
+    }
+}
+
+impl<'a, E: Enum> FromIterator<&'a E> for EnumSet<E> {
+    fn from_iter<I: IntoIterator<Item = &'a E>>(iter: I) -> Self {
+        let mut set = Self::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl<'a, E: Enum> Extend<&'a E> for EnumSet<E> {
+    fn extend<I: IntoIterator<Item = &'a E>>(&mut self, iter: I) {
+        for e in iter {
+            self.insert(e);
+        }
+    }
+}
+
+macro_rules! set_op {
+    ($($trait:ident $method:ident $assign:ident $assign_method:ident => $op:ident),*) => {$(
+        impl<E: Enum> ops::$trait for EnumSet<E> {
+            type Output = Self;
+
+            fn $method(self, rhs: Self) -> Self {
+                self.$op(rhs)
+            }
+        }
+
+        impl<E: Enum> ops::$assign for EnumSet<E> {
+            fn $assign_method(&mut self, rhs: Self) {
+                *self = self.$op(rhs);
+            }
+        }
+    )*};
+}
+
+set_op!(
+    BitOr bitor BitOrAssign bitor_assign => union,
+    BitAnd bitand BitAndAssign bitand_assign => intersection,
+    Sub sub SubAssign sub_assign => difference,
+    BitXor bitxor BitXorAssign bitxor_assign => symmetric_difference
+);
+
+impl<E: Enum> ops::Not for EnumSet<E> {
+    type Output = Self;
+
+    fn not(self) -> Self {
+        self.complement()
+    }
+}
+
+/* ---------------------------- EnumMap ---------------------------- */
+
+// 每个变体对应一个值，按变体序号存放，不需要哈希
+pub struct EnumMap<E: Enum, V> {
+    values: Box<[V]>,
+    _marker: PhantomData<fn() -> E>,
+}
+
+impl<E: Enum, V> EnumMap<E, V> {
+    pub fn from_fn(mut f: impl FnMut(&E) -> V) -> Self {
+        EnumMap {
+            values: E::ALL.iter().map(&mut f).collect(),
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn get(&self, e: &E) -> &V {
+        &self.values[e.index()]
+    }
+
+    pub fn get_mut(&mut self, e: &E) -> &mut V {
+        &mut self.values[e.index()]
+    }
+
+    // 返回旧值
+    pub fn insert(&mut self, e: &E, value: V) -> V {
+        std::mem::replace(self.get_mut(e), value)
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    // 按声明顺序
+    pub fn iter(&self) -> impl Iterator<Item = (&'static E, &V)> {
+        E::ALL.iter().zip(self.values.iter())
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (&'static E, &mut V)> {
+        E::ALL.iter().zip(self.values.iter_mut())
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &V> {
+        self.values.iter()
+    }
+}
+
+impl<E: Enum, V: Default> Default for EnumMap<E, V> {
+    fn default() -> Self {
+        Self::from_fn(|_| V::default())
+    }
+}
+
+impl<E: Enum, V: Clone> Clone for EnumMap<E, V> {
+    fn clone(&self) -> Self {
+        EnumMap {
+            values: self.values.clone(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<E: Enum, V: PartialEq> PartialEq for EnumMap<E, V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.values == other.values
+    }
+}
+
+impl<E: Enum, V> ops::Index<&E> for EnumMap<E, V> {
+    type Output = V;
+
+    fn index(&self, e: &E) -> &V {
+        self.get(e)
+    }
+}
+
+impl<E: Enum, V> ops::IndexMut<&E> for EnumMap<E, V> {
+    fn index_mut(&mut self, e: &E) -> &mut V {
+        self.get_mut(e)
+    }
+}
+
+impl<E: Enum, V: fmt::Debug> fmt::Debug for EnumMap<E, V> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map()
+            .entries(self.iter().map(|(e, v)| (Name(e.name()), v)))
+            .finish()
+    }
+}
@@ -0,0 +1,829 @@
+//! 开放寻址哈希表
+//! std 的 HashMap(hashbrown) 是 SwissTable，这里实现一个 Robin Hood 哈希表用来对比和调参
+
+use std::borrow::Borrow;
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::{BuildHasher, Hash};
+use std::time::{Duration, Instant};
+
+use crate::hasher_bench::{FnvBuildHasher, XorShift};
+use crate::text_stats::{sample_corpus, Segmenter};
+
+/*
+ * Robin Hood 哈希：
+ *   每个元素记录自己离理想位置(hash & mask)的距离 dist
+ *   插入时沿着探测序列向后找，如果遇到 dist 比自己小的元素("富人")，就把位置抢过来，
+ *   再继续为被挤出来的元素找位置，这样所有元素的探测距离都比较平均
+ *   查找时遇到 dist 比当前探测距离小的元素就可以提前结束，因为要找的 key 不可能在后面
+ *   删除时不用墓碑标记，而是把后面 dist > 0 的元素依次向前移动一格(backward shift)
+ */
+struct Slot<K, V> {
+    hash: u64,
+    dist: usize,
+    key: K,
+    value: V,
+}
+
+pub struct FastMap<K, V, S = RandomState> {
+    slots: Vec<Option<Slot<K, V>>>,
+    len: usize,
+    max_load: f64,
+    hasher: S,
+}
+
+const MIN_CAPACITY: usize = 8;
+const DEFAULT_MAX_LOAD: f64 = 0.875;
+
+// NaN 或接近 1 的装载因子会让 reserve 永远不扩容，表满后插入找不到空位；太小则浪费内存
+fn checked_max_load(max_load: f64) -> f64 {
+    assert!(
+        (0.1..=0.95).contains(&max_load),
+        "max load factor must be in [0.1, 0.95], got {}",
+        max_load
+    );
+    max_load
+}
+
+impl<K, V> FastMap<K, V, RandomState> {
+    pub fn new() -> Self {
+        Self::with_hasher(RandomState::new())
+    }
+}
+
+impl<K, V, S: Default> Default for FastMap<K, V, S> {
+    fn default() -> Self {
+        Self::with_hasher(S::default())
+    }
+}
+
+impl<K, V, S> FastMap<K, V, S> {
+    pub fn with_hasher(hasher: S) -> Self {
+        FastMap {
+            slots: Vec::new(),
+            len: 0,
+            max_load: DEFAULT_MAX_LOAD,
+            hasher,
+        }
+    }
+
+    // 装载因子上限，越低探测距离越短但越占内存，必须在 [0.1, 0.95] 内
+    pub fn with_max_load(mut self, max_load: f64) -> Self {
+        self.max_load = checked_max_load(max_load);
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    pub fn load_factor(&self) -> f64 {
+        if self.slots.is_empty() {
+            0.0
+        } else {
+            self.len as f64 / self.slots.len() as f64
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.slots.iter_mut().for_each(|s| *s = None);
+        self.len = 0;
+    }
+
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            slots: self.slots.iter(),
+            remaining: self.len,
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        IterMut {
+            slots: self.slots.iter_mut(),
+            remaining: self.len,
+        }
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &K> {
+        self.iter().map(|(k, _)| k)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &V> {
+        self.iter().map(|(_, v)| v)
+    }
+
+    // 探测距离统计：(平均, 最大)
+    pub fn probe_stats(&self) -> (f64, usize) {
+        let (sum, max) = self
+            .slots
+            .iter()
+            .flatten()
+            .fold((0, 0), |(sum, max), s| (sum + s.dist, max.max(s.dist)));
+        (sum as f64 / self.len.max(1) as f64, max)
+    }
+
+    fn mask(&self) -> usize {
+        self.slots.len() - 1
+    }
+
+    // 把一个确定不存在的元素插入表中(调用前已保证有空位)，返回它最终所在的下标
+    fn insert_new(&mut self, hash: u64, key: K, value: V) -> usize {
+        let mask = self.mask();
+        let mut pos = hash as usize & mask;
+        let mut carry = Slot {
+            hash,
+            dist: 0,
+            key,
+            value,
+        };
+        let mut placed = None;
+        // 装载因子小于 1，一圈之内一定有空位
+        for _ in 0..self.slots.len() {
+            match &mut self.slots[pos] {
+                slot @ None => {
+                    *slot = Some(carry);
+                    self.len += 1;
+                    return placed.unwrap_or(pos);
+                }
+                Some(existing) => {
+                    if existing.dist < carry.dist {
+                        // 劫富济贫：交换后继续为被挤出的元素找位置
+                        std::mem::swap(existing, &mut carry);
+                        placed.get_or_insert(pos);
+                    }
+                }
+            }
+            carry.dist += 1;
+            pos = (pos + 1) & mask;
+        }
+        panic!(
+            "FastMap is full: no empty slot in {} probes",
+            self.slots.len()
+        );
+    }
+
+    fn remove_at(&mut self, index: usize) -> (K, V) {
+        let mask = self.mask();
+        let slot = self.slots[index].take().unwrap();
+        self.len -= 1;
+        // backward shift：后面的元素依次前移，直到遇到空位或已经在理想位置上的元素
+        let mut hole = index;
+        loop {
+            let next = (hole + 1) & mask;
+            match self.slots[next].take() {
+                Some(mut s) if s.dist > 0 => {
+                    s.dist -= 1;
+                    self.slots[hole] = Some(s);
+                    hole = next;
+                }
+                other => {
+                    self.slots[next] = other;
+                    break;
+                }
+            }
+        }
+        (slot.key, slot.value)
+    }
+
+    // 检查 Robin Hood 的不变量，差分测试中使用
+    pub fn check_invariants(&self) {
+        if self.slots.is_empty() {
+            assert_eq!(self.len, 0);
+            return;
+        }
+        let mask = self.mask();
+        assert!(self.slots.len().is_power_of_two());
+        assert_eq!(self.slots.iter().flatten().count(), self.len);
+        assert!(self.len as f64 <= self.slots.len() as f64 * self.max_load);
+        for (i, slot) in self.slots.iter().enumerate() {
+            if let Some(s) = slot {
+                assert_eq!((i.wrapping_sub(s.hash as usize)) & mask, s.dist);
+                // 下一个元素的探测距离最多比当前多 1，否则它应该抢占当前的位置
+                if let Some(next) = &self.slots[(i + 1) & mask] {
+                    assert!(next.dist <= s.dist + 1);
+                }
+            }
+        }
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher> FastMap<K, V, S> {
+    pub fn with_capacity_and_hasher(capacity: usize, hasher: S) -> Self {
+        let mut map = Self::with_hasher(hasher);
+        map.reserve(capacity);
+        map
+    }
+
+    fn hash<Q: Hash + ?Sized>(&self, key: &Q) -> u64 {
+        self.hasher.hash_one(key)
+    }
+
+    // 保证再插入 additional 个元素不需要扩容
+    pub fn reserve(&mut self, additional: usize) {
+        let need = self.len + additional;
+        if need as f64 <= self.slots.len() as f64 * self.max_load {
+            return;
+        }
+        let mut cap = self.slots.len().max(MIN_CAPACITY);
+        while need as f64 > cap as f64 * self.max_load {
+            cap *= 2;
+        }
+        self.resize(cap);
+    }
+
+    fn resize(&mut self, cap: usize) {
+        let old = std::mem::replace(&mut self.slots, (0..cap).map(|_| None).collect());
+        self.len = 0;
+        for s in old.into_iter().flatten() {
+            self.insert_new(s.hash, s.key, s.value);
+        }
+    }
+
+    // 重新设置装载因子上限，必要时扩容
+    pub fn set_max_load(&mut self, max_load: f64) {
+        self.max_load = checked_max_load(max_load);
+        self.reserve(0);
+    }
+
+    fn find<Q>(&self, hash: u64, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+        Q: Eq + ?Sized,
+    {
+        if self.len == 0 {
+            return None;
+        }
+        let mask = self.mask();
+        let mut pos = hash as usize & mask;
+        // 最多探测一圈
+        for dist in 0..self.slots.len() {
+            match &self.slots[pos] {
+                Some(s) if s.dist >= dist => {
+                    if s.hash == hash && s.key.borrow() == key {
+                        return Some(pos);
+                    }
+                }
+                // 空位，或者遇到了更"富"的元素(dist 更小)，要找的 key 不存在
+                _ => return None,
+            }
+            pos = (pos + 1) & mask;
+        }
+        None
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let i = self.find(self.hash(key), key)?;
+        self.slots[i].as_ref().map(|s| &s.value)
+    }
+
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let i = self.find(self.hash(key), key)?;
+        self.slots[i].as_mut().map(|s| &mut s.value)
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.find(self.hash(key), key).is_some()
+    }
+
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        match self.entry(key) {
+            Entry::Occupied(mut e) => Some(e.insert(value)),
+            Entry::Vacant(e) => {
+                e.insert(value);
+                None
+            }
+        }
+    }
+
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let i = self.find(self.hash(key), key)?;
+        Some(self.remove_at(i).1)
+    }
+
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V, S> {
+        let hash = self.hash(&key);
+        match self.find(hash, &key) {
+            Some(index) => Entry::Occupied(OccupiedEntry { map: self, index }),
+            None => Entry::Vacant(VacantEntry {
+                map: self,
+                hash,
+                key,
+            }),
+        }
+    }
+}
+
+/* ---------------------------- entry ---------------------------- */
+
+pub enum Entry<'a, K, V, S> {
+    Occupied(OccupiedEntry<'a, K, V, S>),
+    Vacant(VacantEntry<'a, K, V, S>),
+}
+
+pub struct OccupiedEntry<'a, K, V, S> {
+    map: &'a mut FastMap<K, V, S>,
+    index: usize,
+}
+
+pub struct VacantEntry<'a, K, V, S> {
+    map: &'a mut FastMap<K, V, S>,
+    hash: u64,
+    key: K,
+}
+
+impl<'a, K, V, S> OccupiedEntry<'a, K, V, S> {
+    fn slot(&self) -> &Slot<K, V> {
+        self.map.slots[self.index].as_ref().unwrap()
+    }
+
+    fn slot_mut(&mut self) -> &mut Slot<K, V> {
+        self.map.slots[self.index].as_mut().unwrap()
+    }
+
+    pub fn key(&self) -> &K {
+        &self.slot().key
+    }
+
+    pub fn get(&self) -> &V {
+        &self.slot().value
+    }
+
+    pub fn get_mut(&mut self) -> &mut V {
+        &mut self.slot_mut().value
+    }
+
+    pub fn into_mut(self) -> &'a mut V {
+        &mut self.map.slots[self.index].as_mut().unwrap().value
+    }
+
+    pub fn insert(&mut self, value: V) -> V {
+        std::mem::replace(self.get_mut(), value)
+    }
+
+    pub fn remove(self) -> V {
+        self.map.remove_at(self.index).1
+    }
+}
+
+impl<'a, K: Hash + Eq, V, S: BuildHasher> VacantEntry<'a, K, V, S> {
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    pub fn insert(self, value: V) -> &'a mut V {
+        let map = self.map;
+        // 扩容会改变下标，所以在插入前扩容，且扩容不影响 find 的结果(key 本来就不存在)
+        map.reserve(1);
+        let index = map.insert_new(self.hash, self.key, value);
+        &mut map.slots[index].as_mut().unwrap().value
+    }
+}
+
+impl<'a, K: Hash + Eq, V, S: BuildHasher> Entry<'a, K, V, S> {
+    pub fn key(&self) -> &K {
+        match self {
+            Entry::Occupied(e) => e.key(),
+            Entry::Vacant(e) => e.key(),
+        }
+    }
+
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        self.or_insert_with(|| default)
+    }
+
+    pub fn or_insert_with<F: FnOnce() -> V>(self, default: F) -> &'a mut V {
+        match self {
+            Entry::Occupied(e) => e.into_mut(),
+            Entry::Vacant(e) => e.insert(default()),
+        }
+    }
+
+    pub fn or_default(self) -> &'a mut V
+    where
+        V: Default,
+    {
+        self.or_insert_with(V::default)
+    }
+
+    pub fn and_modify<F: FnOnce(&mut V)>(mut self, f: F) -> Self {
+        if let Entry::Occupied(e) = &mut self {
+            f(e.get_mut());
+        }
+        self
+    }
+}
+
+/* ---------------------------- 迭代 ---------------------------- */
+
+pub struct Iter<'a, K, V> {
+    slots: std::slice::Iter<'a, Option<Slot<K, V>>>,
+    remaining: usize,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let s = self.slots.by_ref().flatten().next()?;
+        self.remaining -= 1;
+        Some((&s.key, &s.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<K, V> ExactSizeIterator for Iter<'_, K, V> {}
+
+pub struct IterMut<'a, K, V> {
+    slots: std::slice::IterMut<'a, Option<Slot<K, V>>>,
+    remaining: usize,
+}
+
+impl<'a, K, V> Iterator for IterMut<'a, K, V> {
+    // key 不能修改，否则哈希值会变
+    type Item = (&'a K, &'a mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let s = self.slots.by_ref().flatten().next()?;
+        self.remaining -= 1;
+        Some((&s.key, &mut s.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<K, V> ExactSizeIterator for IterMut<'_, K, V> {}
+
+pub struct IntoIter<K, V> {
+    slots: std::vec::IntoIter<Option<Slot<K, V>>>,
+    remaining: usize,
+}
+
+impl<K, V> Iterator for IntoIter<K, V> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let s = self.slots.by_ref().flatten().next()?;
+        self.remaining -= 1;
+        Some((s.key, s.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<K, V, S> IntoIterator for FastMap<K, V, S> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V>;
+
+    fn into_iter(self) -> IntoIter<K, V> {
+        IntoIter {
+            remaining: self.len,
+            slots: self.slots.into_iter(),
+        }
+    }
+}
+
+impl<'a, K, V, S> IntoIterator for &'a FastMap<K, V, S> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Iter<'a, K, V> {
+        self.iter()
+    }
+}
+
+impl<'a, K, V, S> IntoIterator for &'a mut FastMap<K, V, S> {
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
+
+    fn into_iter(self) -> IterMut<'a, K, V> {
+        self.iter_mut()
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher> Extend<(K, V)> for FastMap<K, V, S> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+        for (k, v) in iter {
+            self.insert(k, v);
+        }
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher + Default> FromIterator<(K, V)> for FastMap<K, V, S> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = Self::default();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug, S> fmt::Debug for FastMap<K, V, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+/* ---------------------------- 差分测试 ---------------------------- */
+
+// 随机执行 ops 次操作，每一步都和 std 的 HashMap 比较结果
+pub fn differential<S: BuildHasher + Default>(seed: u64, ops: usize, key_space: u64) {
+    let mut rng = XorShift::new(seed);
+    let mut fast: FastMap<u64, u64, S> = FastMap::default().with_max_load(0.9);
+    let mut std_map: HashMap<u64, u64> = HashMap::new();
+    for step in 0..ops {
+        let key = rng.next_u64() % key_space;
+        let value = rng.next_u64();
+        match rng.next_u64() % 6 {
+            0 | 1 => assert_eq!(fast.insert(key, value), std_map.insert(key, value)),
+            2 => assert_eq!(fast.remove(&key), std_map.remove(&key)),
+            3 => assert_eq!(fast.get(&key), std_map.get(&key)),
+            4 => {
+                *fast.entry(key).or_insert(0) += 1;
+                *std_map.entry(key).or_insert(0) += 1;
+            }
+            _ => {
+                let a = fast.entry(key).and_modify(|v| *v /= 2).or_default();
+                let b = std_map.entry(key).and_modify(|v| *v /= 2).or_default();
+                assert_eq!(a, b);
+            }
+        }
+        assert_eq!(fast.len(), std_map.len(), "step {}", step);
+        if step % 97 == 0 {
+            fast.check_invariants();
+        }
+    }
+    fast.check_invariants();
+    let mut a: Vec<_> = fast.iter().map(|(k, v)| (*k, *v)).collect();
+    let mut b: Vec<_> = std_map.into_iter().collect();
+    a.sort_unstable();
+    b.sort_unstable();
+    assert_eq!(a, b);
+}
+
+/* ---------------------------- 基准测试 ---------------------------- */
+
+pub struct BenchRow {
+    pub name: String,
+    pub time: Duration,
+    pub distinct: usize,
+    // (平均探测距离, 最大探测距离)，std 的 HashMap 无法统计
+    pub probe: Option<(f64, usize)>,
+}
+
+fn word_count_std<S: BuildHasher + Default>(tokens: &[String]) -> usize {
+    let mut map: HashMap<&str, u64, S> = HashMap::default();
+    for t in tokens {
+        *map.entry(t.as_str()).or_insert(0) += 1;
+    }
+    std::hint::black_box(&map).len()
+}
+
+fn word_count_fast<S: BuildHasher + Default>(
+    tokens: &[String],
+    max_load: f64,
+) -> FastMap<&str, u64, S> {
+    let mut map: FastMap<&str, u64, S> = FastMap::default().with_max_load(max_load);
+    for t in tokens {
+        *map.entry(t.as_str()).or_insert(0) += 1;
+    }
+    map
+}
+
+fn best_of<T>(rounds: usize, mut f: impl FnMut() -> T) -> (Duration, T) {
+    let mut best = Duration::MAX;
+    let mut out = f();
+    for _ in 0..rounds.max(1) {
+        let start = Instant::now();
+        out = f();
+        best = best.min(start.elapsed());
+    }
+    (best, out)
+}
+
+// 词频统计：std HashMap 与不同装载因子上限的 FastMap
+pub fn bench_word_count(tokens: &[String], rounds: usize) -> Vec<BenchRow> {
+    let mut rows = Vec::new();
+    let (time, distinct) = best_of(rounds, || word_count_std::<RandomState>(tokens));
+    rows.push(BenchRow {
+        name: "std HashMap (sip)".to_string(),
+        time,
+        distinct,
+        probe: None,
+    });
+    let (time, distinct) = best_of(rounds, || word_count_std::<ahash::RandomState>(tokens));
+    rows.push(BenchRow {
+        name: "std HashMap (ahash)".to_string(),
+        time,
+        distinct,
+        probe: None,
+    });
+    for max_load in [0.5, 0.75, 0.875, 0.95] {
+        let (time, map) = best_of(rounds, || word_count_fast::<RandomState>(tokens, max_load));
+        rows.push(BenchRow {
+            name: format!("FastMap sip {:.3}", max_load),
+            time,
+            distinct: map.len(),
+            probe: Some(map.probe_stats()),
+        });
+        let (time, map) = best_of(rounds, || {
+            word_count_fast::<ahash::RandomState>(tokens, max_load)
+        });
+        rows.push(BenchRow {
+            name: format!("FastMap ahash {:.3}", max_load),
+            time,
+            distinct: map.len(),
+            probe: Some(map.probe_stats()),
+        });
+    }
+    rows
+}
+
+pub fn print_bench(rows: &[BenchRow]) {
+    println!(
+        "{:<22}{:>10}{:>10}{:>11}{:>11}",
+        "map", "time(ms)", "distinct", "avg probe", "max probe"
+    );
+    for r in rows {
+        let (avg, max) = match r.probe {
+            Some((avg, max)) => (format!("{:.2}", avg), max.to_string()),
+            None => ("-".to_string(), "-".to_string()),
+        };
+        println!(
+            "{:<22}{:>10.3}{:>10}{:>11}{:>11}",
+            r.name,
+            r.time.as_secs_f64() * 1000.0,
+            r.distinct,
+            avg,
+            max
+        );
+    }
+}
+
+// cargo run --release -- fastmap [--n 1000000] [--rounds 5]
+pub fn fast_map_bench(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let mut n = 1_000_000;
+    let mut rounds = 5;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let value = iter
+            .next()
+            .ok_or_else(|| format!("{} requires a value", arg))?;
+        match arg.as_str() {
+            "--n" => n = value.parse()?,
+            "--rounds" => rounds = value.parse()?,
+            other => return Err(format!("unknown option: {}", other).into()),
+        }
+    }
+    let tokens = sample_corpus(&Segmenter::default(), n);
+    print_bench(&bench_word_count(&tokens, rounds));
+    Ok(())
+}
+
+pub fn fast_map_practice() {
+    let mut map = FastMap::new();
+    assert!(map.is_empty());
+    assert_eq!(map.insert("Blue".to_string(), 10), None);
+    assert_eq!(map.insert("Yellow".to_string(), 50), None);
+    assert_eq!(map.insert("Blue".to_string(), 25), Some(10));
+    assert_eq!(map.get("Blue"), Some(&25));
+    assert_eq!(*map.entry("Yellow".to_string()).or_insert(5), 50);
+    *map.entry("Red".to_string()).or_insert(0) += 1;
+    assert_eq!(map.len(), 3);
+    for (_, v) in map.iter_mut() {
+        *v *= 2;
+    }
+    assert_eq!(map.remove("Red"), Some(2));
+    assert_eq!(map.get("Red"), None);
+    assert!(map.contains_key("Yellow"));
+    map.check_invariants();
+    println!("{:?}", map);
+
+    // hash_map 中的单词计数
+    let mut words: FastMap<&str, i32> = FastMap::new();
+    for word in "hello world wonderful world".split_whitespace() {
+        *words.entry(word).or_default() += 1;
+    }
+    assert_eq!(words.get("world"), Some(&2));
+    let mut pairs: Vec<_> = words.into_iter().collect();
+    pairs.sort();
+    assert_eq!(pairs, [("hello", 1), ("wonderful", 1), ("world", 2)]);
+
+    // 装载因子影响容量
+    let mut dense: FastMap<u32, ()> = (0..800).map(|i| (i, ())).collect();
+    let mut sparse: FastMap<u32, ()> = FastMap::default().with_max_load(0.5);
+    sparse.extend((0..800).map(|i| (i, ())));
+    assert_eq!(dense.capacity(), 1024);
+    assert_eq!(sparse.capacity(), 2048);
+    assert!(sparse.probe_stats().0 <= dense.probe_stats().0);
+    dense.set_max_load(0.3);
+    assert!(dense.load_factor() <= 0.3);
+    dense.check_invariants();
+
+    // 删除全部元素后不留墓碑，探测距离为 0
+    for i in 0..800 {
+        assert_eq!(dense.remove(&i), Some(()));
+    }
+    assert!(dense.is_empty() && dense.iter().next().is_none());
+    dense.check_invariants();
+
+    // 与 std HashMap 的差分测试，更多种子和 key 空间见单元测试
+    differential::<FnvBuildHasher>(1, 20_000, 1_000);
+
+    let tokens = sample_corpus(&Segmenter::default(), 20_000);
+    let rows = bench_word_count(&tokens, 1);
+    assert!(rows.iter().all(|r| r.distinct == rows[0].distinct));
+    print_bench(&rows);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[should_panic(expected = "max load factor must be in [0.1, 0.95]")]
+    fn nan_max_load_is_rejected() {
+        let _: FastMap<u32, u32> = FastMap::new().with_max_load(f64::NAN);
+    }
+
+    #[test]
+    #[should_panic(expected = "max load factor must be in [0.1, 0.95]")]
+    fn infinite_max_load_is_rejected() {
+        FastMap::<u32, u32>::new().set_max_load(f64::INFINITY);
+    }
+
+    #[test]
+    #[should_panic(expected = "max load factor must be in [0.1, 0.95]")]
+    fn full_max_load_is_rejected() {
+        let _: FastMap<u32, u32> = FastMap::new().with_max_load(1.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "max load factor must be in [0.1, 0.95]")]
+    fn tiny_max_load_is_rejected() {
+        FastMap::<u32, u32>::new().set_max_load(0.01);
+    }
+
+    #[test]
+    fn max_load_bounds_are_accepted() {
+        let mut map: FastMap<u32, u32> = FastMap::new().with_max_load(0.95);
+        map.extend((0..1000).map(|i| (i, i)));
+        assert!(map.load_factor() <= 0.95);
+        map.set_max_load(0.1);
+        assert!(map.load_factor() <= 0.1);
+        map.check_invariants();
+        assert_eq!(map.get(&999), Some(&999));
+        assert_eq!(map.get(&1000), None);
+    }
+
+    // 小 key 空间制造大量冲突；FNV 没有随机种子，两种哈希用同样的种子和 key 空间
+    #[test]
+    fn matches_std_with_sip() {
+        for seed in 1..=4 {
+            for key_space in [64, 1_000, 5_000] {
+                differential::<RandomState>(seed, 20_000, key_space);
+            }
+        }
+    }
+
+    #[test]
+    fn matches_std_with_fnv() {
+        for seed in 1..=4 {
+            for key_space in [64, 1_000, 5_000] {
+                differential::<FnvBuildHasher>(seed, 20_000, key_space);
+            }
+        }
+    }
+}
@@ -0,0 +1,752 @@
+//! 信息流
+//! 在 Summary 和 Weibo 的基础上增加文章、转发和评论，按时间合并成时间线，支持按作者过滤和 JSON Lines 导入导出
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Write};
+
+use crate::utf8;
+use crate::{Summary, Weibo};
+
+pub struct Article {
+    pub author: String,
+    pub title: String,
+    pub content: String,
+}
+
+impl Summary for Article {
+    fn summarize_author(&self) -> String {
+        format!("@{}", self.author)
+    }
+
+    fn summarize(&self) -> String {
+        format!(
+            "{}发表了文章《{}》{}",
+            self.author, self.title, self.content
+        )
+    }
+}
+
+// 转发时保存原文的作者和内容，原文删除后转发仍然可以显示
+pub struct Repost {
+    pub author: String,
+    pub comment: String,
+    pub original_author: String,
+    pub original: String,
+}
+
+impl Summary for Repost {
+    fn summarize_author(&self) -> String {
+        format!("@{}", self.author)
+    }
+
+    fn summarize(&self) -> String {
+        format!(
+            "{}转发了{}: {} //{}",
+            self.author, self.original_author, self.comment, self.original
+        )
+    }
+}
+
+pub struct Comment {
+    pub author: String,
+    // 被评论的条目 id
+    pub reply_to: u64,
+    pub content: String,
+}
+
+impl Summary for Comment {
+    fn summarize_author(&self) -> String {
+        format!("@{}", self.author)
+    }
+
+    fn summarize(&self) -> String {
+        format!("{}评论了#{}: {}", self.author, self.reply_to, self.content)
+    }
+}
+
+pub enum Post {
+    Weibo(Weibo),
+    Article(Article),
+    Repost(Repost),
+    Comment(Comment),
+}
+
+impl Post {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Post::Weibo(_) => "weibo",
+            Post::Article(_) => "article",
+            Post::Repost(_) => "repost",
+            Post::Comment(_) => "comment",
+        }
+    }
+
+    pub fn author(&self) -> &str {
+        match self {
+            Post::Weibo(p) => &p.username,
+            Post::Article(p) => &p.author,
+            Post::Repost(p) => &p.author,
+            Post::Comment(p) => &p.author,
+        }
+    }
+
+    // 不同类型的条目都当作 Summary 使用
+    pub fn as_summary(&self) -> &dyn Summary {
+        match self {
+            Post::Weibo(p) => p,
+            Post::Article(p) => p,
+            Post::Repost(p) => p,
+            Post::Comment(p) => p,
+        }
+    }
+}
+
+pub struct FeedItem {
+    pub id: u64,
+    // Unix 时间戳(秒)
+    pub time: u64,
+    pub post: Post,
+}
+
+impl FeedItem {
+    fn order_key(&self) -> (u64, u64) {
+        (self.time, self.id)
+    }
+}
+
+/* ---------------------------- 时间线 ---------------------------- */
+
+// 按时间从新到旧排列，时间相同时 id 大的在前
+#[derive(Default)]
+pub struct Feed {
+    items: Vec<FeedItem>,
+}
+
+impl Feed {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, FeedItem> {
+        self.items.iter()
+    }
+
+    pub fn push(&mut self, item: FeedItem) {
+        let key = Reverse(item.order_key());
+        let pos = self
+            .items
+            .partition_point(|it| Reverse(it.order_key()) <= key);
+        self.items.insert(pos, item);
+    }
+
+    pub fn get(&self, id: u64) -> Option<&FeedItem> {
+        self.items.iter().find(|it| it.id == id)
+    }
+
+    pub fn by_author<'a>(&'a self, author: &'a str) -> impl Iterator<Item = &'a FeedItem> + 'a {
+        self.items
+            .iter()
+            .filter(move |it| it.post.author() == author)
+    }
+
+    /*
+     * 多个已经有序的信息流做 k 路归并：堆中保存每个信息流当前最新的一条，
+     * 每次取出最新的一条后补充该信息流的下一条，复杂度 O(n log k)
+     */
+    pub fn merge(feeds: impl IntoIterator<Item = Feed>) -> Feed {
+        let mut sources: Vec<std::vec::IntoIter<FeedItem>> =
+            feeds.into_iter().map(|f| f.items.into_iter()).collect();
+        let mut heads: Vec<Option<FeedItem>> = sources.iter_mut().map(|s| s.next()).collect();
+        let mut heap: BinaryHeap<((u64, u64), usize)> = heads
+            .iter()
+            .enumerate()
+            .filter_map(|(i, h)| h.as_ref().map(|it| (it.order_key(), i)))
+            .collect();
+        let mut items = Vec::with_capacity(sources.iter().map(|s| s.len() + 1).sum());
+        while let Some((_, i)) = heap.pop() {
+            let item = heads[i].take().unwrap();
+            heads[i] = sources[i].next();
+            if let Some(next) = &heads[i] {
+                heap.push((next.order_key(), i));
+            }
+            items.push(item);
+        }
+        Feed { items }
+    }
+}
+
+// 每条摘要最多显示 budget 个字符(按字素计算，不会切开汉字和 emoji)，换行替换为空格
+pub fn render<'a>(items: impl IntoIterator<Item = &'a FeedItem>, budget: usize) -> String {
+    let mut out = String::new();
+    for it in items {
+        let summary = it.post.as_summary().summarize().replace(['\r', '\n'], " ");
+        out.push_str(&format!(
+            "#{:<4} {}  {}\n",
+            it.id,
+            format_time(it.time),
+            truncate_graphemes(&summary, budget)
+        ));
+    }
+    out
+}
+
+// 超出 budget 个字素时截断并以 … 结尾，结果不超过 budget 个字素
+pub fn truncate_graphemes(s: &str, budget: usize) -> String {
+    if utf8::graphemes(s).nth(budget).is_none() {
+        return s.to_string();
+    }
+    let keep = budget.saturating_sub(1);
+    let mut out: String = utf8::graphemes(s).take(keep).collect();
+    if budget > 0 {
+        out.push('…');
+    }
+    out
+}
+
+// Unix 时间戳转为 UTC 日期时间 "YYYY-MM-DD HH:MM"
+pub fn format_time(ts: u64) -> String {
+    let days = (ts / 86_400) as i64;
+    let secs = ts % 86_400;
+    // 公历日期算法：以 0000-03-01 为起点，每 400 年为一个周期
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}",
+        year,
+        month,
+        day,
+        secs / 3_600,
+        secs % 3_600 / 60
+    )
+}
+
+/* ---------------------------- JSON Lines ---------------------------- */
+
+/*
+ * 每行一个 JSON 对象，例如：
+ * {"id":1,"time":1700000000,"type":"weibo","author":"sunface","content":"m1 maxx太厉害"}
+ * 字段都是字符串或非负整数，这里只实现这个子集
+ */
+#[derive(Debug)]
+pub struct FeedError {
+    pub line: usize,
+    pub msg: String,
+}
+
+impl fmt::Display for FeedError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.msg)
+    }
+}
+
+impl std::error::Error for FeedError {}
+
+enum Value {
+    Str(String),
+    Num(u64),
+}
+
+// JSON 字符串转义，带引号
+pub fn escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+struct Parser<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+}
+
+impl Parser<'_> {
+    fn skip_ws(&mut self) {
+        while self.chars.next_if(|c| c.is_whitespace()).is_some() {}
+    }
+
+    fn expect(&mut self, want: char) -> Result<(), String> {
+        self.skip_ws();
+        match self.chars.next() {
+            Some(c) if c == want => Ok(()),
+            Some(c) => Err(format!("expected `{}`, found `{}`", want, c)),
+            None => Err(format!("expected `{}`, found end of line", want)),
+        }
+    }
+
+    fn hex4(&mut self) -> Result<u32, String> {
+        let mut n = 0;
+        for _ in 0..4 {
+            let d = self
+                .chars
+                .next()
+                .and_then(|c| c.to_digit(16))
+                .ok_or("invalid \\u escape")?;
+            n = n * 16 + d;
+        }
+        Ok(n)
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        self.expect('"')?;
+        let mut out = String::new();
+        loop {
+            match self.chars.next().ok_or("unterminated string")? {
+                '"' => return Ok(out),
+                '\\' => {
+                    let c = match self.chars.next().ok_or("unterminated string")? {
+                        '"' => '"',
+                        '\\' => '\\',
+                        '/' => '/',
+                        'n' => '\n',
+                        'r' => '\r',
+                        't' => '\t',
+                        'b' => '\u{8}',
+                        'f' => '\u{c}',
+                        'u' => {
+                            let hi = self.hex4()?;
+                            // UTF-16 代理对：😀
+                            let code = if (0xD800..0xDC00).contains(&hi) {
+                                if self.chars.next() != Some('\\') || self.chars.next() != Some('u')
+                                {
+                                    return Err("unpaired surrogate".to_string());
+                                }
+                                let lo = self.hex4()?;
+                                if !(0xDC00..0xE000).contains(&lo) {
+                                    return Err("unpaired surrogate".to_string());
+                                }
+                                0x10000 + ((hi - 0xD800) << 10) + (lo - 0xDC00)
+                            } else {
+                                hi
+                            };
+                            char::from_u32(code).ok_or("invalid \\u escape")?
+                        }
+                        c => return Err(format!("invalid escape `\\{}`", c)),
+                    };
+                    out.push(c);
+                }
+                c => out.push(c),
+            }
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, String> {
+        self.skip_ws();
+        match self.chars.peek() {
+            Some('"') => self.string().map(Value::Str),
+            Some(c) if c.is_ascii_digit() => {
+                let mut n: u64 = 0;
+                while let Some(d) = self.chars.peek().and_then(|c| c.to_digit(10)) {
+                    self.chars.next();
+                    n = n
+                        .checked_mul(10)
+                        .and_then(|n| n.checked_add(d as u64))
+                        .ok_or("number too large")?;
+                }
+                Ok(Value::Num(n))
+            }
+            Some(c) => Err(format!("unsupported value starting with `{}`", c)),
+            None => Err("expected value, found end of line".to_string()),
+        }
+    }
+
+    fn object(line: &str) -> Result<Vec<(String, Value)>, String> {
+        let mut p = Parser {
+            chars: line.chars().peekable(),
+        };
+        let mut fields = Vec::new();
+        p.expect('{')?;
+        p.skip_ws();
+        if p.chars.next_if_eq(&'}').is_none() {
+            loop {
+                let key = p.string()?;
+                p.expect(':')?;
+                fields.push((key, p.value()?));
+                p.skip_ws();
+                match p.chars.next() {
+                    Some(',') => continue,
+                    Some('}') => break,
+                    _ => return Err("expected `,` or `}`".to_string()),
+                }
+            }
+        }
+        p.skip_ws();
+        match p.chars.next() {
+            None => Ok(fields),
+            Some(c) => Err(format!("trailing character `{}`", c)),
+        }
+    }
+}
+
+struct Fields(Vec<(String, Value)>);
+
+impl Fields {
+    fn take(&mut self, name: &str) -> Result<Value, String> {
+        let i = self
+            .0
+            .iter()
+            .position(|(k, _)| k == name)
+            .ok_or_else(|| format!("missing field `{}`", name))?;
+        Ok(self.0.swap_remove(i).1)
+    }
+
+    fn str(&mut self, name: &str) -> Result<String, String> {
+        match self.take(name)? {
+            Value::Str(s) => Ok(s),
+            Value::Num(_) => Err(format!("field `{}` should be a string", name)),
+        }
+    }
+
+    fn num(&mut self, name: &str) -> Result<u64, String> {
+        match self.take(name)? {
+            Value::Num(n) => Ok(n),
+            Value::Str(_) => Err(format!("field `{}` should be a number", name)),
+        }
+    }
+}
+
+impl FeedItem {
+    pub fn to_json(&self) -> String {
+        let mut out = format!(
+            "{{\"id\":{},\"time\":{},\"type\":\"{}\",\"author\":{}",
+            self.id,
+            self.time,
+            self.post.kind(),
+            escape(self.post.author())
+        );
+        let mut field = |name: &str, value: String| {
+            out.push_str(&format!(",\"{}\":{}", name, value));
+        };
+        match &self.post {
+            Post::Weibo(p) => field("content", escape(&p.content)),
+            Post::Article(p) => {
+                field("title", escape(&p.title));
+                field("content", escape(&p.content));
+            }
+            Post::Repost(p) => {
+                field("comment", escape(&p.comment));
+                field("original_author", escape(&p.original_author));
+                field("original", escape(&p.original));
+            }
+            Post::Comment(p) => {
+                field("reply_to", p.reply_to.to_string());
+                field("content", escape(&p.content));
+            }
+        }
+        out.push('}');
+        out
+    }
+
+    pub fn from_json(line: &str) -> Result<FeedItem, String> {
+        let mut f = Fields(Parser::object(line)?);
+        let id = f.num("id")?;
+        let time = f.num("time")?;
+        let kind = f.str("type")?;
+        let author = f.str("author")?;
+        let post = match kind.as_str() {
+            "weibo" => Post::Weibo(Weibo {
+                username: author,
+                content: f.str("content")?,
+            }),
+            "article" => Post::Article(Article {
+                author,
+                title: f.str("title")?,
+                content: f.str("content")?,
+            }),
+            "repost" => Post::Repost(Repost {
+                author,
+                comment: f.str("comment")?,
+                original_author: f.str("original_author")?,
+                original: f.str("original")?,
+            }),
+            "comment" => Post::Comment(Comment {
+                author,
+                reply_to: f.num("reply_to")?,
+                content: f.str("content")?,
+            }),
+            other => return Err(format!("unknown type `{}`", other)),
+        };
+        Ok(FeedItem { id, time, post })
+    }
+}
+
+impl Feed {
+    pub fn write_jsonl(&self, mut w: impl Write) -> io::Result<()> {
+        for it in &self.items {
+            writeln!(w, "{}", it.to_json())?;
+        }
+        Ok(())
+    }
+
+    pub fn to_jsonl(&self) -> String {
+        let mut buf = Vec::new();
+        self.write_jsonl(&mut buf).unwrap();
+        String::from_utf8(buf).unwrap()
+    }
+
+    // 空行被忽略，出错时返回行号
+    pub fn read_jsonl(r: impl BufRead) -> Result<Feed, Box<dyn std::error::Error>> {
+        let mut feed = Feed::new();
+        for (i, line) in r.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let item = FeedItem::from_json(&line).map_err(|msg| FeedError { line: i + 1, msg })?;
+            feed.push(item);
+        }
+        Ok(feed)
+    }
+}
+
+/* ---------------------------- 示例 ---------------------------- */
+
+fn weibo(id: u64, time: u64, author: &str, content: &str) -> FeedItem {
+    FeedItem {
+        id,
+        time,
+        post: Post::Weibo(Weibo {
+            username: author.to_string(),
+            content: content.to_string(),
+        }),
+    }
+}
+
+// 两个用户各自的信息流
+pub fn sample_feeds() -> (Feed, Feed) {
+    let mut a = Feed::new();
+    a.push(weibo(1, 1_700_000_000, "sunface", "m1 maxx太厉害"));
+    a.push(FeedItem {
+        id: 3,
+        time: 1_700_000_600,
+        post: Post::Article(Article {
+            author: "sunface".to_string(),
+            title: "Rust语言圣经".to_string(),
+            content: "所有权、借用和生命周期是Rust最重要的概念，理解它们之后就能写出内存安全的代码"
+                .to_string(),
+        }),
+    });
+    let mut b = Feed::new();
+    b.push(FeedItem {
+        id: 2,
+        time: 1_700_000_300,
+        post: Post::Repost(Repost {
+            author: "ferris".to_string(),
+            comment: "同意👍".to_string(),
+            original_author: "sunface".to_string(),
+            original: "m1 maxx太厉害".to_string(),
+        }),
+    });
+    b.push(FeedItem {
+        id: 4,
+        time: 1_700_000_900,
+        post: Post::Comment(Comment {
+            author: "ferris".to_string(),
+            reply_to: 3,
+            content: "写得好，\"引用\"那一章\n讲得很清楚".to_string(),
+        }),
+    });
+    (a, b)
+}
+
+struct Options {
+    author: Option<String>,
+    budget: usize,
+    export: bool,
+    files: Vec<String>,
+}
+
+const USAGE: &str = "usage: feed [--author NAME] [--budget N] [--export] [FILE.jsonl...]";
+
+fn parse_options(args: &[String]) -> Result<Options, String> {
+    let mut opts = Options {
+        author: None,
+        budget: 40,
+        export: false,
+        files: Vec::new(),
+    };
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let mut value = |name: &str| {
+            iter.next()
+                .cloned()
+                .ok_or_else(|| format!("{} requires a value", name))
+        };
+        match arg.as_str() {
+            "--author" => opts.author = Some(value("--author")?),
+            "--budget" => {
+                opts.budget = value("--budget")?
+                    .parse()
+                    .map_err(|e| format!("--budget: {}", e))?
+            }
+            "--export" => opts.export = true,
+            // 拼错的选项不能被当成文件名
+            flag if flag.starts_with("--") => {
+                return Err(format!("unknown option {}; {}", flag, USAGE))
+            }
+            _ => opts.files.push(arg.clone()),
+        }
+    }
+    Ok(opts)
+}
+
+// cargo run -- feed [--author 名字] [--budget 字数] [--export] [文件.jsonl ...]
+pub fn feed_cli(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let Options {
+        author,
+        budget,
+        export,
+        files,
+    } = parse_options(args)?;
+
+    let feed = if files.is_empty() {
+        let (a, b) = sample_feeds();
+        Feed::merge([a, b])
+    } else {
+        let mut feeds = Vec::new();
+        for path in &files {
+            feeds.push(Feed::read_jsonl(BufReader::new(File::open(path)?))?);
+        }
+        Feed::merge(feeds)
+    };
+
+    if export {
+        feed.write_jsonl(io::stdout().lock())?;
+        return Ok(());
+    }
+    let text = match &author {
+        Some(a) => render(feed.by_author(a), budget),
+        None => render(feed.iter(), budget),
+    };
+    print!("{}", text);
+    Ok(())
+}
+
+pub fn feed_practice() {
+    let (a, b) = sample_feeds();
+    let feed = Feed::merge([a, b]);
+    let ids: Vec<u64> = feed.iter().map(|it| it.id).collect();
+    assert_eq!(ids, [4, 3, 2, 1]);
+    assert_eq!(feed.len(), 4);
+    assert!(Feed::merge([Feed::new(), Feed::new()]).is_empty());
+    let ferris: Vec<u64> = feed.by_author("ferris").map(|it| it.id).collect();
+    assert_eq!(ferris, [4, 2]);
+    assert_eq!(feed.get(2).unwrap().post.kind(), "repost");
+
+    // 各种条目都可以传给 notify
+    crate::notify(&Article {
+        author: "sunface".to_string(),
+        title: "Rust".to_string(),
+        content: String::new(),
+    });
+    assert_eq!(
+        feed.get(1).unwrap().post.as_summary().summarize_author(),
+        "author: sunface"
+    );
+
+    print!("{}", render(feed.iter(), 40));
+
+    // JSON Lines 往返
+    let text = feed.to_jsonl();
+    print!("{}", text);
+    let back = Feed::read_jsonl(text.as_bytes()).unwrap();
+    assert_eq!(back.to_jsonl(), text);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(s: &str) -> Vec<String> {
+        s.split_whitespace().map(String::from).collect()
+    }
+
+    fn sample() -> Feed {
+        let (a, b) = sample_feeds();
+        Feed::merge([a, b])
+    }
+
+    // 截断按字素计数，不会切开汉字或 emoji
+    #[test]
+    fn truncation_keeps_chinese_and_emoji_whole() {
+        assert_eq!(truncate_graphemes("m1 maxx太厉害", 20), "m1 maxx太厉害");
+        assert_eq!(truncate_graphemes("m1 maxx太厉害", 10), "m1 maxx太厉害");
+        assert_eq!(truncate_graphemes("m1 maxx太厉害", 9), "m1 maxx太…");
+        assert_eq!(truncate_graphemes("同意👍👍", 3), "同意…");
+        assert_eq!(truncate_graphemes("👨‍👩‍👧家", 2), "👨‍👩‍👧家");
+        assert_eq!(truncate_graphemes("中国人", 1), "…");
+        assert_eq!(truncate_graphemes("中国人", 0), "");
+        let rendered = render(sample().iter(), 12);
+        assert!(rendered.ends_with("#1    2023-11-14 22:13  sunface发表了微…\n"));
+    }
+
+    #[test]
+    fn format_time_handles_leap_days() {
+        assert_eq!(format_time(0), "1970-01-01 00:00");
+        assert_eq!(format_time(951_782_400), "2000-02-29 00:00");
+    }
+
+    #[test]
+    fn jsonl_round_trip() {
+        let feed = sample();
+        let text = feed.to_jsonl();
+        assert_eq!(text.lines().count(), 4);
+        assert!(text.contains(r#""content":"写得好，\"引用\"那一章\n讲得很清楚""#));
+        let back = Feed::read_jsonl(text.as_bytes()).unwrap();
+        assert_eq!(back.to_jsonl(), text);
+        let ids: Vec<u64> = back.iter().map(|it| it.id).collect();
+        assert_eq!(ids, [4, 3, 2, 1]);
+        assert_eq!(render(back.iter(), 40), render(feed.iter(), 40));
+
+        let escaped = r#"{"id": 9, "time": 1, "type": "weibo", "author": "a", "content": "\u4e2d\ud83d\ude00"}"#;
+        let item = FeedItem::from_json(escaped).unwrap();
+        assert_eq!(item.post.as_summary().summarize(), "a发表了微博中😀");
+    }
+
+    #[test]
+    fn jsonl_errors_report_the_line() {
+        let bad =
+            "{\"id\":1,\"time\":2,\"type\":\"weibo\",\"author\":\"a\",\"content\":\"x\"}\n\n{\"id\":2}";
+        let err = Feed::read_jsonl(bad.as_bytes()).err().unwrap();
+        assert_eq!(err.to_string(), "line 3: missing field `time`");
+        assert!(FeedItem::from_json(r#"{"id":1,"time":2,"type":"poll","author":"a"}"#).is_err());
+    }
+
+    #[test]
+    fn parse_options_rejects_unknown_flags() {
+        let opts = parse_options(&args("--author ferris --budget 12 a.jsonl")).unwrap();
+        assert_eq!(opts.author.as_deref(), Some("ferris"));
+        assert_eq!((opts.budget, opts.export, opts.files.len()), (12, false, 1));
+        let err = parse_options(&args("--exprot a.jsonl")).err().unwrap();
+        assert!(err.starts_with("unknown option --exprot; usage: feed"));
+        assert!(parse_options(&args("--budget")).is_err());
+        assert!(parse_options(&args("--budget x")).is_err());
+    }
+}
@@ -0,0 +1,424 @@
+//! 边框和表格
+//! OutlinePrint 用 output.len() 计算宽度，中文按字节数算会对不齐，也不支持多行
+//! 这里按显示列宽计算，支持多种边框样式、内边距、对齐方式、多行内容和表格
+
+use std::fmt::{self, Display};
+use std::io::{self, Read};
+
+use crate::utf8;
+
+/* ---------------------------- 边框样式 ---------------------------- */
+
+pub struct BoxStyle {
+    pub horizontal: &'static str,
+    pub vertical: &'static str,
+    pub top_left: &'static str,
+    pub top_right: &'static str,
+    pub bottom_left: &'static str,
+    pub bottom_right: &'static str,
+    // 表格内部的连接处
+    pub left_tee: &'static str,
+    pub right_tee: &'static str,
+    pub top_tee: &'static str,
+    pub bottom_tee: &'static str,
+    pub cross: &'static str,
+}
+
+const fn uniform(c: &'static str, h: &'static str, v: &'static str) -> BoxStyle {
+    BoxStyle {
+        horizontal: h,
+        vertical: v,
+        top_left: c,
+        top_right: c,
+        bottom_left: c,
+        bottom_right: c,
+        left_tee: c,
+        right_tee: c,
+        top_tee: c,
+        bottom_tee: c,
+        cross: c,
+    }
+}
+
+// OutlinePrint 原来的样式
+pub const ASTERISK: BoxStyle = uniform("*", "*", "*");
+pub const ASCII: BoxStyle = uniform("+", "-", "|");
+
+pub const SINGLE: BoxStyle = BoxStyle {
+    horizontal: "─",
+    vertical: "│",
+    top_left: "┌",
+    top_right: "┐",
+    bottom_left: "└",
+    bottom_right: "┘",
+    left_tee: "├",
+    right_tee: "┤",
+    top_tee: "┬",
+    bottom_tee: "┴",
+    cross: "┼",
+};
+
+pub const ROUNDED: BoxStyle = BoxStyle {
+    top_left: "╭",
+    top_right: "╮",
+    bottom_left: "╰",
+    bottom_right: "╯",
+    ..SINGLE
+};
+
+pub const DOUBLE: BoxStyle = BoxStyle {
+    horizontal: "═",
+    vertical: "║",
+    top_left: "╔",
+    top_right: "╗",
+    bottom_left: "╚",
+    bottom_right: "╝",
+    left_tee: "╠",
+    right_tee: "╣",
+    top_tee: "╦",
+    bottom_tee: "╩",
+    cross: "╬",
+};
+
+pub const HEAVY: BoxStyle = BoxStyle {
+    horizontal: "━",
+    vertical: "┃",
+    top_left: "┏",
+    top_right: "┓",
+    bottom_left: "┗",
+    bottom_right: "┛",
+    left_tee: "┣",
+    right_tee: "┫",
+    top_tee: "┳",
+    bottom_tee: "┻",
+    cross: "╋",
+};
+
+pub fn style_by_name(name: &str) -> Option<&'static BoxStyle> {
+    Some(match name {
+        "asterisk" => &ASTERISK,
+        "ascii" => &ASCII,
+        "single" => &SINGLE,
+        "rounded" => &ROUNDED,
+        "double" => &DOUBLE,
+        "heavy" => &HEAVY,
+        _ => return None,
+    })
+}
+
+/* ---------------------------- 对齐 ---------------------------- */
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Align {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
+// 按显示宽度补齐到 width 列，{:^width$} 按字符数计算，中文会多出一倍
+pub fn align(s: &str, width: usize, align: Align) -> String {
+    let gap = width.saturating_sub(utf8::display_width(s));
+    let (left, right) = match align {
+        Align::Left => (0, gap),
+        Align::Center => (gap / 2, gap - gap / 2),
+        Align::Right => (gap, 0),
+    };
+    format!("{}{}{}", " ".repeat(left), s, " ".repeat(right))
+}
+
+fn max_width<'a>(lines: impl IntoIterator<Item = &'a str>) -> usize {
+    lines
+        .into_iter()
+        .map(utf8::display_width)
+        .max()
+        .unwrap_or(0)
+}
+
+/* ---------------------------- 边框 ---------------------------- */
+
+pub struct Frame {
+    pub style: &'static BoxStyle,
+    // (左右, 上下) 内边距
+    pub padding: (usize, usize),
+    pub align: Align,
+    // 内容区域的最小宽度
+    pub min_width: usize,
+    // 显示在上边框中的标题
+    pub title: Option<String>,
+}
+
+impl Frame {
+    pub fn new(style: &'static BoxStyle) -> Self {
+        Frame {
+            style,
+            padding: (1, 0),
+            align: Align::Left,
+            min_width: 0,
+            title: None,
+        }
+    }
+
+    pub fn padding(mut self, horizontal: usize, vertical: usize) -> Self {
+        self.padding = (horizontal, vertical);
+        self
+    }
+
+    pub fn align(mut self, align: Align) -> Self {
+        self.align = align;
+        self
+    }
+
+    pub fn min_width(mut self, width: usize) -> Self {
+        self.min_width = width;
+        self
+    }
+
+    pub fn title(mut self, title: impl Display) -> Self {
+        self.title = Some(title.to_string());
+        self
+    }
+
+    // 每一行的文本，不含换行符
+    pub fn lines(&self, content: &dyn Display) -> Vec<String> {
+        let text = content.to_string();
+        let body: Vec<&str> = text.lines().collect();
+        let title_width = self
+            .title
+            .as_deref()
+            .map_or(0, |t| utf8::display_width(t) + 2);
+        let (px, py) = self.padding;
+        let inner = max_width(body.iter().copied())
+            .max(self.min_width)
+            .max((title_width + 2).saturating_sub(2 * px));
+        let full = inner + 2 * px;
+        let s = self.style;
+
+        let mut out = Vec::with_capacity(body.len() + 2 * py + 2);
+        let top = match &self.title {
+            Some(t) => format!(
+                "{} {} {}",
+                s.horizontal,
+                t,
+                s.horizontal.repeat(full - title_width - 1)
+            ),
+            None => s.horizontal.repeat(full),
+        };
+        out.push(format!("{}{}{}", s.top_left, top, s.top_right));
+        let blank = format!("{}{}{}", s.vertical, " ".repeat(full), s.vertical);
+        out.extend(std::iter::repeat_n(blank.clone(), py));
+        for line in body {
+            out.push(format!(
+                "{}{}{}{}{}",
+                s.vertical,
+                " ".repeat(px),
+                align(line, inner, self.align),
+                " ".repeat(px),
+                s.vertical
+            ));
+        }
+        out.extend(std::iter::repeat_n(blank, py));
+        out.push(format!(
+            "{}{}{}",
+            s.bottom_left,
+            s.horizontal.repeat(full),
+            s.bottom_right
+        ));
+        out
+    }
+
+    pub fn render(&self, content: &dyn Display) -> String {
+        self.lines(content).join("\n")
+    }
+}
+
+/* ---------------------------- 表格 ---------------------------- */
+
+/*
+ * 表格由若干行 Display 值组成，单元格可以有多行，列宽按显示宽度取每列的最大值
+ *   ┌──────┬─────┐
+ *   │ 表头 │ ... │
+ *   ├──────┼─────┤
+ *   │ ...  │ ... │
+ *   └──────┴─────┘
+ */
+pub struct Table {
+    pub style: &'static BoxStyle,
+    headers: Vec<String>,
+    rows: Vec<Vec<String>>,
+    aligns: Vec<Align>,
+}
+
+impl Table {
+    // 表头为空时不显示表头和分隔线
+    pub fn new(headers: &[&dyn Display]) -> Self {
+        Table {
+            style: &SINGLE,
+            headers: headers.iter().map(|h| h.to_string()).collect(),
+            rows: Vec::new(),
+            aligns: Vec::new(),
+        }
+    }
+
+    pub fn style(mut self, style: &'static BoxStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    // 设置第 col 列的对齐方式，默认左对齐
+    pub fn align(mut self, col: usize, align: Align) -> Self {
+        if self.aligns.len() <= col {
+            self.aligns.resize(col + 1, Align::Left);
+        }
+        self.aligns[col] = align;
+        self
+    }
+
+    pub fn row(&mut self, cells: &[&dyn Display]) -> &mut Self {
+        self.rows
+            .push(cells.iter().map(|c| c.to_string()).collect());
+        self
+    }
+
+    fn columns(&self) -> usize {
+        self.rows
+            .iter()
+            .map(Vec::len)
+            .chain(std::iter::once(self.headers.len()))
+            .max()
+            .unwrap_or(0)
+    }
+
+    fn widths(&self) -> Vec<usize> {
+        let mut widths = vec![0; self.columns()];
+        for row in std::iter::once(&self.headers).chain(&self.rows) {
+            for (w, cell) in widths.iter_mut().zip(row) {
+                *w = (*w).max(max_width(cell.lines()));
+            }
+        }
+        widths
+    }
+
+    fn rule(&self, widths: &[usize], left: &str, mid: &str, right: &str) -> String {
+        let h = self.style.horizontal;
+        let cols: Vec<String> = widths.iter().map(|w| h.repeat(w + 2)).collect();
+        format!("{}{}{}", left, cols.join(mid), right)
+    }
+
+    fn push_row(&self, out: &mut Vec<String>, row: &[String], widths: &[usize]) {
+        let cells: Vec<Vec<&str>> = (0..widths.len())
+            .map(|i| row.get(i).map_or(vec![], |c| c.lines().collect()))
+            .collect();
+        let height = cells.iter().map(Vec::len).max().unwrap_or(0).max(1);
+        let v = self.style.vertical;
+        for line in 0..height {
+            let mut s = String::from(v);
+            for (i, cell) in cells.iter().enumerate() {
+                let text = cell.get(line).copied().unwrap_or("");
+                let a = self.aligns.get(i).copied().unwrap_or_default();
+                s.push_str(&format!(" {} {}", align(text, widths[i], a), v));
+            }
+            out.push(s);
+        }
+    }
+
+    pub fn lines(&self) -> Vec<String> {
+        let widths = self.widths();
+        let s = self.style;
+        let mut out = vec![self.rule(&widths, s.top_left, s.top_tee, s.top_right)];
+        if !self.headers.is_empty() {
+            self.push_row(&mut out, &self.headers, &widths);
+            out.push(self.rule(&widths, s.left_tee, s.cross, s.right_tee));
+        }
+        for row in &self.rows {
+            self.push_row(&mut out, row, &widths);
+        }
+        out.push(self.rule(&widths, s.bottom_left, s.bottom_tee, s.bottom_right));
+        out
+    }
+}
+
+impl Display for Table {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.lines().join("\n"))
+    }
+}
+
+// cargo run -- frame [--style rounded] [--align left|center|right] [--title 标题] < 文件
+pub fn frame_cli(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let mut frame = Frame::new(&ROUNDED);
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let value = iter
+            .next()
+            .ok_or_else(|| format!("{} requires a value", arg))?;
+        frame = match arg.as_str() {
+            "--style" => Frame {
+                style: style_by_name(value).ok_or_else(|| format!("unknown style: {}", value))?,
+                ..frame
+            },
+            "--align" => frame.align(match value.as_str() {
+                "left" => Align::Left,
+                "center" => Align::Center,
+                "right" => Align::Right,
+                other => return Err(format!("unknown alignment: {}", other).into()),
+            }),
+            "--title" => frame.title(value),
+            other => return Err(format!("unknown option: {}", other).into()),
+        };
+    }
+    let mut text = String::new();
+    io::stdin().read_to_string(&mut text)?;
+    println!("{}", frame.render(&text));
+    Ok(())
+}
+
+pub fn frame_practice() {
+    // ASCII 内容与 OutlinePrint 原来的输出一致
+    let outline = Frame::new(&ASTERISK).padding(1, 1);
+    assert_eq!(
+        outline.lines(&"hi"),
+        ["******", "*    *", "* hi *", "*    *", "******"]
+    );
+
+    // 中文按两列计算
+    let lines = Frame::new(&SINGLE).lines(&"中国人\nabc");
+    assert_eq!(
+        lines,
+        ["┌────────┐", "│ 中国人 │", "│ abc    │", "└────────┘"]
+    );
+    assert!(lines
+        .iter()
+        .all(|l| utf8::display_width(l) == utf8::display_width(&lines[0])));
+
+    let lines = Frame::new(&DOUBLE)
+        .align(Align::Right)
+        .min_width(6)
+        .title("标题")
+        .padding(0, 0)
+        .lines(&"ab");
+    assert_eq!(lines, ["╔═ 标题 ═╗", "║      ab║", "╚════════╝"]);
+    assert_eq!(align("中", 5, Align::Center), " 中  ");
+
+    let mut table = Table::new(&[&"name", &"lat", &"说明"]).align(1, Align::Right);
+    table.row(&[&"Oslo", &59.95, &"挪威\n首都"]);
+    table.row(&[&"Vancouver", &-49.25]);
+    assert_eq!(
+        table.lines(),
+        [
+            "┌───────────┬────────┬──────┐",
+            "│ name      │    lat │ 说明 │",
+            "├───────────┼────────┼──────┤",
+            "│ Oslo      │  59.95 │ 挪威 │",
+            "│           │        │ 首都 │",
+            "│ Vancouver │ -49.25 │      │",
+            "└───────────┴────────┴──────┘",
+        ]
+    );
+    let mut empty = Table::new(&[]).style(&ASCII);
+    empty.row(&[&1, &22]);
+    assert_eq!(empty.to_string(), "+---+----+\n| 1 | 22 |\n+---+----+");
+
+    // 表格本身也实现了 Display，可以放进边框中
+    println!("{}", Frame::new(&ROUNDED).title("table").render(&table));
+}
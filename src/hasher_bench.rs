@@ -0,0 +1,594 @@
+//! 哈希函数基准测试
+//! hash_map 中提到 SipHash、ahash 和 XxHash64 的性能差异，这里实际测量一下
+//! FNV 和 xxHash 在本模块中实现，不依赖第三方库
+
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::hash::{BuildHasher, BuildHasherDefault, Hash, Hasher};
+use std::time::{Duration, Instant};
+
+/* ---------------------------- FNV-1a ---------------------------- */
+
+/*
+ * FNV-1a 64 位：每个字节先异或再乘以素数
+ * 实现非常简单，短 key 上很快，但没有随机种子，攻击者可以构造大量冲突的 key
+ */
+pub struct Fnv64(u64);
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+impl Default for Fnv64 {
+    fn default() -> Self {
+        Fnv64(FNV_OFFSET)
+    }
+}
+
+impl Hasher for Fnv64 {
+    fn write(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.0 ^= b as u64;
+            self.0 = self.0.wrapping_mul(FNV_PRIME);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+pub type FnvBuildHasher = BuildHasherDefault<Fnv64>;
+
+/* ---------------------------- xxHash64 ---------------------------- */
+
+const P1: u64 = 0x9E37_79B1_85EB_CA87;
+const P2: u64 = 0xC2B2_AE3D_27D4_EB4F;
+const P3: u64 = 0x1656_67B1_9E37_79F9;
+const P4: u64 = 0x85EB_CA77_C2B2_AE63;
+const P5: u64 = 0x27D4_EB2F_1656_67C5;
+
+/*
+ * xxHash64 流式实现：每 32 字节用 4 个累加器并行处理，不足 32 字节的部分先放在缓冲区，
+ * finish 时再处理缓冲区中剩余的字节，所以多次 write 和一次 write 的结果相同
+ */
+pub struct XxHash64 {
+    seed: u64,
+    acc: [u64; 4],
+    buf: [u8; 32],
+    buf_len: usize,
+    total_len: u64,
+}
+
+impl Default for XxHash64 {
+    fn default() -> Self {
+        XxHash64::with_seed(0)
+    }
+}
+
+fn xx_round(acc: u64, input: u64) -> u64 {
+    acc.wrapping_add(input.wrapping_mul(P2))
+        .rotate_left(31)
+        .wrapping_mul(P1)
+}
+
+fn xx_merge(acc: u64, val: u64) -> u64 {
+    (acc ^ xx_round(0, val)).wrapping_mul(P1).wrapping_add(P4)
+}
+
+fn read_u64(bytes: &[u8]) -> u64 {
+    u64::from_le_bytes(bytes[..8].try_into().unwrap())
+}
+
+fn read_u32(bytes: &[u8]) -> u32 {
+    u32::from_le_bytes(bytes[..4].try_into().unwrap())
+}
+
+impl XxHash64 {
+    pub fn with_seed(seed: u64) -> Self {
+        XxHash64 {
+            seed,
+            acc: [
+                seed.wrapping_add(P1).wrapping_add(P2),
+                seed.wrapping_add(P2),
+                seed,
+                seed.wrapping_sub(P1),
+            ],
+            buf: [0; 32],
+            buf_len: 0,
+            total_len: 0,
+        }
+    }
+
+    fn stripe(&mut self, block: &[u8]) {
+        for (i, acc) in self.acc.iter_mut().enumerate() {
+            *acc = xx_round(*acc, read_u64(&block[i * 8..]));
+        }
+    }
+}
+
+impl Hasher for XxHash64 {
+    fn write(&mut self, mut bytes: &[u8]) {
+        self.total_len += bytes.len() as u64;
+        // 先填满缓冲区
+        if self.buf_len > 0 {
+            let n = bytes.len().min(32 - self.buf_len);
+            self.buf[self.buf_len..self.buf_len + n].copy_from_slice(&bytes[..n]);
+            self.buf_len += n;
+            bytes = &bytes[n..];
+            if self.buf_len < 32 {
+                return;
+            }
+            let block = self.buf;
+            self.stripe(&block);
+            self.buf_len = 0;
+        }
+        let mut blocks = bytes.chunks_exact(32);
+        for block in &mut blocks {
+            self.stripe(block);
+        }
+        let rest = blocks.remainder();
+        self.buf[..rest.len()].copy_from_slice(rest);
+        self.buf_len = rest.len();
+    }
+
+    fn finish(&self) -> u64 {
+        let mut h = if self.total_len >= 32 {
+            let [v1, v2, v3, v4] = self.acc;
+            let h = v1
+                .rotate_left(1)
+                .wrapping_add(v2.rotate_left(7))
+                .wrapping_add(v3.rotate_left(12))
+                .wrapping_add(v4.rotate_left(18));
+            self.acc.iter().fold(h, |h, &v| xx_merge(h, v))
+        } else {
+            self.seed.wrapping_add(P5)
+        };
+        h = h.wrapping_add(self.total_len);
+
+        let mut rest = &self.buf[..self.buf_len];
+        while rest.len() >= 8 {
+            h ^= xx_round(0, read_u64(rest));
+            h = h.rotate_left(27).wrapping_mul(P1).wrapping_add(P4);
+            rest = &rest[8..];
+        }
+        if rest.len() >= 4 {
+            h ^= (read_u32(rest) as u64).wrapping_mul(P1);
+            h = h.rotate_left(23).wrapping_mul(P2).wrapping_add(P3);
+            rest = &rest[4..];
+        }
+        for &b in rest {
+            h ^= (b as u64).wrapping_mul(P5);
+            h = h.rotate_left(11).wrapping_mul(P1);
+        }
+
+        // 雪崩：让每个输入位都影响所有输出位
+        h ^= h >> 33;
+        h = h.wrapping_mul(P2);
+        h ^= h >> 29;
+        h = h.wrapping_mul(P3);
+        h ^ (h >> 32)
+    }
+}
+
+pub type XxBuildHasher = BuildHasherDefault<XxHash64>;
+
+pub fn fnv64(bytes: &[u8]) -> u64 {
+    let mut h = Fnv64::default();
+    h.write(bytes);
+    h.finish()
+}
+
+pub fn xxh64(bytes: &[u8], seed: u64) -> u64 {
+    let mut h = XxHash64::with_seed(seed);
+    h.write(bytes);
+    h.finish()
+}
+
+/* ---------------------------- 负载 ---------------------------- */
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Workload {
+    Insert,
+    Lookup,
+    Entry,
+}
+
+impl Workload {
+    pub const ALL: [Workload; 3] = [Workload::Insert, Workload::Lookup, Workload::Entry];
+
+    fn name(self) -> &'static str {
+        match self {
+            Workload::Insert => "insert",
+            Workload::Lookup => "lookup",
+            Workload::Entry => "entry",
+        }
+    }
+}
+
+pub const HASHERS: [&str; 4] = ["SipHash", "ahash", "FNV-1a", "xxHash64"];
+
+// 执行一次负载，返回耗时和一个校验值(防止被编译器优化掉，也用来比较不同哈希函数的结果是否一致)
+fn run_once<S, K>(keys: &[K], misses: &[K], workload: Workload) -> (Duration, u64)
+where
+    S: BuildHasher + Default,
+    K: Hash + Eq + Copy,
+{
+    let mut map: HashMap<K, u64, S> = HashMap::default();
+    match workload {
+        Workload::Insert => {
+            let start = Instant::now();
+            for (i, &k) in keys.iter().enumerate() {
+                map.insert(k, i as u64);
+            }
+            (start.elapsed(), map.len() as u64)
+        }
+        Workload::Lookup => {
+            for (i, &k) in keys.iter().enumerate() {
+                map.insert(k, i as u64);
+            }
+            // 一半命中一半不命中
+            let start = Instant::now();
+            let mut sum = 0u64;
+            for (hit, miss) in keys.iter().zip(misses) {
+                sum = sum.wrapping_add(*map.get(hit).unwrap_or(&0));
+                sum = sum.wrapping_add(*map.get(miss).unwrap_or(&1));
+            }
+            (start.elapsed(), std::hint::black_box(sum))
+        }
+        Workload::Entry => {
+            // 每个 key 出现 4 次，模拟词频统计
+            let distinct = (keys.len() / 4).max(1);
+            let start = Instant::now();
+            for i in 0..keys.len() {
+                *map.entry(keys[i % distinct]).or_insert(0) += 1;
+            }
+            (start.elapsed(), map.values().sum())
+        }
+    }
+}
+
+pub struct Measurement {
+    pub keys: &'static str,
+    pub workload: Workload,
+    // 与 HASHERS 一一对应：每次操作的纳秒数
+    pub ns_per_op: [f64; 4],
+    // 所有哈希函数得到的校验值必须相同
+    pub checksum: u64,
+}
+
+fn measure<S, K>(keys: &[K], misses: &[K], workload: Workload, rounds: usize) -> (f64, u64)
+where
+    S: BuildHasher + Default,
+    K: Hash + Eq + Copy,
+{
+    let ops = match workload {
+        Workload::Lookup => keys.len() * 2,
+        _ => keys.len(),
+    }
+    .max(1);
+    let mut best = Duration::MAX;
+    let mut checksum = 0;
+    for _ in 0..rounds.max(1) {
+        let (t, c) = run_once::<S, K>(keys, misses, workload);
+        best = best.min(t);
+        checksum = c;
+    }
+    (best.as_nanos() as f64 / ops as f64, checksum)
+}
+
+pub fn bench_keys<K: Hash + Eq + Copy>(
+    name: &'static str,
+    keys: &[K],
+    misses: &[K],
+    rounds: usize,
+) -> Vec<Measurement> {
+    Workload::ALL
+        .iter()
+        .map(|&w| {
+            let results = [
+                measure::<RandomState, K>(keys, misses, w, rounds),
+                measure::<ahash::RandomState, K>(keys, misses, w, rounds),
+                measure::<FnvBuildHasher, K>(keys, misses, w, rounds),
+                measure::<XxBuildHasher, K>(keys, misses, w, rounds),
+            ];
+            assert!(
+                results.iter().all(|r| r.1 == results[0].1),
+                "{} {}: hashers disagree",
+                name,
+                w.name()
+            );
+            Measurement {
+                keys: name,
+                workload: w,
+                ns_per_op: results.map(|r| r.0),
+                checksum: results[0].1,
+            }
+        })
+        .collect()
+}
+
+/* ---------------------------- key 生成 ---------------------------- */
+
+// xorshift64*，只用于生成测试数据
+pub struct XorShift(u64);
+
+impl XorShift {
+    pub fn new(seed: u64) -> Self {
+        XorShift(seed | 1)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+}
+
+pub fn int_keys(n: usize, seed: u64) -> Vec<u64> {
+    let mut rng = XorShift::new(seed);
+    (0..n).map(|_| rng.next_u64()).collect()
+}
+
+// 长度为 len 的字符串，前缀相同，只有末尾的编号不同，长 key 时哈希函数必须读完整个字符串
+pub fn string_keys(n: usize, len: usize, seed: u64) -> Vec<String> {
+    let mut rng = XorShift::new(seed);
+    (0..n)
+        .map(|_| {
+            let id = format!("{:016x}", rng.next_u64());
+            let pad = len.saturating_sub(id.len());
+            format!("{}{}", "k".repeat(pad), id)
+        })
+        .collect()
+}
+
+pub fn run_suite(n: usize, rounds: usize) -> Vec<Measurement> {
+    let ints = int_keys(n, 1);
+    let int_misses = int_keys(n, 2);
+    let short = string_keys(n, 8, 3);
+    let short_misses = string_keys(n, 8, 4);
+    let long = string_keys(n, 256, 5);
+    let long_misses = string_keys(n, 256, 6);
+    fn as_str(v: &[String]) -> Vec<&str> {
+        v.iter().map(String::as_str).collect()
+    }
+
+    let mut out = bench_keys("u64", &ints, &int_misses, rounds);
+    out.extend(bench_keys(
+        "str(8)",
+        &as_str(&short),
+        &as_str(&short_misses),
+        rounds,
+    ));
+    out.extend(bench_keys(
+        "str(256)",
+        &as_str(&long),
+        &as_str(&long_misses),
+        rounds,
+    ));
+    out
+}
+
+pub fn print_table(results: &[Measurement]) {
+    print!("{:<10}{:<8}", "keys", "op");
+    for h in HASHERS {
+        print!("{:>12}", h);
+    }
+    println!("   (ns/op, * = fastest)");
+    for m in results {
+        print!("{:<10}{:<8}", m.keys, m.workload.name());
+        let best = m.ns_per_op.iter().cloned().fold(f64::INFINITY, f64::min);
+        for &ns in &m.ns_per_op {
+            let mark = if ns == best { "*" } else { " " };
+            print!("{:>11.1}{}", ns, mark);
+        }
+        println!();
+    }
+}
+
+/* ---------------------------- 冲突 ---------------------------- */
+
+/*
+ * 哈希表用哈希值的低位作为桶下标，哈希函数固定(没有随机种子)时，
+ * 攻击者可以离线枚举出大量落在同一个桶里的 key(哈希洪水攻击)，使查找退化为线性扫描
+ * SipHash 和 ahash 每个 RandomState 使用不同的随机种子，同样的 key 无法造成冲突
+ */
+pub struct Collisions {
+    pub hasher: &'static str,
+    // 落到已被占用的桶中的 key 数量
+    pub collided: usize,
+    // 最满的桶中 key 的数量
+    pub max_load: usize,
+}
+
+pub fn count_collisions<S: BuildHasher, K: Hash>(
+    hasher: &'static str,
+    build: &S,
+    keys: &[K],
+    bits: u32,
+) -> Collisions {
+    let mask = (1u64 << bits) - 1;
+    let mut buckets = vec![0usize; 1 << bits];
+    for k in keys {
+        buckets[(build.hash_one(k) & mask) as usize] += 1;
+    }
+    Collisions {
+        hasher,
+        collided: keys.len() - buckets.iter().filter(|&&c| c > 0).count(),
+        max_load: buckets.into_iter().max().unwrap_or(0),
+    }
+}
+
+// 枚举 "flood-0", "flood-1", ... 留下在 build 下落入 0 号桶的 n 个 key
+pub fn flood_keys<S: BuildHasher>(build: &S, n: usize, bits: u32) -> Vec<String> {
+    let mask = (1u64 << bits) - 1;
+    (0u64..)
+        .map(|i| format!("flood-{}", i))
+        .filter(|k| build.hash_one(k) & mask == 0)
+        .take(n)
+        .collect()
+}
+
+pub fn collisions_for<K: Hash>(keys: &[K], bits: u32) -> [Collisions; 4] {
+    [
+        count_collisions("SipHash", &RandomState::new(), keys, bits),
+        count_collisions("ahash", &ahash::RandomState::new(), keys, bits),
+        count_collisions("FNV-1a", &FnvBuildHasher::default(), keys, bits),
+        count_collisions("xxHash64", &XxBuildHasher::default(), keys, bits),
+    ]
+}
+
+pub fn print_collisions(title: &str, keys: usize, bits: u32, results: &[Collisions]) {
+    println!("{} ({} keys, {} buckets)", title, keys, 1 << bits);
+    for c in results {
+        println!(
+            "  {:<10} collided = {:<6} max_load = {}",
+            c.hasher, c.collided, c.max_load
+        );
+    }
+}
+
+// cargo run --release -- hashbench [--n 100000] [--rounds 5]
+pub fn hash_bench(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let mut n = 100_000;
+    let mut rounds = 5;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let value = iter
+            .next()
+            .ok_or_else(|| format!("{} requires a value", arg))?;
+        match arg.as_str() {
+            "--n" => n = value.parse()?,
+            "--rounds" => rounds = value.parse()?,
+            other => return Err(format!("unknown option: {}", other).into()),
+        }
+    }
+
+    print_table(&run_suite(n, rounds));
+    println!();
+
+    let bits = 10;
+    let keys = 1 << bits;
+    let fnv_flood = flood_keys(&FnvBuildHasher::default(), keys, bits);
+    print_collisions(
+        "keys flooding FNV-1a",
+        keys,
+        bits,
+        &collisions_for(&fnv_flood, bits),
+    );
+    let xx_flood = flood_keys(&XxBuildHasher::default(), keys, bits);
+    print_collisions(
+        "keys flooding xxHash64",
+        keys,
+        bits,
+        &collisions_for(&xx_flood, bits),
+    );
+    // 只有高位不同的整数
+    let stride: Vec<u64> = (0..keys as u64).map(|i| i << 32).collect();
+    print_collisions(
+        "integers i << 32",
+        keys,
+        bits,
+        &collisions_for(&stride, bits),
+    );
+    Ok(())
+}
+
+pub fn hasher_bench_practice() {
+    for input in ["", "abc", "Nobody inspects the spammish repetition"] {
+        println!(
+            "{:<42} fnv64 = {:016x}  xxh64 = {:016x}",
+            format!("{:?}", input),
+            fnv64(input.as_bytes()),
+            xxh64(input.as_bytes(), 0)
+        );
+    }
+
+    // hash_map 中注释掉的 XxHash64 例子
+    let mut hash: HashMap<_, _, XxBuildHasher> = Default::default();
+    hash.insert(42, "the answer");
+    assert_eq!(hash.get(&42), Some(&"the answer"));
+
+    // 四种哈希函数的统计结果一致(bench_keys 内部会检查)
+    let results = run_suite(2_000, 1);
+    assert_eq!(results.len(), 9);
+    let entry = results
+        .iter()
+        .find(|m| m.keys == "u64" && m.workload == Workload::Entry)
+        .unwrap();
+    assert_eq!(entry.checksum, 2_000);
+    print_table(&results);
+
+    // 针对 FNV、XxHash64 构造的 key 全部落在同一个桶里，带随机种子的哈希函数不受影响
+    let bits = 8;
+    let flood = flood_keys(&FnvBuildHasher::default(), 256, bits);
+    let [sip, ahash, fnv, _] = collisions_for(&flood, bits);
+    print_collisions("keys flooding FNV-1a", 256, bits, &[sip, ahash, fnv]);
+    let flood = flood_keys(&XxBuildHasher::default(), 256, bits);
+    let [sip, ahash, _, xx] = collisions_for(&flood, bits);
+    print_collisions("keys flooding XxHash64", 256, bits, &[sip, ahash, xx]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 官方测试向量
+    #[test]
+    fn fnv64_reference_vectors() {
+        assert_eq!(fnv64(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv64(b"a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(fnv64(b"foobar"), 0x8594_4171_f739_67e8);
+    }
+
+    #[test]
+    fn xxh64_reference_vectors() {
+        assert_eq!(xxh64(b"", 0), 0xEF46_DB37_51D8_E999);
+        assert_eq!(xxh64(b"abc", 0), 0x44BC_2CF5_AD77_0999);
+        // 超过 32 字节才会走四路累加器和合并轮，这个向量来自 python-xxhash 的文档
+        let spam = b"Nobody inspects the spammish repetition";
+        assert_eq!(xxh64(spam, 0), 0xFBCE_A83C_8A37_8BF1);
+        let mut h = XxHash64::with_seed(0);
+        h.write(b"Nobody inspects");
+        h.write(b" the spammish repetition");
+        assert_eq!(h.finish(), 0xFBCE_A83C_8A37_8BF1);
+        // 按 xxHash 规范另外实现一遍算出的结果：109 字节包含 3 个完整的条带和 8、4、1 字节的尾部
+        let bytes: Vec<u8> = (0..109).collect();
+        assert_eq!(xxh64(&bytes, 0), 0x68D3_618A_8A39_5DC8);
+        assert_eq!(xxh64(&bytes, 42), 0xB3E2_573A_8325_FA56);
+    }
+
+    // 分多次写入与一次写入结果相同，覆盖缓冲区跨 32 字节边界的情况
+    #[test]
+    fn xxh64_streaming_matches_one_shot() {
+        let data: Vec<u8> = (0..=255u8).cycle().take(1000).collect();
+        for split in [1, 7, 31, 32, 33, 100] {
+            let mut h = XxHash64::with_seed(42);
+            for chunk in data.chunks(split) {
+                h.write(chunk);
+            }
+            assert_eq!(h.finish(), xxh64(&data, 42), "split = {}", split);
+        }
+        assert_ne!(xxh64(&data, 0), xxh64(&data, 1));
+    }
+
+    // 针对 FNV 构造的 key 全部落在同一个桶里，带随机种子的哈希函数不受影响
+    #[test]
+    fn flooding_fnv_only_hurts_fnv() {
+        let bits = 8;
+        let flood = flood_keys(&FnvBuildHasher::default(), 256, bits);
+        let [sip, ahash, fnv, _] = collisions_for(&flood, bits);
+        assert_eq!(fnv.max_load, 256);
+        assert_eq!(fnv.collided, 255);
+        assert!(sip.max_load < 32 && ahash.max_load < 32);
+    }
+
+    #[test]
+    fn flooding_fixed_seed_xxh64() {
+        let bits = 8;
+        let flood = flood_keys(&XxBuildHasher::default(), 256, bits);
+        let [sip, _, _, xx] = collisions_for(&flood, bits);
+        assert_eq!(xx.max_load, 256);
+        assert!(sip.max_load < 32);
+    }
+}
@@ -0,0 +1,460 @@
+//! 内存布局查看器
+//! data_type 中只打印了 size_of，这里进一步列出每个字段的偏移、填充、对齐和 niche
+
+use std::fmt::Write;
+use std::mem::{align_of, size_of};
+
+/*
+ * niche：类型中永远不会出现的位模式，比如 Box/&T 不会是 0，bool 只会是 0 或 1
+ * 编译器可以用 niche 来存放枚举的判别值，所以 Option<Box<u32>> 和 Box<u32> 一样大
+ * 这里用 size_of::<Option<T>>() == size_of::<T>() 判断一个类型是否带有可用的 niche
+ */
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Struct,
+    Union,
+    Enum,
+    // 只知道大小和对齐的类型，比如标准库中的类型
+    Opaque,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldLayout {
+    pub name: &'static str,
+    pub ty: &'static str,
+    pub offset: usize,
+    pub size: usize,
+    pub align: usize,
+    pub niche: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VariantLayout {
+    pub name: &'static str,
+    pub payload: &'static str,
+    pub size: usize,
+    pub align: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeLayout {
+    pub name: &'static str,
+    pub kind: Kind,
+    pub size: usize,
+    pub align: usize,
+    // Option<T> 的大小，和 size 相同说明 T 带有 niche
+    pub option_size: usize,
+    // 按声明顺序排列
+    pub fields: Vec<FieldLayout>,
+    pub variants: Vec<VariantLayout>,
+}
+
+pub trait Layout {
+    fn layout() -> TypeLayout;
+}
+
+// 通过字段投影闭包推断字段类型，闭包本身不会被调用
+pub fn field_of<T, F>(name: &'static str, offset: usize, _project: fn(&T) -> &F) -> FieldLayout {
+    FieldLayout {
+        name,
+        ty: std::any::type_name::<F>(),
+        offset,
+        size: size_of::<F>(),
+        align: align_of::<F>(),
+        niche: has_niche::<F>(),
+    }
+}
+
+pub fn variant_of<P>(name: &'static str, payload: &'static str) -> VariantLayout {
+    VariantLayout {
+        name,
+        payload,
+        size: size_of::<P>(),
+        align: align_of::<P>(),
+    }
+}
+
+pub fn has_niche<T>() -> bool {
+    size_of::<Option<T>>() == size_of::<T>()
+}
+
+/*
+ * 为已有类型生成 Layout 实现，只需要列出字段名(枚举需要写出载荷类型)：
+ *   impl_layout!(struct A { a, b, c });
+ *   impl_layout!(union F { u, v });
+ *   impl_layout!(enum E { N, H(u32), M(Box<u32>) });
+ */
+#[macro_export]
+macro_rules! impl_layout {
+    (struct $name:ident { $($field:ident),* $(,)? }) => {
+        impl $crate::layout::Layout for $name {
+            fn layout() -> $crate::layout::TypeLayout {
+                $crate::layout::TypeLayout {
+                    name: stringify!($name),
+                    kind: $crate::layout::Kind::Struct,
+                    size: std::mem::size_of::<$name>(),
+                    align: std::mem::align_of::<$name>(),
+                    option_size: std::mem::size_of::<Option<$name>>(),
+                    fields: vec![$($crate::layout::field_of(
+                        stringify!($field),
+                        std::mem::offset_of!($name, $field),
+                        |s: &$name| &s.$field,
+                    ),)*],
+                    variants: Vec::new(),
+                }
+            }
+        }
+    };
+    (union $name:ident { $($field:ident),* $(,)? }) => {
+        impl $crate::layout::Layout for $name {
+            fn layout() -> $crate::layout::TypeLayout {
+                $crate::layout::TypeLayout {
+                    name: stringify!($name),
+                    kind: $crate::layout::Kind::Union,
+                    size: std::mem::size_of::<$name>(),
+                    align: std::mem::align_of::<$name>(),
+                    option_size: std::mem::size_of::<Option<$name>>(),
+                    // 闭包只用来推断字段类型，不会执行，所以不会真的读取联合体
+                    fields: vec![$($crate::layout::field_of(
+                        stringify!($field),
+                        std::mem::offset_of!($name, $field),
+                        |s: &$name| unsafe { &s.$field },
+                    ),)*],
+                    variants: Vec::new(),
+                }
+            }
+        }
+    };
+    (enum $name:ident { $($variant:ident $(( $($payload:ty),* ))?),* $(,)? }) => {
+        impl $crate::layout::Layout for $name {
+            fn layout() -> $crate::layout::TypeLayout {
+                $crate::layout::TypeLayout {
+                    name: stringify!($name),
+                    kind: $crate::layout::Kind::Enum,
+                    size: std::mem::size_of::<$name>(),
+                    align: std::mem::align_of::<$name>(),
+                    option_size: std::mem::size_of::<Option<$name>>(),
+                    fields: Vec::new(),
+                    variants: vec![$($crate::layout::variant_of::<($($($payload,)*)?)>(
+                        stringify!($variant),
+                        stringify!($($($payload),*)?),
+                    ),)*],
+                }
+            }
+        }
+    };
+}
+
+impl TypeLayout {
+    // 标准库等无法列出字段的类型
+    pub fn opaque<T>(name: &'static str) -> TypeLayout {
+        TypeLayout {
+            name,
+            kind: Kind::Opaque,
+            size: size_of::<T>(),
+            align: align_of::<T>(),
+            option_size: size_of::<Option<T>>(),
+            fields: Vec::new(),
+            variants: Vec::new(),
+        }
+    }
+
+    pub fn has_niche(&self) -> bool {
+        self.option_size == self.size
+    }
+
+    // 按内存中的先后顺序排列的字段
+    pub fn fields_by_offset(&self) -> Vec<&FieldLayout> {
+        let mut fields: Vec<&FieldLayout> = self.fields.iter().collect();
+        fields.sort_by_key(|f| (f.offset, std::cmp::Reverse(f.size)));
+        fields
+    }
+
+    // 结构体中没有被任何字段覆盖的字节区间 (起始偏移, 长度)
+    pub fn padding(&self) -> Vec<(usize, usize)> {
+        if self.kind != Kind::Struct && self.kind != Kind::Union {
+            return Vec::new();
+        }
+        let mut used = vec![false; self.size];
+        for f in &self.fields {
+            for b in used.iter_mut().skip(f.offset).take(f.size) {
+                *b = true;
+            }
+        }
+        let mut gaps = Vec::new();
+        let mut i = 0;
+        while i < self.size {
+            if !used[i] {
+                let start = i;
+                while i < self.size && !used[i] {
+                    i += 1;
+                }
+                gaps.push((start, i - start));
+            } else {
+                i += 1;
+            }
+        }
+        gaps
+    }
+
+    pub fn padding_bytes(&self) -> usize {
+        match self.kind {
+            Kind::Struct | Kind::Union => self.padding().iter().map(|(_, len)| len).sum(),
+            // 枚举中除最大载荷外的字节用于判别值和填充
+            Kind::Enum => self.size - self.variants.iter().map(|v| v.size).max().unwrap_or(0),
+            Kind::Opaque => 0,
+        }
+    }
+
+    // 如果这个结构体加上 #[repr(C)]，按声明顺序排列后的布局
+    pub fn as_repr_c(&self) -> Option<TypeLayout> {
+        if self.kind != Kind::Struct {
+            return None;
+        }
+        let mut offset = 0usize;
+        let mut align = 1usize;
+        let fields = self
+            .fields
+            .iter()
+            .map(|f| {
+                offset = offset.next_multiple_of(f.align);
+                let placed = FieldLayout {
+                    offset,
+                    ..f.clone()
+                };
+                offset += f.size;
+                align = align.max(f.align);
+                placed
+            })
+            .collect();
+        let size = offset.next_multiple_of(align);
+        Some(TypeLayout {
+            name: self.name,
+            kind: Kind::Struct,
+            size,
+            align,
+            // repr(C) 不影响字段自身的 niche
+            option_size: if self.fields.iter().any(|f| f.niche) {
+                size
+            } else {
+                (size + align).next_multiple_of(align)
+            },
+            fields,
+            variants: Vec::new(),
+        })
+    }
+
+    // 字段按字母 a、b、c... 标记，填充字节用 . 表示
+    pub fn byte_map(&self) -> String {
+        let mut cells = vec![String::from("."); self.size];
+        for (i, f) in self.fields.iter().enumerate() {
+            let mark = field_mark(i);
+            for cell in cells.iter_mut().skip(f.offset).take(f.size) {
+                // 联合体中多个字段共享同一个字节
+                if cell == "." {
+                    *cell = mark.to_string();
+                } else {
+                    cell.push(mark);
+                }
+            }
+        }
+        if self.kind == Kind::Enum || self.kind == Kind::Opaque {
+            cells = vec![String::from("?"); self.size];
+        }
+        let width = cells.iter().map(|c| c.len()).max().unwrap_or(1).max(2);
+        let mut out = String::new();
+        for (row, chunk) in cells.chunks(16).enumerate() {
+            let _ = write!(out, "{:>6} |", row * 16);
+            for c in chunk {
+                let _ = write!(out, " {:>width$}", c, width = width);
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    pub fn report(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "{} ({:?}) size = {}, align = {}, padding = {}, Option<{}> = {}{}",
+            self.name,
+            self.kind,
+            self.size,
+            self.align,
+            self.padding_bytes(),
+            self.name,
+            self.option_size,
+            if self.has_niche() { " (niche)" } else { "" }
+        );
+        for f in self.fields_by_offset() {
+            let i = self.fields.iter().position(|x| x == f).unwrap();
+            let _ = writeln!(
+                out,
+                "  [{}] {:<8} {:<24} offset {:>3}  size {:>3}  align {:>2}{}",
+                field_mark(i),
+                f.name,
+                f.ty,
+                f.offset,
+                f.size,
+                f.align,
+                if f.niche { "  niche" } else { "" }
+            );
+        }
+        for (start, len) in self.padding() {
+            let _ = writeln!(
+                out,
+                "  [.] padding                           offset {:>3}  size {:>3}",
+                start, len
+            );
+        }
+        for v in &self.variants {
+            let _ = writeln!(
+                out,
+                "  {:<8} {:<24} payload size {:>3}  align {:>2}",
+                v.name,
+                format!("({})", v.payload),
+                v.size,
+                v.align
+            );
+        }
+        out.push_str(&self.byte_map());
+        if let Some(c) = self.as_repr_c() {
+            if c.fields != self.fields || c.size != self.size {
+                let _ = writeln!(
+                    out,
+                    "  as #[repr(C)]: size = {}, padding = {} (Rust layout saves {} bytes)",
+                    c.size,
+                    c.padding_bytes(),
+                    c.size as isize - self.size as isize
+                );
+                out.push_str(&c.byte_map());
+            } else {
+                let _ = writeln!(out, "  same as #[repr(C)]");
+            }
+        }
+        out
+    }
+}
+
+fn field_mark(i: usize) -> char {
+    (b'a' + (i % 26) as u8) as char
+}
+
+/* ---------------------------- 已注册的类型 ---------------------------- */
+
+use crate::{Color, Point2D, User, A, B, D, E, F};
+
+impl_layout!(struct A { a, b, c });
+impl_layout!(struct B { a, b, c });
+impl_layout!(
+    enum D {
+        One,
+        Two,
+    }
+);
+impl_layout!(
+    enum E {
+        N,
+        H(u32),
+        M(Box<u32>),
+    }
+);
+impl_layout!(union F { u, v });
+impl_layout!(struct User { active, username, email, sign_in_count });
+impl_layout!(struct Color { red, green, blue });
+impl_layout!(struct Point2D { x, y });
+
+pub fn registry() -> Vec<TypeLayout> {
+    vec![
+        A::layout(),
+        B::layout(),
+        D::layout(),
+        E::layout(),
+        F::layout(),
+        User::layout(),
+        Color::layout(),
+        Point2D::layout(),
+        TypeLayout::opaque::<Box<u32>>("Box<u32>"),
+        TypeLayout::opaque::<&str>("&str"),
+        TypeLayout::opaque::<String>("String"),
+        TypeLayout::opaque::<char>("char"),
+    ]
+}
+
+// cargo run -- layout [类型名...]
+pub fn layout_report(names: &[String]) -> Result<(), String> {
+    let registry = registry();
+    if names.is_empty() {
+        for t in &registry {
+            println!("{}", t.report());
+        }
+        return Ok(());
+    }
+    for name in names {
+        match registry.iter().find(|t| t.name == name) {
+            Some(t) => println!("{}", t.report()),
+            None => return Err(format!("type `{}` is not registered", name)),
+        }
+    }
+    Ok(())
+}
+
+// 默认布局(repr(Rust))的大小和字段顺序没有保证，只能检查按规则算出的上下界
+fn check_bounds(t: &TypeLayout) {
+    let payload: usize = t.fields.iter().map(|f| f.size).sum();
+    let widest = t.fields.iter().map(|f| f.size).max().unwrap_or(0);
+    assert!(t.size.is_multiple_of(t.align));
+    assert!(t.fields.iter().all(|f| f.offset + f.size <= t.size));
+    match t.kind {
+        Kind::Struct => {
+            assert!(t.size >= payload);
+            assert_eq!(t.padding_bytes(), t.size - payload);
+        }
+        Kind::Union => assert!(t.size >= widest),
+        Kind::Enum | Kind::Opaque => {}
+    }
+}
+
+pub fn layout_practice() {
+    let a = A::layout();
+    let b = B::layout();
+    // B 是 repr(C)，按声明顺序排列，有 5 个字节的填充；A 可以被重排，只检查上下界
+    check_bounds(&a);
+    assert_eq!(b.size, 12);
+    assert_eq!(
+        b.fields.iter().map(|f| f.offset).collect::<Vec<_>>(),
+        [0, 4, 8]
+    );
+    assert_eq!(b.padding(), [(1, 3), (10, 2)]);
+    // A 加上 repr(C) 后就和 B 一样
+    let a_c = a.as_repr_c().unwrap();
+    assert_eq!(a_c.size, b.size);
+    assert_eq!(a_c.fields, b.fields);
+    assert_eq!(b.as_repr_c().unwrap().fields, b.fields);
+
+    // E 的 Box 带有 niche 但还需要区分 N 和 H
+    let e = E::layout();
+    assert_eq!(e.variants.len(), 3);
+    assert_eq!(e.variants[2].size, size_of::<Box<u32>>());
+    assert!(e.size >= size_of::<Box<u32>>());
+    assert!(TypeLayout::opaque::<Box<u32>>("Box<u32>").has_niche());
+
+    // 联合体的大小至少是最大的字段，并且没有 niche
+    let f = F::layout();
+    check_bounds(&f);
+    assert!(!f.has_niche());
+
+    // bool 字段带有 niche，所以 Option<User> 不变大
+    let user = User::layout();
+    check_bounds(&user);
+    assert!(user.fields[0].niche);
+    assert!(user.has_niche());
+
+    let color = Color::layout();
+    check_bounds(&color);
+    for t in [&a, &b, &D::layout(), &e, &f, &color] {
+        print!("{}", t.report());
+    }
+}
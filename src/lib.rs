@@ -0,0 +1,182 @@
+//! 库 crate：二进制和文档测试共用的部分
+//! 目前只有 back_to_enum! 宏和它依赖的 enums 模块，宏中的 $crate 指向这里，
+//! 这样 compile_fail 文档测试可以直接使用宏，检查不支持的输入确实编译失败
+
+pub mod enums;
+
+/// 无数据枚举和整数、名称之间的转换
+/// 除了各种整数类型的 TryFrom 外还生成按名称的 FromStr/Display、ALL、COUNT 和 iter()，错误类型和 Enum 特征见 enums 模块
+///   back_to_enum! { enum E { A = 1, B => "b", } }             => "b" 指定显示和解析用的名称
+///   back_to_enum! { ignore_case; enum E { ... } }             解析名称时不区分大小写
+///   back_to_enum!(impl Enum for E { A, B });                  只为已有的枚举实现 Enum 特征，可用于 EnumSet/EnumMap
+///
+/// ```
+/// use rust::back_to_enum;
+/// use rust::enums::EnumSet;
+///
+/// back_to_enum! {
+///     #[derive(Debug, PartialEq)]
+///     enum Suit { Clubs = 1, Spades => "♠" }
+/// }
+/// assert_eq!(Suit::try_from(2u8), Ok(Suit::Spades));
+/// assert_eq!("♠".parse(), Ok(Suit::Spades));
+/// assert_eq!(EnumSet::<Suit>::all().len(), Suit::COUNT);
+/// ```
+///
+/// 宏不支持的输入在编译时报错。变体不能带数据：
+///
+/// ```compile_fail
+/// use rust::back_to_enum;
+/// back_to_enum! { enum Shape { Circle(f64), Square } }
+/// ```
+///
+/// ```compile_fail
+/// use rust::back_to_enum;
+/// back_to_enum! { enum Point { X { x: i32 } } }
+/// ```
+///
+/// 重命名必须是字符串字面量：
+///
+/// ```compile_fail
+/// use rust::back_to_enum;
+/// back_to_enum! { enum Name { A => b } }
+/// ```
+///
+/// 只接受枚举：
+///
+/// ```compile_fail
+/// use rust::back_to_enum;
+/// back_to_enum! { struct S { a: i32 } }
+/// ```
+///
+/// 至少需要一个变体：
+///
+/// ```compile_fail
+/// use rust::back_to_enum;
+/// back_to_enum! { enum Empty {} }
+/// ```
+///
+/// 判别值和变体名不能重复：
+///
+/// ```compile_fail,E0081
+/// use rust::back_to_enum;
+/// back_to_enum! { enum Dup { A = 1, B = 1 } }
+/// ```
+///
+/// ```compile_fail,E0428
+/// use rust::back_to_enum;
+/// back_to_enum! { enum Dup { A, A } }
+/// ```
+///
+/// 只实现 Enum 特征时列出的变体必须存在：
+///
+/// ```compile_fail,E0599
+/// use rust::back_to_enum;
+/// enum Two { A, B }
+/// back_to_enum!(impl Enum for Two { A, C });
+/// ```
+#[macro_export]
+macro_rules! back_to_enum {
+    (ignore_case; $($rest:tt)*) => {
+        $crate::back_to_enum!(@enum true; $($rest)*);
+    };
+
+    (impl Enum for $name:ident { $($vname:ident $(=> $rename:literal)?),+ $(,)? }) => {
+        impl $crate::enums::Enum for $name {
+            const ALL: &'static [$name] = &[$($name::$vname),*];
+
+            // 在声明中的序号，与判别值无关
+            fn index(&self) -> usize {
+                #[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+                enum Index {
+                    $($vname,)*
+                }
+                match self {
+                    $($name::$vname => Index::$vname as usize,)*
+                }
+            }
+
+            fn name(&self) -> &'static str {
+                match self {
+                    $($name::$vname => $crate::back_to_enum!(@name $vname $($rename)?),)*
+                }
+            }
+        }
+    };
+
+    (@name $vname:ident) => { stringify!($vname) };
+    (@name $vname:ident $rename:literal) => { $rename };
+
+    // 对每种整数类型生成 TryFrom，先转成 i128 再比较判别值
+    (@try_from $name:ident: $($int:ty),*) => {
+        $(impl std::convert::TryFrom<$int> for $name {
+            type Error = $crate::enums::InvalidValue<$int>;
+
+            fn try_from(v: $int) -> Result<Self, Self::Error> {
+                i128::try_from(v)
+                    .ok()
+                    .and_then($name::from_discriminant)
+                    .ok_or($crate::enums::InvalidValue::new(stringify!($name), v))
+            }
+        })*
+    };
+
+    (@enum $ignore_case:literal; $(#[$meta:meta])* $vis:vis enum $name:ident {
+        $($(#[$vmeta:meta])* $vname:ident $(= $val:expr)? $(=> $rename:literal)?),+ $(,)?
+    }) => {
+        $(#[$meta])*
+        $vis enum $name {
+            $($(#[$vmeta])* $vname $(= $val)?,)*
+        }
+
+        #[allow(dead_code)]
+        impl $name {
+            pub const ALL: [$name; [$(stringify!($vname)),*].len()] = [$($name::$vname),*];
+            pub const COUNT: usize = $name::ALL.len();
+
+            // 按声明顺序遍历所有变体
+            pub fn iter() -> impl Iterator<Item = $name> {
+                $name::ALL.into_iter()
+            }
+
+            pub fn name(&self) -> &'static str {
+                $crate::enums::Enum::name(self)
+            }
+
+            pub fn index(&self) -> usize {
+                $crate::enums::Enum::index(self)
+            }
+
+            fn from_discriminant(v: i128) -> Option<$name> {
+                match v {
+                    $(x if x == $name::$vname as i128 => Some($name::$vname),)*
+                    _ => None,
+                }
+            }
+        }
+
+        $crate::back_to_enum!(impl Enum for $name { $($vname $(=> $rename)?),* });
+
+        $crate::back_to_enum!(@try_from $name: i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+        impl std::fmt::Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.pad(self.name())
+            }
+        }
+
+        impl std::str::FromStr for $name {
+            type Err = $crate::enums::ParseEnumError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                $name::iter()
+                    .find(|v| $crate::enums::name_matches(v.name(), s, $ignore_case))
+                    .ok_or_else(|| $crate::enums::ParseEnumError::new(stringify!($name), s))
+            }
+        }
+    };
+
+    ($(#[$meta:meta])* $vis:vis enum $($rest:tt)*) => {
+        $crate::back_to_enum!(@enum false; $(#[$meta])* $vis enum $($rest)*);
+    };
+}
@@ -12,6 +12,33 @@ use std::thread::spawn;
 // 有理数和复数社区库，没有标准库
 use num::complex::Complex;
 
+// 库 crate(src/lib.rs)
+use rust::back_to_enum;
+
+mod algorithms;
+mod alloc_stats;
+mod cache;
+mod calc;
+mod const_fn;
+mod dispatch_bench;
+mod enum_practice;
+mod fast_map;
+mod feed;
+mod frame;
+mod hasher_bench;
+mod layout;
+mod numeric;
+mod persistent;
+mod pq;
+mod raw_ptr;
+mod rc_structs;
+mod records;
+mod serial;
+mod sorting;
+mod text_stats;
+mod utf8;
+mod widget;
+
 fn comment() {
     // 普通行注释
 
@@ -106,6 +133,18 @@ fn debugout() {
 
     // 美化打印
     println!("{:#?}", peter);
+
+    // 实现了 records::Fields 的类型可以整体输出为表格
+    let people = [
+        peter,
+        Person {
+            name: "Mary",
+            age: 31,
+        },
+    ];
+    println!("{}", records::Records::new(&people).table());
+    let deep = [Deep(Structure(3)), Deep(Structure(7))];
+    println!("{}", records::Records::new(&deep).markdown());
 }
 
 struct DisplayStructure(i32);
@@ -157,6 +196,15 @@ fn displayout() {
         small = small_range,
         big = big_range
     );
+    big_range.outline_print();
+    // 多行内容按最宽的一行加边框
+    let ranges = format!("big:   {}\nsmall: {}", big_range, small_range);
+    println!(
+        "{}",
+        frame::Frame::new(&frame::ROUNDED)
+            .title("ranges")
+            .render(&ranges)
+    );
 
     let point = Point2D { x: 3.3, y: 7.2 };
 
@@ -164,6 +212,13 @@ fn displayout() {
     println!("Display: {}", point);
     println!("Debug: {:?}", point);
 
+    let ranges = [minmax, big_range, small_range];
+    println!("{}", records::Records::new(&ranges).table());
+    let points = [point, Point2D { x: -1.0, y: 0.5 }];
+    if let Ok(points) = records::Records::new(&points).precision("x", 2) {
+        println!("{}", points.csv());
+    }
+
     // 报错。`Debug` 和 `Display` 都被实现了，但 `{:b}` 需要 `fmt::Binary`
     // 得到实现。这语句不能运行。
     // println!("What does Point2D look like in binary: {:b}?", point);
@@ -189,15 +244,16 @@ impl fmt::Display for List {
 fn DisplayList() {
     let v = List(vec![1, 2, 3]);
     println!("{}", v);
+    v.outline_print();
 }
 
-struct City {
-    name: &'static str,
+struct City<'a> {
+    name: &'a str,
     lat: f32,
     lon: f32,
 }
 
-impl fmt::Display for City {
+impl fmt::Display for City<'_> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let lat_c = if self.lat >= 0.0 { 'N' } else { 'S' };
         let lon_c = if self.lon >= 0.0 { 'E' } else { 'W' };
@@ -222,7 +278,7 @@ struct Color {
 }
 
 fn DisplayCity() {
-    for city in [
+    let cities = [
         City {
             name: "Dublin",
             lat: 53.347778,
@@ -238,11 +294,19 @@ fn DisplayCity() {
             lat: 49.25,
             lon: -123.1,
         },
-    ]
-    .iter()
-    {
+    ];
+    for city in cities.iter() {
         println!("{}", *city)
     }
+    cities[0].outline_print();
+    let mut table = frame::Table::new(&[&"city", &"lat", &"lon", &"Display"])
+        .align(1, frame::Align::Right)
+        .align(2, frame::Align::Right);
+    for city in &cities {
+        table.row(&[&city.name, &city.lat, &city.lon, city]);
+    }
+    println!("{}", table);
+    println!("{}", records::Records::new(&cities).json());
     for color in [
         Color {
             red: 128,
@@ -696,6 +760,8 @@ enum PokerSuit {
     Hearts,
 }
 
+back_to_enum!(impl Enum for PokerSuit { Clubs, Spades, Diamonds, Hearts });
+
 #[derive(Debug)]
 struct PokerCard {
     suit: PokerSuit,
@@ -874,6 +940,8 @@ enum Direction {
     South,
 }
 
+back_to_enum!(impl Enum for Direction { East, West, North, South });
+
 enum IpAddr {
     Ipv4,
     Ipv6,
@@ -1070,6 +1138,7 @@ fn scenes() {
  * 出队
  * 求取队列长度
  */
+// 按优先级出队的堆(二叉堆、配对堆、索引堆)、EDF 调度器和时间轮见 pq.rs
 
 #[derive(Debug)]
 struct MyQueue<T> {
@@ -1425,7 +1494,7 @@ fn vector() {
         Person_2::new("Al".to_string(), 60),
         Person_2::new("John".to_string(), 1),
     ];
-    // 定义一个按照年龄倒序排序的对比函数
+    // 定义一个按照年龄倒序排序的对比函数，自己实现的排序和选择算法见 sorting.rs
     people.sort_unstable_by(|a, b| b.age.cmp(&a.age));
 
     println!("{:?}", people);
@@ -1488,6 +1557,7 @@ fn hash_map() {
     // rust hash函数的安全性很高，函数SipHash在中等大小的key上性能不错,
     // 但对于小型key（整数）或者大型key（字符串）来说，性能不够，
     // 如果需要机制性能，可以考虑ahash库
+    // 各哈希函数的实际测量见 hasher_bench 模块: cargo run --release -- hashbench
     // use std::hash::BuildHasherDefault;
     // use std::collections::HashMap;
     // // 引入第三方的哈希函数
@@ -1617,11 +1687,34 @@ fn t() {
 
 pub trait Draw {
     fn draw(&self);
+
+    // 下面的方法都有默认实现，只实现 draw 的类型仍然可以作为特征对象使用，终端界面见 widget 模块
+    // 组件期望占用的大小(列数, 行数)
+    fn size(&self) -> (u16, u16) {
+        (0, 0)
+    }
+    // 绘制到单元格缓冲区的 area 区域中，focused 表示是否拥有键盘焦点
+    fn render(&self, _buf: &mut widget::Buffer, _area: widget::Rect, _focused: bool) {}
+    // 能否获得键盘焦点
+    fn focusable(&self) -> bool {
+        false
+    }
+    // 处理按键或鼠标事件，area 是组件所在的区域，返回 false 时事件会冒泡到 Screen
+    fn handle_event(
+        &mut self,
+        _event: &widget::Event,
+        _area: widget::Rect,
+        _ctx: &mut widget::Ctx,
+    ) -> bool {
+        false
+    }
 }
 pub struct Button {
     pub width: u32,
     pub height: u32,
     pub label: String,
+    // 按回车或鼠标点击时调用，闭包可以捕获外部的状态(见 closure)
+    pub on_click: Option<widget::ClickHandler>,
 }
 
 impl Draw for Button {
@@ -1629,12 +1722,43 @@ impl Draw for Button {
         // 绘制按钮的代码
         println!("{:?}", self.label);
     }
+
+    fn size(&self) -> (u16, u16) {
+        widget::clamp_size(self.width, self.height)
+    }
+
+    fn render(&self, buf: &mut widget::Buffer, area: widget::Rect, focused: bool) {
+        widget::render_button(buf, area, &self.label, focused);
+    }
+
+    fn focusable(&self) -> bool {
+        true
+    }
+
+    fn handle_event(
+        &mut self,
+        event: &widget::Event,
+        area: widget::Rect,
+        ctx: &mut widget::Ctx,
+    ) -> bool {
+        if !widget::is_activation(event, area) {
+            return false;
+        }
+        if let Some(on_click) = self.on_click.as_mut() {
+            on_click(ctx);
+        }
+        true
+    }
 }
 
 struct SelectBox {
     width: u32,
     height: u32,
     options: Vec<String>,
+    // 当前选中的选项下标
+    selected: usize,
+    // 选中项改变时调用，参数为新的下标和选项
+    on_change: Option<widget::ChangeHandler>,
 }
 
 impl Draw for SelectBox {
@@ -1642,6 +1766,36 @@ impl Draw for SelectBox {
         // 绘制SelectBox的代码
         println!("{:?}", self.options);
     }
+
+    fn size(&self) -> (u16, u16) {
+        widget::clamp_size(self.width, self.height)
+    }
+
+    fn render(&self, buf: &mut widget::Buffer, area: widget::Rect, focused: bool) {
+        widget::render_select(buf, area, &self.options, self.selected, focused);
+    }
+
+    fn focusable(&self) -> bool {
+        !self.options.is_empty()
+    }
+
+    fn handle_event(
+        &mut self,
+        event: &widget::Event,
+        area: widget::Rect,
+        ctx: &mut widget::Ctx,
+    ) -> bool {
+        let Some(i) = widget::select_event(self.selected, self.options.len(), event, area) else {
+            return false;
+        };
+        if i != self.selected {
+            self.selected = i;
+            if let Some(on_change) = self.on_change.as_mut() {
+                on_change(ctx, i, &self.options[i]);
+            }
+        }
+        true
+    }
 }
 
 // 若 T 实现了 Draw 特征， 则调用该函数时传入的 Box<T> 可以被隐式转换成函数参数签名中的 Box<dyn Draw>
@@ -1655,8 +1809,15 @@ fn draw2(x: &dyn Draw) {
 }
 
 // 这种写法有点是components可以存储实现Draw的所有类型
+#[derive(Default)]
 pub struct Screen {
     pub components: Vec<Box<dyn Draw>>,
+    // 组件按行(从上到下)还是按列(从左到右)排列
+    pub layout: widget::Layout,
+    // 拥有键盘焦点的组件下标
+    pub focus: Option<usize>,
+    // 组件没有处理的事件冒泡到这里，按注册顺序调用，返回 true 时停止传递
+    pub handlers: Vec<widget::EventHandler>,
 }
 
 impl Screen {
@@ -1667,6 +1828,7 @@ impl Screen {
     }
 }
 // 如果只存储Draw的其中一个类型，可以使用泛型搭配特征约束，代码更清晰，性能更好
+// 实际的性能差异见 dispatch_bench 模块: cargo run --release -- dispatch
 pub struct Screen_1<T: Draw> {
     pub components: Vec<T>,
 }
@@ -1743,18 +1905,19 @@ impl Human {
 }
 
 // 特征定义中的特征约束
+// 宽度按显示列宽计算，中文不会错位，更多边框样式和表格见 frame 模块
 trait OutlinePrint: Display {
     fn outline_print(&self) {
-        let output = self.to_string();
-        let len = output.len();
-        println!("{}", "*".repeat(len + 4));
-        println!("*{}*", " ".repeat(len + 2));
-        println!("* {} *", output);
-        println!("*{}*", " ".repeat(len + 2));
-        println!("{}", "*".repeat(len + 4));
+        let frame = frame::Frame::new(&frame::ASTERISK).padding(1, 1);
+        println!("{}", frame.render(&self.to_string()));
     }
 }
 
+impl OutlinePrint for Wrapper {}
+impl OutlinePrint for MinMax {}
+impl OutlinePrint for List {}
+impl OutlinePrint for City<'_> {}
+
 // 在外部类型上实现外部特征（newtype）---- 打破孤儿规则
 // 为Vec实现Display
 struct Wrapper(Vec<String>);
@@ -1773,11 +1936,14 @@ fn t_obj() {
         width: 1,
         height: 2,
         label: String::from("I'am Button."),
+        on_click: None,
     };
     let selectbox = SelectBox {
         width: 1,
         height: 2,
         options: vec![String::from("I'am SelectBox."); 3],
+        selected: 0,
+        on_change: None,
     };
 
     // x 和 y 的类型 T 都实现了 `Draw` 特征，因为 Box<T> 可以在函数调用时隐式地被转换为特征对象 Box<dyn Draw>
@@ -1798,13 +1964,17 @@ fn t_obj() {
                     String::from("Maybe"),
                     String::from("No"),
                 ],
+                selected: 0,
+                on_change: None,
             }),
             Box::new(Button {
                 width: 50,
                 height: 10,
                 label: String::from("OK"),
+                on_click: None,
             }),
         ],
+        ..Default::default()
     };
 
     screen.run();
@@ -1821,6 +1991,8 @@ fn t_obj() {
     // 打破孤儿规则 newtype
     let w = Wrapper(vec![String::from("hello"), String::from("world")]);
     print!("w = {}", w);
+    println!();
+    w.outline_print();
 }
 
 /*
@@ -2034,6 +2206,7 @@ fn advanced_lifetime() {
 }
 
 // 结构体闭包
+// 这个 Cacher 只缓存第一次的结果，按参数缓存并支持淘汰策略的版本见 cache 模块
 // impl<T> Cacher<T>
 // where
 //     T: Fn(u32) -> u32,
@@ -2130,6 +2303,11 @@ fn closure() {
     exec(update_string);
     // 如果拿到的是s的所有权或可变引用，都是不能Copy的
     // exec(update_string);
+
+    // 结构体中保存闭包，按参数缓存结果
+    let mut cacher = cache::Cacher::new(|x: u32| x + 1);
+    assert_eq!(cacher.value(1), 2);
+    assert_eq!(cacher.value(2), 3);
 }
 
 // 实现Iterator特征
@@ -2211,6 +2389,7 @@ fn foo_3() -> i32 {
 }
 
 // 生命周期
+// 这两个 transmute 都可能产生悬垂引用，不用 unsafe 的替代写法见 raw_ptr.rs
 // 将 'b 生命周期延长至 'static 生命周期
 unsafe fn extend_lifetime<'b>(r: R1<'b>) -> R1<'static> {
     std::mem::transmute::<R1<'b>, R1<'static>>(r)
@@ -2246,6 +2425,8 @@ enum MyEnum {
     C,
 }
 
+back_to_enum!(impl Enum for MyEnum { A, B, C });
+
 //  Tryfrom
 use std::convert::TryFrom;
 
@@ -2255,6 +2436,8 @@ enum MyEnum1 {
     C,
 }
 
+back_to_enum!(impl Enum for MyEnum1 { A, B, C });
+
 impl TryFrom<i32> for MyEnum1 {
     type Error = ();
 
@@ -2268,30 +2451,7 @@ impl TryFrom<i32> for MyEnum1 {
     }
 }
 
-// 使用TryFrom+宏
-#[macro_export]
-macro_rules! back_to_enum {
-    ($(#[$meta:meta])* $vis:vis enum $name:ident {
-        $($(#[$vmeta:meta])* $vname:ident $(= $val:expr)?,)*
-    }) => {
-        $(#[$meta])*
-        $vis enum $name {
-            $($(#[$vmeta])* $vname $(= $val)?,)*
-        }
-
-        impl std::convert::TryFrom<i32> for $name {
-            type Error = ();
-
-            fn try_from(v: i32) -> Result<Self, Self::Error> {
-                match v {
-                    $(x if x == $name::$vname as i32 => Ok($name::$vname),)*
-                    _ => Err(()),
-                }
-            }
-        }
-    }
-}
-
+// 使用TryFrom+宏，back_to_enum! 和 Enum 特征在库 crate 的 enums 模块中
 back_to_enum! {
     enum MyEnum2 {
         A = 1,
@@ -2310,6 +2470,7 @@ fn type_parctice() {
     }
 
     // 内存地址转指针
+    // 整数转回指针会丢失指针来源，安全的写法见 raw_ptr.rs 中的 Offset / RawSlice
     let mut values: [i32; 2] = [1, 2];
     let p1: *mut i32 = values.as_mut_ptr();
     let first_address = p1 as usize; // 将p1内存地址转为一个整数
@@ -2335,6 +2496,7 @@ fn type_parctice() {
 
     // 裸指针转函数在指针
 
+    // 函数项可以直接转换成 fn() -> i32，从地址换回函数指针的查表写法见 raw_ptr.rs 中的 FnRegistry
     let pointer = foo_3 as *const ();
     let function = unsafe {
         // 将裸指针转换为函数指针
@@ -2380,6 +2542,17 @@ fn type_parctice() {
         Ok(MyEnum2::C) => println!("c"),
         Err(_) => eprintln!("unknow number"),
     }
+
+    // 错误中带有被拒绝的值，也可以按名称解析和打印
+    if let Err(e) = MyEnum2::try_from(7u8) {
+        println!("{}", e);
+    }
+    for v in MyEnum2::iter() {
+        println!("{} = {}", v.name(), v as i32);
+    }
+    if let Ok(v) = "B".parse::<MyEnum2>() {
+        println!("parsed {} of {}", v, MyEnum2::COUNT);
+    }
 }
 
 trait Draw1 {
@@ -2431,6 +2604,7 @@ fn auto_ptr() {
     println!("{:?}", arr1.len());
 
     // 在堆上创建一个长度为1000的数组，然后使用一个智能指针指向它
+    // 这里在堆上分配一次 4000 字节，`cargo run --features track-allocs -- allocs auto_ptr` 可以看到整章的分配统计，见 alloc_stats.rs
     let arr = Box::new([0; 1000]);
     // 将堆上数组的所有权转移给 arr1，由于数据在堆上，因此仅仅拷贝了智能指针的结构体，底层数据并没有被拷贝
     // 所有权顺利转移给 arr1，arr 不再拥有所有权
@@ -2449,6 +2623,7 @@ fn auto_ptr() {
     //     Cons(i32, Box<List>),
     //     Nil,
     // }
+    // Box 只能有一个所有者，多个链表要共享同一段尾部时换成 Rc，见 persistent.rs 中的 ConsList
 
     // 特征对象
     let elems: Vec<Box<dyn Draw1>> = vec![Box::new(Button1 { id: 1 }), Box::new(Select { id: 2 })];
@@ -2472,6 +2647,7 @@ fn auto_ptr() {
         println!("count after creating c = {}", Rc::strong_count(&c));
     }
     println!("count after c goes out of scope = {}", Rc::strong_count(&a));
+    // 用 Rc、RefCell 和 Weak 构建的双向链表、树和图见 rc_structs 模块
 
     // 多线程不能使用Rc，需要使用Arc(atomic Rc)，实现了原子化，实现了Send特征
     let s = Arc::new(String::from("多线程漫游者"));
@@ -2537,6 +2713,7 @@ struct Counter1 {
 }
 
 // 裸指针实现Send
+// 无条件实现 Send/Sync 绕过了编译器的检查，带约束的 SendPtr / SharedPtr 见 raw_ptr.rs
 #[derive(Debug)]
 struct MyBox(*mut u8);
 unsafe impl Send for MyBox {}
@@ -3047,6 +3224,46 @@ fn async_parctice() {
     block_on(asyc_main());
 }
 
+// 各章节的入口，allocs 命令逐个运行并统计堆分配
+const CHAPTERS: &[alloc_stats::Chapter] = &[
+    ("hellworld", hellworld),
+    ("var_shadowing", var_shadowing),
+    ("int_overflow", int_overflow),
+    ("float_trap", float_trap),
+    ("range_chapter", range_chapter),
+    ("complex_num", complex_num),
+    ("data_type", data_type),
+    ("ownership", ownership),
+    ("string_unicode", string_unicode),
+    ("struct_practice", struct_practice),
+    ("enum_practice", enum_practice),
+    ("arrary_practice", arrary_practice),
+    ("control", control),
+    ("match_practice", || {
+        match_practice();
+    }),
+    ("deconstruct_option", deconstruct_option),
+    ("scenes", scenes),
+    ("queue_practice", queue_practice),
+    ("circular_queue_practice", circular_queue_practice),
+    ("generics", generics),
+    ("vector", vector),
+    ("hash_map", hash_map),
+    ("t", t),
+    ("t_obj", t_obj),
+    ("lifetime", lifetime),
+    ("error", || {
+        let _ = error();
+    }),
+    ("advanced_lifetime", advanced_lifetime),
+    ("closure", closure),
+    ("Iterator_parctice", Iterator_parctice),
+    ("type_parctice", type_parctice),
+    ("auto_ptr", auto_ptr),
+    ("multiple_thread", multiple_thread),
+    ("async_parctice", async_parctice),
+];
+
 fn advanced_parctice() {
     // advanced
     // advanced_lifetime();
@@ -3058,10 +3275,58 @@ fn advanced_parctice() {
     async_parctice();
 }
 
+// 子命令：cargo run -- <命令> [参数...]
+fn run_command(cmd: &str, args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    match cmd {
+        "numeric" => numeric::numeric_practice(),
+        "cache-practice" => cache::cache_practice(),
+        "calc" => calc::repl(args)?,
+        "calc-practice" => calc::calc_practice(),
+        "const" => const_fn::const_fn_practice(args),
+        "layout" => layout::layout_report(args)?,
+        "layout-practice" => layout::layout_practice(),
+        "utf8" => utf8::utf8_practice(),
+        "wordfreq" => text_stats::wordfreq(args)?,
+        "wordfreq-practice" => text_stats::text_stats_practice(),
+        "hashbench" => hasher_bench::hash_bench(args)?,
+        "hashbench-practice" => hasher_bench::hasher_bench_practice(),
+        "fastmap" => fast_map::fast_map_bench(args)?,
+        "fastmap-practice" => fast_map::fast_map_practice(),
+        "feed" => feed::feed_cli(args)?,
+        "feed-practice" => feed::feed_practice(),
+        "ui" => widget::ui(args)?,
+        "ui-practice" => widget::widget_practice(),
+        "dispatch" => dispatch_bench::dispatch_bench(args)?,
+        "dispatch-practice" => dispatch_bench::dispatch_practice(),
+        "enums-practice" => enum_practice::enums_practice(),
+        "frame" => frame::frame_cli(args)?,
+        "frame-practice" => frame::frame_practice(),
+        "records" => records::records_cli(args)?,
+        "records-practice" => records::records_practice(),
+        "serial-practice" => serial::serial_practice(),
+        "ptr-practice" => raw_ptr::ptr_practice(),
+        "rc-practice" => rc_structs::rc_structs_practice(),
+        "algorithms-practice" => algorithms::algorithms_practice(),
+        "sort-practice" => sorting::sorting_practice(),
+        "allocs" => alloc_stats::chapter_report(CHAPTERS, args)?,
+        "alloc-practice" => alloc_stats::alloc_practice(),
+        "persistent" => persistent::persistent_bench(args)?,
+        "persistent-practice" => persistent::persistent_practice(),
+        "pq-practice" => pq::pq_practice(),
+        _ => return Err(format!("unknown command: {}", cmd).into()),
+    }
+    Ok(())
+}
+
 // 信号量 Semaphore
 use tokio::sync::Semaphore;
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(cmd) = args.first() {
+        return run_command(cmd, &args[1..]);
+    }
+
     // base_parctice();
     advanced_parctice();
 
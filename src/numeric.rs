@@ -0,0 +1,628 @@
+//! 数值计算模式
+//! 同一个算法可以分别在 精确 / 检查 / 回绕 / 饱和 / 浮点 五种模式下运行
+
+use std::fmt;
+use std::num::{Saturating, Wrapping};
+
+use num::bigint::BigInt;
+use num::rational::BigRational;
+use num::{One, ToPrimitive, Zero};
+
+/*
+ * int_overflow 中介绍的四类溢出处理方法，在这里统一成 Number 特征的不同实现：
+ * Checked<T>     对应 checked_*，溢出时返回 NumError::Overflow
+ * Wrapping<T>    对应 wrapping_*，按补码循环溢出
+ * Saturating<T>  对应 saturating_*，结果被限制在类型的最大/最小值
+ * f64            浮点模式，存在 float_trap 中的精度问题
+ * BigInt / BigRational / Decimal  精确模式，不会溢出
+ */
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NumError {
+    Overflow,
+    DivideByZero,
+    // 该模式无法精确表示输入，比如整数模式解析 "0.1"
+    Inexact,
+    Parse,
+}
+
+impl fmt::Display for NumError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NumError::Overflow => write!(f, "arithmetic overflow"),
+            NumError::DivideByZero => write!(f, "divide by zero"),
+            NumError::Inexact => write!(f, "value is not representable exactly"),
+            NumError::Parse => write!(f, "invalid number literal"),
+        }
+    }
+}
+
+impl std::error::Error for NumError {}
+
+pub trait Number: Clone + PartialEq + fmt::Debug + fmt::Display {
+    // 模式名称，用于输出报告
+    const MODE: &'static str;
+
+    fn zero() -> Self;
+    fn one() -> Self;
+    fn from_i64(v: i64) -> Result<Self, NumError>;
+    // 解析十进制字面量，例如 "0.1"、"-42"
+    fn parse(s: &str) -> Result<Self, NumError>;
+
+    fn add(&self, rhs: &Self) -> Result<Self, NumError>;
+    fn sub(&self, rhs: &Self) -> Result<Self, NumError>;
+    fn mul(&self, rhs: &Self) -> Result<Self, NumError>;
+    fn div(&self, rhs: &Self) -> Result<Self, NumError>;
+
+    fn to_f64(&self) -> f64;
+}
+
+// 把 "12.345" 拆成整数部分、小数部分和符号
+fn split_decimal(s: &str) -> Result<(bool, &str, &str), NumError> {
+    let s = s.trim();
+    let (neg, digits) = match s.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, s.strip_prefix('+').unwrap_or(s)),
+    };
+    let (int_part, frac_part) = digits.split_once('.').unwrap_or((digits, ""));
+    if int_part.is_empty() && frac_part.is_empty() {
+        return Err(NumError::Parse);
+    }
+    if !int_part
+        .bytes()
+        .chain(frac_part.bytes())
+        .all(|b| b.is_ascii_digit())
+    {
+        return Err(NumError::Parse);
+    }
+    Ok((neg, int_part, frac_part))
+}
+
+// 十进制字面量转为 (分子, 10 的幂次)
+fn decimal_to_big(s: &str) -> Result<(BigInt, u32), NumError> {
+    let (neg, int_part, frac_part) = split_decimal(s)?;
+    let all = format!("{}{}", int_part, frac_part);
+    let mut n: BigInt = all.parse().map_err(|_| NumError::Parse)?;
+    if neg {
+        n = -n;
+    }
+    Ok((n, frac_part.len() as u32))
+}
+
+// 整数模式只接受没有非零小数的字面量
+fn parse_integer(s: &str) -> Result<i128, NumError> {
+    let (neg, int_part, frac_part) = split_decimal(s)?;
+    if frac_part.bytes().any(|b| b != b'0') {
+        return Err(NumError::Inexact);
+    }
+    if int_part.is_empty() {
+        return Ok(0);
+    }
+    let v: i128 = int_part.parse().map_err(|_| NumError::Overflow)?;
+    Ok(if neg { -v } else { v })
+}
+
+/* ---------------------------- 检查模式 ---------------------------- */
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Checked<T>(pub T);
+
+impl<T: fmt::Display> fmt::Display for Checked<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+// 为所有原生整数生成三种溢出模式的实现
+macro_rules! int_modes {
+    ($($t:ty),*) => {$(
+        impl Number for Checked<$t> {
+            const MODE: &'static str = concat!("checked ", stringify!($t));
+
+            fn zero() -> Self { Checked(0) }
+            fn one() -> Self { Checked(1) }
+            fn from_i64(v: i64) -> Result<Self, NumError> {
+                <$t>::try_from(v).map(Checked).map_err(|_| NumError::Overflow)
+            }
+            fn parse(s: &str) -> Result<Self, NumError> {
+                <$t>::try_from(parse_integer(s)?).map(Checked).map_err(|_| NumError::Overflow)
+            }
+            fn add(&self, rhs: &Self) -> Result<Self, NumError> {
+                self.0.checked_add(rhs.0).map(Checked).ok_or(NumError::Overflow)
+            }
+            fn sub(&self, rhs: &Self) -> Result<Self, NumError> {
+                self.0.checked_sub(rhs.0).map(Checked).ok_or(NumError::Overflow)
+            }
+            fn mul(&self, rhs: &Self) -> Result<Self, NumError> {
+                self.0.checked_mul(rhs.0).map(Checked).ok_or(NumError::Overflow)
+            }
+            fn div(&self, rhs: &Self) -> Result<Self, NumError> {
+                if rhs.0 == 0 {
+                    return Err(NumError::DivideByZero);
+                }
+                self.0.checked_div(rhs.0).map(Checked).ok_or(NumError::Overflow)
+            }
+            fn to_f64(&self) -> f64 { self.0 as f64 }
+        }
+
+        impl Number for Wrapping<$t> {
+            const MODE: &'static str = concat!("wrapping ", stringify!($t));
+
+            fn zero() -> Self { Wrapping(0) }
+            fn one() -> Self { Wrapping(1) }
+            fn from_i64(v: i64) -> Result<Self, NumError> {
+                // 和 as 转换一样截断高位
+                Ok(Wrapping(v as $t))
+            }
+            fn parse(s: &str) -> Result<Self, NumError> {
+                Ok(Wrapping(parse_integer(s)? as $t))
+            }
+            fn add(&self, rhs: &Self) -> Result<Self, NumError> { Ok(*self + *rhs) }
+            fn sub(&self, rhs: &Self) -> Result<Self, NumError> { Ok(*self - *rhs) }
+            fn mul(&self, rhs: &Self) -> Result<Self, NumError> { Ok(*self * *rhs) }
+            fn div(&self, rhs: &Self) -> Result<Self, NumError> {
+                if rhs.0 == 0 {
+                    return Err(NumError::DivideByZero);
+                }
+                Ok(Wrapping(self.0.wrapping_div(rhs.0)))
+            }
+            fn to_f64(&self) -> f64 { self.0 as f64 }
+        }
+
+        impl Number for Saturating<$t> {
+            const MODE: &'static str = concat!("saturating ", stringify!($t));
+
+            fn zero() -> Self { Saturating(0) }
+            fn one() -> Self { Saturating(1) }
+            fn from_i64(v: i64) -> Result<Self, NumError> {
+                let v = (v as i128).clamp(<$t>::MIN as i128, <$t>::MAX as i128);
+                Ok(Saturating(v as $t))
+            }
+            fn parse(s: &str) -> Result<Self, NumError> {
+                let v = parse_integer(s)?.clamp(<$t>::MIN as i128, <$t>::MAX as i128);
+                Ok(Saturating(v as $t))
+            }
+            fn add(&self, rhs: &Self) -> Result<Self, NumError> { Ok(*self + *rhs) }
+            fn sub(&self, rhs: &Self) -> Result<Self, NumError> { Ok(*self - *rhs) }
+            fn mul(&self, rhs: &Self) -> Result<Self, NumError> { Ok(*self * *rhs) }
+            fn div(&self, rhs: &Self) -> Result<Self, NumError> {
+                if rhs.0 == 0 {
+                    return Err(NumError::DivideByZero);
+                }
+                Ok(Saturating(self.0.saturating_div(rhs.0)))
+            }
+            fn to_f64(&self) -> f64 { self.0 as f64 }
+        }
+    )*};
+}
+
+int_modes!(i8, i16, i32, i64, u8, u16, u32, u64);
+
+/* ---------------------------- 浮点模式 ---------------------------- */
+
+macro_rules! float_mode {
+    ($($t:ty),*) => {$(
+        impl Number for $t {
+            const MODE: &'static str = stringify!($t);
+
+            fn zero() -> Self { 0.0 }
+            fn one() -> Self { 1.0 }
+            fn from_i64(v: i64) -> Result<Self, NumError> { Ok(v as $t) }
+            fn parse(s: &str) -> Result<Self, NumError> {
+                s.trim().parse().map_err(|_| NumError::Parse)
+            }
+            // 浮点数不报告溢出，溢出得到 inf，0 作除数得到 inf 或 NaN
+            fn add(&self, rhs: &Self) -> Result<Self, NumError> { Ok(self + rhs) }
+            fn sub(&self, rhs: &Self) -> Result<Self, NumError> { Ok(self - rhs) }
+            fn mul(&self, rhs: &Self) -> Result<Self, NumError> { Ok(self * rhs) }
+            fn div(&self, rhs: &Self) -> Result<Self, NumError> { Ok(self / rhs) }
+            fn to_f64(&self) -> f64 { *self as f64 }
+        }
+    )*};
+}
+
+float_mode!(f32, f64);
+
+/* ---------------------------- 精确模式 ---------------------------- */
+
+impl Number for BigInt {
+    const MODE: &'static str = "BigInt";
+
+    fn zero() -> Self {
+        Zero::zero()
+    }
+    fn one() -> Self {
+        One::one()
+    }
+    fn from_i64(v: i64) -> Result<Self, NumError> {
+        Ok(BigInt::from(v))
+    }
+    fn parse(s: &str) -> Result<Self, NumError> {
+        let (n, scale) = decimal_to_big(s)?;
+        let p = BigInt::from(10).pow(scale);
+        if (&n % &p).is_zero() {
+            Ok(n / p)
+        } else {
+            Err(NumError::Inexact)
+        }
+    }
+    fn add(&self, rhs: &Self) -> Result<Self, NumError> {
+        Ok(self + rhs)
+    }
+    fn sub(&self, rhs: &Self) -> Result<Self, NumError> {
+        Ok(self - rhs)
+    }
+    fn mul(&self, rhs: &Self) -> Result<Self, NumError> {
+        Ok(self * rhs)
+    }
+    // 整数除法向零取整，和原生整数一致
+    fn div(&self, rhs: &Self) -> Result<Self, NumError> {
+        if rhs.is_zero() {
+            return Err(NumError::DivideByZero);
+        }
+        Ok(self / rhs)
+    }
+    fn to_f64(&self) -> f64 {
+        ToPrimitive::to_f64(self).unwrap_or(f64::NAN)
+    }
+}
+
+impl Number for BigRational {
+    const MODE: &'static str = "BigRational";
+
+    fn zero() -> Self {
+        Zero::zero()
+    }
+    fn one() -> Self {
+        One::one()
+    }
+    fn from_i64(v: i64) -> Result<Self, NumError> {
+        Ok(BigRational::from_integer(BigInt::from(v)))
+    }
+    // "0.1" 被精确地解析为 1/10，而不是离 0.1 最近的二进制浮点数
+    fn parse(s: &str) -> Result<Self, NumError> {
+        let (n, scale) = decimal_to_big(s)?;
+        Ok(BigRational::new(n, BigInt::from(10).pow(scale)))
+    }
+    fn add(&self, rhs: &Self) -> Result<Self, NumError> {
+        Ok(self + rhs)
+    }
+    fn sub(&self, rhs: &Self) -> Result<Self, NumError> {
+        Ok(self - rhs)
+    }
+    fn mul(&self, rhs: &Self) -> Result<Self, NumError> {
+        Ok(self * rhs)
+    }
+    fn div(&self, rhs: &Self) -> Result<Self, NumError> {
+        if rhs.is_zero() {
+            return Err(NumError::DivideByZero);
+        }
+        Ok(self / rhs)
+    }
+    fn to_f64(&self) -> f64 {
+        ToPrimitive::to_f64(self).unwrap_or(f64::NAN)
+    }
+}
+
+/*
+ * 十进制定点数
+ * 内部用 i128 保存 value * 10^SCALE，例如 Decimal<2> 中 12.34 保存为 1234
+ * 加减法是精确的，乘除法在第 SCALE 位之后四舍五入(远离零)
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Decimal<const SCALE: u32>(i128);
+
+impl<const SCALE: u32> Decimal<SCALE> {
+    const FACTOR: i128 = 10i128.pow(SCALE);
+
+    // 四舍五入的整数除法，远离零
+    fn div_round(n: i128, d: i128) -> Result<i128, NumError> {
+        let q = n.checked_div(d).ok_or(NumError::Overflow)?;
+        let r = n % d;
+        if r.unsigned_abs() * 2 >= d.unsigned_abs() {
+            let step = if (n < 0) == (d < 0) { 1 } else { -1 };
+            q.checked_add(step).ok_or(NumError::Overflow)
+        } else {
+            Ok(q)
+        }
+    }
+}
+
+impl<const SCALE: u32> fmt::Display for Decimal<SCALE> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.0 < 0 { "-" } else { "" };
+        let abs = self.0.unsigned_abs();
+        let factor = Self::FACTOR as u128;
+        if SCALE == 0 {
+            write!(f, "{}{}", sign, abs)
+        } else {
+            write!(
+                f,
+                "{}{}.{:0width$}",
+                sign,
+                abs / factor,
+                abs % factor,
+                width = SCALE as usize
+            )
+        }
+    }
+}
+
+impl<const SCALE: u32> Number for Decimal<SCALE> {
+    const MODE: &'static str = "Decimal";
+
+    fn zero() -> Self {
+        Decimal(0)
+    }
+    fn one() -> Self {
+        Decimal(Self::FACTOR)
+    }
+    fn from_i64(v: i64) -> Result<Self, NumError> {
+        (v as i128)
+            .checked_mul(Self::FACTOR)
+            .map(Decimal)
+            .ok_or(NumError::Overflow)
+    }
+    fn parse(s: &str) -> Result<Self, NumError> {
+        let (n, scale) = decimal_to_big(s)?;
+        // 小数位比 SCALE 多时只有多出来的都是 0 才算精确
+        let n = if scale > SCALE {
+            let p = BigInt::from(10).pow(scale - SCALE);
+            if !(&n % &p).is_zero() {
+                return Err(NumError::Inexact);
+            }
+            n / p
+        } else {
+            n * BigInt::from(10).pow(SCALE - scale)
+        };
+        n.to_i128().map(Decimal).ok_or(NumError::Overflow)
+    }
+    fn add(&self, rhs: &Self) -> Result<Self, NumError> {
+        self.0
+            .checked_add(rhs.0)
+            .map(Decimal)
+            .ok_or(NumError::Overflow)
+    }
+    fn sub(&self, rhs: &Self) -> Result<Self, NumError> {
+        self.0
+            .checked_sub(rhs.0)
+            .map(Decimal)
+            .ok_or(NumError::Overflow)
+    }
+    fn mul(&self, rhs: &Self) -> Result<Self, NumError> {
+        let p = self.0.checked_mul(rhs.0).ok_or(NumError::Overflow)?;
+        Self::div_round(p, Self::FACTOR).map(Decimal)
+    }
+    fn div(&self, rhs: &Self) -> Result<Self, NumError> {
+        if rhs.0 == 0 {
+            return Err(NumError::DivideByZero);
+        }
+        let n = self.0.checked_mul(Self::FACTOR).ok_or(NumError::Overflow)?;
+        Self::div_round(n, rhs.0).map(Decimal)
+    }
+    fn to_f64(&self) -> f64 {
+        self.0 as f64 / Self::FACTOR as f64
+    }
+}
+
+/* ---------------------------- 通用算法 ---------------------------- */
+
+// 求和
+pub fn sum<T: Number>(items: &[T]) -> Result<T, NumError> {
+    items.iter().try_fold(T::zero(), |acc, x| acc.add(x))
+}
+
+// 解析一组字面量再求和
+pub fn sum_literals<T: Number>(literals: &[&str]) -> Result<T, NumError> {
+    let items = literals
+        .iter()
+        .map(|s| T::parse(s))
+        .collect::<Result<Vec<T>, NumError>>()?;
+    sum(&items)
+}
+
+// 阶乘
+pub fn factorial<T: Number>(n: u32) -> Result<T, NumError> {
+    (1..=n as i64).try_fold(T::one(), |acc, i| acc.mul(&T::from_i64(i)?))
+}
+
+// 秦九韶算法(Horner)求多项式的值，coeffs 从最高次项开始
+pub fn horner<T: Number>(coeffs: &[T], x: &T) -> Result<T, NumError> {
+    coeffs
+        .iter()
+        .try_fold(T::zero(), |acc, c| acc.mul(x)?.add(c))
+}
+
+// 调和级数 1 + 1/2 + ... + 1/n
+pub fn harmonic<T: Number>(n: u32) -> Result<T, NumError> {
+    (1..=n as i64).try_fold(T::zero(), |acc, i| {
+        acc.add(&T::one().div(&T::from_i64(i)?)?)
+    })
+}
+
+// 把浮点数按二进制的真实值精确地转换为有理数，用来观察 0.1 在内存中到底是多少
+// NaN 和 ±inf 没有对应的有理数，返回 None
+pub fn exact_f64(x: f64) -> Option<BigRational> {
+    BigRational::from_float(x)
+}
+
+pub fn exact_f32(x: f32) -> Option<BigRational> {
+    BigRational::from_float(x)
+}
+
+// 在五种模式下各算一遍，打印对比报告
+pub fn report<T: Number>(label: &str, result: Result<T, NumError>) {
+    match result {
+        Ok(v) => println!("{:<16} {:<18} = {}", T::MODE, label, v),
+        Err(e) => println!("{:<16} {:<18} ! {}", T::MODE, label, e),
+    }
+}
+
+pub fn numeric_practice() {
+    // float_trap：0.1 + 0.2 != 0.3 (f64)
+    let f: f64 = sum_literals(&["0.1", "0.2"]).unwrap();
+    assert_ne!(f, f64::parse("0.3").unwrap());
+
+    // 有理数和定点数模式下 0.1 + 0.2 == 0.3
+    let r: BigRational = sum_literals(&["0.1", "0.2"]).unwrap();
+    assert_eq!(r, BigRational::parse("0.3").unwrap());
+    let d: Decimal<2> = sum_literals(&["0.1", "0.2"]).unwrap();
+    assert_eq!(d, Decimal::<2>::parse("0.3").unwrap());
+    assert_eq!(d.to_string(), "0.30");
+
+    // 0.3 - 0.2 - 0.1 在浮点模式下不等于 0
+    let back = f64::parse("0.3")
+        .unwrap()
+        .sub(&0.2)
+        .unwrap()
+        .sub(&0.1)
+        .unwrap();
+    assert_ne!(back, 0.0);
+    let back = r.sub(&BigRational::parse("0.2").unwrap()).unwrap();
+    assert_eq!(back, BigRational::parse("0.1").unwrap());
+
+    // 用有理数精确复现浮点陷阱，完整的检查见下方的测试
+    let tenth = exact_f64(0.1).unwrap();
+    println!("0.1_f64 = {}", tenth);
+    println!("0.1_f64 + 0.2_f64 = {}", tenth + exact_f64(0.2).unwrap());
+    println!("0.1_f32 = {}", exact_f32(0.1).unwrap());
+    assert_eq!(exact_f64(f64::NAN), None);
+
+    // int_overflow：255u8 + 20
+    let a = Checked(255u8);
+    assert_eq!(a.add(&Checked(20)), Err(NumError::Overflow));
+    assert_eq!(Wrapping(255u8).add(&Wrapping(20)), Ok(Wrapping(19)));
+    assert_eq!(Saturating(255u8).add(&Saturating(20)), Ok(Saturating(255)));
+
+    // 同一个阶乘算法在不同模式下的结果
+    assert_eq!(
+        factorial::<Checked<i64>>(20),
+        Ok(Checked(2432902008176640000))
+    );
+    assert_eq!(factorial::<Checked<i64>>(21), Err(NumError::Overflow));
+    assert_eq!(
+        factorial::<Wrapping<i64>>(21),
+        Ok(Wrapping(2432902008176640000i64.wrapping_mul(21)))
+    );
+    assert_eq!(factorial::<Saturating<i64>>(21), Ok(Saturating(i64::MAX)));
+    assert_eq!(
+        factorial::<BigInt>(25).unwrap().to_string(),
+        "15511210043330985984000000"
+    );
+
+    // 调和级数：有理数精确，浮点数有误差
+    let h: BigRational = harmonic(10).unwrap();
+    assert_eq!(h, BigRational::new(BigInt::from(7381), BigInt::from(2520)));
+    let hf: f64 = harmonic(10).unwrap();
+    assert!((Number::to_f64(&h) - hf).abs() < 1e-12);
+    assert_ne!(Number::to_f64(&h), 0.0);
+    // 整数模式下 1/i 截断为 0
+    assert_eq!(harmonic::<Checked<i32>>(10), Ok(Checked(1)));
+
+    // 2x^2 - 3x + 1 在 x = 0.5 时为 0
+    let coeffs: Vec<BigRational> = ["2", "-3", "1"]
+        .iter()
+        .map(|s| BigRational::parse(s).unwrap())
+        .collect();
+    assert!(horner(&coeffs, &BigRational::parse("0.5").unwrap())
+        .unwrap()
+        .is_zero());
+
+    // 定点数乘除法四舍五入
+    let x = Decimal::<2>::parse("1.25").unwrap();
+    let y = Decimal::<2>::parse("0.5").unwrap();
+    assert_eq!(x.mul(&y).unwrap().to_string(), "0.63");
+    assert_eq!(
+        Decimal::<2>::one()
+            .div(&Decimal::from_i64(3).unwrap())
+            .unwrap()
+            .to_string(),
+        "0.33"
+    );
+    assert_eq!(Decimal::<2>::parse("0.125"), Err(NumError::Inexact));
+    assert_eq!(Checked::<i32>::parse("0.5"), Err(NumError::Inexact));
+    assert_eq!(Decimal::<2>::parse("-1.05").unwrap().to_string(), "-1.05");
+
+    report::<f64>("0.1 + 0.2", sum_literals(&["0.1", "0.2"]));
+    report::<BigRational>("0.1 + 0.2", sum_literals(&["0.1", "0.2"]));
+    report::<Decimal<4>>("0.1 + 0.2", sum_literals(&["0.1", "0.2"]));
+    report::<Checked<i64>>("21!", factorial(21));
+    report::<Wrapping<i64>>("21!", factorial(21));
+    report::<Saturating<i64>>("21!", factorial(21));
+    report::<BigInt>("21!", factorial(21));
+    report::<f64>("21!", factorial(21));
+    report::<BigRational>("H(10)", harmonic(10));
+    report::<f64>("H(10)", harmonic(10));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn exact(x: f64) -> BigRational {
+        exact_f64(x).unwrap()
+    }
+
+    #[test]
+    fn tenth_is_not_one_tenth() {
+        // 0.1_f64 实际上是 3602879701896397/36028797018963968
+        let tenth = exact(0.1);
+        assert_eq!(
+            tenth,
+            BigRational::new(
+                BigInt::from(3602879701896397u64),
+                BigInt::from(36028797018963968u64)
+            )
+        );
+        assert_ne!(tenth, BigRational::parse("0.1").unwrap());
+    }
+
+    #[test]
+    fn point_one_plus_point_two() {
+        assert_ne!(0.1f64 + 0.2, 0.3);
+        // 两个浮点数的精确和，既不是 0.3，也不是 f64 的 0.3，而是舍入前的真实值
+        let exact_sum = exact(0.1) + exact(0.2);
+        assert_ne!(exact_sum, exact(0.3));
+        assert_ne!(exact_sum, BigRational::parse("0.3").unwrap());
+        // f64 加法把精确和舍入到最近的可表示值，结果比 0.3 大一个 ulp
+        assert_eq!(exact(0.1 + 0.2), exact(0.30000000000000004));
+        assert!(exact(0.1 + 0.2) > exact(0.3));
+        assert_eq!((0.1f64 + 0.2).to_bits() - 0.3f64.to_bits(), 1);
+        // 有理数和定点数模式下 0.1 + 0.2 == 0.3
+        let r: BigRational = sum_literals(&["0.1", "0.2"]).unwrap();
+        assert_eq!(r, BigRational::parse("0.3").unwrap());
+        let d: Decimal<2> = sum_literals(&["0.1", "0.2"]).unwrap();
+        assert_eq!(d, Decimal::<2>::parse("0.3").unwrap());
+    }
+
+    #[test]
+    fn f32_rounds_to_the_same_value() {
+        // f32 下 0.1 + 0.2 == 0.3 成立，但精确和依然不等于 0.3_f32，只是舍入后恰好相同
+        let exact_sum = exact_f32(0.1).unwrap() + exact_f32(0.2).unwrap();
+        assert_ne!(exact_sum, exact_f32(0.3).unwrap());
+        assert_eq!(exact_f32(0.1 + 0.2), exact_f32(0.3));
+    }
+
+    #[test]
+    fn subtraction_does_not_cancel() {
+        assert_ne!(0.3f64 - 0.2 - 0.1, 0.0);
+        let r = BigRational::parse("0.3").unwrap();
+        let back = r
+            .sub(&BigRational::parse("0.2").unwrap())
+            .unwrap()
+            .sub(&BigRational::parse("0.1").unwrap())
+            .unwrap();
+        assert!(back.is_zero());
+    }
+
+    #[test]
+    fn non_finite_has_no_exact_value() {
+        assert_eq!(exact_f64(f64::NAN), None);
+        assert_eq!(exact_f64(f64::INFINITY), None);
+        assert_eq!(exact_f64(f64::NEG_INFINITY), None);
+        assert_eq!(exact_f32(f32::NAN), None);
+        // 溢出得到无穷大，同样没有精确值
+        assert_eq!(exact_f64(f64::MAX * 2.0), None);
+        assert_eq!(exact_f64(-0.0), Some(Zero::zero()));
+    }
+}
@@ -0,0 +1,1213 @@
+//! 持久化(不可变、结构共享)集合
+//! auto_ptr 中注释掉的 `enum List { Cons(i32, Box<List>), Nil }` 用 Box 只能有一个所有者，
+//! 这里换成 Rc，让多个版本共享同一段尾部：
+//! - ConsList：单链表，cons 和 tail 都是 O(1)，旧版本保持不变
+//! - PVec：32 叉位分区前缀树 + 尾部缓冲(RRB 树不带 relaxed 节点的形式)，clone 是 O(1)，修改只复制一条路径
+//! - PMap：哈希数组映射前缀树(HAMT)，每层取哈希的 5 位，哈希完全相同时放进冲突节点
+//!
+//! PVec 和 PMap 的修改方法接收 &mut self，内部用 Rc::make_mut 写时复制：
+//! 节点只被当前版本引用时原地修改，被快照共享时才复制，所以 clone 出来的快照不会被后续修改影响
+
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::{BuildHasher, Hash};
+use std::hint::black_box;
+use std::iter::FromIterator;
+use std::mem;
+use std::ops::Index;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use crate::hasher_bench::XorShift;
+
+/* ---------------------------- 单链表 ---------------------------- */
+
+struct ConsNode<T> {
+    head: T,
+    tail: Option<Rc<ConsNode<T>>>,
+}
+
+pub struct ConsList<T> {
+    node: Option<Rc<ConsNode<T>>>,
+    len: usize,
+}
+
+impl<T> ConsList<T> {
+    pub fn new() -> Self {
+        ConsList { node: None, len: 0 }
+    }
+
+    // 返回在头部加上 value 的新链表，self 不变，两者共享原来的所有节点
+    pub fn cons(&self, head: T) -> Self {
+        ConsList {
+            node: Some(Rc::new(ConsNode {
+                head,
+                tail: self.node.clone(),
+            })),
+            len: self.len + 1,
+        }
+    }
+
+    pub fn head(&self) -> Option<&T> {
+        self.node.as_ref().map(|n| &n.head)
+    }
+
+    // 去掉头部后的链表，空链表的 tail 还是空链表
+    pub fn tail(&self) -> Self {
+        match &self.node {
+            Some(n) => ConsList {
+                node: n.tail.clone(),
+                len: self.len - 1,
+            },
+            None => ConsList::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.node.is_none()
+    }
+
+    pub fn iter(&self) -> ConsIter<'_, T> {
+        ConsIter {
+            next: self.node.as_deref(),
+        }
+    }
+
+    // 两个链表是否共享同一个头节点(而不只是元素相等)
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        match (&self.node, &other.node) {
+            (Some(a), Some(b)) => Rc::ptr_eq(a, b),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+}
+
+impl<T: Clone> ConsList<T> {
+    pub fn reverse(&self) -> Self {
+        self.iter()
+            .fold(ConsList::new(), |acc, x| acc.cons(x.clone()))
+    }
+}
+
+impl<T> Default for ConsList<T> {
+    fn default() -> Self {
+        ConsList::new()
+    }
+}
+
+// 只复制头指针
+impl<T> Clone for ConsList<T> {
+    fn clone(&self) -> Self {
+        ConsList {
+            node: self.node.clone(),
+            len: self.len,
+        }
+    }
+}
+
+// 默认的递归析构在长链表上会栈溢出，改成循环；遇到仍被其他链表共享的节点就停下
+impl<T> Drop for ConsList<T> {
+    fn drop(&mut self) {
+        let mut next = self.node.take();
+        while let Some(node) = next {
+            match Rc::try_unwrap(node) {
+                Ok(mut node) => next = node.tail.take(),
+                Err(_) => break,
+            }
+        }
+    }
+}
+
+pub struct ConsIter<'a, T> {
+    next: Option<&'a ConsNode<T>>,
+}
+
+impl<'a, T> Iterator for ConsIter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        let node = self.next?;
+        self.next = node.tail.as_deref();
+        Some(&node.head)
+    }
+}
+
+impl<'a, T> IntoIterator for &'a ConsList<T> {
+    type Item = &'a T;
+    type IntoIter = ConsIter<'a, T>;
+
+    fn into_iter(self) -> ConsIter<'a, T> {
+        self.iter()
+    }
+}
+
+// 保持迭代顺序：collect 之后 iter 得到的顺序和输入相同
+impl<T> FromIterator<T> for ConsList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let items: Vec<T> = iter.into_iter().collect();
+        items
+            .into_iter()
+            .rev()
+            .fold(ConsList::new(), |acc, x| acc.cons(x))
+    }
+}
+
+impl<T: PartialEq> PartialEq for ConsList<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for ConsList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+/* ---------------------------- 持久化向量 ---------------------------- */
+
+const BITS: usize = 5;
+const WIDTH: usize = 1 << BITS;
+const MASK: usize = WIDTH - 1;
+
+#[derive(Clone)]
+enum VecNode<T> {
+    Branch(Vec<Rc<VecNode<T>>>),
+    Leaf(Vec<T>),
+}
+
+// 最后(不满 32 个)的元素放在 tail 中，push 大多只改 tail；tail 满了才作为一个叶子挂进树里
+pub struct PVec<T> {
+    len: usize,
+    shift: usize,
+    root: Rc<VecNode<T>>,
+    tail: Rc<Vec<T>>,
+}
+
+impl<T> PVec<T> {
+    pub fn new() -> Self {
+        PVec {
+            len: 0,
+            shift: BITS,
+            root: Rc::new(VecNode::Branch(Vec::new())),
+            tail: Rc::new(Vec::new()),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // 树中元素的个数，也就是 tail 第一个元素的下标
+    fn tail_offset(&self) -> usize {
+        if self.len < WIDTH {
+            0
+        } else {
+            ((self.len - 1) >> BITS) << BITS
+        }
+    }
+
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if index >= self.len {
+            return None;
+        }
+        let offset = self.tail_offset();
+        if index >= offset {
+            return self.tail.get(index - offset);
+        }
+        let mut node = &*self.root;
+        let mut level = self.shift;
+        loop {
+            match node {
+                VecNode::Branch(children) => {
+                    node = &children[(index >> level) & MASK];
+                    level -= BITS;
+                }
+                VecNode::Leaf(values) => return values.get(index & MASK),
+            }
+        }
+    }
+
+    pub fn first(&self) -> Option<&T> {
+        self.get(0)
+    }
+
+    pub fn last(&self) -> Option<&T> {
+        self.tail.last()
+    }
+
+    pub fn iter(&self) -> PVecIter<'_, T> {
+        PVecIter {
+            vec: self,
+            front: 0,
+            back: self.len,
+        }
+    }
+
+    // 两个版本共享的节点数，用来观察结构共享(树中的节点 + tail)
+    pub fn shared_nodes(&self, other: &Self) -> usize {
+        fn walk<T>(a: &Rc<VecNode<T>>, b: &Rc<VecNode<T>>) -> usize {
+            if Rc::ptr_eq(a, b) {
+                return count(a);
+            }
+            match (&**a, &**b) {
+                (VecNode::Branch(x), VecNode::Branch(y)) => {
+                    x.iter().zip(y).map(|(a, b)| walk(a, b)).sum()
+                }
+                _ => 0,
+            }
+        }
+        fn count<T>(node: &VecNode<T>) -> usize {
+            match node {
+                VecNode::Branch(children) => 1 + children.iter().map(|c| count(c)).sum::<usize>(),
+                VecNode::Leaf(_) => 1,
+            }
+        }
+        let tail = usize::from(Rc::ptr_eq(&self.tail, &other.tail));
+        if self.shift == other.shift {
+            walk(&self.root, &other.root) + tail
+        } else {
+            tail
+        }
+    }
+}
+
+impl<T: Clone> PVec<T> {
+    pub fn push(&mut self, value: T) {
+        if self.len - self.tail_offset() < WIDTH {
+            Rc::make_mut(&mut self.tail).push(value);
+            self.len += 1;
+            return;
+        }
+        // tail 已满，把它变成叶子挂进树中
+        let full = mem::replace(&mut self.tail, Rc::new(vec![value]));
+        let leaf = Rc::new(VecNode::Leaf(
+            Rc::try_unwrap(full).unwrap_or_else(|rc| (*rc).clone()),
+        ));
+        let offset = self.len - WIDTH;
+        if (offset >> BITS) + 1 > 1 << self.shift {
+            // 根节点满了，树长高一层
+            let old = mem::replace(&mut self.root, Rc::new(VecNode::Branch(Vec::new())));
+            let path = new_path(self.shift, leaf);
+            self.root = Rc::new(VecNode::Branch(vec![old, path]));
+            self.shift += BITS;
+        } else {
+            push_leaf(&mut self.root, self.shift, offset, leaf);
+        }
+        self.len += 1;
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        let offset = self.tail_offset();
+        let value = Rc::make_mut(&mut self.tail).pop();
+        self.len -= 1;
+        if self.len > offset || self.len == 0 {
+            return value;
+        }
+        // tail 空了，把树中最后一个叶子取回来作为新的 tail
+        self.tail = Rc::new(pop_leaf(&mut self.root, self.shift, self.len - 1));
+        while self.shift > BITS {
+            let only = match &*self.root {
+                VecNode::Branch(children) if children.len() == 1 => children[0].clone(),
+                _ => break,
+            };
+            self.root = only;
+            self.shift -= BITS;
+        }
+        value
+    }
+
+    // 替换下标处的元素，返回旧值；越界时返回 Err(value)
+    pub fn set(&mut self, index: usize, value: T) -> Result<T, T> {
+        if index >= self.len {
+            return Err(value);
+        }
+        let offset = self.tail_offset();
+        if index >= offset {
+            let slot = &mut Rc::make_mut(&mut self.tail)[index - offset];
+            return Ok(mem::replace(slot, value));
+        }
+        let mut node = &mut self.root;
+        let mut level = self.shift;
+        loop {
+            match Rc::make_mut(node) {
+                VecNode::Branch(children) => {
+                    node = &mut children[(index >> level) & MASK];
+                    level -= BITS;
+                }
+                VecNode::Leaf(values) => return Ok(mem::replace(&mut values[index & MASK], value)),
+            }
+        }
+    }
+
+    // 不修改 self，返回替换后的新版本
+    pub fn update(&self, index: usize, value: T) -> Option<Self> {
+        let mut next = self.clone();
+        next.set(index, value).ok()?;
+        Some(next)
+    }
+}
+
+// 从 level 层一直向下建到叶子的单链路径
+fn new_path<T>(level: usize, leaf: Rc<VecNode<T>>) -> Rc<VecNode<T>> {
+    (0..level / BITS).fold(leaf, |node, _| Rc::new(VecNode::Branch(vec![node])))
+}
+
+// offset 是新叶子第一个元素的下标
+fn push_leaf<T: Clone>(
+    node: &mut Rc<VecNode<T>>,
+    level: usize,
+    offset: usize,
+    leaf: Rc<VecNode<T>>,
+) {
+    let VecNode::Branch(children) = Rc::make_mut(node) else {
+        unreachable!("leaf above level 0");
+    };
+    if level == BITS {
+        children.push(leaf);
+        return;
+    }
+    let sub = (offset >> level) & MASK;
+    if sub < children.len() {
+        push_leaf(&mut children[sub], level - BITS, offset, leaf);
+    } else {
+        children.push(new_path(level - BITS, leaf));
+    }
+}
+
+// 取出包含下标 index(树中最后一个元素)的叶子，顺便删掉变空的分支
+fn pop_leaf<T: Clone>(node: &mut Rc<VecNode<T>>, level: usize, index: usize) -> Vec<T> {
+    let VecNode::Branch(children) = Rc::make_mut(node) else {
+        unreachable!("leaf above level 0");
+    };
+    if level == BITS {
+        let leaf = children.pop().expect("non-empty branch");
+        return match Rc::try_unwrap(leaf) {
+            Ok(VecNode::Leaf(values)) => values,
+            Err(rc) => match &*rc {
+                VecNode::Leaf(values) => values.clone(),
+                VecNode::Branch(_) => unreachable!("branch at level 0"),
+            },
+            Ok(VecNode::Branch(_)) => unreachable!("branch at level 0"),
+        };
+    }
+    let sub = (index >> level) & MASK;
+    let values = pop_leaf(&mut children[sub], level - BITS, index);
+    if matches!(&*children[sub], VecNode::Branch(c) if c.is_empty()) {
+        children.pop();
+    }
+    values
+}
+
+impl<T> Default for PVec<T> {
+    fn default() -> Self {
+        PVec::new()
+    }
+}
+
+impl<T> Clone for PVec<T> {
+    fn clone(&self) -> Self {
+        PVec {
+            len: self.len,
+            shift: self.shift,
+            root: self.root.clone(),
+            tail: self.tail.clone(),
+        }
+    }
+}
+
+impl<T> Index<usize> for PVec<T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &T {
+        match self.get(index) {
+            Some(v) => v,
+            None => panic!(
+                "index {} out of range for PVec of length {}",
+                index, self.len
+            ),
+        }
+    }
+}
+
+pub struct PVecIter<'a, T> {
+    vec: &'a PVec<T>,
+    front: usize,
+    back: usize,
+}
+
+impl<'a, T> Iterator for PVecIter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        if self.front == self.back {
+            return None;
+        }
+        self.front += 1;
+        self.vec.get(self.front - 1)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let n = self.back - self.front;
+        (n, Some(n))
+    }
+}
+
+impl<T> DoubleEndedIterator for PVecIter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.front == self.back {
+            return None;
+        }
+        self.back -= 1;
+        self.vec.get(self.back)
+    }
+}
+
+impl<T> ExactSizeIterator for PVecIter<'_, T> {}
+
+impl<'a, T> IntoIterator for &'a PVec<T> {
+    type Item = &'a T;
+    type IntoIter = PVecIter<'a, T>;
+
+    fn into_iter(self) -> PVecIter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<T: Clone> FromIterator<T> for PVec<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut v = PVec::new();
+        v.extend(iter);
+        v
+    }
+}
+
+impl<T: Clone> Extend<T> for PVec<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for x in iter {
+            self.push(x);
+        }
+    }
+}
+
+impl<T: PartialEq> PartialEq for PVec<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for PVec<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+/* ---------------------------- 持久化映射 ---------------------------- */
+
+#[derive(Clone)]
+enum MapNode<K, V> {
+    // bitmap 的第 i 位表示这一层哈希片段为 i 的子节点存在，children 按位紧凑存放
+    Branch {
+        bitmap: u32,
+        children: Vec<Rc<MapNode<K, V>>>,
+    },
+    Leaf {
+        hash: u64,
+        key: K,
+        value: V,
+    },
+    // 64 位哈希完全相同的不同 key
+    Collision {
+        hash: u64,
+        entries: Vec<(K, V)>,
+    },
+}
+
+impl<K, V> MapNode<K, V> {
+    fn empty() -> Self {
+        MapNode::Branch {
+            bitmap: 0,
+            children: Vec::new(),
+        }
+    }
+
+    fn hash(&self) -> u64 {
+        match self {
+            MapNode::Leaf { hash, .. } | MapNode::Collision { hash, .. } => *hash,
+            MapNode::Branch { .. } => unreachable!("branch has no single hash"),
+        }
+    }
+}
+
+fn fragment(hash: u64, shift: u32) -> u32 {
+    1 << ((hash >> shift) & MASK as u64)
+}
+
+// 两个哈希不同的节点放进同一个 shift 层：片段相同就继续向下分叉
+fn merge<K, V>(shift: u32, a: Rc<MapNode<K, V>>, b: Rc<MapNode<K, V>>) -> MapNode<K, V> {
+    let (bit_a, bit_b) = (fragment(a.hash(), shift), fragment(b.hash(), shift));
+    if bit_a == bit_b {
+        MapNode::Branch {
+            bitmap: bit_a,
+            children: vec![Rc::new(merge(shift + BITS as u32, a, b))],
+        }
+    } else {
+        let children = if bit_a < bit_b {
+            vec![a, b]
+        } else {
+            vec![b, a]
+        };
+        MapNode::Branch {
+            bitmap: bit_a | bit_b,
+            children,
+        }
+    }
+}
+
+fn map_insert<K: Eq + Clone, V: Clone>(
+    node: &mut Rc<MapNode<K, V>>,
+    shift: u32,
+    hash: u64,
+    key: K,
+    value: V,
+) -> Option<V> {
+    let this = Rc::make_mut(node);
+    match this {
+        MapNode::Branch { bitmap, children } => {
+            let bit = fragment(hash, shift);
+            let idx = (*bitmap & (bit - 1)).count_ones() as usize;
+            if *bitmap & bit == 0 {
+                *bitmap |= bit;
+                children.insert(idx, Rc::new(MapNode::Leaf { hash, key, value }));
+                None
+            } else {
+                map_insert(&mut children[idx], shift + BITS as u32, hash, key, value)
+            }
+        }
+        MapNode::Leaf {
+            hash: h,
+            key: k,
+            value: v,
+        } if *h == hash && *k == key => Some(mem::replace(v, value)),
+        MapNode::Collision { hash: h, entries } if *h == hash => {
+            match entries.iter_mut().find(|(k, _)| *k == key) {
+                Some((_, v)) => Some(mem::replace(v, value)),
+                None => {
+                    entries.push((key, value));
+                    None
+                }
+            }
+        }
+        _ => {
+            let old = mem::replace(this, MapNode::empty());
+            *this = if old.hash() == hash {
+                // 只可能是哈希相同、key 不同的叶子
+                let MapNode::Leaf {
+                    key: k, value: v, ..
+                } = old
+                else {
+                    unreachable!("collision with same hash handled above");
+                };
+                MapNode::Collision {
+                    hash,
+                    entries: vec![(k, v), (key, value)],
+                }
+            } else {
+                merge(
+                    shift,
+                    Rc::new(old),
+                    Rc::new(MapNode::Leaf { hash, key, value }),
+                )
+            };
+            None
+        }
+    }
+}
+
+// 调用前已确认 key 存在；返回旧值以及当前节点是否应该从父节点中删掉
+fn map_remove<K: Eq + Clone, V: Clone>(
+    node: &mut Rc<MapNode<K, V>>,
+    shift: u32,
+    hash: u64,
+    key: &K,
+) -> (V, bool) {
+    match Rc::make_mut(node) {
+        MapNode::Branch { bitmap, children } => {
+            let bit = fragment(hash, shift);
+            let idx = (*bitmap & (bit - 1)).count_ones() as usize;
+            let (value, gone) = map_remove(&mut children[idx], shift + BITS as u32, hash, key);
+            if gone {
+                children.remove(idx);
+                *bitmap &= !bit;
+            } else if let MapNode::Branch {
+                children: grand, ..
+            } = &*children[idx]
+            {
+                // 只剩一个叶子的分支上提一层，保持树的形状只由内容决定
+                if grand.len() == 1 && !matches!(&*grand[0], MapNode::Branch { .. }) {
+                    children[idx] = grand[0].clone();
+                }
+            }
+            (value, children.is_empty())
+        }
+        MapNode::Leaf { value, .. } => (value.clone(), true),
+        MapNode::Collision { hash, entries } => {
+            let pos = entries
+                .iter()
+                .position(|(k, _)| k == key)
+                .expect("key checked before remove");
+            let (_, value) = entries.swap_remove(pos);
+            if entries.len() == 1 {
+                let (key, v) = entries.pop().expect("one entry left");
+                *Rc::make_mut(node) = MapNode::Leaf {
+                    hash: *hash,
+                    key,
+                    value: v,
+                };
+            }
+            (value, false)
+        }
+    }
+}
+
+pub struct PMap<K, V, S = RandomState> {
+    root: Rc<MapNode<K, V>>,
+    len: usize,
+    hasher: S,
+}
+
+impl<K, V> PMap<K, V> {
+    pub fn new() -> Self {
+        PMap::with_hasher(RandomState::new())
+    }
+}
+
+impl<K, V, S> PMap<K, V, S> {
+    pub fn with_hasher(hasher: S) -> Self {
+        PMap {
+            root: Rc::new(MapNode::empty()),
+            len: 0,
+            hasher,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn iter(&self) -> PMapIter<'_, K, V> {
+        PMapIter {
+            stack: vec![std::slice::from_ref(&self.root).iter()],
+            collision: [].iter(),
+        }
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &K> {
+        self.iter().map(|(k, _)| k)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &V> {
+        self.iter().map(|(_, v)| v)
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher> PMap<K, V, S> {
+    pub fn get(&self, key: &K) -> Option<&V> {
+        let hash = self.hasher.hash_one(key);
+        let mut node = &*self.root;
+        let mut shift = 0;
+        loop {
+            match node {
+                MapNode::Branch { bitmap, children } => {
+                    let bit = fragment(hash, shift);
+                    if bitmap & bit == 0 {
+                        return None;
+                    }
+                    node = &children[(bitmap & (bit - 1)).count_ones() as usize];
+                    shift += BITS as u32;
+                }
+                MapNode::Leaf { key: k, value, .. } => {
+                    return (k == key).then_some(value);
+                }
+                MapNode::Collision { entries, .. } => {
+                    return entries.iter().find(|(k, _)| k == key).map(|(_, v)| v);
+                }
+            }
+        }
+    }
+
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+}
+
+impl<K: Hash + Eq + Clone, V: Clone, S: BuildHasher> PMap<K, V, S> {
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let hash = self.hasher.hash_one(&key);
+        let old = map_insert(&mut self.root, 0, hash, key, value);
+        if old.is_none() {
+            self.len += 1;
+        }
+        old
+    }
+
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        // 先查找，key 不存在时不复制任何节点
+        if !self.contains_key(key) {
+            return None;
+        }
+        let hash = self.hasher.hash_one(key);
+        let (value, _) = map_remove(&mut self.root, 0, hash, key);
+        self.len -= 1;
+        Some(value)
+    }
+}
+
+impl<K: Hash + Eq + Clone, V: Clone, S: BuildHasher + Clone> PMap<K, V, S> {
+    // 不修改 self，返回插入后的新版本
+    pub fn inserted(&self, key: K, value: V) -> Self {
+        let mut next = self.clone();
+        next.insert(key, value);
+        next
+    }
+
+    pub fn removed(&self, key: &K) -> Self {
+        let mut next = self.clone();
+        next.remove(key);
+        next
+    }
+}
+
+impl<K, V> Default for PMap<K, V> {
+    fn default() -> Self {
+        PMap::new()
+    }
+}
+
+// 哈希器也要一起复制，不同版本的同一个 key 才会落在同一个位置
+impl<K, V, S: Clone> Clone for PMap<K, V, S> {
+    fn clone(&self) -> Self {
+        PMap {
+            root: self.root.clone(),
+            len: self.len,
+            hasher: self.hasher.clone(),
+        }
+    }
+}
+
+pub struct PMapIter<'a, K, V> {
+    stack: Vec<std::slice::Iter<'a, Rc<MapNode<K, V>>>>,
+    collision: std::slice::Iter<'a, (K, V)>,
+}
+
+impl<'a, K, V> Iterator for PMapIter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some((k, v)) = self.collision.next() {
+            return Some((k, v));
+        }
+        loop {
+            let node = match self.stack.last_mut()?.next() {
+                Some(node) => node,
+                None => {
+                    self.stack.pop();
+                    continue;
+                }
+            };
+            match &**node {
+                MapNode::Branch { children, .. } => self.stack.push(children.iter()),
+                MapNode::Leaf { key, value, .. } => return Some((key, value)),
+                MapNode::Collision { entries, .. } => {
+                    self.collision = entries.iter();
+                    if let Some((k, v)) = self.collision.next() {
+                        return Some((k, v));
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<'a, K, V, S> IntoIterator for &'a PMap<K, V, S> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = PMapIter<'a, K, V>;
+
+    fn into_iter(self) -> PMapIter<'a, K, V> {
+        self.iter()
+    }
+}
+
+impl<K: Hash + Eq + Clone, V: Clone> FromIterator<(K, V)> for PMap<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = PMap::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K: Hash + Eq + Clone, V: Clone, S: BuildHasher> Extend<(K, V)> for PMap<K, V, S> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (k, v) in iter {
+            self.insert(k, v);
+        }
+    }
+}
+
+impl<K: Hash + Eq, V: PartialEq, S: BuildHasher> PartialEq for PMap<K, V, S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().all(|(k, v)| other.get(k) == Some(v))
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug, S> fmt::Debug for PMap<K, V, S> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+/* ---------------------------- 基准测试 ---------------------------- */
+
+/*
+ * 快照密集的负载：在 n 个元素上做 ops 次随机修改，每次修改后保存一份快照，
+ * 最后在所有快照上各读一个随机位置。
+ * Vec/HashMap 只能整体 clone 保存快照，每次 O(n)；持久化集合 clone 是 O(1)，修改复制 O(log n) 个节点
+ */
+pub struct BenchRow {
+    pub name: &'static str,
+    pub elapsed: Duration,
+    pub checksum: u64,
+}
+
+fn best_of(rounds: usize, mut f: impl FnMut() -> u64) -> (Duration, u64) {
+    let mut best = Duration::MAX;
+    let mut checksum = 0;
+    for _ in 0..rounds.max(1) {
+        let start = Instant::now();
+        checksum = black_box(f());
+        best = best.min(start.elapsed());
+    }
+    (best, checksum)
+}
+
+pub fn snapshot_bench(n: usize, ops: usize, rounds: usize) -> Vec<BenchRow> {
+    let n = n.max(1);
+    let mut rng = XorShift::new(45);
+    let updates: Vec<(usize, u64)> = (0..ops)
+        .map(|_| (rng.next_u64() as usize % n, rng.next_u64()))
+        .collect();
+    let reads: Vec<usize> = (0..ops).map(|_| rng.next_u64() as usize % n).collect();
+    let base: Vec<u64> = (0..n as u64).collect();
+
+    let mut rows = Vec::new();
+    let mut row = |name, (elapsed, checksum)| {
+        rows.push(BenchRow {
+            name,
+            elapsed,
+            checksum,
+        })
+    };
+
+    row(
+        "Vec clone",
+        best_of(rounds, || {
+            let mut current = base.clone();
+            let mut snapshots = Vec::with_capacity(ops);
+            for &(i, x) in &updates {
+                current[i] = x;
+                snapshots.push(current.clone());
+            }
+            snapshots
+                .iter()
+                .zip(&reads)
+                .map(|(s, &i)| s[i])
+                .fold(0, u64::wrapping_add)
+        }),
+    );
+    let pbase: PVec<u64> = base.iter().copied().collect();
+    row(
+        "PVec",
+        best_of(rounds, || {
+            let mut current = pbase.clone();
+            let mut snapshots = Vec::with_capacity(ops);
+            for &(i, x) in &updates {
+                let _ = current.set(i, x);
+                snapshots.push(current.clone());
+            }
+            snapshots
+                .iter()
+                .zip(&reads)
+                .map(|(s, &i)| s[i])
+                .fold(0, u64::wrapping_add)
+        }),
+    );
+
+    let hbase: HashMap<u64, u64> = base.iter().map(|&k| (k, k)).collect();
+    row(
+        "HashMap clone",
+        best_of(rounds, || {
+            let mut current = hbase.clone();
+            let mut snapshots = Vec::with_capacity(ops);
+            for &(i, x) in &updates {
+                current.insert(i as u64, x);
+                snapshots.push(current.clone());
+            }
+            snapshots
+                .iter()
+                .zip(&reads)
+                .map(|(s, &i)| s[&(i as u64)])
+                .fold(0, u64::wrapping_add)
+        }),
+    );
+    let mbase: PMap<u64, u64> = base.iter().map(|&k| (k, k)).collect();
+    row(
+        "PMap",
+        best_of(rounds, || {
+            let mut current = mbase.clone();
+            let mut snapshots = Vec::with_capacity(ops);
+            for &(i, x) in &updates {
+                current.insert(i as u64, x);
+                snapshots.push(current.clone());
+            }
+            snapshots
+                .iter()
+                .zip(&reads)
+                .map(|(s, &i)| s.get(&(i as u64)).copied().unwrap_or(0))
+                .fold(0u64, u64::wrapping_add)
+        }),
+    );
+    rows
+}
+
+pub fn print_bench(n: usize, ops: usize, rows: &[BenchRow]) {
+    println!("{} elements, {} updates, one snapshot per update", n, ops);
+    println!(
+        "  {:<14}{:>12}{:>14}",
+        "collection", "total ms", "ns/update"
+    );
+    for r in rows {
+        println!(
+            "  {:<14}{:>12.3}{:>14.1}",
+            r.name,
+            r.elapsed.as_secs_f64() * 1e3,
+            r.elapsed.as_nanos() as f64 / ops.max(1) as f64
+        );
+    }
+}
+
+// cargo run --release -- persistent [--n 10000] [--ops 2000] [--rounds 3]
+pub fn persistent_bench(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let mut n = 10_000;
+    let mut ops = 2_000;
+    let mut rounds = 3;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let value = iter
+            .next()
+            .ok_or_else(|| format!("{} requires a value", arg))?;
+        match arg.as_str() {
+            "--n" => n = value.parse()?,
+            "--ops" => ops = value.parse()?,
+            "--rounds" => rounds = value.parse()?,
+            other => return Err(format!("unknown option: {}", other).into()),
+        }
+    }
+    print_bench(n, ops, &snapshot_bench(n, ops, rounds));
+    Ok(())
+}
+
+/* ---------------------------- 练习 ---------------------------- */
+
+// 所有 key 哈希到同一个值，用来测试冲突节点
+#[derive(Clone, Default)]
+struct ConstHasher;
+
+impl BuildHasher for ConstHasher {
+    type Hasher = ConstHash;
+
+    fn build_hasher(&self) -> ConstHash {
+        ConstHash
+    }
+}
+
+struct ConstHash;
+
+impl std::hash::Hasher for ConstHash {
+    fn finish(&self) -> u64 {
+        0x2A
+    }
+
+    fn write(&mut self, _: &[u8]) {}
+}
+
+fn cons_list_practice() {
+    let empty: ConsList<i32> = ConsList::new();
+    let a = empty.cons(3).cons(2).cons(1);
+    let b = a.tail().cons(10);
+    assert_eq!(a.iter().copied().collect::<Vec<_>>(), [1, 2, 3]);
+    assert_eq!(b.iter().copied().collect::<Vec<_>>(), [10, 2, 3]);
+    // a 和 b 共享 [2, 3]
+    assert!(a.tail().ptr_eq(&b.tail()));
+    assert_eq!((a.head(), a.len(), empty.tail().len()), (Some(&1), 3, 0));
+    assert_eq!(a.reverse(), (1..=3).rev().collect());
+    assert_eq!(format!("{:?}", b), "[10, 2, 3]");
+    assert!(empty.is_empty() && empty.head().is_none());
+    drop(a);
+    assert_eq!(b.iter().sum::<i32>(), 15);
+
+    // 迭代式析构：一百万个节点不会栈溢出，共享的尾部在最后一个所有者销毁时才释放
+    let long: ConsList<u32> = (0..1_000_000).collect();
+    let shared = long.tail().tail();
+    drop(long);
+    assert_eq!(shared.len(), 999_998);
+    assert_eq!(shared.head(), Some(&2));
+}
+
+fn pvec_practice() {
+    // 与 Vec 做差分测试，跨越 32 / 1024 / 32768 的层次边界，并检查所有快照都没有被修改
+    let mut rng = XorShift::new(45);
+    let mut pv: PVec<u64> = PVec::new();
+    let mut model: Vec<u64> = Vec::new();
+    let mut snapshots: Vec<(PVec<u64>, Vec<u64>)> = Vec::new();
+    for step in 0..60_000 {
+        let r = rng.next_u64();
+        match r % 10 {
+            0..=5 => {
+                pv.push(r);
+                model.push(r);
+            }
+            6 | 7 if !model.is_empty() => {
+                let i = (r >> 8) as usize % model.len();
+                assert_eq!(pv.set(i, r), Ok(mem::replace(&mut model[i], r)));
+            }
+            8 => assert_eq!(pv.pop(), model.pop()),
+            _ => {}
+        }
+        if step % 997 == 0 {
+            snapshots.push((pv.clone(), model.clone()));
+        }
+    }
+    assert_eq!(pv.len(), model.len());
+    assert!(pv.iter().eq(model.iter()));
+    assert!(pv.iter().rev().eq(model.iter().rev()));
+    for (snap, expected) in &snapshots {
+        assert!(snap.iter().eq(expected.iter()));
+    }
+    assert_eq!(pv.set(model.len(), 0), Err(0));
+    assert_eq!(pv.get(model.len()), None);
+    assert_eq!(pv.last(), model.last());
+
+    // 一直 pop 到空，树会逐层变矮
+    let mut big: PVec<usize> = (0..40_000).collect();
+    assert_eq!(big.shift, 3 * BITS);
+    let copy = big.clone();
+    while let Some(x) = big.pop() {
+        assert_eq!(x, big.len());
+    }
+    assert_eq!((big.shift, big.is_empty()), (BITS, true));
+    assert_eq!(copy.len(), 40_000);
+    assert_eq!(copy[39_999], 39_999);
+
+    // 修改一个元素只复制根到叶子的路径：10000 个元素有 1 + 10 + 312 个树节点和 tail，其中 3 个被复制
+    let base: PVec<usize> = (0..10_000).collect();
+    let changed = base.update(5, 0).expect("in range");
+    assert_eq!((base[5], changed[5]), (5, 0));
+    assert_eq!(base.shared_nodes(&changed), 1 + 10 + 312 + 1 - 3);
+    assert!(base.update(10_000, 0).is_none());
+    assert_eq!(format!("{:?}", (0..3).collect::<PVec<_>>()), "[0, 1, 2]");
+    assert_eq!(PVec::<u8>::new().first(), None);
+}
+
+fn pmap_practice() {
+    let mut rng = XorShift::new(46);
+    let mut map: PMap<u64, u64> = PMap::new();
+    let mut model: HashMap<u64, u64> = HashMap::new();
+    let mut snapshots = Vec::new();
+    for step in 0..50_000 {
+        let r = rng.next_u64();
+        let key = (r >> 16) % 4_096;
+        if r.is_multiple_of(3) {
+            assert_eq!(map.remove(&key), model.remove(&key));
+        } else {
+            assert_eq!(map.insert(key, r), model.insert(key, r));
+        }
+        if step % 1_009 == 0 {
+            snapshots.push((map.clone(), model.clone()));
+        }
+    }
+    let check = |map: &PMap<u64, u64>, model: &HashMap<u64, u64>| {
+        assert_eq!(map.len(), model.len());
+        assert_eq!(map.iter().count(), model.len());
+        assert!(map.iter().all(|(k, v)| model.get(k) == Some(v)));
+        assert!(model.iter().all(|(k, v)| map.get(k) == Some(v)));
+    };
+    check(&map, &model);
+    for (snap, expected) in &snapshots {
+        check(snap, expected);
+    }
+
+    // 删光之后回到空的根节点
+    let keys: Vec<u64> = map.keys().copied().collect();
+    let before = map.clone();
+    for k in &keys {
+        assert!(map.remove(k).is_some());
+    }
+    assert!(map.is_empty() && map.iter().next().is_none());
+    assert!(matches!(&*map.root, MapNode::Branch { children, .. } if children.is_empty()));
+    assert_eq!(before.len(), keys.len());
+    assert_eq!(before.values().count(), keys.len());
+
+    // 内容相同就相等，和插入顺序无关
+    let a: PMap<&str, i32> = [("a", 1), ("b", 2), ("c", 3)].into_iter().collect();
+    let b = PMap::new()
+        .inserted("c", 3)
+        .inserted("a", 1)
+        .inserted("b", 2);
+    assert_eq!(a, b);
+    assert_ne!(a, b.removed(&"a"));
+    assert_eq!(b.removed(&"z").len(), 3);
+    assert_eq!(format!("{:?}", PMap::new().inserted(1, "x")), "{1: \"x\"}");
+
+    // 所有 key 哈希相同：进入冲突节点，删到只剩一个时变回叶子
+    let mut same: PMap<u32, u32, ConstHasher> = PMap::with_hasher(ConstHasher);
+    for i in 0..5 {
+        assert_eq!(same.insert(i, i * 10), None);
+    }
+    assert_eq!(same.insert(2, 99), Some(20));
+    let snap = same.clone();
+    for i in [0, 1, 3, 4] {
+        assert_eq!(same.remove(&i), Some(i * 10));
+    }
+    assert_eq!(same.get(&2), Some(&99));
+    assert!(!same.contains_key(&0));
+    assert_eq!(snap.iter().count(), 5);
+    assert_eq!(snap.get(&4), Some(&40));
+}
+
+pub fn persistent_practice() {
+    cons_list_practice();
+    pvec_practice();
+    pmap_practice();
+
+    let rows = snapshot_bench(2_000, 300, 1);
+    // 四种实现读到的值必须相同
+    assert!(rows.iter().all(|r| r.checksum == rows[0].checksum));
+    print_bench(2_000, 300, &rows);
+}
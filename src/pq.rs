@@ -0,0 +1,1227 @@
+//! 优先队列和任务调度
+//! main.rs 里有先进先出的 MyQueue、环形的 MyCircularQueue，scenes 里用 Vec 当栈，这里补上按优先级出队的结构：
+//! - IndexedMinHeap<P>：元素就是下标 0..n，可以按下标查询、修改、删除优先级(Dijkstra、Prim 常用)
+//! - MinHeap<P, T>：二叉最小堆，push 返回 Handle，之后可以 decrease_key / remove 任意元素；内部用 IndexedMinHeap 按槽位号排序
+//! - PairingHeap<P, T>：配对堆，节点放在数组里用下标互相链接，decrease_key 摊还 O(1)
+//! - Scheduler<Q>：最早截止时间优先(EDF)的任务调度器，后端可以是上面任意一种堆
+//! - TimerWheel<T>：单层时间轮，调度器用它保存延迟提交的任务
+//!
+//! 三种堆都实现了 PriorityQueue。测试中用同一组随机操作分别对照朴素模型(BTreeMap、线性扫描)检查
+
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+
+use crate::hasher_bench::XorShift;
+
+// 空链接和“不在堆中”
+const NIL: usize = usize::MAX;
+
+/* ---------------------------- 公共部分 ---------------------------- */
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeapError {
+    // 句柄对应的元素已经出堆，或者下标不在堆中
+    Missing,
+    // decrease_key 不能让优先级变大
+    Increase,
+}
+
+impl fmt::Display for HeapError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HeapError::Missing => write!(f, "element is no longer in the heap"),
+            HeapError::Increase => write!(f, "decrease_key cannot raise the priority"),
+        }
+    }
+}
+
+impl std::error::Error for HeapError {}
+
+// 槽位号加代数：槽位被复用后代数加一，旧句柄自然失效
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Handle {
+    slot: usize,
+    generation: u32,
+}
+
+// 调度器和练习使用的统一接口，元素固定是 usize(任务编号之类)
+pub trait PriorityQueue<P: Ord> {
+    type Handle: Copy;
+
+    fn push(&mut self, priority: P, item: usize) -> Self::Handle;
+    fn pop(&mut self) -> Option<(P, usize)>;
+    fn decrease_key(&mut self, handle: Self::Handle, priority: P) -> Result<(), HeapError>;
+    fn remove(&mut self, handle: Self::Handle) -> Option<(P, usize)>;
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/* ---------------------------- 索引堆 ---------------------------- */
+
+pub struct IndexedMinHeap<P> {
+    // 堆中存的是下标
+    heap: Vec<usize>,
+    // pos[i] 是下标 i 在 heap 中的位置，NIL 表示不在堆中
+    pos: Vec<usize>,
+    priority: Vec<Option<P>>,
+}
+
+impl<P: Ord> IndexedMinHeap<P> {
+    pub fn new() -> Self {
+        IndexedMinHeap {
+            heap: Vec::new(),
+            pos: Vec::new(),
+            priority: Vec::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    pub fn contains(&self, i: usize) -> bool {
+        self.pos.get(i).is_some_and(|&p| p != NIL)
+    }
+
+    pub fn priority(&self, i: usize) -> Option<&P> {
+        self.priority.get(i)?.as_ref()
+    }
+
+    // 插入下标 i；i 已经在堆中时改成新的优先级(变大变小都可以)，返回旧的优先级
+    pub fn insert(&mut self, i: usize, priority: P) -> Option<P> {
+        if i >= self.pos.len() {
+            self.pos.resize(i + 1, NIL);
+            self.priority.resize_with(i + 1, || None);
+        }
+        let old = self.priority[i].replace(priority);
+        if old.is_none() {
+            self.pos[i] = self.heap.len();
+            self.heap.push(i);
+        }
+        self.restore(self.pos[i]);
+        old
+    }
+
+    pub fn peek(&self) -> Option<(usize, &P)> {
+        let &i = self.heap.first()?;
+        Some((i, self.priority(i)?))
+    }
+
+    pub fn pop(&mut self) -> Option<(usize, P)> {
+        let &i = self.heap.first()?;
+        Some((i, self.remove(i)?))
+    }
+
+    pub fn decrease_key(&mut self, i: usize, priority: P) -> Result<(), HeapError> {
+        let current = self.priority(i).ok_or(HeapError::Missing)?;
+        if priority > *current {
+            return Err(HeapError::Increase);
+        }
+        self.priority[i] = Some(priority);
+        self.sift_up(self.pos[i]);
+        Ok(())
+    }
+
+    pub fn remove(&mut self, i: usize) -> Option<P> {
+        if !self.contains(i) {
+            return None;
+        }
+        // 和末尾交换后删掉，再让换过来的元素上浮或下沉
+        let at = self.pos[i];
+        self.swap(at, self.heap.len() - 1);
+        self.heap.pop();
+        self.pos[i] = NIL;
+        if at < self.heap.len() {
+            self.restore(at);
+        }
+        self.priority[i].take()
+    }
+
+    fn key(&self, at: usize) -> &P {
+        self.priority[self.heap[at]]
+            .as_ref()
+            .expect("index in the heap has a priority")
+    }
+
+    fn swap(&mut self, a: usize, b: usize) {
+        self.heap.swap(a, b);
+        self.pos[self.heap[a]] = a;
+        self.pos[self.heap[b]] = b;
+    }
+
+    // 位置 at 上的优先级改变后恢复堆序：下沉没有移动时才可能需要上浮
+    fn restore(&mut self, at: usize) {
+        self.sift_down(at);
+        self.sift_up(at);
+    }
+
+    fn sift_up(&mut self, mut at: usize) {
+        while at > 0 {
+            let parent = (at - 1) / 2;
+            if self.key(at) >= self.key(parent) {
+                break;
+            }
+            self.swap(at, parent);
+            at = parent;
+        }
+    }
+
+    fn sift_down(&mut self, mut at: usize) {
+        let n = self.heap.len();
+        loop {
+            let left = 2 * at + 1;
+            if left >= n {
+                break;
+            }
+            let child = if left + 1 < n && self.key(left + 1) < self.key(left) {
+                left + 1
+            } else {
+                left
+            };
+            if self.key(child) >= self.key(at) {
+                break;
+            }
+            self.swap(at, child);
+            at = child;
+        }
+    }
+}
+
+impl<P: Ord> Default for IndexedMinHeap<P> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// 元素本身就是下标，句柄也是下标，所以同一个元素不能同时入堆两次：
+// 重复 push 会让旧句柄指向新的优先级，这里直接 panic，需要覆盖时用 insert
+impl<P: Ord> PriorityQueue<P> for IndexedMinHeap<P> {
+    type Handle = usize;
+
+    fn push(&mut self, priority: P, item: usize) -> usize {
+        assert!(
+            !self.contains(item),
+            "index {} is already in the heap",
+            item
+        );
+        self.insert(item, priority);
+        item
+    }
+
+    fn pop(&mut self) -> Option<(P, usize)> {
+        IndexedMinHeap::pop(self).map(|(i, p)| (p, i))
+    }
+
+    fn decrease_key(&mut self, handle: usize, priority: P) -> Result<(), HeapError> {
+        IndexedMinHeap::decrease_key(self, handle, priority)
+    }
+
+    fn remove(&mut self, handle: usize) -> Option<(P, usize)> {
+        IndexedMinHeap::remove(self, handle).map(|p| (p, handle))
+    }
+
+    fn len(&self) -> usize {
+        IndexedMinHeap::len(self)
+    }
+}
+
+/* ---------------------------- 二叉堆 ---------------------------- */
+
+pub struct MinHeap<P, T> {
+    // 槽位号当作索引堆的下标
+    index: IndexedMinHeap<P>,
+    items: Vec<Option<T>>,
+    generations: Vec<u32>,
+    free: Vec<usize>,
+}
+
+impl<P: Ord, T> MinHeap<P, T> {
+    pub fn new() -> Self {
+        MinHeap {
+            index: IndexedMinHeap::new(),
+            items: Vec::new(),
+            generations: Vec::new(),
+            free: Vec::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+
+    pub fn push(&mut self, priority: P, item: T) -> Handle {
+        let slot = self.free.pop().unwrap_or_else(|| {
+            self.items.push(None);
+            self.generations.push(0);
+            self.items.len() - 1
+        });
+        self.items[slot] = Some(item);
+        self.index.insert(slot, priority);
+        Handle {
+            slot,
+            generation: self.generations[slot],
+        }
+    }
+
+    pub fn peek(&self) -> Option<(&P, &T)> {
+        let (slot, priority) = self.index.peek()?;
+        Some((priority, self.items[slot].as_ref()?))
+    }
+
+    pub fn get(&self, handle: Handle) -> Option<(&P, &T)> {
+        let slot = self.live(handle)?;
+        Some((self.index.priority(slot)?, self.items[slot].as_ref()?))
+    }
+
+    pub fn pop(&mut self) -> Option<(P, T)> {
+        let (slot, priority) = self.index.pop()?;
+        Some((priority, self.release(slot)))
+    }
+
+    pub fn decrease_key(&mut self, handle: Handle, priority: P) -> Result<(), HeapError> {
+        let slot = self.live(handle).ok_or(HeapError::Missing)?;
+        self.index.decrease_key(slot, priority)
+    }
+
+    pub fn remove(&mut self, handle: Handle) -> Option<(P, T)> {
+        let slot = self.live(handle)?;
+        let priority = self.index.remove(slot)?;
+        Some((priority, self.release(slot)))
+    }
+
+    fn live(&self, handle: Handle) -> Option<usize> {
+        let current = self.generations.get(handle.slot)?;
+        (*current == handle.generation && self.index.contains(handle.slot)).then_some(handle.slot)
+    }
+
+    fn release(&mut self, slot: usize) -> T {
+        self.generations[slot] = self.generations[slot].wrapping_add(1);
+        self.free.push(slot);
+        self.items[slot].take().expect("heap slot holds an entry")
+    }
+}
+
+impl<P: Ord, T> Default for MinHeap<P, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<P: Ord> PriorityQueue<P> for MinHeap<P, usize> {
+    type Handle = Handle;
+
+    fn push(&mut self, priority: P, item: usize) -> Handle {
+        MinHeap::push(self, priority, item)
+    }
+
+    fn pop(&mut self) -> Option<(P, usize)> {
+        MinHeap::pop(self)
+    }
+
+    fn decrease_key(&mut self, handle: Handle, priority: P) -> Result<(), HeapError> {
+        MinHeap::decrease_key(self, handle, priority)
+    }
+
+    fn remove(&mut self, handle: Handle) -> Option<(P, usize)> {
+        MinHeap::remove(self, handle)
+    }
+
+    fn len(&self) -> usize {
+        MinHeap::len(self)
+    }
+}
+
+/* ---------------------------- 配对堆 ---------------------------- */
+
+struct Node<P, T> {
+    generation: u32,
+    entry: Option<(P, T)>,
+    // 第一个孩子
+    child: usize,
+    // 右兄弟
+    next: usize,
+    // 左兄弟；第一个孩子的 prev 指向父节点
+    prev: usize,
+}
+
+/*
+ * 堆是一棵多叉树，根最小。push 和 decrease_key 都只是把一棵树 link 到根上；
+ * pop 删掉根之后用两趟合并把所有孩子并成一棵：先从左到右两两 link，再从右到左依次 link。
+ * 节点放在 Vec 里，链接用下标，删除的节点进 free 复用
+ */
+pub struct PairingHeap<P, T> {
+    nodes: Vec<Node<P, T>>,
+    root: usize,
+    len: usize,
+    free: Vec<usize>,
+}
+
+impl<P: Ord, T> PairingHeap<P, T> {
+    pub fn new() -> Self {
+        PairingHeap {
+            nodes: Vec::new(),
+            root: NIL,
+            len: 0,
+            free: Vec::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn push(&mut self, priority: P, item: T) -> Handle {
+        let entry = Some((priority, item));
+        let n = match self.free.pop() {
+            Some(n) => {
+                self.nodes[n].entry = entry;
+                n
+            }
+            None => {
+                self.nodes.push(Node {
+                    generation: 0,
+                    entry,
+                    child: NIL,
+                    next: NIL,
+                    prev: NIL,
+                });
+                self.nodes.len() - 1
+            }
+        };
+        self.root = self.link(self.root, n);
+        self.len += 1;
+        Handle {
+            slot: n,
+            generation: self.nodes[n].generation,
+        }
+    }
+
+    pub fn peek(&self) -> Option<(&P, &T)> {
+        let (priority, item) = self.nodes.get(self.root)?.entry.as_ref()?;
+        Some((priority, item))
+    }
+
+    pub fn get(&self, handle: Handle) -> Option<(&P, &T)> {
+        let (priority, item) = self.nodes[self.live(handle)?].entry.as_ref()?;
+        Some((priority, item))
+    }
+
+    pub fn pop(&mut self) -> Option<(P, T)> {
+        if self.root == NIL {
+            return None;
+        }
+        let root = self.root;
+        self.root = self.merge_pairs(self.nodes[root].child);
+        Some(self.release(root))
+    }
+
+    pub fn decrease_key(&mut self, handle: Handle, priority: P) -> Result<(), HeapError> {
+        let n = self.live(handle).ok_or(HeapError::Missing)?;
+        if priority > *self.key(n) {
+            return Err(HeapError::Increase);
+        }
+        if let Some(entry) = self.nodes[n].entry.as_mut() {
+            entry.0 = priority;
+        }
+        // 子树内的堆序不受影响，把整棵子树摘下来重新 link 到根上
+        if n != self.root {
+            self.cut(n);
+            self.root = self.link(self.root, n);
+        }
+        Ok(())
+    }
+
+    pub fn remove(&mut self, handle: Handle) -> Option<(P, T)> {
+        let n = self.live(handle)?;
+        if n == self.root {
+            return self.pop();
+        }
+        self.cut(n);
+        let rest = self.merge_pairs(self.nodes[n].child);
+        self.root = self.link(self.root, rest);
+        Some(self.release(n))
+    }
+
+    fn live(&self, handle: Handle) -> Option<usize> {
+        let node = self.nodes.get(handle.slot)?;
+        (node.generation == handle.generation && node.entry.is_some()).then_some(handle.slot)
+    }
+
+    fn key(&self, n: usize) -> &P {
+        &self.nodes[n]
+            .entry
+            .as_ref()
+            .expect("tree node holds an entry")
+            .0
+    }
+
+    // 合并两棵没有兄弟的树，根较大的一棵成为另一个根的第一个孩子
+    fn link(&mut self, a: usize, b: usize) -> usize {
+        if a == NIL {
+            return b;
+        }
+        if b == NIL {
+            return a;
+        }
+        let (parent, child) = if self.key(b) < self.key(a) {
+            (b, a)
+        } else {
+            (a, b)
+        };
+        let first = self.nodes[parent].child;
+        self.nodes[child].next = first;
+        self.nodes[child].prev = parent;
+        if first != NIL {
+            self.nodes[first].prev = child;
+        }
+        self.nodes[parent].child = child;
+        parent
+    }
+
+    // 把以 n 为根的子树从父节点的孩子链表中摘下来
+    fn cut(&mut self, n: usize) {
+        let (prev, next) = (self.nodes[n].prev, self.nodes[n].next);
+        if self.nodes[prev].child == n {
+            self.nodes[prev].child = next;
+        } else {
+            self.nodes[prev].next = next;
+        }
+        if next != NIL {
+            self.nodes[next].prev = prev;
+        }
+        self.nodes[n].prev = NIL;
+        self.nodes[n].next = NIL;
+    }
+
+    fn merge_pairs(&mut self, first: usize) -> usize {
+        let mut trees = Vec::new();
+        let mut cur = first;
+        while cur != NIL {
+            let next = self.nodes[cur].next;
+            self.nodes[cur].prev = NIL;
+            self.nodes[cur].next = NIL;
+            trees.push(cur);
+            cur = next;
+        }
+        let paired: Vec<usize> = trees
+            .chunks(2)
+            .map(|pair| self.link(pair[0], pair.get(1).copied().unwrap_or(NIL)))
+            .collect();
+        paired
+            .into_iter()
+            .rev()
+            .fold(NIL, |acc, tree| self.link(tree, acc))
+    }
+
+    fn release(&mut self, n: usize) -> (P, T) {
+        let node = &mut self.nodes[n];
+        node.generation = node.generation.wrapping_add(1);
+        node.child = NIL;
+        self.free.push(n);
+        self.len -= 1;
+        node.entry.take().expect("tree node holds an entry")
+    }
+}
+
+impl<P: Ord, T> Default for PairingHeap<P, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<P: Ord> PriorityQueue<P> for PairingHeap<P, usize> {
+    type Handle = Handle;
+
+    fn push(&mut self, priority: P, item: usize) -> Handle {
+        PairingHeap::push(self, priority, item)
+    }
+
+    fn pop(&mut self) -> Option<(P, usize)> {
+        PairingHeap::pop(self)
+    }
+
+    fn decrease_key(&mut self, handle: Handle, priority: P) -> Result<(), HeapError> {
+        PairingHeap::decrease_key(self, handle, priority)
+    }
+
+    fn remove(&mut self, handle: Handle) -> Option<(P, usize)> {
+        PairingHeap::remove(self, handle)
+    }
+
+    fn len(&self) -> usize {
+        PairingHeap::len(self)
+    }
+}
+
+/* ---------------------------- 时间轮 ---------------------------- */
+
+pub type TimerId = u64;
+
+/*
+ * 单层时间轮：定时器按到期时刻对槽位数取模放进槽位，每 tick 一次只检查当前槽位。
+ * 延迟超过一圈的定时器留在槽位里，转到到期的那一圈才触发；取消只删 timers 中的记录，槽位里的编号在经过时顺便清掉
+ * expiries 按到期时刻排好序，advance_to 靠它直接跳到最早到期的格子
+ */
+pub struct TimerWheel<T> {
+    slots: Vec<Vec<TimerId>>,
+    timers: HashMap<TimerId, (u64, T)>,
+    expiries: BTreeSet<(u64, TimerId)>,
+    now: u64,
+    next_id: TimerId,
+}
+
+impl<T> TimerWheel<T> {
+    pub fn new(slots: usize) -> Self {
+        assert!(slots > 0, "timer wheel needs at least one slot");
+        TimerWheel {
+            slots: vec![Vec::new(); slots],
+            timers: HashMap::new(),
+            expiries: BTreeSet::new(),
+            now: 0,
+            next_id: 0,
+        }
+    }
+
+    pub fn now(&self) -> u64 {
+        self.now
+    }
+
+    pub fn len(&self) -> usize {
+        self.timers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.timers.is_empty()
+    }
+
+    // delay 为 0 时在下一次 tick 触发
+    pub fn schedule(&mut self, delay: u64, item: T) -> TimerId {
+        let at = self.now + delay.max(1);
+        let id = self.next_id;
+        self.next_id += 1;
+        let slot = (at % self.slots.len() as u64) as usize;
+        self.slots[slot].push(id);
+        self.timers.insert(id, (at, item));
+        self.expiries.insert((at, id));
+        id
+    }
+
+    pub fn expires_at(&self, id: TimerId) -> Option<u64> {
+        self.timers.get(&id).map(|&(at, _)| at)
+    }
+
+    pub fn cancel(&mut self, id: TimerId) -> Option<T> {
+        let (at, item) = self.timers.remove(&id)?;
+        self.expiries.remove(&(at, id));
+        Some(item)
+    }
+
+    pub fn next_expiry(&self) -> Option<u64> {
+        self.expiries.first().map(|&(at, _)| at)
+    }
+
+    // 前进一格，返回到期的定时器，按调度的先后顺序
+    pub fn tick(&mut self) -> Vec<T> {
+        self.now += 1;
+        let slot = (self.now % self.slots.len() as u64) as usize;
+        let mut fired = Vec::new();
+        for id in std::mem::take(&mut self.slots[slot]) {
+            match self.expires_at(id) {
+                Some(at) if at == self.now => fired.extend(self.cancel(id)),
+                // 还要再转几圈
+                Some(_) => self.slots[slot].push(id),
+                // 已经取消
+                None => {}
+            }
+        }
+        fired
+    }
+
+    // 没有定时器到期的格子直接跳过，未到期的定时器都在 now 之后，跳到最早到期的前一格不会错过
+    pub fn advance_to(&mut self, time: u64) -> Vec<T> {
+        let mut fired = Vec::new();
+        while self.now < time {
+            let next = self.next_expiry().map_or(time, |at| at.min(time));
+            self.now = next - 1;
+            fired.extend(self.tick());
+        }
+        fired
+    }
+}
+
+/* ---------------------------- 调度器 ---------------------------- */
+
+pub type TaskId = usize;
+
+// 调度器中的优先级：截止时间相同时编号小(先提交)的先运行
+pub type Key = (u64, TaskId);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Task {
+    pub id: TaskId,
+    pub name: String,
+    pub deadline: u64,
+    pub cost: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Completed {
+    pub task: Task,
+    pub start: u64,
+    pub finish: u64,
+}
+
+impl Completed {
+    pub fn late(&self) -> bool {
+        self.finish > self.task.deadline
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchedError {
+    // 任务不存在、已经运行或已经取消
+    NotPending(TaskId),
+    LaterDeadline { current: u64, requested: u64 },
+}
+
+impl fmt::Display for SchedError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SchedError::NotPending(id) => write!(f, "task {} is not pending", id),
+            SchedError::LaterDeadline { current, requested } => write!(
+                f,
+                "deadline can only move earlier: {} -> {}",
+                current, requested
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SchedError {}
+
+enum State<H> {
+    // 还在时间轮上
+    Delayed(Task, TimerId),
+    // 在就绪队列中
+    Ready(Task, H),
+    // 已经运行或取消
+    Done,
+}
+
+/*
+ * 单机、不可抢占的 EDF 调度：每次从就绪队列中取截止时间最早的任务运行 cost 个时间单位。
+ * submit_after 提交的任务先挂在时间轮上，到期后才进入就绪队列；没有就绪任务时时钟直接跳到下一个任务到期
+ */
+pub struct Scheduler<Q: PriorityQueue<Key>> {
+    queue: Q,
+    wheel: TimerWheel<TaskId>,
+    tasks: Vec<State<Q::Handle>>,
+    now: u64,
+}
+
+impl<Q: PriorityQueue<Key> + Default> Scheduler<Q> {
+    pub fn new(wheel_slots: usize) -> Self {
+        Scheduler {
+            queue: Q::default(),
+            wheel: TimerWheel::new(wheel_slots),
+            tasks: Vec::new(),
+            now: 0,
+        }
+    }
+
+    pub fn now(&self) -> u64 {
+        self.now
+    }
+
+    // 还没有运行也没有取消的任务数
+    pub fn pending(&self) -> usize {
+        self.queue.len() + self.wheel.len()
+    }
+
+    pub fn submit(&mut self, name: impl Into<String>, deadline: u64, cost: u64) -> TaskId {
+        self.submit_after(0, name, deadline, cost)
+    }
+
+    pub fn submit_after(
+        &mut self,
+        delay: u64,
+        name: impl Into<String>,
+        deadline: u64,
+        cost: u64,
+    ) -> TaskId {
+        let id = self.tasks.len();
+        let task = Task {
+            id,
+            name: name.into(),
+            deadline,
+            cost,
+        };
+        let state = if delay == 0 {
+            State::Ready(task, self.queue.push((deadline, id), id))
+        } else {
+            // 时间轮的时钟只在需要时追上调度器，先同步再按相对时间挂上去
+            self.catch_up();
+            State::Delayed(task, self.wheel.schedule(delay, id))
+        };
+        self.tasks.push(state);
+        id
+    }
+
+    // 提前截止时间；延后会破坏 decrease_key 的前提，返回错误
+    pub fn expedite(&mut self, id: TaskId, deadline: u64) -> Result<(), SchedError> {
+        let (task, handle) = match self.tasks.get_mut(id) {
+            Some(State::Delayed(task, _)) => (task, None),
+            Some(State::Ready(task, handle)) => (task, Some(*handle)),
+            _ => return Err(SchedError::NotPending(id)),
+        };
+        if deadline > task.deadline {
+            return Err(SchedError::LaterDeadline {
+                current: task.deadline,
+                requested: deadline,
+            });
+        }
+        task.deadline = deadline;
+        if let Some(handle) = handle {
+            self.queue
+                .decrease_key(handle, (deadline, id))
+                .expect("ready task is in the queue");
+        }
+        Ok(())
+    }
+
+    pub fn cancel(&mut self, id: TaskId) -> Option<Task> {
+        match std::mem::replace(self.tasks.get_mut(id)?, State::Done) {
+            State::Delayed(task, timer) => {
+                self.wheel.cancel(timer);
+                Some(task)
+            }
+            State::Ready(task, handle) => {
+                self.queue.remove(handle);
+                Some(task)
+            }
+            State::Done => None,
+        }
+    }
+
+    // 运行下一个任务；没有任何待运行的任务时返回 None
+    pub fn run_next(&mut self) -> Option<Completed> {
+        let id = loop {
+            self.catch_up();
+            if let Some((_, id)) = self.queue.pop() {
+                break id;
+            }
+            // 空转：直接跳到下一个延迟任务到期的时刻
+            self.now = self.wheel.next_expiry()?;
+        };
+        let State::Ready(task, _) = std::mem::replace(&mut self.tasks[id], State::Done) else {
+            unreachable!("queued task is ready");
+        };
+        let start = self.now;
+        self.now += task.cost;
+        Some(Completed {
+            task,
+            start,
+            finish: self.now,
+        })
+    }
+
+    pub fn run_all(&mut self) -> Vec<Completed> {
+        std::iter::from_fn(|| self.run_next()).collect()
+    }
+
+    // 时间轮追到当前时刻，到期的延迟任务进入就绪队列
+    fn catch_up(&mut self) {
+        for id in self.wheel.advance_to(self.now) {
+            if let State::Delayed(task, _) = std::mem::replace(&mut self.tasks[id], State::Done) {
+                let handle = self.queue.push((task.deadline, id), id);
+                self.tasks[id] = State::Ready(task, handle);
+            }
+        }
+    }
+}
+
+/* ---------------------------- 练习 ---------------------------- */
+
+fn timer_wheel_practice() {
+    let mut wheel = TimerWheel::new(8);
+    let a = wheel.schedule(3, "a");
+    let b = wheel.schedule(11, "b"); // 和 a 落在同一个槽位，要多转一圈
+    wheel.schedule(0, "c");
+    assert_eq!(wheel.expires_at(b), Some(11));
+    assert_eq!(wheel.tick(), ["c"]);
+    assert_eq!(wheel.advance_to(10), ["a"]);
+    assert_eq!(wheel.expires_at(a), None);
+    assert_eq!(wheel.cancel(b), Some("b"));
+    assert!(wheel.advance_to(20).is_empty() && wheel.is_empty());
+    assert_eq!(wheel.now(), 20);
+}
+
+fn scheduler_practice() {
+    let mut s: Scheduler<MinHeap<Key, TaskId>> = Scheduler::new(8);
+    let build = s.submit("build", 10, 4);
+    s.submit("lint", 5, 2);
+    s.submit_after(3, "docs", 6, 1);
+    let deploy = s.submit("deploy", 20, 3);
+    let bench = s.submit("bench", 30, 5);
+    assert_eq!(s.cancel(bench).map(|t| t.name), Some("bench".to_string()));
+    assert_eq!(s.cancel(bench), None);
+    assert_eq!(s.expedite(bench, 1), Err(SchedError::NotPending(bench)));
+    let err = s.expedite(build, 12).unwrap_err();
+    assert_eq!(err.to_string(), "deadline can only move earlier: 10 -> 12");
+    s.expedite(deploy, 1).unwrap();
+    assert_eq!(s.pending(), 4);
+
+    // deploy 0..3(超时)，lint 3..5，docs 在 3 时到期、5..6，build 6..10
+    let done = s.run_all();
+    let names: Vec<&str> = done.iter().map(|c| c.task.name.as_str()).collect();
+    assert_eq!(names, ["deploy", "lint", "docs", "build"]);
+    let finish: Vec<u64> = done.iter().map(|c| c.finish).collect();
+    assert_eq!(finish, [3, 5, 6, 10]);
+    let late: Vec<bool> = done.iter().map(Completed::late).collect();
+    assert_eq!(late, [true, false, false, false]);
+    assert_eq!((s.now(), s.pending()), (10, 0));
+
+    // 没有就绪任务时空转到延迟任务到期
+    s.submit_after(5, "later", 100, 1);
+    let c = s.run_next().unwrap();
+    assert_eq!((c.start, c.finish), (15, 16));
+    // 时钟跳过中间的时刻，很远的到期时刻也不需要逐格前进
+    s.submit_after(1 << 40, "far", u64::MAX, 1);
+    let c = s.run_next().unwrap();
+    assert_eq!((c.start, s.pending()), (16 + (1 << 40), 0));
+    assert_eq!(s.run_next(), None);
+}
+
+pub fn pq_practice() {
+    let mut heap = MinHeap::new();
+    let write = heap.push(3, "write");
+    heap.push(1, "read");
+    let flush = heap.push(5, "flush");
+    assert_eq!(heap.peek(), Some((&1, &"read")));
+    heap.decrease_key(flush, 0).unwrap();
+    assert_eq!(heap.get(flush), Some((&0, &"flush")));
+    assert_eq!(heap.decrease_key(write, 4), Err(HeapError::Increase));
+    assert_eq!(heap.remove(write), Some((3, "write")));
+    assert_eq!(heap.get(write), None);
+    let order: Vec<&str> = std::iter::from_fn(|| heap.pop()).map(|(_, s)| s).collect();
+    assert_eq!(order, ["flush", "read"]);
+    assert!(heap.is_empty());
+    assert_eq!(
+        HeapError::Missing.to_string(),
+        "element is no longer in the heap"
+    );
+
+    let mut heap = PairingHeap::new();
+    let mut rng = XorShift::new(5000);
+    let mut values: Vec<u64> = (0..1000).map(|_| rng.next_u64() % 500).collect();
+    let handles: Vec<Handle> = values.iter().map(|&v| heap.push(v, v)).collect();
+    assert_eq!(heap.get(handles[0]), Some((&values[0], &values[0])));
+    values.sort_unstable();
+    assert_eq!(heap.peek().map(|(p, _)| *p), Some(values[0]));
+    let sorted: Vec<u64> = std::iter::from_fn(|| heap.pop()).map(|(p, _)| p).collect();
+    assert_eq!(sorted, values);
+    assert!(heap.is_empty() && heap.peek().is_none());
+
+    let mut index = IndexedMinHeap::new();
+    assert_eq!(index.insert(3, 10), None);
+    assert_eq!(index.insert(7, 4), None);
+    assert_eq!(index.insert(3, 20), Some(10));
+    assert_eq!(index.priority(3), Some(&20));
+    assert_eq!(index.peek(), Some((7, &4)));
+    index.decrease_key(3, 2).unwrap();
+    assert_eq!(index.pop(), Some((3, 2)));
+    assert!(!index.contains(3) && index.contains(7));
+    assert_eq!(index.decrease_key(3, 1), Err(HeapError::Missing));
+    assert_eq!(index.remove(7), Some(4));
+    assert!(PriorityQueue::is_empty(&index));
+
+    timer_wheel_practice();
+    scheduler_practice();
+    println!("pq: all checks passed");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    // 三种堆共用的随机测试，模型是 元素 -> (优先级, 句柄)
+    fn check_queue<Q: PriorityQueue<u64> + Default>(seed: u64) {
+        let mut rng = XorShift::new(seed);
+        let mut q = Q::default();
+        let mut model: BTreeMap<usize, (u64, Q::Handle)> = BTreeMap::new();
+        let mut gone = Vec::new();
+        let mut next_item = 0;
+        for _ in 0..5000 {
+            match rng.next_u64() % 7 {
+                0..=2 => {
+                    let priority = rng.next_u64() % 100;
+                    model.insert(next_item, (priority, q.push(priority, next_item)));
+                    next_item += 1;
+                }
+                3 => {
+                    let min = model.values().map(|&(p, _)| p).min();
+                    match q.pop() {
+                        Some((priority, item)) => {
+                            assert_eq!(Some(priority), min);
+                            let (expected, handle) =
+                                model.remove(&item).expect("popped item is in the model");
+                            assert_eq!(priority, expected);
+                            gone.push(handle);
+                        }
+                        None => assert!(min.is_none()),
+                    }
+                }
+                4 if !model.is_empty() => {
+                    let k = rng.next_u64() as usize % model.len();
+                    let (&item, &(priority, handle)) = model.iter().nth(k).unwrap();
+                    if priority < 99 {
+                        assert_eq!(
+                            q.decrease_key(handle, priority + 1),
+                            Err(HeapError::Increase)
+                        );
+                    }
+                    let lower = rng.next_u64() % (priority + 1);
+                    assert_eq!(q.decrease_key(handle, lower), Ok(()));
+                    model.insert(item, (lower, handle));
+                }
+                5 if !model.is_empty() => {
+                    let k = rng.next_u64() as usize % model.len();
+                    let (&item, &(priority, handle)) = model.iter().nth(k).unwrap();
+                    assert_eq!(q.remove(handle), Some((priority, item)));
+                    model.remove(&item);
+                    gone.push(handle);
+                }
+                _ if !gone.is_empty() => {
+                    // 出堆后的句柄(包括槽位已被复用的)必须失效
+                    let handle = gone[rng.next_u64() as usize % gone.len()];
+                    assert_eq!(q.decrease_key(handle, 0), Err(HeapError::Missing));
+                    assert_eq!(q.remove(handle), None);
+                }
+                _ => {}
+            }
+            assert_eq!(q.len(), model.len());
+        }
+        let mut last = 0;
+        while let Some((priority, item)) = q.pop() {
+            assert!(priority >= last);
+            last = priority;
+            assert_eq!(model.remove(&item).map(|(p, _)| p), Some(priority));
+        }
+        assert!(q.is_empty() && model.is_empty());
+    }
+
+    // 调度器的朴素模型：每一步线性扫描所有任务
+    struct ModelTask {
+        release: u64,
+        deadline: u64,
+        cost: u64,
+        pending: bool,
+    }
+
+    struct ModelScheduler {
+        tasks: Vec<ModelTask>,
+        now: u64,
+    }
+
+    impl ModelScheduler {
+        fn expedite(&mut self, id: TaskId, deadline: u64) -> Result<(), SchedError> {
+            match self.tasks.get_mut(id) {
+                Some(t) if t.pending && deadline > t.deadline => Err(SchedError::LaterDeadline {
+                    current: t.deadline,
+                    requested: deadline,
+                }),
+                Some(t) if t.pending => {
+                    t.deadline = deadline;
+                    Ok(())
+                }
+                _ => Err(SchedError::NotPending(id)),
+            }
+        }
+
+        fn cancel(&mut self, id: TaskId) -> bool {
+            self.tasks
+                .get_mut(id)
+                .is_some_and(|t| std::mem::replace(&mut t.pending, false))
+        }
+
+        // 返回 (编号, 开始, 结束)
+        fn run_next(&mut self) -> Option<(TaskId, u64, u64)> {
+            loop {
+                let now = self.now;
+                let ready = self
+                    .tasks
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, t)| t.pending && t.release <= now)
+                    .min_by_key(|(id, t)| (t.deadline, *id))
+                    .map(|(id, _)| id);
+                if let Some(id) = ready {
+                    let task = &mut self.tasks[id];
+                    task.pending = false;
+                    self.now += task.cost;
+                    return Some((id, now, self.now));
+                }
+                self.now = self
+                    .tasks
+                    .iter()
+                    .filter(|t| t.pending)
+                    .map(|t| t.release)
+                    .min()?;
+            }
+        }
+    }
+
+    fn check_scheduler<Q: PriorityQueue<Key> + Default>(seed: u64) -> Vec<(TaskId, u64, u64)> {
+        let mut rng = XorShift::new(seed);
+        let mut sched: Scheduler<Q> = Scheduler::new(16);
+        let mut model = ModelScheduler {
+            tasks: Vec::new(),
+            now: 0,
+        };
+        let mut trace = Vec::new();
+        for _ in 0..2000 {
+            match rng.next_u64() % 5 {
+                0 | 1 => {
+                    let delay = if rng.next_u64().is_multiple_of(2) {
+                        0
+                    } else {
+                        rng.next_u64() % 40
+                    };
+                    let deadline = sched.now() + rng.next_u64() % 60;
+                    let cost = 1 + rng.next_u64() % 5;
+                    let id = sched.submit_after(delay, "task", deadline, cost);
+                    assert_eq!(id, model.tasks.len());
+                    model.tasks.push(ModelTask {
+                        release: model.now + delay,
+                        deadline,
+                        cost,
+                        pending: true,
+                    });
+                }
+                2 => {
+                    let id = rng.next_u64() as usize % (model.tasks.len() + 1);
+                    let deadline = sched.now() + rng.next_u64() % 60;
+                    assert_eq!(sched.expedite(id, deadline), model.expedite(id, deadline));
+                }
+                3 => {
+                    let id = rng.next_u64() as usize % (model.tasks.len() + 1);
+                    assert_eq!(
+                        sched.cancel(id).map(|t| t.id),
+                        model.cancel(id).then_some(id)
+                    );
+                }
+                _ => {
+                    let done = sched.run_next();
+                    let expected = model.run_next();
+                    if let (Some(done), Some((_, _, finish))) = (&done, expected) {
+                        assert_eq!(done.late(), finish > model.tasks[done.task.id].deadline);
+                    }
+                    let done = done.map(|c| (c.task.id, c.start, c.finish));
+                    assert_eq!(done, expected);
+                    trace.extend(done);
+                }
+            }
+            assert_eq!(sched.now(), model.now);
+            assert_eq!(
+                sched.pending(),
+                model.tasks.iter().filter(|t| t.pending).count()
+            );
+        }
+        for c in sched.run_all() {
+            let done = (c.task.id, c.start, c.finish);
+            assert_eq!(Some(done), model.run_next());
+            trace.push(done);
+        }
+        assert_eq!(model.run_next(), None);
+        trace
+    }
+
+    #[test]
+    fn heaps_match_model() {
+        for seed in [5010, 5011] {
+            check_queue::<MinHeap<u64, usize>>(seed);
+            check_queue::<PairingHeap<u64, usize>>(seed);
+            check_queue::<IndexedMinHeap<u64>>(seed);
+        }
+    }
+
+    #[test]
+    fn timer_wheel_matches_model() {
+        // 模型：(到期时刻, 编号) -> 内容，到期顺序就是 BTreeMap 的顺序
+        let mut rng = XorShift::new(5001);
+        let mut wheel = TimerWheel::new(16);
+        let mut model: BTreeMap<(u64, TimerId), u64> = BTreeMap::new();
+        let mut scheduled = Vec::new();
+        for _ in 0..3000 {
+            match rng.next_u64() % 3 {
+                0 => {
+                    let (delay, value) = (rng.next_u64() % 50, rng.next_u64());
+                    let id = wheel.schedule(delay, value);
+                    let at = wheel.now() + delay.max(1);
+                    model.insert((at, id), value);
+                    scheduled.push((at, id));
+                }
+                1 if !scheduled.is_empty() => {
+                    let (at, id) = scheduled[rng.next_u64() as usize % scheduled.len()];
+                    assert_eq!(wheel.cancel(id), model.remove(&(at, id)));
+                }
+                _ => {
+                    let to = wheel.now() + rng.next_u64() % 20;
+                    let due: Vec<(u64, TimerId)> =
+                        model.range(..(to + 1, 0)).map(|(&k, _)| k).collect();
+                    let expected: Vec<u64> = due.iter().filter_map(|k| model.remove(k)).collect();
+                    assert_eq!(wheel.advance_to(to), expected);
+                }
+            }
+            assert_eq!(wheel.len(), model.len());
+            assert_eq!(wheel.next_expiry(), model.keys().next().map(|&(at, _)| at));
+        }
+    }
+
+    #[test]
+    fn schedulers_match_model() {
+        // 三种后端对同一组操作给出相同的调度结果，并且都和模型一致
+        for seed in [5002, 5003, 5004] {
+            let binary = check_scheduler::<MinHeap<Key, TaskId>>(seed);
+            assert_eq!(binary, check_scheduler::<PairingHeap<Key, TaskId>>(seed));
+            assert_eq!(binary, check_scheduler::<IndexedMinHeap<Key>>(seed));
+            assert!(binary.len() > 500);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "index 3 is already in the heap")]
+    fn indexed_push_rejects_duplicates() {
+        let mut heap = IndexedMinHeap::new();
+        PriorityQueue::push(&mut heap, 10, 3);
+        PriorityQueue::push(&mut heap, 5, 3);
+    }
+
+    #[test]
+    fn indexed_insert_overwrites() {
+        let mut heap = IndexedMinHeap::new();
+        PriorityQueue::push(&mut heap, 10, 3);
+        assert_eq!(heap.insert(3, 5), Some(10));
+        assert_eq!(heap.len(), 1);
+        // 出堆后同一个下标可以重新 push
+        assert_eq!(PriorityQueue::pop(&mut heap), Some((5, 3)));
+        PriorityQueue::push(&mut heap, 7, 3);
+        assert_eq!(heap.peek(), Some((3, &7)));
+    }
+}
@@ -0,0 +1,621 @@
+//! 裸指针实验的安全封装
+//! type_parctice 中的地址运算和函数指针 transmute、extend_lifetime / shorten_invariant_lifetime 的生命周期 transmute、
+//! 以及 MyBox / MyBox1 为裸指针实现 Send / Sync，这里各自改写成对外只暴露安全接口的类型：
+//! - Offset / RawSlice：带类型的偏移访问，越界和未对齐的字节偏移返回错误
+//! - FnRegistry：函数地址只能换回登记过的函数指针，不需要 transmute
+//! - leak_r1 / shorten / reborrow / scoped_sum：用所有权、协变和作用域线程代替生命周期 transmute
+//! - SendPtr / SharedPtr：独占的和借用的跨线程指针，构造时检查非空和对齐
+//!
+//! 所有 unsafe 块都写明了成立条件，tests 模块覆盖了每一条 unsafe 路径，可以在 Miri 下运行检查未定义行为：
+//!   cargo +nightly miri test raw_ptr
+
+use std::fmt;
+use std::marker::PhantomData;
+use std::mem::{align_of, size_of, ManuallyDrop};
+use std::ptr::NonNull;
+use std::thread;
+
+use crate::R1;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PtrError {
+    OutOfBounds { index: usize, len: usize },
+    // 字节偏移不是元素大小的整数倍，或者地址不满足对齐要求
+    Misaligned { bytes: usize, align: usize },
+    // 零大小类型的所有元素都在偏移 0，非零的字节偏移没有对应的下标
+    ZeroSized { bytes: usize },
+    Aliased { index: usize },
+    Null,
+    UnknownFn { addr: usize },
+}
+
+impl fmt::Display for PtrError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PtrError::OutOfBounds { index, len } => {
+                write!(f, "index {} out of bounds for length {}", index, len)
+            }
+            PtrError::Misaligned { bytes, align } => {
+                write!(f, "{} is not a multiple of {}", bytes, align)
+            }
+            PtrError::ZeroSized { bytes } => {
+                write!(f, "byte offset {} into a zero-sized type", bytes)
+            }
+            PtrError::Aliased { index } => write!(f, "index {} borrowed mutably twice", index),
+            PtrError::Null => write!(f, "null pointer"),
+            PtrError::UnknownFn { addr } => write!(f, "no function registered at {:#x}", addr),
+        }
+    }
+}
+
+impl std::error::Error for PtrError {}
+
+/* ---------------------------- 带类型的偏移 ---------------------------- */
+
+/*
+ * type_parctice 里的 `first_address + 4` 先把指针转成整数再转回来：
+ * - 整数丢掉了指针的来源(provenance)，Miri 默认会警告，加上 -Zmiri-strict-provenance 直接报错
+ * - 4 是手写的 size_of::<i32>()，换成别的元素类型就错了，也没有检查越界
+ * Offset<T> 以元素为单位记录偏移，RawSlice 用 ptr.add 计算地址，始终保留来源并检查边界
+ */
+pub struct Offset<T> {
+    index: usize,
+    _elem: PhantomData<fn() -> T>,
+}
+
+impl<T> Offset<T> {
+    pub const fn new(index: usize) -> Self {
+        Offset {
+            index,
+            _elem: PhantomData,
+        }
+    }
+
+    // 字节偏移必须落在元素边界上
+    pub fn from_bytes(bytes: usize) -> Result<Self, PtrError> {
+        let size = size_of::<T>();
+        if size == 0 {
+            return if bytes == 0 {
+                Ok(Offset::new(0))
+            } else {
+                Err(PtrError::ZeroSized { bytes })
+            };
+        }
+        if !bytes.is_multiple_of(size) {
+            return Err(PtrError::Misaligned { bytes, align: size });
+        }
+        Ok(Offset::new(bytes / size))
+    }
+
+    pub const fn index(self) -> usize {
+        self.index
+    }
+
+    pub const fn bytes(self) -> usize {
+        self.index * size_of::<T>()
+    }
+}
+
+// derive 会要求 T: Copy，偏移本身和 T 无关
+impl<T> Clone for Offset<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Offset<T> {}
+
+impl<T> fmt::Debug for Offset<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Offset({} = {} bytes)", self.index, self.bytes())
+    }
+}
+
+// 可变借用一段切片，通过裸指针按偏移读写
+pub struct RawSlice<'a, T> {
+    ptr: NonNull<T>,
+    len: usize,
+    _borrow: PhantomData<&'a mut [T]>,
+}
+
+impl<'a, T> RawSlice<'a, T> {
+    pub fn new(slice: &'a mut [T]) -> Self {
+        RawSlice {
+            len: slice.len(),
+            ptr: NonNull::from(slice).cast(),
+            _borrow: PhantomData,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn ptr_at(&self, offset: Offset<T>) -> Result<NonNull<T>, PtrError> {
+        if offset.index >= self.len {
+            return Err(PtrError::OutOfBounds {
+                index: offset.index,
+                len: self.len,
+            });
+        }
+        // SAFETY: index < len，结果仍在 new 借来的那段切片之内，ptr 来自切片引用，来源不变
+        Ok(unsafe { self.ptr.add(offset.index) })
+    }
+
+    pub fn get(&self, offset: Offset<T>) -> Result<&T, PtrError> {
+        // SAFETY: 指针在界内且对齐，&self 期间没有可变借用
+        self.ptr_at(offset).map(|p| unsafe { p.as_ref() })
+    }
+
+    pub fn get_mut(&mut self, offset: Offset<T>) -> Result<&mut T, PtrError> {
+        // SAFETY: 同上，&mut self 保证这是唯一的借用
+        self.ptr_at(offset).map(|mut p| unsafe { p.as_mut() })
+    }
+
+    // 同时可变借用两个不同位置，相同时返回 Aliased
+    pub fn pair_mut(&mut self, a: Offset<T>, b: Offset<T>) -> Result<(&mut T, &mut T), PtrError> {
+        if a.index == b.index {
+            return Err(PtrError::Aliased { index: a.index });
+        }
+        let (mut pa, mut pb) = (self.ptr_at(a)?, self.ptr_at(b)?);
+        // SAFETY: 两个下标不同，指向不重叠的元素，两个 &mut 不会别名
+        Ok(unsafe { (pa.as_mut(), pb.as_mut()) })
+    }
+
+    pub fn replace(&mut self, offset: Offset<T>, value: T) -> Result<T, PtrError> {
+        self.get_mut(offset)
+            .map(|slot| std::mem::replace(slot, value))
+    }
+}
+
+/* ---------------------------- 函数指针 ---------------------------- */
+
+/*
+ * 非捕获的函数项可以直接强制转换成函数指针：`let f: fn() -> i32 = foo_3;`，不需要 transmute。
+ * 只有拿到的是一个地址(比如从 C 回调或表里读出来的 *const ())时才需要转回去，
+ * 这时 transmute 无法检查地址是不是真的指向签名相同的函数。
+ * FnRegistry 只接受登记过的地址：查表得到原来的函数指针，没有任何 unsafe。
+ * 注意同一个函数在不同代码生成单元可能有不同地址，不同函数也可能被合并成同一个地址，
+ * 所以地址只在同一张表内部比较
+ */
+pub type Thunk = fn() -> i32;
+
+#[derive(Default)]
+pub struct FnRegistry {
+    entries: Vec<(&'static str, Thunk)>,
+}
+
+impl FnRegistry {
+    pub fn new() -> Self {
+        FnRegistry::default()
+    }
+
+    pub fn addr_of(f: Thunk) -> *const () {
+        f as *const ()
+    }
+
+    // 返回函数地址，相当于 `foo_3 as *const ()`
+    pub fn register(&mut self, name: &'static str, f: Thunk) -> *const () {
+        self.entries.push((name, f));
+        FnRegistry::addr_of(f)
+    }
+
+    pub fn resolve(&self, addr: *const ()) -> Result<(&'static str, Thunk), PtrError> {
+        self.entries
+            .iter()
+            .find(|(_, f)| FnRegistry::addr_of(*f) == addr)
+            .copied()
+            .ok_or(PtrError::UnknownFn {
+                addr: addr as usize,
+            })
+    }
+
+    pub fn call(&self, addr: *const ()) -> Result<i32, PtrError> {
+        self.resolve(addr).map(|(_, f)| f())
+    }
+}
+
+/* ---------------------------- 生命周期 ---------------------------- */
+
+/*
+ * extend_lifetime 把 R1<'b> 直接标成 R1<'static>，原来的 i32 被释放后就是悬垂引用。
+ * 需要 'static 时应该让数据本身活得足够久：leak_r1 把值移到一块永不释放的堆内存上。
+ * 如果只是想把借用传给其他线程，用 thread::scope，编译器能证明线程在借用结束前退出(见 scoped_sum)。
+ *
+ * shorten_invariant_lifetime 把 &mut R1<'static> 变成 &mut R1<'c>，它本身就是不健全的：
+ * 拿到的 &mut R1<'c> 可以写进一个只活到 'c 的引用，函数返回后原来的 R1<'static> 就指向了已释放的栈变量，
+ * 之后读取它时 Miri 会报
+ * "Undefined Behavior: constructing invalid value of type &i32: encountered a dangling reference (use-after-free)"。
+ * 安全代码里编译器直接拒绝(rustc 1.95 的实际输出)：
+ *
+ *   fn shorten_mut<'b, 'c>(r: &'b mut R1<'static>) -> &'b mut R1<'c> { r }
+ *   // error: lifetime may not live long enough
+ *   // note: requirement occurs because of a mutable reference to `R1<'_>`
+ *   // note: mutable references are invariant over their type parameter
+ *
+ * 真正需要的通常只是读取：共享引用对类型参数协变，缩短是自动的(shorten)；
+ * 或者把里面的引用复制出来，得到一个更短的 R1(reborrow)
+ */
+pub fn leak_r1(value: i32) -> R1<'static> {
+    R1(Box::leak(Box::new(value)))
+}
+
+pub fn shorten<'c>(r: &'c R1<'static>) -> &'c R1<'c> {
+    r
+}
+
+pub fn reborrow<'b>(r: &'b mut R1<'static>) -> R1<'b> {
+    R1(r.0)
+}
+
+// 每个线程借用 values 的一部分，不需要把借用延长到 'static
+pub fn scoped_sum(values: &[i32], threads: usize) -> i32 {
+    let chunk = values.len().div_ceil(threads.max(1)).max(1);
+    thread::scope(|s| {
+        let handles: Vec<_> = values
+            .chunks(chunk)
+            .map(|part| {
+                let refs: Vec<R1<'_>> = part.iter().map(R1).collect();
+                s.spawn(move || refs.iter().map(|r| *r.0).sum::<i32>())
+            })
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).sum()
+    })
+}
+
+/* ---------------------------- 跨线程指针 ---------------------------- */
+
+/*
+ * MyBox(*mut u8) 无条件 unsafe impl Send：编译器不再检查指向的数据能不能跨线程，指针是否有效、归谁释放也没人管。
+ * SendPtr<T> 独占一块由 Box 分配的 T：
+ * - 非空且对齐(NonNull + 构造时检查)
+ * - 只有一个所有者，Drop 时释放，into_box 可以取回
+ * 移动 SendPtr 就等于移动 Box<T>，所以只在 T: Send 时实现 Send，T: Sync 时实现 Sync。
+ * (MyBox(5 as *mut u8) 那种从不解引用的地址应该直接存成 usize)
+ */
+pub struct SendPtr<T> {
+    ptr: NonNull<T>,
+    _owns: PhantomData<T>,
+}
+
+// SAFETY: SendPtr 和 Box<T> 一样独占所指向的值，跨线程移动或共享的条件与 Box<T> 相同
+unsafe impl<T: Send> Send for SendPtr<T> {}
+unsafe impl<T: Sync> Sync for SendPtr<T> {}
+
+fn check_ptr<T>(ptr: *const T) -> Result<NonNull<T>, PtrError> {
+    let nn = NonNull::new(ptr as *mut T).ok_or(PtrError::Null)?;
+    if !ptr.is_aligned() {
+        return Err(PtrError::Misaligned {
+            bytes: ptr.addr(),
+            align: align_of::<T>(),
+        });
+    }
+    Ok(nn)
+}
+
+impl<T> SendPtr<T> {
+    pub fn new(value: T) -> Self {
+        SendPtr::from_box(Box::new(value))
+    }
+
+    pub fn from_box(value: Box<T>) -> Self {
+        SendPtr {
+            ptr: NonNull::from(Box::leak(value)),
+            _owns: PhantomData,
+        }
+    }
+
+    /// # Safety
+    /// ptr 必须来自 Box::<T>::into_raw，并且之后不再通过其他途径使用或释放。
+    /// 空指针和未对齐的指针会被检查出来返回错误，所有权无法检查
+    pub unsafe fn from_raw(ptr: *mut T) -> Result<Self, PtrError> {
+        Ok(SendPtr {
+            ptr: check_ptr(ptr)?,
+            _owns: PhantomData,
+        })
+    }
+
+    pub fn as_ptr(&self) -> *const T {
+        self.ptr.as_ptr()
+    }
+
+    pub fn get(&self) -> &T {
+        // SAFETY: 指针来自 Box，在 self 存活期间有效，&self 期间没有可变借用
+        unsafe { self.ptr.as_ref() }
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        // SAFETY: 同上，&mut self 保证独占
+        unsafe { self.ptr.as_mut() }
+    }
+
+    pub fn into_box(self) -> Box<T> {
+        let this = ManuallyDrop::new(self);
+        // SAFETY: 指针来自 Box::leak 或 Box::into_raw，ManuallyDrop 保证不会再被释放一次
+        unsafe { Box::from_raw(this.ptr.as_ptr()) }
+    }
+
+    pub fn into_raw(self) -> *mut T {
+        ManuallyDrop::new(self).ptr.as_ptr()
+    }
+}
+
+impl<T> Drop for SendPtr<T> {
+    fn drop(&mut self) {
+        // SAFETY: self 仍拥有这块内存
+        drop(unsafe { Box::from_raw(self.ptr.as_ptr()) });
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for SendPtr<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("SendPtr").field(self.get()).finish()
+    }
+}
+
+/*
+ * MyBox1(*const u8) 还实现了 Sync，让多个线程同时读。
+ * SharedPtr<'a, T> 是一个不拥有数据的只读指针，生命周期 'a 保证数据比指针活得久，
+ * 和 &'a T 一样只在 T: Sync 时可以跨线程
+ */
+pub struct SharedPtr<'a, T> {
+    ptr: NonNull<T>,
+    _borrow: PhantomData<&'a T>,
+}
+
+// SAFETY: SharedPtr 只提供 &T，和 &'a T 的条件相同
+unsafe impl<T: Sync> Send for SharedPtr<'_, T> {}
+unsafe impl<T: Sync> Sync for SharedPtr<'_, T> {}
+
+impl<'a, T> SharedPtr<'a, T> {
+    pub fn new(value: &'a T) -> Self {
+        SharedPtr {
+            ptr: NonNull::from(value),
+            _borrow: PhantomData,
+        }
+    }
+
+    /// # Safety
+    /// ptr 指向的 T 在 'a 内有效，且这期间没有可变借用
+    pub unsafe fn from_raw(ptr: *const T) -> Result<Self, PtrError> {
+        Ok(SharedPtr {
+            ptr: check_ptr(ptr)?,
+            _borrow: PhantomData,
+        })
+    }
+
+    pub fn get(&self) -> &'a T {
+        // SAFETY: 由构造函数保证在 'a 内有效且只读
+        unsafe { self.ptr.as_ref() }
+    }
+}
+
+impl<T> Clone for SharedPtr<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for SharedPtr<'_, T> {}
+
+impl<T: fmt::Debug> fmt::Debug for SharedPtr<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("SharedPtr").field(self.get()).finish()
+    }
+}
+
+/* ---------------------------- 练习 ---------------------------- */
+
+// 逐条检查每个 unsafe 路径的断言在下面的 tests 模块中，Miri 下运行：
+//   cargo +nightly miri test raw_ptr
+pub fn ptr_practice() {
+    // 对应 type_parctice：第二个 i32 在第 4 个字节
+    let mut values: [i32; 2] = [1, 2];
+    let mut slots = RawSlice::new(&mut values);
+    let second = Offset::<i32>::from_bytes(4).unwrap();
+    *slots.get_mut(second).unwrap() += 1;
+    if let Ok((a, b)) = slots.pair_mut(Offset::new(0), second) {
+        std::mem::swap(a, b);
+    }
+    println!("{:?}: {:?}", second, slots.get(second));
+    println!(
+        "replaced {:?} of {}",
+        slots.replace(Offset::new(0), 7),
+        slots.len()
+    );
+    let errors = [
+        Offset::<i32>::from_bytes(3).err(),
+        Offset::<()>::from_bytes(1).err(),
+        slots.get(Offset::new(2)).err(),
+        slots.pair_mut(second, second).err(),
+    ];
+    for e in errors.iter().flatten() {
+        println!("error: {}", e);
+    }
+    println!(
+        "{} element(s) of index {} at byte {}, empty: {}",
+        values.len(),
+        second.index(),
+        second.bytes(),
+        RawSlice::new(&mut [0u8; 0]).is_empty()
+    );
+
+    // 对应 `foo_3 as *const ()` 和 transmute
+    let mut registry = FnRegistry::new();
+    let zero = registry.register("foo_3", crate::foo_3);
+    let answer = registry.register("answer", || 42);
+    println!(
+        "{:?} {:?} {:?}",
+        registry.call(zero),
+        registry.resolve(answer).map(|(name, f)| (name, f())),
+        registry
+            .call(std::ptr::without_provenance(0x1000))
+            .map_err(|e| e.to_string())
+    );
+
+    // 对应 extend_lifetime / shorten_invariant_lifetime
+    let mut forever = leak_r1(5);
+    println!("shortened: {}", shorten(&forever).0);
+    println!("reborrowed: {}", reborrow(&mut forever).0);
+    let values: Vec<i32> = (1..=100).collect();
+    println!("scoped sum = {}", scoped_sum(&values, 4));
+
+    // 对应 MyBox / MyBox1
+    let mut p = SendPtr::new(vec![1, 2, 3]);
+    p.get_mut().push(4);
+    let p = thread::spawn(move || p).join().unwrap();
+    println!("{:?}", p);
+    println!("{:?}", p.into_box());
+    let raw = SendPtr::new(String::from("raw")).into_raw();
+    // SAFETY: raw 刚由 into_raw 得到，之后只通过 back 使用
+    let back = unsafe { SendPtr::from_raw(raw) }.unwrap();
+    println!("{} at {:p}", back.get(), back.as_ptr());
+    let total = 21u64;
+    let shared = SharedPtr::new(&total);
+    // SAFETY: total 在 again 使用期间一直存在
+    let again = unsafe { SharedPtr::from_raw(&total) }.unwrap();
+    let sum: u64 = thread::scope(|s| s.spawn(move || *shared.get()).join().unwrap());
+    println!("{:?} {}", again, sum + again.get());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::OnceLock;
+
+    #[test]
+    fn offsets_are_typed_and_checked() {
+        // 对应 type_parctice：第二个 i32 在第 4 个字节
+        let mut values: [i32; 2] = [1, 2];
+        let mut slots = RawSlice::new(&mut values);
+        let second = Offset::<i32>::from_bytes(4).unwrap();
+        assert_eq!((second.index(), second.bytes()), (1, 4));
+        *slots.get_mut(second).unwrap() += 1;
+        assert_eq!(
+            Offset::<i32>::from_bytes(3).unwrap_err(),
+            PtrError::Misaligned { bytes: 3, align: 4 }
+        );
+        assert_eq!(
+            slots.get(Offset::new(2)).unwrap_err(),
+            PtrError::OutOfBounds { index: 2, len: 2 }
+        );
+        let (a, b) = slots.pair_mut(Offset::new(0), second).unwrap();
+        std::mem::swap(a, b);
+        assert_eq!(
+            slots.pair_mut(second, second).unwrap_err(),
+            PtrError::Aliased { index: 1 }
+        );
+        assert_eq!(slots.replace(Offset::new(0), 7), Ok(3));
+        assert_eq!((slots.len(), slots.is_empty()), (2, false));
+        assert_eq!(values, [7, 1]);
+
+        // 元素大小不同，同一个字节偏移对应不同下标
+        let mut wide = [0u64, 10, 20];
+        let slots = RawSlice::new(&mut wide);
+        assert_eq!(slots.get(Offset::from_bytes(16).unwrap()), Ok(&20));
+        assert_eq!(
+            format!("{:?}", Offset::<u64>::new(2)),
+            "Offset(2 = 16 bytes)"
+        );
+        // 零大小类型只有偏移 0
+        assert_eq!(Offset::<()>::from_bytes(0).unwrap().index(), 0);
+        assert_eq!(
+            Offset::<()>::from_bytes(1).unwrap_err().to_string(),
+            "byte offset 1 into a zero-sized type"
+        );
+        let mut empty: [u8; 0] = [];
+        assert!(RawSlice::new(&mut empty).is_empty());
+    }
+
+    #[test]
+    fn registry_resolves_only_registered_fns() {
+        // 对应 type_parctice 中的 `foo_3 as *const ()` 和 transmute
+        let direct: Thunk = crate::foo_3;
+        assert_eq!(direct(), 0);
+
+        let mut registry = FnRegistry::new();
+        let zero = registry.register("foo_3", crate::foo_3);
+        let answer = registry.register("answer", || 42);
+        assert_eq!(registry.call(zero), Ok(0));
+        assert_eq!(registry.call(answer), Ok(42));
+        assert_eq!(registry.resolve(answer).unwrap().0, "answer");
+        let unknown = std::ptr::without_provenance::<()>(0x1000);
+        assert_eq!(
+            registry.call(unknown).unwrap_err().to_string(),
+            "no function registered at 0x1000"
+        );
+    }
+
+    #[test]
+    fn lifetimes_without_transmute() {
+        // 泄漏的内存挂在静态变量上，Miri 的泄漏检查不会把它当成错误
+        static FOREVER: OnceLock<R1<'static>> = OnceLock::new();
+        let mut forever = R1(FOREVER.get_or_init(|| leak_r1(5)).0);
+        assert_eq!(*forever.0, 5);
+        {
+            let short = shorten(&forever);
+            assert_eq!(*short.0, 5);
+        }
+        let copy = reborrow(&mut forever);
+        assert_eq!(*copy.0, 5);
+
+        let values: Vec<i32> = (1..=100).collect();
+        assert_eq!(scoped_sum(&values, 4), 5050);
+        assert_eq!(scoped_sum(&values, 0), 5050);
+        assert_eq!(scoped_sum(&[], 3), 0);
+    }
+
+    #[test]
+    fn pointers_cross_threads() {
+        // 对应 MyBox：把独占指针移到另一个线程修改，再取回
+        let mut p = SendPtr::new(vec![1, 2, 3]);
+        p.get_mut().push(4);
+        let p = thread::spawn(move || {
+            let mut p = p;
+            p.get_mut().push(5);
+            p
+        })
+        .join()
+        .unwrap();
+        assert_eq!(format!("{:?}", p), "SendPtr([1, 2, 3, 4, 5])");
+        assert_eq!(*p.into_box(), [1, 2, 3, 4, 5]);
+
+        // into_raw / from_raw 往返，检查空指针和未对齐
+        let raw = SendPtr::new(String::from("raw")).into_raw();
+        // SAFETY: raw 刚由 into_raw 得到，之后只通过 back 使用
+        let back = unsafe { SendPtr::from_raw(raw) }.unwrap();
+        assert_eq!(back.get(), "raw");
+        assert_eq!(back.as_ptr(), raw as *const String);
+        drop(back);
+        // SAFETY: 空指针在检查时被拒绝，不会被当作所有权
+        assert_eq!(
+            unsafe { SendPtr::<u32>::from_raw(std::ptr::null_mut()) }.unwrap_err(),
+            PtrError::Null
+        );
+        let mut words = [0u32; 2];
+        let odd = words
+            .as_mut_ptr()
+            .cast::<u8>()
+            .wrapping_add(1)
+            .cast::<u32>();
+        // SAFETY: 未对齐的指针在检查时被拒绝，不会被解引用
+        let err = unsafe { SharedPtr::from_raw(odd) }.unwrap_err();
+        assert!(matches!(err, PtrError::Misaligned { align: 4, .. }));
+
+        // 对应 MyBox1：多个线程同时通过只读指针读取同一个值
+        let total = 21u64;
+        let shared = SharedPtr::new(&total);
+        let sum: u64 = thread::scope(|s| {
+            let handles: Vec<_> = (0..3).map(|_| s.spawn(move || *shared.get())).collect();
+            handles.into_iter().map(|h| h.join().unwrap()).sum()
+        });
+        assert_eq!(sum, 63);
+        // SAFETY: total 在 from_raw 返回的指针使用期间一直存在
+        let again = unsafe { SharedPtr::from_raw(&total) }.unwrap();
+        assert_eq!(*again.get(), 21);
+    }
+}
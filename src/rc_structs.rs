@@ -0,0 +1,887 @@
+//! 用 Rc<RefCell<_>> 和 Weak 构建的数据结构
+//! auto_ptr 只在字符串上演示了 Rc、RefCell 和引用计数，这里实现双向链表、带父指针的多叉树和有向图
+//! 反向指针都用 Weak，避免循环引用导致内存泄漏，测试中检查结构销毁后所有节点的强引用计数都降为 0
+
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::rc::{Rc, Weak};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// 为每个链表分配编号，防止把其他链表的节点句柄传进来
+static NEXT_LIST: AtomicUsize = AtomicUsize::new(0);
+
+/* ---------------------------- 双向链表 ---------------------------- */
+
+type Link<T> = Option<Rc<RefCell<ListNode<T>>>>;
+
+// next 持有强引用，prev 只持有弱引用
+struct ListNode<T> {
+    value: T,
+    list: usize,
+    next: Link<T>,
+    prev: Weak<RefCell<ListNode<T>>>,
+}
+
+// 节点句柄，不延长节点的生命周期
+pub struct Handle<T>(Weak<RefCell<ListNode<T>>>);
+
+impl<T> Handle<T> {
+    pub fn is_alive(&self) -> bool {
+        self.0.strong_count() > 0
+    }
+}
+
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Self {
+        Handle(self.0.clone())
+    }
+}
+
+pub struct DList<T> {
+    id: usize,
+    head: Link<T>,
+    tail: Weak<RefCell<ListNode<T>>>,
+    len: usize,
+}
+
+impl<T> DList<T> {
+    pub fn new() -> Self {
+        DList {
+            id: NEXT_LIST.fetch_add(1, Ordering::Relaxed),
+            head: None,
+            tail: Weak::new(),
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn node(&self, value: T) -> Rc<RefCell<ListNode<T>>> {
+        Rc::new(RefCell::new(ListNode {
+            value,
+            list: self.id,
+            next: None,
+            prev: Weak::new(),
+        }))
+    }
+
+    pub fn push_front(&mut self, value: T) -> Handle<T> {
+        let node = self.node(value);
+        match self.head.take() {
+            Some(old) => {
+                old.borrow_mut().prev = Rc::downgrade(&node);
+                node.borrow_mut().next = Some(old);
+            }
+            None => self.tail = Rc::downgrade(&node),
+        }
+        let handle = Handle(Rc::downgrade(&node));
+        self.head = Some(node);
+        self.len += 1;
+        handle
+    }
+
+    pub fn push_back(&mut self, value: T) -> Handle<T> {
+        match self.tail.upgrade() {
+            Some(tail) => self.link_after(&tail, value),
+            None => self.push_front(value),
+        }
+    }
+
+    fn link_after(&mut self, prev: &Rc<RefCell<ListNode<T>>>, value: T) -> Handle<T> {
+        let node = self.node(value);
+        let next = prev.borrow_mut().next.take();
+        match &next {
+            Some(n) => n.borrow_mut().prev = Rc::downgrade(&node),
+            None => self.tail = Rc::downgrade(&node),
+        }
+        {
+            let mut n = node.borrow_mut();
+            n.next = next;
+            n.prev = Rc::downgrade(prev);
+        }
+        let handle = Handle(Rc::downgrade(&node));
+        prev.borrow_mut().next = Some(node);
+        self.len += 1;
+        handle
+    }
+
+    // 句柄对应的节点已删除或不属于这个链表时返回 None
+    fn owned(&self, handle: &Handle<T>) -> Option<Rc<RefCell<ListNode<T>>>> {
+        handle.0.upgrade().filter(|n| n.borrow().list == self.id)
+    }
+
+    pub fn insert_after(&mut self, handle: &Handle<T>, value: T) -> Option<Handle<T>> {
+        let prev = self.owned(handle)?;
+        Some(self.link_after(&prev, value))
+    }
+
+    pub fn remove(&mut self, handle: &Handle<T>) -> Option<T> {
+        let node = self.owned(handle)?;
+        let (prev, next) = {
+            let mut n = node.borrow_mut();
+            (n.prev.upgrade(), n.next.take())
+        };
+        match &next {
+            Some(n) => n.borrow_mut().prev = prev.as_ref().map_or_else(Weak::new, Rc::downgrade),
+            None => self.tail = prev.as_ref().map_or_else(Weak::new, Rc::downgrade),
+        }
+        // 去掉前一个节点（或 head）对它的强引用
+        match prev {
+            Some(p) => p.borrow_mut().next = next,
+            None => self.head = next,
+        }
+        self.len -= 1;
+        let node = Rc::try_unwrap(node).ok().expect("list node still shared");
+        Some(node.into_inner().value)
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        let head = Handle(Rc::downgrade(self.head.as_ref()?));
+        self.remove(&head)
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        let tail = Handle(self.tail.clone());
+        self.remove(&tail)
+    }
+
+    pub fn get(&self, handle: &Handle<T>) -> Option<T>
+    where
+        T: Clone,
+    {
+        self.owned(handle).map(|n| n.borrow().value.clone())
+    }
+
+    pub fn to_vec(&self) -> Vec<T>
+    where
+        T: Clone,
+    {
+        let mut out = Vec::with_capacity(self.len);
+        let mut cur = self.head.clone();
+        while let Some(node) = cur {
+            out.push(node.borrow().value.clone());
+            cur = node.borrow().next.clone();
+        }
+        out
+    }
+
+    // 沿 prev 从尾到头遍历，用来检查反向指针
+    pub fn to_vec_rev(&self) -> Vec<T>
+    where
+        T: Clone,
+    {
+        let mut out = Vec::with_capacity(self.len);
+        let mut cur = self.tail.upgrade();
+        while let Some(node) = cur {
+            out.push(node.borrow().value.clone());
+            cur = node.borrow().prev.upgrade();
+        }
+        out
+    }
+}
+
+impl<T> Default for DList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// 默认的析构会沿 next 递归，链表很长时会栈溢出，这里逐个断开
+impl<T> Drop for DList<T> {
+    fn drop(&mut self) {
+        let mut cur = self.head.take();
+        while let Some(node) = cur {
+            cur = node.borrow_mut().next.take();
+        }
+    }
+}
+
+/* ---------------------------- 多叉树 ---------------------------- */
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct CycleError;
+
+impl fmt::Display for CycleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "node cannot become a descendant of itself")
+    }
+}
+
+// 子节点由父节点持有强引用，父指针是弱引用
+pub struct TreeNode<T> {
+    pub value: RefCell<T>,
+    parent: RefCell<Weak<TreeNode<T>>>,
+    children: RefCell<Vec<Rc<TreeNode<T>>>>,
+}
+
+impl<T> TreeNode<T> {
+    pub fn new(value: T) -> Rc<Self> {
+        Rc::new(TreeNode {
+            value: RefCell::new(value),
+            parent: RefCell::new(Weak::new()),
+            children: RefCell::new(Vec::new()),
+        })
+    }
+
+    pub fn parent(&self) -> Option<Rc<Self>> {
+        self.parent.borrow().upgrade()
+    }
+
+    pub fn children(&self) -> Vec<Rc<Self>> {
+        self.children.borrow().clone()
+    }
+
+    // self 是否是 other 或 other 的祖先
+    pub fn is_ancestor_of(self: &Rc<Self>, other: &Rc<Self>) -> bool {
+        let mut cur = Some(other.clone());
+        while let Some(node) = cur {
+            if Rc::ptr_eq(self, &node) {
+                return true;
+            }
+            cur = node.parent();
+        }
+        false
+    }
+
+    // child 原来有父节点时先从原来的位置移走；child 是 self 的祖先时会形成强引用环，拒绝
+    pub fn add_child(self: &Rc<Self>, child: Rc<Self>) -> Result<(), CycleError> {
+        // 没有子节点的节点只可能是它自己的祖先，不用沿父指针向上找
+        let has_children = !child.children.borrow().is_empty();
+        if Rc::ptr_eq(&child, self) || (has_children && child.is_ancestor_of(self)) {
+            return Err(CycleError);
+        }
+        child.detach();
+        *child.parent.borrow_mut() = Rc::downgrade(self);
+        self.children.borrow_mut().push(child);
+        Ok(())
+    }
+
+    pub fn detach(self: &Rc<Self>) {
+        if let Some(parent) = self.parent() {
+            parent
+                .children
+                .borrow_mut()
+                .retain(|c| !Rc::ptr_eq(c, self));
+        }
+        *self.parent.borrow_mut() = Weak::new();
+    }
+
+    pub fn depth(&self) -> usize {
+        let mut depth = 0;
+        let mut cur = self.parent();
+        while let Some(node) = cur {
+            depth += 1;
+            cur = node.parent();
+        }
+        depth
+    }
+
+    pub fn root(self: &Rc<Self>) -> Rc<Self> {
+        let mut cur = self.clone();
+        while let Some(parent) = cur.parent() {
+            cur = parent;
+        }
+        cur
+    }
+
+    // 先序深度优先
+    pub fn dfs(self: &Rc<Self>) -> Vec<Rc<Self>> {
+        let mut out = Vec::new();
+        let mut stack = vec![self.clone()];
+        while let Some(node) = stack.pop() {
+            stack.extend(node.children.borrow().iter().rev().cloned());
+            out.push(node);
+        }
+        out
+    }
+
+    pub fn bfs(self: &Rc<Self>) -> Vec<Rc<Self>> {
+        let mut out = Vec::new();
+        let mut queue = VecDeque::from([self.clone()]);
+        while let Some(node) = queue.pop_front() {
+            queue.extend(node.children.borrow().iter().cloned());
+            out.push(node);
+        }
+        out
+    }
+}
+
+// 与链表相同，树很深时避免递归析构
+impl<T> Drop for TreeNode<T> {
+    fn drop(&mut self) {
+        let mut stack = self.children.take();
+        while let Some(node) = stack.pop() {
+            if let Ok(node) = Rc::try_unwrap(node) {
+                stack.append(&mut node.children.take());
+            }
+        }
+    }
+}
+
+/* ---------------------------- 有向图 ---------------------------- */
+
+// 图持有所有节点的强引用，边只是弱引用，因此图中的环不会造成泄漏
+pub struct GraphNode<T> {
+    id: usize,
+    pub value: T,
+    edges: RefCell<Vec<Weak<GraphNode<T>>>>,
+}
+
+impl<T> GraphNode<T> {
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    // 已删除的节点会被跳过
+    pub fn neighbors(&self) -> Nodes<T> {
+        self.edges
+            .borrow()
+            .iter()
+            .filter_map(Weak::upgrade)
+            .collect()
+    }
+}
+
+pub type Nodes<T> = Vec<Rc<GraphNode<T>>>;
+
+pub struct Graph<T> {
+    nodes: Nodes<T>,
+    next_id: usize,
+}
+
+impl<T> Graph<T> {
+    pub fn new() -> Self {
+        Graph {
+            nodes: Vec::new(),
+            next_id: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn nodes(&self) -> &[Rc<GraphNode<T>>] {
+        &self.nodes
+    }
+
+    pub fn add_node(&mut self, value: T) -> Rc<GraphNode<T>> {
+        let node = Rc::new(GraphNode {
+            id: self.next_id,
+            value,
+            edges: RefCell::new(Vec::new()),
+        });
+        self.next_id += 1;
+        self.nodes.push(node.clone());
+        node
+    }
+
+    fn contains(&self, node: &Rc<GraphNode<T>>) -> bool {
+        self.nodes.iter().any(|n| Rc::ptr_eq(n, node))
+    }
+
+    // 重复的边只保留一条，返回是否新增
+    pub fn add_edge(&mut self, from: &Rc<GraphNode<T>>, to: &Rc<GraphNode<T>>) -> bool {
+        if !self.contains(from) || !self.contains(to) {
+            return false;
+        }
+        let mut edges = from.edges.borrow_mut();
+        if edges.iter().any(|e| e.as_ptr() == Rc::as_ptr(to)) {
+            return false;
+        }
+        edges.push(Rc::downgrade(to));
+        true
+    }
+
+    pub fn remove_edge(&mut self, from: &Rc<GraphNode<T>>, to: &Rc<GraphNode<T>>) -> bool {
+        let mut edges = from.edges.borrow_mut();
+        let before = edges.len();
+        edges.retain(|e| e.as_ptr() != Rc::as_ptr(to));
+        edges.len() != before
+    }
+
+    // 同时删除所有指向它的边
+    pub fn remove_node(&mut self, node: &Rc<GraphNode<T>>) -> bool {
+        let before = self.nodes.len();
+        self.nodes.retain(|n| !Rc::ptr_eq(n, node));
+        if self.nodes.len() == before {
+            return false;
+        }
+        for n in &self.nodes {
+            n.edges
+                .borrow_mut()
+                .retain(|e| e.as_ptr() != Rc::as_ptr(node));
+        }
+        node.edges.borrow_mut().clear();
+        true
+    }
+
+    pub fn dfs(&self, start: &Rc<GraphNode<T>>) -> Nodes<T> {
+        let mut seen = HashSet::new();
+        let mut out = Vec::new();
+        let mut stack = vec![start.clone()];
+        while let Some(node) = stack.pop() {
+            if !seen.insert(node.id) {
+                continue;
+            }
+            stack.extend(node.neighbors().into_iter().rev());
+            out.push(node);
+        }
+        out
+    }
+
+    pub fn bfs(&self, start: &Rc<GraphNode<T>>) -> Nodes<T> {
+        let mut seen = HashSet::from([start.id]);
+        let mut out = Vec::new();
+        let mut queue = VecDeque::from([start.clone()]);
+        while let Some(node) = queue.pop_front() {
+            for next in node.neighbors() {
+                if seen.insert(next.id) {
+                    queue.push_back(next);
+                }
+            }
+            out.push(node);
+        }
+        out
+    }
+
+    // Kahn 算法，入度相同时按加入顺序；有环时返回环上的节点
+    pub fn topo_sort(&self) -> Result<Nodes<T>, Nodes<T>> {
+        let mut indegree: HashMap<usize, usize> = self.nodes.iter().map(|n| (n.id, 0)).collect();
+        for node in &self.nodes {
+            for next in node.neighbors() {
+                *indegree.get_mut(&next.id).unwrap() += 1;
+            }
+        }
+        let mut queue: VecDeque<_> = self
+            .nodes
+            .iter()
+            .filter(|n| indegree[&n.id] == 0)
+            .cloned()
+            .collect();
+        let mut out = Vec::with_capacity(self.nodes.len());
+        while let Some(node) = queue.pop_front() {
+            for next in node.neighbors() {
+                let d = indegree.get_mut(&next.id).unwrap();
+                *d -= 1;
+                if *d == 0 {
+                    queue.push_back(next);
+                }
+            }
+            out.push(node);
+        }
+        if out.len() == self.nodes.len() {
+            Ok(out)
+        } else {
+            Err(self.find_cycle().expect("nodes left over imply a cycle"))
+        }
+    }
+
+    /*
+     * 三色深度优先：白色未访问，灰色在当前路径上，黑色已完成
+     * 沿边遇到灰色节点说明找到了环，环就是路径上从该节点开始的部分
+     */
+    pub fn find_cycle(&self) -> Option<Nodes<T>> {
+        #[derive(Clone, Copy, PartialEq)]
+        enum Color {
+            Gray,
+            Black,
+        }
+        let mut color: HashMap<usize, Color> = HashMap::new();
+        for start in &self.nodes {
+            if color.contains_key(&start.id) {
+                continue;
+            }
+            // (节点, 它的邻居, 下一个要看的邻居)
+            let mut path = vec![(start.clone(), start.neighbors(), 0)];
+            color.insert(start.id, Color::Gray);
+            while let Some((_, neighbors, i)) = path.last_mut() {
+                let Some(next) = neighbors.get(*i).cloned() else {
+                    let (done, _, _) = path.pop().unwrap();
+                    color.insert(done.id, Color::Black);
+                    continue;
+                };
+                *i += 1;
+                match color.get(&next.id) {
+                    Some(Color::Gray) => {
+                        let at = path.iter().position(|(n, _, _)| n.id == next.id).unwrap();
+                        return Some(path.drain(at..).map(|(n, _, _)| n).collect());
+                    }
+                    Some(Color::Black) => {}
+                    None => {
+                        color.insert(next.id, Color::Gray);
+                        let neighbors = next.neighbors();
+                        path.push((next, neighbors, 0));
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+impl<T> Default for Graph<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/* ---------------------------- 练习 ---------------------------- */
+
+fn values<T: Clone>(nodes: &[Rc<TreeNode<T>>]) -> Vec<T> {
+    nodes.iter().map(|n| n.value.borrow().clone()).collect()
+}
+
+fn names(nodes: &[Rc<GraphNode<&'static str>>]) -> Vec<&'static str> {
+    nodes.iter().map(|n| n.value).collect()
+}
+
+pub fn rc_structs_practice() {
+    let mut list = DList::new();
+    let b = list.push_back("b");
+    let a = list.push_front("a");
+    let d = list.push_back("d");
+    list.insert_after(&b, "c");
+    println!(
+        "list: {:?}, reversed: {:?}",
+        list.to_vec(),
+        list.to_vec_rev()
+    );
+    list.remove(&b);
+    println!(
+        "after removing b: a = {:?}, front {:?}, back {:?}, d alive {}, {} left",
+        list.get(&a),
+        list.pop_front(),
+        list.pop_back(),
+        d.is_alive(),
+        list.len()
+    );
+    assert!(!list.is_empty());
+
+    let root = TreeNode::new("root");
+    let (left, right, leaf) = (TreeNode::new("a"), TreeNode::new("b"), TreeNode::new("c"));
+    root.add_child(left.clone()).unwrap();
+    root.add_child(right.clone()).unwrap();
+    left.add_child(leaf.clone()).unwrap();
+    println!(
+        "tree dfs: {:?}, bfs: {:?}",
+        values(&root.dfs()),
+        values(&root.bfs())
+    );
+    if let Err(e) = leaf.add_child(root.clone()) {
+        println!("attach root under c: {}", e);
+    }
+    right.add_child(left.clone()).unwrap();
+    println!(
+        "moved a under b: depth of c {}, children of root {:?}, b is parent of a {}",
+        leaf.depth(),
+        values(&root.children()),
+        left.parent().is_some_and(|p| Rc::ptr_eq(&p, &right))
+    );
+    left.detach();
+    assert!(Rc::ptr_eq(&leaf.root(), &left) && !root.is_ancestor_of(&leaf));
+
+    let mut g = Graph::new();
+    let shirt = g.add_node("shirt");
+    let tie = g.add_node("tie");
+    let jacket = g.add_node("jacket");
+    g.add_edge(&shirt, &tie);
+    g.add_edge(&tie, &jacket);
+    println!(
+        "graph dfs: {:?}, bfs: {:?}, topo: {:?}",
+        names(&g.dfs(&shirt)),
+        names(&g.bfs(&shirt)),
+        g.topo_sort().map(|n| names(&n)).map_err(|n| names(&n))
+    );
+    g.add_edge(&jacket, &shirt);
+    println!("cycle: {:?}", g.find_cycle().map(|n| names(&n)));
+    g.remove_edge(&jacket, &shirt);
+    g.remove_node(&tie);
+    println!(
+        "without tie: {} nodes, shirt -> {:?}, ids {:?}",
+        g.len(),
+        names(&shirt.neighbors()),
+        g.nodes().iter().map(|n| n.id()).collect::<Vec<_>>()
+    );
+    assert!(!g.is_empty());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn all_dropped<T>(weak: &[Weak<T>]) -> bool {
+        weak.iter().all(|w| w.strong_count() == 0)
+    }
+
+    #[test]
+    fn list_frees_every_node() {
+        let mut list = DList::new();
+        let b = list.push_back("b");
+        let a = list.push_front("a");
+        let d = list.push_back("d");
+        let c = list.insert_after(&b, "c").unwrap();
+        assert_eq!(list.to_vec(), ["a", "b", "c", "d"]);
+        assert_eq!(list.to_vec_rev(), ["d", "c", "b", "a"]);
+        assert_eq!(list.len(), 4);
+
+        // 删除中间、头、尾
+        assert_eq!(list.remove(&c), Some("c"));
+        assert!(!c.is_alive());
+        assert_eq!(list.remove(&c), None);
+        assert_eq!(list.to_vec_rev(), ["d", "b", "a"]);
+        assert_eq!(list.pop_front(), Some("a"));
+        assert!(!a.is_alive());
+        assert_eq!(list.pop_back(), Some("d"));
+        assert_eq!(list.to_vec(), ["b"]);
+        assert_eq!(list.get(&b), Some("b"));
+        assert_eq!(list.get(&d), None);
+
+        // 其他链表的句柄无效
+        let mut other = DList::new();
+        let x = other.push_back("x");
+        assert_eq!(list.remove(&x), None);
+        assert_eq!(list.insert_after(&x, "y").map(|_| ()), None);
+        assert_eq!(other.pop_back(), Some("x"));
+        assert_eq!(other.pop_back(), None);
+        assert!(other.is_empty());
+
+        // 与 VecDeque 随机比较
+        let mut rng = crate::hasher_bench::XorShift::new(44);
+        let mut model = VecDeque::new();
+        let mut handles = Vec::new();
+        let mut list = DList::default();
+        for i in 0..2000u64 {
+            match rng.next_u64() % 4 {
+                0 => {
+                    handles.push(list.push_front(i));
+                    model.push_front(i);
+                }
+                1 => {
+                    handles.push(list.push_back(i));
+                    model.push_back(i);
+                }
+                2 => assert_eq!(list.pop_front(), model.pop_front()),
+                _ => {
+                    let h = &handles[(rng.next_u64() % handles.len() as u64) as usize];
+                    if let Some(v) = list.remove(h) {
+                        let at = model.iter().position(|&m| m == v).unwrap();
+                        model.remove(at);
+                    }
+                }
+            }
+            assert_eq!(list.len(), model.len());
+        }
+        assert_eq!(list.to_vec(), Vec::from(model.clone()));
+        let mut rev = list.to_vec_rev();
+        rev.reverse();
+        assert_eq!(rev, Vec::from(model));
+
+        // 销毁后所有节点都已释放
+        drop(list);
+        assert!(handles.iter().all(|h| !h.is_alive()));
+
+        // 很长的链表不会在析构时栈溢出
+        let mut long = DList::new();
+        let first = long.push_back(0);
+        for i in 1..200_000 {
+            long.push_back(i);
+        }
+        drop(long);
+        assert!(!first.is_alive());
+    }
+
+    #[test]
+    fn tree_frees_every_node() {
+        /*
+         *        root
+         *       /    \
+         *      a      b
+         *     / \      \
+         *    c   d      e
+         */
+        let root = TreeNode::new("root");
+        let a = TreeNode::new("a");
+        let b = TreeNode::new("b");
+        let (c, d, e) = (TreeNode::new("c"), TreeNode::new("d"), TreeNode::new("e"));
+        root.add_child(a.clone()).unwrap();
+        root.add_child(b.clone()).unwrap();
+        a.add_child(c.clone()).unwrap();
+        a.add_child(d.clone()).unwrap();
+        b.add_child(e.clone()).unwrap();
+        let weak: Vec<Weak<TreeNode<&str>>> = [&root, &a, &b, &c, &d, &e]
+            .iter()
+            .map(|n| Rc::downgrade(n))
+            .collect();
+
+        assert_eq!(values(&root.dfs()), ["root", "a", "c", "d", "b", "e"]);
+        assert_eq!(values(&root.bfs()), ["root", "a", "b", "c", "d", "e"]);
+        assert_eq!(e.depth(), 2);
+        assert!(Rc::ptr_eq(&e.root(), &root));
+        assert_eq!(*d.parent().unwrap().value.borrow(), "a");
+        // 父节点和局部变量各持有一个强引用，父指针不计入
+        assert_eq!(Rc::strong_count(&a), 2);
+        assert_eq!(Rc::weak_count(&a), 3);
+
+        // 不能把祖先挂到后代下面
+        assert_eq!(c.add_child(root.clone()), Err(CycleError));
+        assert_eq!(a.add_child(a.clone()), Err(CycleError));
+        assert!(root.is_ancestor_of(&d));
+        assert!(!b.is_ancestor_of(&d));
+
+        // 移动子树
+        e.add_child(a.clone()).unwrap();
+        assert_eq!(values(&root.dfs()), ["root", "b", "e", "a", "c", "d"]);
+        assert_eq!(d.depth(), 4);
+        assert_eq!(values(&root.children()), ["b"]);
+        *c.value.borrow_mut() = "C";
+        assert_eq!(values(&e.bfs()), ["e", "a", "C", "d"]);
+
+        // 摘下的子树独立存在
+        a.detach();
+        assert!(a.parent().is_none());
+        assert_eq!(values(&root.dfs()), ["root", "b", "e"]);
+        assert_eq!(values(&a.dfs()), ["a", "C", "d"]);
+
+        drop((root, a, b, c, d, e));
+        assert!(all_dropped(&weak));
+
+        // 很深的树
+        let top = TreeNode::new(0);
+        let mut cur = top.clone();
+        for i in 1..100_000 {
+            let next = TreeNode::new(i);
+            cur.add_child(next.clone()).unwrap();
+            cur = next;
+        }
+        assert_eq!(cur.depth(), 99_999);
+        let (wt, wc) = (Rc::downgrade(&top), Rc::downgrade(&cur));
+        drop((top, cur));
+        assert!(all_dropped(&[wt, wc]));
+    }
+
+    #[test]
+    fn graph_with_cycle_frees_every_node() {
+        // shirt -> tie -> jacket <- pants <- underwear, belt -> jacket, pants -> shoes
+        let mut g = Graph::new();
+        let underwear = g.add_node("underwear");
+        let pants = g.add_node("pants");
+        let shirt = g.add_node("shirt");
+        let tie = g.add_node("tie");
+        let belt = g.add_node("belt");
+        let jacket = g.add_node("jacket");
+        let shoes = g.add_node("shoes");
+        for (from, to) in [
+            (&underwear, &pants),
+            (&pants, &shoes),
+            (&pants, &belt),
+            (&shirt, &tie),
+            (&shirt, &belt),
+            (&tie, &jacket),
+            (&belt, &jacket),
+        ] {
+            assert!(g.add_edge(from, to));
+        }
+        assert!(!g.add_edge(&shirt, &tie));
+        assert_eq!(g.len(), 7);
+        assert!(!g.is_empty() && Graph::<u8>::default().is_empty());
+
+        assert_eq!(
+            names(&g.dfs(&underwear)),
+            ["underwear", "pants", "shoes", "belt", "jacket"]
+        );
+        assert_eq!(names(&g.bfs(&shirt)), ["shirt", "tie", "belt", "jacket"]);
+        let order = g.topo_sort().ok().unwrap();
+        assert_eq!(
+            names(&order),
+            [
+                "underwear",
+                "shirt",
+                "pants",
+                "tie",
+                "shoes",
+                "belt",
+                "jacket"
+            ]
+        );
+        // 检查每条边都是从前往后
+        let pos: HashMap<usize, usize> =
+            order.iter().enumerate().map(|(i, n)| (n.id(), i)).collect();
+        for n in g.nodes() {
+            for m in n.neighbors() {
+                assert!(pos[&n.id()] < pos[&m.id()]);
+            }
+        }
+        assert!(g.find_cycle().is_none());
+
+        // 加一条边形成环
+        g.add_edge(&jacket, &shirt);
+        let cycle = g.topo_sort().err().unwrap();
+        assert_eq!(names(&cycle), ["jacket", "shirt", "tie"]);
+        drop(cycle);
+        assert!(g.remove_edge(&jacket, &shirt));
+        assert!(!g.remove_edge(&jacket, &shirt));
+        assert!(g.topo_sort().is_ok());
+
+        // 自环
+        g.add_edge(&shoes, &shoes);
+        assert_eq!(names(&g.find_cycle().unwrap()), ["shoes"]);
+        g.remove_edge(&shoes, &shoes);
+
+        // 删除节点会去掉指向它的边
+        assert!(g.remove_node(&belt));
+        assert!(!g.remove_node(&belt));
+        assert_eq!(names(&pants.neighbors()), ["shoes"]);
+        assert_eq!(names(&g.dfs(&shirt)), ["shirt", "tie", "jacket"]);
+        // 只剩局部变量 belt 和拓扑排序结果持有它
+        assert_eq!(Rc::strong_count(&belt), 2);
+        drop(order);
+        assert_eq!(Rc::strong_count(&belt), 1);
+        let foreign = Graph::new().add_node("x");
+        assert!(!g.add_edge(&shirt, &foreign));
+
+        // 图中有环，但边是弱引用，销毁后仍然全部释放
+        g.add_edge(&jacket, &underwear);
+        g.add_edge(&shoes, &underwear);
+        assert!(g.find_cycle().is_some());
+        let weak: Vec<Weak<GraphNode<&str>>> = g.nodes().iter().map(Rc::downgrade).collect();
+        drop((underwear, pants, shirt, tie, belt, jacket, shoes, foreign));
+        assert!(!all_dropped(&weak));
+        drop(g);
+        assert!(all_dropped(&weak));
+    }
+
+    #[test]
+    fn strong_cycle_leaks_until_broken() {
+        // 对比：两个节点用强引用互相指向，离开作用域后计数仍为 1，内存泄漏
+        struct Cyclic {
+            other: RefCell<Option<Rc<Cyclic>>>,
+        }
+        let a = Rc::new(Cyclic {
+            other: RefCell::new(None),
+        });
+        let b = Rc::new(Cyclic {
+            other: RefCell::new(Some(a.clone())),
+        });
+        *a.other.borrow_mut() = Some(b.clone());
+        let (wa, wb) = (Rc::downgrade(&a), Rc::downgrade(&b));
+        drop((a, b));
+        assert_eq!((wa.strong_count(), wb.strong_count()), (1, 1));
+        // 手动断开环之后才能释放
+        if let Some(a) = wa.upgrade() {
+            a.other.borrow_mut().take();
+        }
+        assert_eq!((wa.strong_count(), wb.strong_count()), (0, 0));
+    }
+}
@@ -0,0 +1,506 @@
+//! 记录的表格化输出
+//! debugout、displayout、DisplayCity 都是手工拼格式字符串打印结构体
+//! 这里由类型通过 Fields 特征描述自己的列，同一组记录可以输出为对齐表格、CSV、JSON 和 Markdown
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::feed::escape;
+use crate::frame::{self, Align, Table};
+use crate::utf8;
+use crate::{City, Deep, MinMax, Person, Point2D, Structure};
+
+/* ---------------------------- 字段描述 ---------------------------- */
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Int(i64),
+    Float(f64),
+    Text(String),
+}
+
+impl Value {
+    fn is_numeric(&self) -> bool {
+        matches!(self, Value::Int(_) | Value::Float(_))
+    }
+
+    // precision 只对浮点数有效
+    fn format(&self, precision: Option<usize>) -> String {
+        match (self, precision) {
+            (Value::Int(n), _) => n.to_string(),
+            (Value::Float(x), Some(p)) => format!("{:.*}", p, x),
+            (Value::Float(x), None) => x.to_string(),
+            (Value::Text(s), _) => s.clone(),
+        }
+    }
+}
+
+impl From<&str> for Value {
+    fn from(s: &str) -> Self {
+        Value::Text(s.to_string())
+    }
+}
+
+macro_rules! value_from {
+    ($variant:ident as $target:ty: $($t:ty),*) => {
+        $(impl From<$t> for Value {
+            fn from(v: $t) -> Self {
+                Value::$variant(v as $target)
+            }
+        })*
+    };
+}
+
+value_from!(Int as i64: i8, i16, i32, i64, u8, u16, u32);
+value_from!(Float as f64: f32, f64);
+
+#[derive(Clone, Copy, Debug)]
+pub struct Field {
+    pub name: &'static str,
+    // 浮点数默认保留的小数位数，None 表示按 Display 输出
+    pub precision: Option<usize>,
+}
+
+impl Field {
+    pub const fn new(name: &'static str) -> Self {
+        Field {
+            name,
+            precision: None,
+        }
+    }
+
+    pub const fn precision(self, precision: usize) -> Self {
+        Field {
+            precision: Some(precision),
+            ..self
+        }
+    }
+}
+
+// 需要以表格输出的类型自行实现，values 的顺序与 FIELDS 一致
+pub trait Fields {
+    const FIELDS: &'static [Field];
+    fn values(&self) -> Vec<Value>;
+}
+
+impl Fields for Structure {
+    const FIELDS: &'static [Field] = &[Field::new("value")];
+    fn values(&self) -> Vec<Value> {
+        vec![self.0.into()]
+    }
+}
+
+// 嵌套的结构体展开为内层的列
+impl Fields for Deep {
+    const FIELDS: &'static [Field] = Structure::FIELDS;
+    fn values(&self) -> Vec<Value> {
+        self.0.values()
+    }
+}
+
+impl Fields for Person<'_> {
+    const FIELDS: &'static [Field] = &[Field::new("name"), Field::new("age")];
+    fn values(&self) -> Vec<Value> {
+        vec![self.name.into(), self.age.into()]
+    }
+}
+
+impl Fields for MinMax {
+    const FIELDS: &'static [Field] = &[Field::new("min"), Field::new("max")];
+    fn values(&self) -> Vec<Value> {
+        vec![self.0.into(), self.1.into()]
+    }
+}
+
+impl Fields for Point2D {
+    const FIELDS: &'static [Field] = &[Field::new("x"), Field::new("y")];
+    fn values(&self) -> Vec<Value> {
+        vec![self.x.into(), self.y.into()]
+    }
+}
+
+// 与 City 的 Display 一致，坐标保留三位小数
+impl Fields for City<'_> {
+    const FIELDS: &'static [Field] = &[
+        Field::new("name"),
+        Field::new("lat").precision(3),
+        Field::new("lon").precision(3),
+    ];
+    fn values(&self) -> Vec<Value> {
+        vec![self.name.into(), self.lat.into(), self.lon.into()]
+    }
+}
+
+/* ---------------------------- 输出格式 ---------------------------- */
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    Table,
+    Csv,
+    Json,
+    Markdown,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "table" => Ok(Format::Table),
+            "csv" => Ok(Format::Csv),
+            "json" => Ok(Format::Json),
+            "markdown" | "md" => Ok(Format::Markdown),
+            _ => Err(format!("unknown format: {}", s)),
+        }
+    }
+}
+
+pub struct Records<'a, T: Fields> {
+    items: &'a [T],
+    precision: Vec<Option<usize>>,
+}
+
+impl<'a, T: Fields> Records<'a, T> {
+    pub fn new(items: &'a [T]) -> Self {
+        Records {
+            items,
+            precision: T::FIELDS.iter().map(|f| f.precision).collect(),
+        }
+    }
+
+    // 覆盖某一列的小数位数，列名不存在时返回错误
+    pub fn precision(mut self, column: &str, precision: usize) -> Result<Self, String> {
+        let i = T::FIELDS
+            .iter()
+            .position(|f| f.name == column)
+            .ok_or_else(|| format!("unknown column: {}", column))?;
+        self.precision[i] = Some(precision);
+        Ok(self)
+    }
+
+    fn rows(&self) -> impl Iterator<Item = Vec<Value>> + 'a {
+        self.items.iter().map(T::values)
+    }
+
+    // 一列中全是数字时右对齐
+    fn numeric(&self) -> Vec<bool> {
+        let mut numeric = vec![!self.items.is_empty(); T::FIELDS.len()];
+        for row in self.rows() {
+            for (n, v) in numeric.iter_mut().zip(&row) {
+                *n &= v.is_numeric();
+            }
+        }
+        numeric
+    }
+
+    fn cells(&self, row: &[Value]) -> Vec<String> {
+        row.iter()
+            .zip(&self.precision)
+            .map(|(v, &p)| v.format(p))
+            .collect()
+    }
+
+    pub fn table(&self) -> String {
+        let headers: Vec<&dyn fmt::Display> = T::FIELDS
+            .iter()
+            .map(|f| &f.name as &dyn fmt::Display)
+            .collect();
+        let mut table = Table::new(&headers);
+        for (i, numeric) in self.numeric().into_iter().enumerate() {
+            if numeric {
+                table = table.align(i, Align::Right);
+            }
+        }
+        for row in self.rows() {
+            let cells = self.cells(&row);
+            let cells: Vec<&dyn fmt::Display> = cells.iter().map(|c| c as _).collect();
+            table.row(&cells);
+        }
+        table.to_string()
+    }
+
+    pub fn csv(&self) -> String {
+        let mut out = csv_line(T::FIELDS.iter().map(|f| f.name));
+        for row in self.rows() {
+            out.push_str(&csv_line(self.cells(&row).iter().map(String::as_str)));
+        }
+        out
+    }
+
+    pub fn json(&self) -> String {
+        let objects: Vec<String> = self
+            .rows()
+            .map(|row| {
+                let members: Vec<String> = T::FIELDS
+                    .iter()
+                    .zip(&row)
+                    .zip(&self.precision)
+                    .map(|((f, v), &p)| {
+                        let v = match v {
+                            Value::Text(s) => escape(s),
+                            Value::Float(x) if !x.is_finite() => "null".to_string(),
+                            v => v.format(p),
+                        };
+                        format!("{}: {}", escape(f.name), v)
+                    })
+                    .collect();
+                format!("  {{{}}}", members.join(", "))
+            })
+            .collect();
+        if objects.is_empty() {
+            "[]".to_string()
+        } else {
+            format!("[\n{}\n]", objects.join(",\n"))
+        }
+    }
+
+    pub fn markdown(&self) -> String {
+        let rows: Vec<Vec<String>> = self
+            .rows()
+            .map(|row| {
+                self.cells(&row)
+                    .iter()
+                    .map(|c| c.replace('|', "\\|").replace('\n', "<br>"))
+                    .collect()
+            })
+            .collect();
+        let numeric = self.numeric();
+        let widths: Vec<usize> = T::FIELDS
+            .iter()
+            .enumerate()
+            .map(|(i, f)| {
+                rows.iter()
+                    .map(|r| utf8::display_width(&r[i]))
+                    .chain([f.name.len(), 3])
+                    .max()
+                    .unwrap_or(3)
+            })
+            .collect();
+        let line = |cells: Vec<String>| format!("| {} |\n", cells.join(" | "));
+        let mut out = line(
+            T::FIELDS
+                .iter()
+                .zip(&widths)
+                .map(|(f, &w)| frame::align(f.name, w, Align::Left))
+                .collect(),
+        );
+        out.push_str(&line(
+            widths
+                .iter()
+                .zip(&numeric)
+                .map(|(&w, &n)| {
+                    if n {
+                        format!("{}:", "-".repeat(w - 1))
+                    } else {
+                        "-".repeat(w)
+                    }
+                })
+                .collect(),
+        ));
+        for row in rows {
+            out.push_str(&line(
+                row.iter()
+                    .zip(&widths)
+                    .zip(&numeric)
+                    .map(|((c, &w), &n)| {
+                        frame::align(c, w, if n { Align::Right } else { Align::Left })
+                    })
+                    .collect(),
+            ));
+        }
+        out
+    }
+
+    pub fn render(&self, format: Format) -> String {
+        match format {
+            Format::Table => self.table(),
+            Format::Csv => self.csv(),
+            Format::Json => self.json(),
+            Format::Markdown => self.markdown(),
+        }
+    }
+}
+
+// RFC 4180：含逗号、引号、换行或首尾空格时加引号，引号写两次
+fn csv_line<'s>(cells: impl Iterator<Item = &'s str>) -> String {
+    let cells: Vec<String> = cells
+        .map(|c| {
+            if c.contains([',', '"', '\n', '\r']) || c.trim() != c {
+                format!("\"{}\"", c.replace('"', "\"\""))
+            } else {
+                c.to_string()
+            }
+        })
+        .collect();
+    format!("{}\r\n", cells.join(","))
+}
+
+pub fn sample_cities() -> Vec<City<'static>> {
+    vec![
+        City {
+            name: "Dublin",
+            lat: 53.34778,
+            lon: -6.259722,
+        },
+        City {
+            name: "Oslo",
+            lat: 59.95,
+            lon: 10.75,
+        },
+        City {
+            name: "Vancouver",
+            lat: 49.25,
+            lon: -123.1,
+        },
+    ]
+}
+
+// cargo run -- records [people|cities|ranges|points] [--format table|csv|json|markdown] [--precision 列=位数]
+pub fn records_cli(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let mut kind = "cities";
+    let mut format = Format::Table;
+    let mut precision = Vec::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--format" => format = iter.next().ok_or("--format requires a value")?.parse()?,
+            "--precision" => {
+                let spec = iter.next().ok_or("--precision requires a value")?;
+                let (col, p) = spec
+                    .split_once('=')
+                    .ok_or_else(|| format!("expected column=digits: {}", spec))?;
+                precision.push((col.to_string(), p.parse::<usize>()?));
+            }
+            other => kind = other,
+        }
+    }
+
+    fn show<T: Fields>(
+        items: &[T],
+        format: Format,
+        precision: &[(String, usize)],
+    ) -> Result<(), String> {
+        let mut records = Records::new(items);
+        for (col, p) in precision {
+            records = records.precision(col, *p)?;
+        }
+        let out = records.render(format);
+        println!("{}", out.trim_end());
+        Ok(())
+    }
+
+    match kind {
+        "cities" => show(&sample_cities(), format, &precision)?,
+        "people" => show(
+            &[
+                Person {
+                    name: "Peter",
+                    age: 27,
+                },
+                Person {
+                    name: "张三, Jr.",
+                    age: 8,
+                },
+            ],
+            format,
+            &precision,
+        )?,
+        "ranges" => show(&[MinMax(0, 14), MinMax(-300, 300)], format, &precision)?,
+        "points" => show(
+            &[Point2D { x: 3.3, y: 7.2 }, Point2D { x: -1.0, y: 0.125 }],
+            format,
+            &precision,
+        )?,
+        other => return Err(format!("unknown record kind: {}", other).into()),
+    }
+    Ok(())
+}
+
+pub fn records_practice() {
+    let cities = sample_cities();
+    let records = Records::new(&cities);
+    assert_eq!(
+        records.table(),
+        [
+            "┌───────────┬────────┬──────────┐",
+            "│ name      │    lat │      lon │",
+            "├───────────┼────────┼──────────┤",
+            "│ Dublin    │ 53.348 │   -6.260 │",
+            "│ Oslo      │ 59.950 │   10.750 │",
+            "│ Vancouver │ 49.250 │ -123.100 │",
+            "└───────────┴────────┴──────────┘",
+        ]
+        .join("\n")
+    );
+
+    let records = records
+        .precision("lat", 1)
+        .unwrap()
+        .precision("lon", 0)
+        .unwrap();
+    assert_eq!(
+        records.csv(),
+        "name,lat,lon\r\nDublin,53.3,-6\r\nOslo,60.0,11\r\nVancouver,49.2,-123\r\n"
+    );
+    assert!(Records::new(&cities).precision("population", 0).is_err());
+    assert_eq!(
+        Records::new(&cities[1..2]).json(),
+        "[\n  {\"name\": \"Oslo\", \"lat\": 59.950, \"lon\": 10.750}\n]"
+    );
+    assert_eq!(Records::<City>::new(&[]).json(), "[]");
+
+    let people = [
+        Person {
+            name: "Peter",
+            age: 27,
+        },
+        Person {
+            name: "a|\"b\", 中",
+            age: 8,
+        },
+    ];
+    let people = Records::new(&people);
+    assert_eq!(
+        people.csv(),
+        "name,age\r\nPeter,27\r\n\"a|\"\"b\"\", 中\",8\r\n"
+    );
+    assert_eq!(
+        people.markdown(),
+        "| name       | age |\n\
+         | ---------- | --: |\n\
+         | Peter      |  27 |\n\
+         | a\\|\"b\", 中 |   8 |\n"
+    );
+    assert_eq!(
+        people.json(),
+        "[\n  {\"name\": \"Peter\", \"age\": 27},\n  {\"name\": \"a|\\\"b\\\", 中\", \"age\": 8}\n]"
+    );
+
+    // 没有指定精度的浮点数按 Display 输出
+    let points = [
+        Point2D { x: 3.3, y: 7.2 },
+        Point2D {
+            x: f64::NAN,
+            y: 1.0,
+        },
+    ];
+    assert_eq!(
+        Records::new(&points).json(),
+        "[\n  {\"x\": 3.3, \"y\": 7.2},\n  {\"x\": null, \"y\": 1}\n]"
+    );
+    assert_eq!(
+        Records::new(&[MinMax(-3, 3)]).render(Format::Csv),
+        "min,max\r\n-3,3\r\n"
+    );
+    let deep = [Deep(Structure(7)), Deep(Structure(-1))];
+    assert_eq!(
+        Records::new(&deep).render(Format::Markdown).lines().count(),
+        4
+    );
+    assert_eq!("MD".parse::<Format>(), Ok(Format::Markdown));
+    assert!("xml".parse::<Format>().is_err());
+
+    for format in [Format::Table, Format::Csv, Format::Json, Format::Markdown] {
+        println!("{}", Records::new(&cities).render(format));
+    }
+}
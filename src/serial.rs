@@ -0,0 +1,1710 @@
+//! 序列化
+//! User、City、Color、PokerCard、Song、Person_2 这些类型都无法保存和读取
+//! 这里用特征描述如何与中间表示 Node 互相转换，由宏为结构体和枚举生成实现，
+//! 再由 JSON 和紧凑二进制两种后端把 Node 写成文本或字节，数据带有版本号，旧版本的数据可以迁移
+//! 带 &str 字段的类型(City、Person)借用读到的数据：先用 load_json / load_binary 得到 Node，再用 read 读取
+
+use std::fmt;
+
+use std::ops::Add;
+
+use crate::feed::escape;
+use crate::{
+    Action, Button, Button1, City, Coin, Color, Counter, Counter1, Deep, Direction,
+    DisplayStructure, Draw, Foo3, Fruit, Human, ImportantExcerpt, IpAddr, IpAddr_1, List, Message,
+    Messagea, MinMax, MyCircularQueue, MyEnum, MyEnum1, MyQueue, Person, Person_1, Person_2,
+    Person_3, Point, Point2D, Point_1, Point_2, Point_3, PokerCard, PokerCard_1, PokerSuit,
+    Screen_1, Select, SelectBox, Song, Structure, UsState, User, Weibo, Wrapper, Wrapper_1, A, B,
+    D, E, G, V4, V6,
+};
+
+/* ---------------------------- 中间表示 ---------------------------- */
+
+// i128 同时容纳 i64 和 u64 的全部取值
+#[derive(Clone, Debug, PartialEq)]
+pub enum Node {
+    Null,
+    Bool(bool),
+    Int(i128),
+    Float(f64),
+    Str(String),
+    Seq(Vec<Node>),
+    Map(Vec<(String, Node)>),
+}
+
+impl Node {
+    fn kind(&self) -> &'static str {
+        match self {
+            Node::Null => "null",
+            Node::Bool(_) => "bool",
+            Node::Int(_) => "integer",
+            Node::Float(_) => "float",
+            Node::Str(_) => "string",
+            Node::Seq(_) => "array",
+            Node::Map(_) => "object",
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Node> {
+        match self {
+            Node::Map(members) => members.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+}
+
+/* ---------------------------- 错误 ---------------------------- */
+
+// path 按出错位置由内向外记录，显示时反转，例如 $.data.suit
+#[derive(Clone, Debug, PartialEq)]
+pub struct Error {
+    path: Vec<String>,
+    msg: String,
+}
+
+impl Error {
+    pub fn new(msg: impl Into<String>) -> Self {
+        Error {
+            path: Vec::new(),
+            msg: msg.into(),
+        }
+    }
+
+    fn expected(what: &str, found: &Node) -> Self {
+        Error::new(format!("expected {}, found {}", what, found.kind()))
+    }
+
+    // 外层在传递错误时补上自己的路径片段
+    pub fn at(mut self, segment: impl Into<String>) -> Self {
+        self.path.push(segment.into());
+        self
+    }
+
+    pub fn path(&self) -> String {
+        let mut path = String::from("$");
+        for segment in self.path.iter().rev() {
+            path.push_str(segment);
+        }
+        path
+    }
+
+    pub fn message(&self) -> &str {
+        &self.msg
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.path(), self.msg)
+    }
+}
+
+impl std::error::Error for Error {}
+
+/* ---------------------------- 特征 ---------------------------- */
+
+pub trait Serialize {
+    fn to_node(&self) -> Node;
+}
+
+// 'de 是被读取的 Node 的生命周期，&'de str 这样的字段直接借用 Node 中的字符串
+pub trait Deserialize<'de>: Sized {
+    fn from_node(node: &'de Node) -> Result<Self, Error>;
+
+    // 结构体中缺少这个字段时的取值，Option 为 None，其余类型报错
+    fn missing() -> Result<Self, Error> {
+        Err(Error::new("missing field"))
+    }
+}
+
+// 不借用输入的类型，可以直接从 JSON 或字节中读出来
+pub trait DeserializeOwned: for<'de> Deserialize<'de> {}
+
+impl<T: for<'de> Deserialize<'de>> DeserializeOwned for T {}
+
+// 顶层保存的类型需要实现，数据格式变化时增加 VERSION 并在 migrate 中把旧数据转换为当前格式
+pub trait Versioned: Serialize {
+    const VERSION: u32 = 1;
+
+    fn migrate(version: u32, _data: Node) -> Result<Node, Error> {
+        Err(Error::new(format!(
+            "no migration from version {} to {}",
+            version,
+            Self::VERSION
+        )))
+    }
+}
+
+// 把读到的数据迁移到 T 的当前版本
+fn upgrade<T: Versioned + ?Sized>(version: u32, data: Node) -> Result<Node, Error> {
+    match version {
+        v if v == T::VERSION => Ok(data),
+        v if v > T::VERSION => Err(Error::new(format!(
+            "unsupported version {} (current {})",
+            v,
+            T::VERSION
+        ))),
+        v => T::migrate(v, data),
+    }
+}
+
+// 从 load_json / load_binary 得到的数据中读取，返回值可以借用 data
+pub fn read<'de, T: Deserialize<'de>>(data: &'de Node) -> Result<T, Error> {
+    T::from_node(data).map_err(|e| e.at(".data"))
+}
+
+/* ---------------------------- 基本类型 ---------------------------- */
+
+impl Serialize for bool {
+    fn to_node(&self) -> Node {
+        Node::Bool(*self)
+    }
+}
+
+impl Deserialize<'_> for bool {
+    fn from_node(node: &Node) -> Result<Self, Error> {
+        match node {
+            Node::Bool(b) => Ok(*b),
+            other => Err(Error::expected("bool", other)),
+        }
+    }
+}
+
+macro_rules! int_impl {
+    ($($t:ty),*) => {$(
+        impl Serialize for $t {
+            fn to_node(&self) -> Node {
+                Node::Int(*self as i128)
+            }
+        }
+
+        impl Deserialize<'_> for $t {
+            fn from_node(node: &Node) -> Result<Self, Error> {
+                match node {
+                    Node::Int(n) => <$t>::try_from(*n).map_err(|_| {
+                        Error::new(format!("{} out of range for {}", n, stringify!($t)))
+                    }),
+                    other => Err(Error::expected(stringify!($t), other)),
+                }
+            }
+        }
+    )*};
+}
+
+int_impl!(i8, i16, i32, i64, u8, u16, u32, u64, usize);
+
+macro_rules! float_impl {
+    ($($t:ty),*) => {$(
+        // JSON 不能表示 NaN 和无穷大，写成字符串，不能写成 null，否则 Option 的 None 和 Some(NaN) 无法区分
+        impl Serialize for $t {
+            fn to_node(&self) -> Node {
+                let name = match *self {
+                    x if x.is_finite() => return Node::Float(x as f64),
+                    x if x.is_nan() => "NaN",
+                    x if x > 0.0 => "Infinity",
+                    _ => "-Infinity",
+                };
+                Node::Str(name.to_string())
+            }
+        }
+
+        impl Deserialize<'_> for $t {
+            fn from_node(node: &Node) -> Result<Self, Error> {
+                match node {
+                    Node::Float(x) => Ok(*x as $t),
+                    Node::Int(n) => Ok(*n as $t),
+                    Node::Str(s) => match s.as_str() {
+                        "NaN" => Ok(<$t>::NAN),
+                        "Infinity" => Ok(<$t>::INFINITY),
+                        "-Infinity" => Ok(<$t>::NEG_INFINITY),
+                        _ => Err(Error::new(format!("invalid {} {:?}", stringify!($t), s))),
+                    },
+                    other => Err(Error::expected(stringify!($t), other)),
+                }
+            }
+        }
+    )*};
+}
+
+float_impl!(f32, f64);
+
+impl Serialize for String {
+    fn to_node(&self) -> Node {
+        Node::Str(self.clone())
+    }
+}
+
+impl Deserialize<'_> for String {
+    fn from_node(node: &Node) -> Result<Self, Error> {
+        match node {
+            Node::Str(s) => Ok(s.clone()),
+            other => Err(Error::expected("string", other)),
+        }
+    }
+}
+
+impl Serialize for &str {
+    fn to_node(&self) -> Node {
+        Node::Str(self.to_string())
+    }
+}
+
+// City、Person 的 name 是 &str，直接借用 Node 中的字符串
+impl<'de: 'a, 'a> Deserialize<'de> for &'a str {
+    fn from_node(node: &'de Node) -> Result<Self, Error> {
+        match node {
+            Node::Str(s) => Ok(s),
+            other => Err(Error::expected("string", other)),
+        }
+    }
+}
+
+impl<T: Serialize> Serialize for Box<T> {
+    fn to_node(&self) -> Node {
+        T::to_node(self)
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Box<T> {
+    fn from_node(node: &'de Node) -> Result<Self, Error> {
+        T::from_node(node).map(Box::new)
+    }
+}
+
+impl<T: Serialize> Serialize for Option<T> {
+    fn to_node(&self) -> Node {
+        self.as_ref().map_or(Node::Null, T::to_node)
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Option<T> {
+    fn from_node(node: &'de Node) -> Result<Self, Error> {
+        match node {
+            Node::Null => Ok(None),
+            node => T::from_node(node).map(Some),
+        }
+    }
+
+    fn missing() -> Result<Self, Error> {
+        Ok(None)
+    }
+}
+
+impl<T: Serialize> Serialize for Vec<T> {
+    fn to_node(&self) -> Node {
+        Node::Seq(self.iter().map(T::to_node).collect())
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Vec<T> {
+    fn from_node(node: &'de Node) -> Result<Self, Error> {
+        match node {
+            Node::Seq(items) => items
+                .iter()
+                .enumerate()
+                .map(|(i, item)| T::from_node(item).map_err(|e| e.at(format!("[{}]", i))))
+                .collect(),
+            other => Err(Error::expected("array", other)),
+        }
+    }
+}
+
+/* ---------------------------- 派生宏 ---------------------------- */
+
+// 结构体的字段或结构体变体的字段，缺少时由字段类型决定取值
+fn field<'de, T: Deserialize<'de>>(node: &'de Node, name: &str) -> Result<T, Error> {
+    match node.get(name) {
+        Some(v) => T::from_node(v),
+        None => T::missing(),
+    }
+    .map_err(|e| e.at(format!(".{}", name)))
+}
+
+// 元组结构体和元组变体只有一个元素时写成这个值本身，否则写成等长的数组
+fn tuple_node(mut items: Vec<Node>) -> Node {
+    if items.len() == 1 {
+        items.pop().expect("one element")
+    } else {
+        Node::Seq(items)
+    }
+}
+
+// 按顺序读取元组的元素，和 tuple_node 对应
+struct Elements<'de> {
+    items: Vec<&'de Node>,
+    next: usize,
+}
+
+impl<'de> Elements<'de> {
+    fn new(node: &'de Node, len: usize, what: &str) -> Result<Self, Error> {
+        let items = match node {
+            _ if len == 1 => vec![node],
+            Node::Seq(items) if items.len() == len => items.iter().collect(),
+            Node::Seq(items) => {
+                return Err(Error::new(format!(
+                    "expected {} elements for {}, found {}",
+                    len,
+                    what,
+                    items.len()
+                )))
+            }
+            other => return Err(Error::expected(what, other)),
+        };
+        Ok(Elements { items, next: 0 })
+    }
+
+    fn next<T: Deserialize<'de>>(&mut self) -> Result<T, Error> {
+        let i = self.next;
+        self.next += 1;
+        let value = T::from_node(self.items[i]);
+        if self.items.len() == 1 {
+            value
+        } else {
+            value.map_err(|e| e.at(format!("[{}]", i)))
+        }
+    }
+}
+
+// 无数据的变体写成变体名字符串，其余写成 {"变体名": 数据}
+fn variant<'de>(node: &'de Node, what: &str) -> Result<(&'de str, Option<&'de Node>), Error> {
+    match node {
+        Node::Str(s) => Ok((s, None)),
+        Node::Map(members) if members.len() == 1 => Ok((&members[0].0, Some(&members[0].1))),
+        other => Err(Error::expected(what, other)),
+    }
+}
+
+fn variant_data(data: Option<&Node>) -> Result<&Node, Error> {
+    data.ok_or_else(|| Error::new("missing variant data"))
+}
+
+/*
+ * 相当于 #[derive(Serialize, Deserialize)]：
+ *   serial!(struct Name { field, ... })            结构体写成以字段名为键的对象，多余的键忽略
+ *   serial!(struct Name<T: Bound> { field, ... })  泛型结构体，T 还需要实现 Serialize / Deserialize
+ *   serial!(struct Name<'a> { field, ... })        &'a str 字段借用读取的数据
+ *   serial!(struct Name { ... } skip { field })    跳过闭包这类字段，读取时取 Default::default()
+ *   serial!(struct Name(a, b))                     元组结构体写成数组，只有一个元素时写成这个值
+ *   serial!(struct Name;)                          单元结构体写成 null
+ *   serial!(enum Name { A, B(a, b), C { x, y } })  无数据的变体写成变体名 "A"，
+ *                                                  其余写成 {"B": [a, b]}、{"C": {"x": x, "y": y}}
+ * 元组的元素名只用作绑定，和字段名一样需要和定义中的个数一致
+ */
+macro_rules! serial {
+    (struct $name:ident<$lt:lifetime> { $($field:ident),* $(,)? }) => {
+        impl Serialize for $name<'_> {
+            fn to_node(&self) -> Node {
+                Node::Map(vec![$((stringify!($field).to_string(), self.$field.to_node())),*])
+            }
+        }
+
+        impl<'de: $lt, $lt> Deserialize<'de> for $name<$lt> {
+            fn from_node(node: &'de Node) -> Result<Self, Error> {
+                if !matches!(node, Node::Map(_)) {
+                    return Err(Error::expected(stringify!($name), node));
+                }
+                Ok($name { $($field: field(node, stringify!($field))?,)* })
+            }
+        }
+    };
+
+    (
+        struct $name:ident $(<$($g:ident $(: $bound:path)?),*>)? { $($field:ident),* $(,)? }
+        $(skip { $($skip:ident),* $(,)? })?
+    ) => {
+        impl$(<$($g: Serialize $(+ $bound)?),*>)? Serialize for $name$(<$($g),*>)? {
+            fn to_node(&self) -> Node {
+                Node::Map(vec![$((stringify!($field).to_string(), self.$field.to_node())),*])
+            }
+        }
+
+        impl<'de $($(, $g: Deserialize<'de> $(+ $bound)?)*)?> Deserialize<'de>
+            for $name$(<$($g),*>)?
+        {
+            fn from_node(node: &'de Node) -> Result<Self, Error> {
+                if !matches!(node, Node::Map(_)) {
+                    return Err(Error::expected(stringify!($name), node));
+                }
+                Ok($name {
+                    $($field: field(node, stringify!($field))?,)*
+                    $($($skip: Default::default(),)*)?
+                })
+            }
+        }
+    };
+
+    (struct $name:ident($($elem:ident),* $(,)?)) => {
+        impl Serialize for $name {
+            fn to_node(&self) -> Node {
+                let $name($($elem),*) = self;
+                tuple_node(vec![$($elem.to_node()),*])
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn from_node(node: &'de Node) -> Result<Self, Error> {
+                let len = [$(stringify!($elem)),*].len();
+                let mut elements = Elements::new(node, len, stringify!($name))?;
+                $(let $elem = elements.next()?;)*
+                Ok($name($($elem),*))
+            }
+        }
+    };
+
+    (struct $name:ident;) => {
+        impl Serialize for $name {
+            fn to_node(&self) -> Node {
+                Node::Null
+            }
+        }
+
+        impl Deserialize<'_> for $name {
+            fn from_node(node: &Node) -> Result<Self, Error> {
+                match node {
+                    Node::Null => Ok($name),
+                    other => Err(Error::expected(stringify!($name), other)),
+                }
+            }
+        }
+    };
+
+    (
+        enum $name:ident {
+            $($variant:ident $(($($elem:ident),*))? $({ $($field:ident),* })?),* $(,)?
+        }
+    ) => {
+        impl Serialize for $name {
+            fn to_node(&self) -> Node {
+                match self {
+                    $($name::$variant $(($($elem),*))? $({ $($field),* })? => {
+                        serial!(@variant $variant $(($($elem),*))? $({ $($field),* })?)
+                    })*
+                }
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn from_node(node: &'de Node) -> Result<Self, Error> {
+                let (name, data) = variant(node, stringify!($name))?;
+                match name {
+                    $(stringify!($variant) => {
+                        fn read_variant(data: Option<&Node>) -> Result<$name, Error> {
+                            serial!(@read $name $variant data $(($($elem),*))? $({ $($field),* })?)
+                        }
+                        read_variant(data).map_err(|e| e.at(concat!(".", stringify!($variant))))
+                    })*
+                    _ => Err(Error::new(format!(
+                        "unknown variant {:?} of {}",
+                        name,
+                        stringify!($name)
+                    ))),
+                }
+            }
+        }
+    };
+
+    (@variant $variant:ident) => {
+        Node::Str(stringify!($variant).to_string())
+    };
+
+    (@variant $variant:ident ($($elem:ident),*)) => {
+        Node::Map(vec![(
+            stringify!($variant).to_string(),
+            tuple_node(vec![$($elem.to_node()),*]),
+        )])
+    };
+
+    (@variant $variant:ident { $($field:ident),* }) => {
+        Node::Map(vec![(
+            stringify!($variant).to_string(),
+            Node::Map(vec![$((stringify!($field).to_string(), $field.to_node())),*]),
+        )])
+    };
+
+    (@read $name:ident $variant:ident $data:ident) => {
+        match $data {
+            None => Ok($name::$variant),
+            Some(_) => Err(Error::new("unexpected data for unit variant")),
+        }
+    };
+
+    (@read $name:ident $variant:ident $data:ident ($($elem:ident),*)) => {{
+        let len = [$(stringify!($elem)),*].len();
+        let mut elements = Elements::new(variant_data($data)?, len, stringify!($variant))?;
+        $(let $elem = elements.next()?;)*
+        Ok($name::$variant($($elem),*))
+    }};
+
+    (@read $name:ident $variant:ident $data:ident { $($field:ident),* }) => {{
+        let data = variant_data($data)?;
+        if !matches!(data, Node::Map(_)) {
+            return Err(Error::expected(stringify!($variant), data));
+        }
+        Ok($name::$variant { $($field: field(data, stringify!($field))?),* })
+    }};
+}
+
+serial!(struct Structure(value));
+serial!(struct Deep(structure));
+serial!(struct Person<'a> { name, age });
+serial!(struct DisplayStructure(value));
+serial!(struct MinMax(min, max));
+serial!(struct Point2D { x, y });
+serial!(struct List(items));
+serial!(struct City<'a> { name, lat, lon });
+serial!(struct Color { red, green, blue });
+serial!(struct A { a, b, c });
+serial!(struct B { a, b, c });
+serial!(
+    enum D {
+        One,
+        Two,
+    }
+);
+serial!(
+    enum E {
+        N,
+        H(value),
+        M(boxed),
+    }
+);
+serial!(
+    struct G;
+);
+serial!(struct User { active, username, email, sign_in_count });
+serial!(struct Person_1 { name, age });
+serial!(struct Point { x, y });
+serial!(
+    enum PokerSuit {
+        Clubs,
+        Spades,
+        Diamonds,
+        Hearts,
+    }
+);
+serial!(struct PokerCard { suit, value });
+serial!(
+    enum PokerCard_1 {
+        Clubs(value),
+        Spades(value),
+        Diamonds(value),
+        Hearts(value),
+    }
+);
+serial!(
+    enum Direction {
+        East,
+        West,
+        North,
+        South,
+    }
+);
+serial!(
+    enum IpAddr {
+        Ipv4,
+        Ipv6,
+    }
+);
+serial!(
+    enum UsState {
+        Alabama,
+        Alaska,
+    }
+);
+serial!(
+    enum Coin {
+        Penny,
+        Nickel,
+        Dime,
+        Quarter(state),
+    }
+);
+serial!(
+    enum Action {
+        Say(text),
+        MoveTo(x, y),
+        ChangeColorRGB(r, g, b),
+    }
+);
+serial!(
+    enum Message {
+        Quit,
+        Move { x, y },
+        Write(text),
+        ChangeColor(r, g, b),
+    }
+);
+serial!(struct MyQueue<T> { qdata });
+serial!(struct MyCircularQueue { v, head, tail });
+serial!(
+    enum Messagea {
+        Quit,
+        Move { x, y },
+        Write(text),
+        ChangeColor(r, g, b),
+    }
+);
+serial!(struct Point_1<T> { x, y });
+serial!(struct Point_2<T, U> { x, y });
+serial!(
+    enum IpAddr_1 {
+        V4(addr),
+        V6(addr),
+    }
+);
+serial!(struct V4(addr));
+serial!(struct V6(addr));
+serial!(struct Person_2 { name, age });
+serial!(struct Person_3 { name, age });
+serial!(struct Weibo { username, content });
+serial!(struct Point_3<T: Add<T, Output = T>> { x, y });
+serial!(struct Button { width, height, label } skip { on_click });
+serial!(struct SelectBox { width, height, options, selected } skip { on_change });
+serial!(struct Screen_1<T: Draw> { components });
+serial!(
+    struct Human;
+);
+serial!(struct Wrapper(items));
+serial!(struct ImportantExcerpt<'a> { part });
+serial!(
+    struct Foo3;
+);
+serial!(struct Counter { count });
+serial!(struct Wrapper_1(items));
+serial!(
+    enum MyEnum {
+        A,
+        B,
+        C,
+    }
+);
+serial!(
+    enum MyEnum1 {
+        A,
+        B,
+        C,
+    }
+);
+serial!(struct Button1 { id });
+serial!(struct Select { id });
+serial!(
+    enum Fruit {
+        Apple(weight),
+        Orange(name),
+    }
+);
+serial!(struct Counter1 { count });
+serial!(struct Song { author, name });
+
+// 版本 1 的 User 没有 active 字段，迁移时视为活跃用户
+impl Versioned for User {
+    const VERSION: u32 = 2;
+
+    fn migrate(version: u32, data: Node) -> Result<Node, Error> {
+        match (version, data) {
+            (1, Node::Map(mut members)) => {
+                members.retain(|(k, _)| k != "active");
+                members.insert(0, ("active".to_string(), Node::Bool(true)));
+                Ok(Node::Map(members))
+            }
+            (1, other) => Err(Error::expected("User", &other).at(".data")),
+            (v, _) => Err(Error::new(format!("no migration from version {} to 2", v))),
+        }
+    }
+}
+
+impl Versioned for Structure {}
+impl Versioned for Deep {}
+impl Versioned for Person<'_> {}
+impl Versioned for DisplayStructure {}
+impl Versioned for MinMax {}
+impl Versioned for Point2D {}
+impl Versioned for List {}
+impl Versioned for City<'_> {}
+impl Versioned for Color {}
+impl Versioned for A {}
+impl Versioned for B {}
+impl Versioned for D {}
+impl Versioned for E {}
+impl Versioned for G {}
+impl Versioned for Person_1 {}
+impl Versioned for Point {}
+impl Versioned for PokerSuit {}
+impl Versioned for PokerCard {}
+impl Versioned for PokerCard_1 {}
+impl Versioned for Direction {}
+impl Versioned for IpAddr {}
+impl Versioned for UsState {}
+impl Versioned for Coin {}
+impl Versioned for Action {}
+impl Versioned for Message {}
+impl<T: Serialize> Versioned for MyQueue<T> {}
+impl Versioned for MyCircularQueue {}
+impl Versioned for Messagea {}
+impl<T: Serialize> Versioned for Point_1<T> {}
+impl<T: Serialize, U: Serialize> Versioned for Point_2<T, U> {}
+impl Versioned for IpAddr_1 {}
+impl Versioned for V4 {}
+impl Versioned for V6 {}
+impl Versioned for Person_2 {}
+impl Versioned for Person_3 {}
+impl Versioned for Weibo {}
+impl<T: Serialize + Add<T, Output = T>> Versioned for Point_3<T> {}
+impl Versioned for Button {}
+impl Versioned for SelectBox {}
+impl<T: Serialize + Draw> Versioned for Screen_1<T> {}
+impl Versioned for Human {}
+impl Versioned for Wrapper {}
+impl Versioned for ImportantExcerpt<'_> {}
+impl Versioned for Foo3 {}
+impl Versioned for Counter {}
+impl Versioned for Wrapper_1 {}
+impl Versioned for MyEnum {}
+impl Versioned for MyEnum1 {}
+impl Versioned for Button1 {}
+impl Versioned for Select {}
+impl Versioned for Fruit {}
+impl Versioned for Counter1 {}
+impl Versioned for Song {}
+impl<T: Versioned> Versioned for Vec<T> {
+    const VERSION: u32 = T::VERSION;
+
+    fn migrate(version: u32, data: Node) -> Result<Node, Error> {
+        match data {
+            Node::Seq(items) => items
+                .into_iter()
+                .enumerate()
+                .map(|(i, item)| T::migrate(version, item).map_err(|e| e.at(format!("[{}]", i))))
+                .collect::<Result<_, _>>()
+                .map(Node::Seq),
+            other => Err(Error::expected("array", &other)),
+        }
+    }
+}
+
+/* ---------------------------- JSON ---------------------------- */
+
+fn write_json(node: &Node, out: &mut String) {
+    match node {
+        Node::Null => out.push_str("null"),
+        Node::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+        Node::Int(n) => out.push_str(&n.to_string()),
+        // {:?} 总是带小数点或指数，读回时仍是浮点数
+        Node::Float(x) => out.push_str(&format!("{:?}", x)),
+        Node::Str(s) => out.push_str(&escape(s)),
+        Node::Seq(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_json(item, out);
+            }
+            out.push(']');
+        }
+        Node::Map(members) => {
+            out.push('{');
+            for (i, (k, v)) in members.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                out.push_str(&escape(k));
+                out.push(':');
+                write_json(v, out);
+            }
+            out.push('}');
+        }
+    }
+}
+
+pub fn node_to_json(node: &Node) -> String {
+    let mut out = String::new();
+    write_json(node, &mut out);
+    out
+}
+
+// 嵌套层数上限，防止恶意输入耗尽栈
+const MAX_DEPTH: usize = 128;
+
+struct JsonParser<'a> {
+    src: &'a str,
+    pos: usize,
+}
+
+impl JsonParser<'_> {
+    fn error(&self, msg: impl fmt::Display) -> Error {
+        Error::new(format!("{} at byte {}", msg, self.pos))
+    }
+
+    fn skip_ws(&mut self) {
+        let rest = &self.src[self.pos..];
+        self.pos += rest.len() - rest.trim_start_matches([' ', '\t', '\n', '\r']).len();
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.src.as_bytes().get(self.pos).copied()
+    }
+
+    fn eat(&mut self, want: u8) -> Result<(), Error> {
+        self.skip_ws();
+        if self.peek() == Some(want) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(self.error(format!("expected '{}'", want as char)))
+        }
+    }
+
+    fn literal(&mut self, word: &str, node: Node) -> Result<Node, Error> {
+        if self.src[self.pos..].starts_with(word) {
+            self.pos += word.len();
+            Ok(node)
+        } else {
+            Err(self.error("invalid literal"))
+        }
+    }
+
+    fn value(&mut self, depth: usize) -> Result<Node, Error> {
+        if depth > MAX_DEPTH {
+            return Err(self.error("nesting too deep"));
+        }
+        self.skip_ws();
+        match self.peek() {
+            Some(b'n') => self.literal("null", Node::Null),
+            Some(b't') => self.literal("true", Node::Bool(true)),
+            Some(b'f') => self.literal("false", Node::Bool(false)),
+            Some(b'"') => self.string().map(Node::Str),
+            Some(b'[') => {
+                self.pos += 1;
+                let mut items = Vec::new();
+                self.skip_ws();
+                if self.peek() == Some(b']') {
+                    self.pos += 1;
+                    return Ok(Node::Seq(items));
+                }
+                loop {
+                    items.push(self.value(depth + 1)?);
+                    self.skip_ws();
+                    match self.peek() {
+                        Some(b',') => self.pos += 1,
+                        Some(b']') => {
+                            self.pos += 1;
+                            return Ok(Node::Seq(items));
+                        }
+                        _ => return Err(self.error("expected ',' or ']'")),
+                    }
+                }
+            }
+            Some(b'{') => {
+                self.pos += 1;
+                let mut members = Vec::new();
+                self.skip_ws();
+                if self.peek() == Some(b'}') {
+                    self.pos += 1;
+                    return Ok(Node::Map(members));
+                }
+                loop {
+                    self.skip_ws();
+                    if self.peek() != Some(b'"') {
+                        return Err(self.error("expected string key"));
+                    }
+                    let key = self.string()?;
+                    self.eat(b':')?;
+                    members.push((key, self.value(depth + 1)?));
+                    self.skip_ws();
+                    match self.peek() {
+                        Some(b',') => self.pos += 1,
+                        Some(b'}') => {
+                            self.pos += 1;
+                            return Ok(Node::Map(members));
+                        }
+                        _ => return Err(self.error("expected ',' or '}'")),
+                    }
+                }
+            }
+            Some(b'-' | b'0'..=b'9') => self.number(),
+            Some(_) => Err(self.error("unexpected character")),
+            None => Err(self.error("unexpected end of input")),
+        }
+    }
+
+    // 按 JSON 的语法 -?(0|[1-9][0-9]*)(.[0-9]+)?([eE][+-]?[0-9]+)? 读取，不接受 01、1.、.5 这样的写法
+    fn number(&mut self) -> Result<Node, Error> {
+        let start = self.pos;
+        let bytes = self.src.as_bytes();
+        let digits = |from: usize| {
+            bytes[from..]
+                .iter()
+                .take_while(|b| b.is_ascii_digit())
+                .count()
+        };
+        let mut end = start + usize::from(bytes[start] == b'-');
+        let int = digits(end);
+        let mut valid = int == 1 || (int > 1 && bytes[end] != b'0');
+        end += int;
+        let mut float = false;
+        if bytes.get(end) == Some(&b'.') {
+            let frac = digits(end + 1);
+            valid &= frac > 0;
+            end += 1 + frac;
+            float = true;
+        }
+        if matches!(bytes.get(end), Some(b'e' | b'E')) {
+            end += 1;
+            if matches!(bytes.get(end), Some(b'+' | b'-')) {
+                end += 1;
+            }
+            let exp = digits(end);
+            valid &= exp > 0;
+            end += exp;
+            float = true;
+        }
+        // 紧跟在数字后面的数字也算在内，例如 01 报告为 "01" 而不是 "0" 后面多余的字符
+        end += digits(end);
+        self.pos = end;
+        let text = &self.src[start..end];
+        let invalid = || Error::new(format!("invalid number {:?} at byte {}", text, start));
+        if !valid {
+            return Err(invalid());
+        }
+        if !float {
+            if let Ok(n) = text.parse() {
+                return Ok(Node::Int(n));
+            }
+        }
+        // 超出 f64 范围的数会解析成无穷大，写回时不再是合法的 JSON
+        match text.parse::<f64>() {
+            Ok(x) if x.is_finite() => Ok(Node::Float(x)),
+            _ => Err(invalid()),
+        }
+    }
+
+    fn hex4(&mut self) -> Result<u32, Error> {
+        let digits = self.src.get(self.pos..self.pos + 4);
+        let n = digits
+            .and_then(|d| u32::from_str_radix(d, 16).ok())
+            .ok_or_else(|| self.error("invalid \\u escape"))?;
+        self.pos += 4;
+        Ok(n)
+    }
+
+    fn string(&mut self) -> Result<String, Error> {
+        self.pos += 1;
+        let mut out = String::new();
+        loop {
+            let c = self.src[self.pos..]
+                .chars()
+                .next()
+                .ok_or_else(|| self.error("unterminated string"))?;
+            self.pos += c.len_utf8();
+            match c {
+                '"' => return Ok(out),
+                '\\' => {
+                    let e = self
+                        .peek()
+                        .ok_or_else(|| self.error("unterminated string"))?;
+                    self.pos += 1;
+                    match e {
+                        b'"' => out.push('"'),
+                        b'\\' => out.push('\\'),
+                        b'/' => out.push('/'),
+                        b'n' => out.push('\n'),
+                        b'r' => out.push('\r'),
+                        b't' => out.push('\t'),
+                        b'b' => out.push('\u{8}'),
+                        b'f' => out.push('\u{c}'),
+                        b'u' => {
+                            let hi = self.hex4()?;
+                            let code = if (0xD800..0xDC00).contains(&hi) {
+                                if !self.src[self.pos..].starts_with("\\u") {
+                                    return Err(self.error("unpaired surrogate"));
+                                }
+                                self.pos += 2;
+                                let lo = self.hex4()?;
+                                if !(0xDC00..0xE000).contains(&lo) {
+                                    return Err(self.error("unpaired surrogate"));
+                                }
+                                0x10000 + ((hi - 0xD800) << 10) + (lo - 0xDC00)
+                            } else {
+                                hi
+                            };
+                            out.push(
+                                char::from_u32(code)
+                                    .ok_or_else(|| self.error("unpaired surrogate"))?,
+                            );
+                        }
+                        _ => return Err(self.error("invalid escape")),
+                    }
+                }
+                c if (c as u32) < 0x20 => return Err(self.error("control character in string")),
+                c => out.push(c),
+            }
+        }
+    }
+}
+
+pub fn parse_json(src: &str) -> Result<Node, Error> {
+    let mut parser = JsonParser { src, pos: 0 };
+    let node = parser.value(0)?;
+    parser.skip_ws();
+    if parser.pos != src.len() {
+        return Err(parser.error("trailing characters"));
+    }
+    Ok(node)
+}
+
+// {"version":2,"data":...}
+pub fn to_json<T: Versioned>(value: &T) -> String {
+    node_to_json(&Node::Map(vec![
+        ("version".to_string(), Node::Int(T::VERSION as i128)),
+        ("data".to_string(), value.to_node()),
+    ]))
+}
+
+// 读取并迁移到 T 的当前版本，T 借用输入时先 load_json 再 read，否则直接用 from_json
+pub fn load_json<T: Versioned + ?Sized>(src: &str) -> Result<Node, Error> {
+    let root = parse_json(src)?;
+    let version = root
+        .get("version")
+        .ok_or_else(|| Error::new("missing version"))
+        .and_then(u32::from_node)
+        .map_err(|e| e.at(".version"))?;
+    let data = root
+        .get("data")
+        .cloned()
+        .ok_or_else(|| Error::new("missing field").at(".data"))?;
+    upgrade::<T>(version, data)
+}
+
+pub fn from_json<T: Versioned + DeserializeOwned>(src: &str) -> Result<T, Error> {
+    read(&load_json::<T>(src)?)
+}
+
+/* ---------------------------- 二进制 ---------------------------- */
+
+/*
+ * 格式：魔数 "RS"，版本号，然后是数据
+ * 每个值以一个字节的标签开头，整数使用 zigzag + LEB128 变长编码，
+ * 字符串、数组、对象先写长度
+ */
+const MAGIC: &[u8] = b"RS";
+
+const TAG_NULL: u8 = 0;
+const TAG_FALSE: u8 = 1;
+const TAG_TRUE: u8 = 2;
+const TAG_INT: u8 = 3;
+const TAG_FLOAT: u8 = 4;
+const TAG_STR: u8 = 5;
+const TAG_SEQ: u8 = 6;
+const TAG_MAP: u8 = 7;
+
+fn write_varint(mut n: u128, out: &mut Vec<u8>) {
+    while n >= 0x80 {
+        out.push(n as u8 | 0x80);
+        n >>= 7;
+    }
+    out.push(n as u8);
+}
+
+fn zigzag(n: i128) -> u128 {
+    ((n << 1) ^ (n >> 127)) as u128
+}
+
+fn unzigzag(n: u128) -> i128 {
+    (n >> 1) as i128 ^ -((n & 1) as i128)
+}
+
+fn write_str(s: &str, out: &mut Vec<u8>) {
+    write_varint(s.len() as u128, out);
+    out.extend_from_slice(s.as_bytes());
+}
+
+fn write_binary(node: &Node, out: &mut Vec<u8>) {
+    match node {
+        Node::Null => out.push(TAG_NULL),
+        Node::Bool(false) => out.push(TAG_FALSE),
+        Node::Bool(true) => out.push(TAG_TRUE),
+        Node::Int(n) => {
+            out.push(TAG_INT);
+            write_varint(zigzag(*n), out);
+        }
+        Node::Float(x) => {
+            out.push(TAG_FLOAT);
+            out.extend_from_slice(&x.to_le_bytes());
+        }
+        Node::Str(s) => {
+            out.push(TAG_STR);
+            write_str(s, out);
+        }
+        Node::Seq(items) => {
+            out.push(TAG_SEQ);
+            write_varint(items.len() as u128, out);
+            for item in items {
+                write_binary(item, out);
+            }
+        }
+        Node::Map(members) => {
+            out.push(TAG_MAP);
+            write_varint(members.len() as u128, out);
+            for (k, v) in members {
+                write_str(k, out);
+                write_binary(v, out);
+            }
+        }
+    }
+}
+
+struct BinaryReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl BinaryReader<'_> {
+    fn error(&self, msg: &str) -> Error {
+        Error::new(format!("{} at byte {}", msg, self.pos))
+    }
+
+    fn take(&mut self, n: usize) -> Result<&[u8], Error> {
+        if self.bytes.len() - self.pos < n {
+            return Err(self.error("unexpected end of input"));
+        }
+        self.pos += n;
+        Ok(&self.bytes[self.pos - n..self.pos])
+    }
+
+    fn varint(&mut self) -> Result<u128, Error> {
+        let mut n = 0u128;
+        for shift in (0..128).step_by(7) {
+            let b = self.take(1)?[0];
+            n |= ((b & 0x7f) as u128) << shift;
+            if b & 0x80 == 0 {
+                return Ok(n);
+            }
+        }
+        Err(self.error("varint too long"))
+    }
+
+    // 长度不可能超过剩余的字节数，提前检查避免按恶意长度分配内存
+    fn len(&mut self) -> Result<usize, Error> {
+        let n = self.varint()?;
+        if n > (self.bytes.len() - self.pos) as u128 {
+            return Err(self.error("length exceeds input"));
+        }
+        Ok(n as usize)
+    }
+
+    fn string(&mut self) -> Result<String, Error> {
+        let n = self.len()?;
+        let start = self.pos;
+        String::from_utf8(self.take(n)?.to_vec())
+            .map_err(|_| Error::new(format!("invalid utf-8 at byte {}", start)))
+    }
+
+    fn node(&mut self, depth: usize) -> Result<Node, Error> {
+        if depth > MAX_DEPTH {
+            return Err(self.error("nesting too deep"));
+        }
+        Ok(match self.take(1)?[0] {
+            TAG_NULL => Node::Null,
+            TAG_FALSE => Node::Bool(false),
+            TAG_TRUE => Node::Bool(true),
+            TAG_INT => Node::Int(unzigzag(self.varint()?)),
+            TAG_FLOAT => Node::Float(f64::from_le_bytes(self.take(8)?.try_into().unwrap())),
+            TAG_STR => Node::Str(self.string()?),
+            TAG_SEQ => {
+                let n = self.len()?;
+                let items: Result<Vec<_>, _> = (0..n).map(|_| self.node(depth + 1)).collect();
+                Node::Seq(items?)
+            }
+            TAG_MAP => {
+                let n = self.len()?;
+                let mut members = Vec::with_capacity(n);
+                for _ in 0..n {
+                    let k = self.string()?;
+                    members.push((k, self.node(depth + 1)?));
+                }
+                Node::Map(members)
+            }
+            _ => {
+                self.pos -= 1;
+                return Err(self.error("invalid tag"));
+            }
+        })
+    }
+}
+
+pub fn to_binary<T: Versioned>(value: &T) -> Vec<u8> {
+    let mut out = MAGIC.to_vec();
+    write_varint(T::VERSION as u128, &mut out);
+    write_binary(&value.to_node(), &mut out);
+    out
+}
+
+pub fn load_binary<T: Versioned + ?Sized>(bytes: &[u8]) -> Result<Node, Error> {
+    if !bytes.starts_with(MAGIC) {
+        return Err(Error::new("bad magic"));
+    }
+    let mut reader = BinaryReader {
+        bytes,
+        pos: MAGIC.len(),
+    };
+    let version = u32::try_from(reader.varint()?).map_err(|_| Error::new("bad version"))?;
+    let data = reader.node(0)?;
+    if reader.pos != bytes.len() {
+        return Err(reader.error("trailing bytes"));
+    }
+    upgrade::<T>(version, data)
+}
+
+pub fn from_binary<T: Versioned + DeserializeOwned>(bytes: &[u8]) -> Result<T, Error> {
+    read(&load_binary::<T>(bytes)?)
+}
+
+/* ---------------------------- 练习 ---------------------------- */
+
+// 两种格式都要能读回同样的值，比较中间表示即可，不要求类型实现 PartialEq
+fn round_trip<T: Versioned + DeserializeOwned>(value: &T) -> usize {
+    let json = to_json(value);
+    let back: T = from_json(&json).unwrap_or_else(|e| panic!("{}: {}", json, e));
+    assert_eq!(back.to_node(), value.to_node(), "{}", json);
+    let bytes = to_binary(value);
+    let back: T = from_binary(&bytes).unwrap();
+    assert_eq!(back.to_node(), value.to_node());
+    assert!(bytes.len() < json.len());
+    bytes.len()
+}
+
+pub fn serial_practice() {
+    let user = User {
+        active: false,
+        username: "someusername123".to_string(),
+        email: "someone@example.com".to_string(),
+        sign_in_count: u64::MAX,
+    };
+    round_trip(&user);
+    // City 借用读到的数据，先读出 Node 再借用
+    let dublin = City {
+        name: "Dublin",
+        lat: 53.34778,
+        lon: -6.259722,
+    };
+    let data = load_json::<City>(&to_json(&dublin)).unwrap();
+    let city: City = read(&data).unwrap();
+    assert_eq!(city.to_node(), dublin.to_node());
+    let data = load_binary::<City>(&to_binary(&dublin)).unwrap();
+    let city: City = read(&data).unwrap();
+    assert_eq!(city.name, "Dublin");
+    round_trip(&Color {
+        red: 128,
+        green: 255,
+        blue: 90,
+    });
+    for suit in [
+        PokerSuit::Clubs,
+        PokerSuit::Spades,
+        PokerSuit::Diamonds,
+        PokerSuit::Hearts,
+    ] {
+        round_trip(&suit);
+    }
+    round_trip(&PokerCard {
+        suit: PokerSuit::Hearts,
+        value: 12,
+    });
+    round_trip(&vec![
+        PokerCard_1::Clubs(1),
+        PokerCard_1::Spades(5),
+        PokerCard_1::Diamonds(13),
+        PokerCard_1::Hearts(0),
+    ]);
+    round_trip(&Song {
+        author: "曲婉婷".to_string(),
+        name: String::from("《我的歌声里》\n\"\u{1F3B5}\""),
+    });
+    round_trip(&Person_2::new("Tom".to_string(), 30));
+
+    assert_eq!(
+        to_json(&Color {
+            red: 0,
+            green: 3,
+            blue: 254
+        }),
+        r#"{"version":1,"data":{"red":0,"green":3,"blue":254}}"#
+    );
+    assert_eq!(
+        to_binary(&PokerSuit::Spades),
+        [b'R', b'S', 1, TAG_STR, 6, b'S', b'p', b'a', b'd', b'e', b's']
+    );
+
+    // 版本迁移
+    let v1 = r#"{"version":1,"data":{"username":"u","email":"e","sign_in_count":1}}"#;
+    let user: User = from_json(v1).unwrap();
+    assert!(user.active);
+    let future = r#"{"version":3,"data":{}}"#;
+    let err = from_json::<User>(future).err().unwrap();
+    assert_eq!(err.message(), "unsupported version 3 (current 2)");
+
+    // 错误信息带有字段路径
+    let err = from_json::<PokerCard>(r#"{"version":1,"data":{"suit":"Joker","value":1}}"#)
+        .err()
+        .unwrap();
+    assert_eq!(
+        err.to_string(),
+        "$.data.suit: unknown variant \"Joker\" of PokerSuit"
+    );
+    let err = from_json::<Vec<PokerCard_1>>(r#"{"version":1,"data":[{"Clubs":1},{"Hearts":300}]}"#)
+        .err()
+        .unwrap();
+    assert_eq!(err.path(), "$.data[1].Hearts");
+    assert_eq!(err.message(), "300 out of range for u8");
+    let err = from_json::<Song>(r#"{"version":1,"data":{"author":"a"}}"#)
+        .err()
+        .unwrap();
+    assert_eq!(err.to_string(), "$.data.name: missing field");
+    let err = from_json::<Color>(r#"{"version":1,"data":{"red":"1"}}"#)
+        .err()
+        .unwrap();
+    assert_eq!(err.to_string(), "$.data.red: expected u8, found string");
+    assert!(from_json::<Color>(r#"{"version":1,"data":{"red":1,}"#).is_err());
+    assert!(parse_json(&"[".repeat(1000)).is_err());
+    assert_eq!(
+        parse_json(r#" "🎵" "#),
+        Ok(Node::Str("\u{1F3B5}".to_string()))
+    );
+
+    // 截断或损坏的二进制数据
+    let bytes = to_binary(&Person_2::new("Tom".to_string(), 30));
+    for n in 0..bytes.len() {
+        assert!(from_binary::<Person_2>(&bytes[..n]).is_err());
+    }
+    let mut huge = MAGIC.to_vec();
+    huge.extend_from_slice(&[1, TAG_SEQ, 0xff, 0xff, 0xff, 0xff, 0x0f]);
+    assert!(from_binary::<Vec<Color>>(&huge).is_err());
+    for n in [0, 1, -1, 63, -64, 64, i64::MIN as i128, u64::MAX as i128] {
+        assert_eq!(unzigzag(zigzag(n)), n);
+    }
+
+    println!("{}", to_json(&user));
+    println!("{} bytes in binary", to_binary(&user).len());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /*
+     * 两种格式都要读回同样的中间表示，类型借用输入时也能用同一套写法
+     * 没有实现的类型：R1 借用的是 &i32，无法从数据中借用；MyBox、MyBox1 是裸指针；
+     * Screen 持有 trait 对象和闭包；
+     * 函数内部定义的 Structure、Color、AlwaysEqual、MyEnum2 在函数外无法命名
+     */
+    macro_rules! assert_round_trip {
+        ($ty:ty; $($value:expr),+ $(,)?) => {$(
+            let value: $ty = $value;
+            let json = to_json(&value);
+            let data = load_json::<$ty>(&json).unwrap_or_else(|e| panic!("{}: {}", json, e));
+            let back: $ty = read(&data).unwrap_or_else(|e| panic!("{}: {}", json, e));
+            assert_eq!(back.to_node(), value.to_node(), "{}", json);
+            let data = load_binary::<$ty>(&to_binary(&value)).unwrap();
+            let back: $ty = read(&data).unwrap();
+            assert_eq!(back.to_node(), value.to_node(), "{}", json);
+        )+};
+    }
+
+    #[test]
+    fn display_chapter_types() {
+        assert_round_trip!(Structure; Structure(3), Structure(i32::MIN));
+        assert_round_trip!(Deep; Deep(Structure(7)));
+        assert_round_trip!(Person<'_>; Person { name: "Peter", age: 27 });
+        assert_round_trip!(DisplayStructure; DisplayStructure(-1));
+        assert_round_trip!(MinMax; MinMax(0, 14), MinMax(i64::MIN, i64::MAX));
+        assert_round_trip!(Point2D; Point2D { x: 3.3, y: 7.2 });
+        assert_round_trip!(List; List(vec![1, 2, 3]), List(Vec::new()));
+        assert_round_trip!(City<'_>; City { name: "Oslo", lat: 59.95, lon: 10.75 });
+        assert_round_trip!(Color; Color { red: 128, green: 255, blue: 90 });
+    }
+
+    #[test]
+    fn layout_chapter_types() {
+        assert_round_trip!(A; A { a: 1, b: 2, c: 3 });
+        assert_round_trip!(B; B { a: u8::MAX, b: u32::MAX, c: u16::MAX });
+        assert_round_trip!(D; D::One, D::Two);
+        assert_round_trip!(E; E::N, E::H(5), E::M(Box::new(6)));
+        assert_round_trip!(G; G);
+    }
+
+    #[test]
+    fn struct_chapter_types() {
+        assert_round_trip!(User; User {
+            active: true,
+            username: "someusername123".to_string(),
+            email: "someone@example.com".to_string(),
+            sign_in_count: 1,
+        });
+        assert_round_trip!(Person_1; Person_1 { name: "Alice".to_string(), age: Box::new(30) });
+        assert_round_trip!(Point; Point { x: -1, y: 1 });
+        assert_round_trip!(PokerCard; PokerCard { suit: PokerSuit::Clubs, value: 1 });
+        assert_round_trip!(MyCircularQueue; MyCircularQueue { v: vec![1, 2, 0], head: 0, tail: 2 });
+        assert_round_trip!(V4; V4("127.0.0.1".to_string()));
+        assert_round_trip!(V6; V6("::1".to_string()));
+        assert_round_trip!(Person_2; Person_2::new("Tom".to_string(), 30));
+        assert_round_trip!(Person_3; Person_3 { name: "Zoe".to_string(), age: 18 });
+        assert_round_trip!(
+            Weibo;
+            Weibo { username: "sunface".to_string(), content: "好像微博没Tweet好用".to_string() },
+        );
+        assert_round_trip!(Human; Human);
+        assert_round_trip!(Wrapper; Wrapper(vec!["hello".to_string(), "world".to_string()]));
+        assert_round_trip!(ImportantExcerpt<'_>; ImportantExcerpt { part: "Call me Ishmael" });
+        assert_round_trip!(Foo3; Foo3);
+        assert_round_trip!(Counter; Counter { count: 5 });
+        assert_round_trip!(Wrapper_1; Wrapper_1(vec![String::new()]));
+        assert_round_trip!(Button1; Button1 { id: 1 });
+        assert_round_trip!(Select; Select { id: 2 });
+        assert_round_trip!(Counter1; Counter1 { count: u64::MAX });
+        assert_round_trip!(Song; Song { author: "曲婉婷".to_string(), name: "《我的歌声里》".to_string() });
+    }
+
+    #[test]
+    fn enum_chapter_types() {
+        assert_round_trip!(
+            PokerSuit;
+            PokerSuit::Clubs,
+            PokerSuit::Spades,
+            PokerSuit::Diamonds,
+            PokerSuit::Hearts,
+        );
+        assert_round_trip!(
+            PokerCard_1;
+            PokerCard_1::Clubs(1),
+            PokerCard_1::Spades(5),
+            PokerCard_1::Diamonds(13),
+            PokerCard_1::Hearts(0),
+        );
+        assert_round_trip!(
+            Direction;
+            Direction::East,
+            Direction::West,
+            Direction::North,
+            Direction::South,
+        );
+        assert_round_trip!(IpAddr; IpAddr::Ipv4, IpAddr::Ipv6);
+        assert_round_trip!(UsState; UsState::Alabama, UsState::Alaska);
+        assert_round_trip!(
+            Coin;
+            Coin::Penny,
+            Coin::Nickel,
+            Coin::Dime,
+            Coin::Quarter(UsState::Alaska),
+        );
+        assert_round_trip!(
+            Action;
+            Action::Say("hi".to_string()),
+            Action::MoveTo(1, -2),
+            Action::ChangeColorRGB(1, 2, 3),
+        );
+        assert_round_trip!(
+            Message;
+            Message::Quit,
+            Message::Move { x: 1, y: 2 },
+            Message::Write("hello".to_string()),
+            Message::ChangeColor(0, 160, 255),
+        );
+        assert_round_trip!(
+            Messagea;
+            Messagea::Quit,
+            Messagea::Move { x: -3, y: 4 },
+            Messagea::Write(String::new()),
+            Messagea::ChangeColor(1, 2, 3),
+        );
+        assert_round_trip!(
+            IpAddr_1;
+            IpAddr_1::V4("127.0.0.1".to_string()),
+            IpAddr_1::V6("::1".to_string()),
+        );
+        assert_round_trip!(MyEnum; MyEnum::A, MyEnum::B, MyEnum::C);
+        assert_round_trip!(MyEnum1; MyEnum1::A, MyEnum1::B, MyEnum1::C);
+        assert_round_trip!(Fruit; Fruit::Apple(3), Fruit::Orange("ripe".to_string()));
+    }
+
+    #[test]
+    fn generic_and_widget_types() {
+        assert_round_trip!(MyQueue<i32>; MyQueue { qdata: vec![1, 2, 3] });
+        assert_round_trip!(MyQueue<String>; MyQueue { qdata: vec!["a".to_string()] });
+        assert_round_trip!(Point_1<f64>; Point_1 { x: 1.0, y: 4.0 });
+        assert_round_trip!(Point_2<i32, String>; Point_2 { x: 5, y: "c".to_string() });
+        assert_round_trip!(Point_3<i64>; Point_3 { x: 1, y: 2 });
+        let button = || Button {
+            width: 50,
+            height: 10,
+            label: "OK".to_string(),
+            on_click: Some(Box::new(|_| {})),
+        };
+        assert_round_trip!(Button; button());
+        assert_round_trip!(SelectBox; SelectBox {
+            width: 75,
+            height: 10,
+            options: vec!["Yes".to_string(), "No".to_string()],
+            selected: 1,
+            on_change: None,
+        });
+        assert_round_trip!(Screen_1<Button>; Screen_1 { components: vec![button(), button()] });
+
+        // 闭包不保存，读回时为 None
+        let back: Button = from_json(&to_json(&button())).unwrap();
+        assert!(back.on_click.is_none());
+        assert_eq!(back.label, "OK");
+    }
+
+    #[test]
+    fn shapes() {
+        let data =
+            |json: String| json[json.find("\"data\":").unwrap() + 7..json.len() - 1].to_string();
+        assert_eq!(data(to_json(&Structure(3))), "3");
+        assert_eq!(data(to_json(&MinMax(0, 14))), "[0,14]");
+        assert_eq!(data(to_json(&G)), "null");
+        assert_eq!(data(to_json(&Message::Quit)), r#""Quit""#);
+        assert_eq!(
+            data(to_json(&Message::Write("w".to_string()))),
+            r#"{"Write":"w"}"#
+        );
+        assert_eq!(data(to_json(&Action::MoveTo(1, 2))), r#"{"MoveTo":[1,2]}"#);
+        assert_eq!(
+            data(to_json(&Message::Move { x: 1, y: 2 })),
+            r#"{"Move":{"x":1,"y":2}}"#
+        );
+    }
+
+    #[test]
+    fn variant_and_tuple_errors() {
+        let err = |json: &str| from_json::<Action>(json).err().unwrap().to_string();
+        assert_eq!(
+            err(r#"{"version":1,"data":{"MoveTo":[1]}}"#),
+            "$.data.MoveTo: expected 2 elements for MoveTo, found 1"
+        );
+        assert_eq!(
+            err(r#"{"version":1,"data":{"MoveTo":[1,"2"]}}"#),
+            "$.data.MoveTo[1]: expected i32, found string"
+        );
+        assert_eq!(
+            err(r#"{"version":1,"data":"Say"}"#),
+            "$.data.Say: missing variant data"
+        );
+        assert_eq!(
+            err(r#"{"version":1,"data":{"Jump":1}}"#),
+            "$.data: unknown variant \"Jump\" of Action"
+        );
+        assert_eq!(
+            from_json::<Message>(r#"{"version":1,"data":{"Quit":1}}"#)
+                .err()
+                .unwrap()
+                .to_string(),
+            "$.data.Quit: unexpected data for unit variant"
+        );
+        assert_eq!(
+            from_json::<Message>(r#"{"version":1,"data":{"Move":{"x":1}}}"#)
+                .err()
+                .unwrap()
+                .to_string(),
+            "$.data.Move.y: missing field"
+        );
+        assert_eq!(
+            from_json::<G>(r#"{"version":1,"data":{}}"#)
+                .err()
+                .unwrap()
+                .to_string(),
+            "$.data: expected G, found object"
+        );
+    }
+
+    #[test]
+    fn borrowed_str_points_into_the_data() {
+        let data =
+            load_json::<Person>(r#"{"version":1,"data":{"name":"Peter","age":27}}"#).unwrap();
+        let person: Person = read(&data).unwrap();
+        let Some(Node::Str(name)) = data.get("name") else {
+            panic!("name is a string");
+        };
+        assert_eq!(person.name.as_ptr(), name.as_ptr());
+        assert_eq!((person.name, person.age), ("Peter", 27));
+    }
+
+    #[test]
+    fn non_finite_floats_round_trip() {
+        for x in [f64::INFINITY, f64::NEG_INFINITY] {
+            let back: f64 = from_json(&to_json(&Point_1 { x, y: 0.0 }))
+                .map(|p: Point_1<f64>| p.x)
+                .unwrap();
+            assert_eq!(back, x);
+        }
+        let p: Point_1<f64> = from_json(&to_json(&Point_1 {
+            x: f64::NAN,
+            y: 1.0,
+        }))
+        .unwrap();
+        assert!(p.x.is_nan());
+        assert_eq!(
+            to_json(&Point2D {
+                x: f64::NAN,
+                y: f64::NEG_INFINITY
+            }),
+            r#"{"version":1,"data":{"x":"NaN","y":"-Infinity"}}"#
+        );
+        assert_eq!(
+            f32::from_node(&Node::Str("Infinity".to_string())),
+            Ok(f32::INFINITY)
+        );
+
+        // None 和 Some(NaN) 可以区分
+        let values = vec![None, Some(f64::NAN), Some(1.5)];
+        let back: Vec<Option<f64>> =
+            read(&parse_json(&node_to_json(&values.to_node())).unwrap()).unwrap();
+        assert_eq!(back[0], None);
+        assert!(back[1].is_some_and(f64::is_nan));
+        assert_eq!(back[2], Some(1.5));
+
+        assert_eq!(
+            f64::from_node(&Node::Null).err().unwrap().message(),
+            "expected f64, found null"
+        );
+        assert_eq!(
+            f64::from_node(&Node::Str("nan".to_string()))
+                .err()
+                .unwrap()
+                .message(),
+            "invalid f64 \"nan\""
+        );
+    }
+
+    #[test]
+    fn strict_json_numbers() {
+        for ok in [
+            "0",
+            "-0",
+            "10",
+            "0.5",
+            "-1.25e-3",
+            "1E+2",
+            "18446744073709551615",
+        ] {
+            assert!(parse_json(ok).is_ok(), "{}", ok);
+        }
+        assert_eq!(parse_json("-0"), Ok(Node::Int(0)));
+        for bad in [
+            "01", "-01", "00", "1.", "1.e5", "1e", "1e+", "-", "1e400", "[00]", "-.5",
+        ] {
+            assert!(parse_json(bad).is_err(), "{}", bad);
+        }
+        assert_eq!(
+            parse_json("01").err().unwrap().message(),
+            "invalid number \"01\" at byte 0"
+        );
+        assert_eq!(
+            from_json::<Color>(r#"{"version":1,"data":{"red":007,"green":0,"blue":0}}"#)
+                .err()
+                .unwrap()
+                .message(),
+            "invalid number \"007\" at byte 27"
+        );
+    }
+}
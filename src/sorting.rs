@@ -0,0 +1,801 @@
+//! 泛型排序、选择和二分查找
+//! 原来只有 largest<T: PartialOrd + Copy> 和 Person_2 上的 sort_unstable_by，这里补上常见的算法：
+//! - merge_sort_by：稳定，对下标数组归并后按置换原地重排，不要求 T: Clone
+//! - quick_sort_by：内省排序，三数取中的快排，递归过深时改用堆排序，小区间用插入排序
+//! - heap_sort_by
+//! - radix_sort：整数的 LSD 基数排序，有符号数翻转符号位后按无符号处理
+//! - Keys：多关键字比较器(比如先按 name 再按 age，和 Person_3 派生的 Ord 相同)，用稳定排序
+//! - select_nth_by / top_k：快速选择和用堆维护的前 k 大
+//! - lower_bound / upper_bound / equal_range / partition_point 等二分查找
+//!
+//! 所有比较函数都是 FnMut(&T, &T) -> Ordering，和 std 的 *_by 方法一致。测试中用随机数据和 std 的结果逐一比较
+
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+
+use crate::{Person_2, Person_3};
+
+// 小于这个长度的区间用插入排序
+const INSERTION_MAX: usize = 16;
+
+/* ---------------------------- 插入排序 ---------------------------- */
+
+// 稳定：只有严格小于时才向前移动
+pub fn insertion_sort_by<T, F>(v: &mut [T], mut cmp: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    for i in 1..v.len() {
+        let mut j = i;
+        while j > 0 && cmp(&v[j], &v[j - 1]) == Ordering::Less {
+            v.swap(j, j - 1);
+            j -= 1;
+        }
+    }
+}
+
+/* ---------------------------- 归并排序 ---------------------------- */
+
+// 自底向上归并，先用插入排序整理长度为 INSERTION_MAX 的小段；返回 order[i] 为排序后第 i 个元素的原下标
+fn merge_order<T, F>(v: &[T], cmp: &mut F) -> Vec<usize>
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let n = v.len();
+    let mut order: Vec<usize> = (0..n).collect();
+    for chunk in order.chunks_mut(INSERTION_MAX) {
+        insertion_sort_by(chunk, |&a, &b| cmp(&v[a], &v[b]));
+    }
+    let mut buf = vec![0; n];
+    let mut width = INSERTION_MAX;
+    while width < n {
+        for start in (0..n).step_by(2 * width) {
+            let mid = (start + width).min(n);
+            let end = (start + 2 * width).min(n);
+            let (mut i, mut j) = (start, mid);
+            for slot in &mut buf[start..end] {
+                // 相等时取左边的，保证稳定
+                let take_left =
+                    j >= end || (i < mid && cmp(&v[order[j]], &v[order[i]]) != Ordering::Less);
+                if take_left {
+                    *slot = order[i];
+                    i += 1;
+                } else {
+                    *slot = order[j];
+                    j += 1;
+                }
+            }
+        }
+        std::mem::swap(&mut order, &mut buf);
+        width *= 2;
+    }
+    order
+}
+
+// 按 order 重排：位置 i 放原来的 v[order[i]]，沿着置换的环交换，每个元素只移动一次
+fn apply_order<T>(v: &mut [T], mut order: Vec<usize>) {
+    for start in 0..v.len() {
+        if order[start] == start {
+            continue;
+        }
+        let mut i = start;
+        loop {
+            let j = order[i];
+            order[i] = i;
+            if j == start {
+                break;
+            }
+            v.swap(i, j);
+            i = j;
+        }
+    }
+}
+
+pub fn merge_sort_by<T, F>(v: &mut [T], mut cmp: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let order = merge_order(v, &mut cmp);
+    apply_order(v, order);
+}
+
+pub fn merge_sort<T: Ord>(v: &mut [T]) {
+    merge_sort_by(v, T::cmp);
+}
+
+pub fn merge_sort_by_key<T, K: Ord>(v: &mut [T], mut key: impl FnMut(&T) -> K) {
+    merge_sort_by(v, |a, b| key(a).cmp(&key(b)));
+}
+
+/* ---------------------------- 堆排序 ---------------------------- */
+
+fn sift_down<T, F>(v: &mut [T], mut root: usize, cmp: &mut F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    loop {
+        let mut child = 2 * root + 1;
+        if child >= v.len() {
+            return;
+        }
+        if child + 1 < v.len() && cmp(&v[child], &v[child + 1]) == Ordering::Less {
+            child += 1;
+        }
+        if cmp(&v[root], &v[child]) != Ordering::Less {
+            return;
+        }
+        v.swap(root, child);
+        root = child;
+    }
+}
+
+fn heap_sort_with<T, F>(v: &mut [T], cmp: &mut F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    for i in (0..v.len() / 2).rev() {
+        sift_down(v, i, cmp);
+    }
+    for end in (1..v.len()).rev() {
+        v.swap(0, end);
+        sift_down(&mut v[..end], 0, cmp);
+    }
+}
+
+pub fn heap_sort_by<T, F>(v: &mut [T], mut cmp: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    heap_sort_with(v, &mut cmp);
+}
+
+pub fn heap_sort<T: Ord>(v: &mut [T]) {
+    heap_sort_by(v, T::cmp);
+}
+
+/* ---------------------------- 内省排序 ---------------------------- */
+
+// 把 lo、mid、hi 三个位置的中位数换到 hi 作为基准
+fn median_of_three<T, F>(v: &mut [T], cmp: &mut F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let (lo, mid, hi) = (0, v.len() / 2, v.len() - 1);
+    if cmp(&v[mid], &v[lo]) == Ordering::Less {
+        v.swap(mid, lo);
+    }
+    if cmp(&v[hi], &v[lo]) == Ordering::Less {
+        v.swap(hi, lo);
+    }
+    if cmp(&v[mid], &v[hi]) == Ordering::Less {
+        v.swap(mid, hi);
+    }
+}
+
+// 三路划分：返回 (lt, gt)，[0, lt) 小于基准，[lt, gt) 等于基准，[gt, len) 大于基准；大量重复元素时不会退化
+fn partition3<T, F>(v: &mut [T], cmp: &mut F) -> (usize, usize)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    median_of_three(v, cmp);
+    let last = v.len() - 1;
+    v.swap(0, last);
+    // 基准放在位置 0，划分过程中一直在等于区间的开头
+    let (mut lt, mut i, mut gt) = (0, 1, v.len());
+    while i < gt {
+        match cmp(&v[i], &v[lt]) {
+            Ordering::Less => {
+                v.swap(lt, i);
+                lt += 1;
+                i += 1;
+            }
+            Ordering::Greater => {
+                gt -= 1;
+                v.swap(i, gt);
+            }
+            Ordering::Equal => i += 1,
+        }
+    }
+    (lt, gt)
+}
+
+fn introsort<T, F>(mut v: &mut [T], mut depth: u32, cmp: &mut F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    // 只对较短的一侧递归，较长的一侧循环处理，栈深度 O(log n)
+    while v.len() > INSERTION_MAX {
+        if depth == 0 {
+            heap_sort_with(v, cmp);
+            return;
+        }
+        depth -= 1;
+        let (lt, gt) = partition3(v, cmp);
+        let (left, rest) = v.split_at_mut(lt);
+        let right = &mut rest[gt - lt..];
+        if left.len() < right.len() {
+            introsort(left, depth, cmp);
+            v = right;
+        } else {
+            introsort(right, depth, cmp);
+            v = left;
+        }
+    }
+    insertion_sort_by(v, |a, b| cmp(a, b));
+}
+
+fn depth_limit(n: usize) -> u32 {
+    2 * (usize::BITS - n.leading_zeros())
+}
+
+pub fn quick_sort_by<T, F>(v: &mut [T], mut cmp: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    introsort(v, depth_limit(v.len()), &mut cmp);
+}
+
+pub fn quick_sort<T: Ord>(v: &mut [T]) {
+    quick_sort_by(v, T::cmp);
+}
+
+/* ---------------------------- 基数排序 ---------------------------- */
+
+// 映射到保持顺序的无符号数：有符号数翻转符号位，负数就排到了正数前面
+pub trait RadixKey: Copy {
+    const BYTES: usize;
+    fn radix_key(self) -> u64;
+}
+
+macro_rules! radix_key {
+    ($($t:ty => $u:ty),* $(,)?) => {$(
+        impl RadixKey for $t {
+            const BYTES: usize = std::mem::size_of::<$t>();
+
+            #[allow(clippy::unnecessary_cast)]
+            fn radix_key(self) -> u64 {
+                const FLIP: $u = if <$t>::MIN == 0 as $t { 0 } else { 1 << (<$u>::BITS - 1) };
+                (self as $u ^ FLIP) as u64
+            }
+        }
+    )*};
+}
+
+radix_key!(
+    u8 => u8, u16 => u16, u32 => u32, u64 => u64, usize => usize,
+    i8 => u8, i16 => u16, i32 => u32, i64 => u64, isize => usize,
+);
+
+// LSD：从最低字节开始做稳定的计数排序，某一字节全部相同时跳过这一轮
+pub fn radix_sort_by_key<T: Copy, K: RadixKey>(v: &mut [T], key: impl Fn(&T) -> K) {
+    let mut buf = v.to_vec();
+    let mut src_is_v = true;
+    for byte in 0..K::BYTES {
+        let shift = 8 * byte;
+        let digit = |x: &T| ((key(x).radix_key() >> shift) & 0xFF) as usize;
+        let (src, dst): (&[T], &mut [T]) = if src_is_v {
+            (&*v, &mut buf)
+        } else {
+            (&buf, &mut *v)
+        };
+        let mut counts = [0usize; 256];
+        for x in src {
+            counts[digit(x)] += 1;
+        }
+        if counts.contains(&src.len()) {
+            continue;
+        }
+        let mut next = 0;
+        for c in counts.iter_mut() {
+            next += std::mem::replace(c, next);
+        }
+        for x in src {
+            let d = digit(x);
+            dst[counts[d]] = *x;
+            counts[d] += 1;
+        }
+        src_is_v = !src_is_v;
+    }
+    if !src_is_v {
+        v.copy_from_slice(&buf);
+    }
+}
+
+pub fn radix_sort<T: RadixKey>(v: &mut [T]) {
+    radix_sort_by_key(v, |&x| x);
+}
+
+/* ---------------------------- 多关键字 ---------------------------- */
+
+type Compare<'a, T> = Box<dyn Fn(&T, &T) -> Ordering + 'a>;
+
+// 依次比较每个关键字，前一个相等时才看下一个
+pub struct Keys<'a, T> {
+    keys: Vec<Compare<'a, T>>,
+}
+
+impl<'a, T> Keys<'a, T> {
+    pub fn new() -> Self {
+        Keys { keys: Vec::new() }
+    }
+
+    pub fn asc<K: Ord>(mut self, key: impl Fn(&T) -> K + 'a) -> Self {
+        self.keys.push(Box::new(move |a, b| key(a).cmp(&key(b))));
+        self
+    }
+
+    pub fn desc<K: Ord>(mut self, key: impl Fn(&T) -> K + 'a) -> Self {
+        self.keys.push(Box::new(move |a, b| key(b).cmp(&key(a))));
+        self
+    }
+
+    // 关键字是借用的字段(比如 String)时直接给出比较函数，避免每次比较都复制
+    pub fn by(mut self, cmp: impl Fn(&T, &T) -> Ordering + 'a) -> Self {
+        self.keys.push(Box::new(cmp));
+        self
+    }
+
+    pub fn compare(&self, a: &T, b: &T) -> Ordering {
+        self.keys
+            .iter()
+            .map(|k| k(a, b))
+            .find(|o| o.is_ne())
+            .unwrap_or(Ordering::Equal)
+    }
+
+    // 稳定排序：所有关键字都相等的元素保持原来的相对顺序
+    pub fn sort(&self, v: &mut [T]) {
+        merge_sort_by(v, |a, b| self.compare(a, b));
+    }
+}
+
+impl<T> Default for Keys<'_, T> {
+    fn default() -> Self {
+        Keys::new()
+    }
+}
+
+/* ---------------------------- 选择 ---------------------------- */
+
+// 快速选择：把第 k 小的元素放到位置 k，左边都不大于它、右边都不小于它，返回它的引用
+pub fn select_nth_by<T, F>(v: &mut [T], k: usize, mut cmp: F) -> &mut T
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    assert!(
+        k < v.len(),
+        "select_nth: index {} out of range for length {}",
+        k,
+        v.len()
+    );
+    let (mut lo, mut hi) = (0, v.len());
+    let mut depth = depth_limit(v.len());
+    while hi - lo > INSERTION_MAX {
+        if depth == 0 {
+            // 划分总是很不均匀，退化成排序保证 O(n log n)
+            heap_sort_with(&mut v[lo..hi], &mut cmp);
+            return &mut v[k];
+        }
+        depth -= 1;
+        let (lt, gt) = partition3(&mut v[lo..hi], &mut cmp);
+        if k < lo + lt {
+            hi = lo + lt;
+        } else if k >= lo + gt {
+            lo += gt;
+        } else {
+            return &mut v[k];
+        }
+    }
+    insertion_sort_by(&mut v[lo..hi], &mut cmp);
+    &mut v[k]
+}
+
+pub fn select_nth<T: Ord>(v: &mut [T], k: usize) -> &mut T {
+    select_nth_by(v, k, T::cmp)
+}
+
+// 最大的 k 个元素，从大到小；只保留一个大小为 k 的小顶堆，适合在很长的迭代器上取前几名
+pub fn top_k<T: Ord>(iter: impl IntoIterator<Item = T>, k: usize) -> Vec<T> {
+    if k == 0 {
+        return Vec::new();
+    }
+    let mut heap = BinaryHeap::with_capacity(k + 1);
+    for x in iter {
+        if heap.len() < k {
+            heap.push(Reverse(x));
+        } else if let Some(mut min) = heap.peek_mut() {
+            if x > min.0 {
+                min.0 = x;
+            }
+        }
+    }
+    heap.into_sorted_vec()
+        .into_iter()
+        .map(|Reverse(x)| x)
+        .collect()
+}
+
+// 按 key 取最大的 k 个，key 相同时先出现的优先
+pub fn top_k_by_key<T, K: Ord>(
+    iter: impl IntoIterator<Item = T>,
+    k: usize,
+    key: impl Fn(&T) -> K,
+) -> Vec<T> {
+    let keyed = iter
+        .into_iter()
+        .enumerate()
+        .map(|(i, x)| (key(&x), Reverse(i), Unordered(x)));
+    top_k(keyed, k).into_iter().map(|(_, _, w)| w.0).collect()
+}
+
+// 让不可比较的 T 跟在可比较的键后面参与比较，键都不同所以不会真的比较到它
+struct Unordered<T>(T);
+
+impl<T> PartialEq for Unordered<T> {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+impl<T> Eq for Unordered<T> {}
+
+impl<T> PartialOrd for Unordered<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for Unordered<T> {
+    fn cmp(&self, _: &Self) -> Ordering {
+        Ordering::Equal
+    }
+}
+
+/* ---------------------------- 二分查找 ---------------------------- */
+
+// 第一个使 pred 为 false 的位置，要求 v 中 pred 为 true 的元素全部在前面
+pub fn partition_point<T>(v: &[T], mut pred: impl FnMut(&T) -> bool) -> usize {
+    let (mut lo, mut hi) = (0, v.len());
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        if pred(&v[mid]) {
+            lo = mid + 1;
+        } else {
+            hi = mid;
+        }
+    }
+    lo
+}
+
+// 第一个不小于 x 的位置
+pub fn lower_bound<T: Ord>(v: &[T], x: &T) -> usize {
+    partition_point(v, |e| e < x)
+}
+
+// 第一个大于 x 的位置
+pub fn upper_bound<T: Ord>(v: &[T], x: &T) -> usize {
+    partition_point(v, |e| e <= x)
+}
+
+// 所有等于 x 的元素的范围
+pub fn equal_range<T: Ord>(v: &[T], x: &T) -> std::ops::Range<usize> {
+    lower_bound(v, x)..upper_bound(v, x)
+}
+
+// 第一个和最后一个等于 x 的下标；std 的 binary_search 在有重复时返回其中任意一个
+pub fn find_first<T: Ord>(v: &[T], x: &T) -> Option<usize> {
+    let i = lower_bound(v, x);
+    (i < v.len() && v[i] == *x).then_some(i)
+}
+
+pub fn find_last<T: Ord>(v: &[T], x: &T) -> Option<usize> {
+    let i = upper_bound(v, x);
+    (i > 0 && v[i - 1] == *x).then(|| i - 1)
+}
+
+// 升序数组旋转之后(比如 [4, 5, 6, 1, 2, 3])查找 x，元素不重复
+pub fn search_rotated<T: Ord>(v: &[T], x: &T) -> Option<usize> {
+    let first = v.first()?;
+    // 旋转点：第一个小于 v[0] 的位置
+    let pivot = partition_point(v, |e| e >= first);
+    let (head, tail) = v.split_at(pivot);
+    if x >= first {
+        find_first(head, x)
+    } else {
+        find_first(tail, x).map(|i| pivot + i)
+    }
+}
+
+/* ---------------------------- 练习 ---------------------------- */
+
+// 随机数据和 std 对照的性质测试在下面的 tests 模块中，这里是固定的例子
+
+fn sort_practice() {
+    let v = vec![5, -3, 9, 0, -3, 2, 7, 1];
+    let mut expected = v.clone();
+    expected.sort();
+    type SortFn = fn(&mut [i64]);
+    let sorts: [(&str, SortFn); 4] = [
+        ("merge", merge_sort),
+        ("quick", quick_sort),
+        ("heap", heap_sort),
+        ("radix", radix_sort),
+    ];
+    for (name, sort) in sorts {
+        let mut got = v.clone();
+        sort(&mut got);
+        assert_eq!(got, expected, "{} sort", name);
+    }
+    let mut desc = v.clone();
+    quick_sort_by(&mut desc, |a, b| b.cmp(a));
+    assert_eq!(desc, [9, 7, 5, 2, 1, 0, -3, -3]);
+
+    // 其他整数类型，包括边界值
+    let mut bytes: Vec<i8> = vec![0, -1, 127, -128, 5, -5];
+    radix_sort(&mut bytes);
+    assert_eq!(bytes, [-128, -5, -1, 0, 5, 127]);
+    let mut wide: Vec<u64> = vec![u64::MAX, 0, 1 << 40, 3];
+    radix_sort(&mut wide);
+    assert_eq!(wide, [0, 3, 1 << 40, u64::MAX]);
+    let mut sizes: Vec<isize> = vec![isize::MIN, isize::MAX, 0];
+    radix_sort(&mut sizes);
+    assert_eq!(sizes, [isize::MIN, 0, isize::MAX]);
+
+    // 不能复制的元素也可以用归并排序，长度相同的保持原顺序
+    let mut words: Vec<String> = ["pear", "fig", "apple", "kiwi", "date"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+    merge_sort_by_key(&mut words, |w| w.len());
+    assert_eq!(words, ["fig", "pear", "kiwi", "date", "apple"]);
+    let mut pairs = [(3, 'a'), (1, 'b'), (3, 'c'), (1, 'd')];
+    radix_sort_by_key(&mut pairs, |p| p.0);
+    assert_eq!(pairs, [(1, 'b'), (1, 'd'), (3, 'a'), (3, 'c')]);
+}
+
+fn people_practice() {
+    // 对应 vector() 中按年龄倒序的 sort_unstable_by
+    let mut people = vec![
+        Person_2::new("Zoe".to_string(), 25),
+        Person_2::new("Al".to_string(), 60),
+        Person_2::new("John".to_string(), 1),
+    ];
+    quick_sort_by(&mut people, |a, b| b.age.cmp(&a.age));
+    let ages: Vec<u32> = people.iter().map(|p| p.age).collect();
+    assert_eq!(ages, [60, 25, 1]);
+
+    // 先按 name 再按 age，和 Person_3 派生的 Ord 结果相同
+    let make = || {
+        vec![
+            Person_3::new("Zoe".to_string(), 25),
+            Person_3::new("Al".to_string(), 60),
+            Person_3::new("Al".to_string(), 30),
+            Person_3::new("John".to_string(), 1),
+            Person_3::new("John".to_string(), 25),
+        ]
+    };
+    let mut expected = make();
+    expected.sort_unstable();
+    let mut people = make();
+    Keys::new()
+        .by(|a: &Person_3, b| a.name.cmp(&b.name))
+        .asc(|p| p.age)
+        .sort(&mut people);
+    assert_eq!(people, expected);
+
+    // 年龄降序，相同年龄保持原顺序(Zoe 在 John 前面)
+    let mut people = make();
+    Keys::new().desc(|p: &Person_3| p.age).sort(&mut people);
+    let names: Vec<&str> = people.iter().map(|p| p.name.as_str()).collect();
+    assert_eq!(names, ["Al", "Al", "Zoe", "John", "John"]);
+    assert_eq!(
+        Keys::<Person_3>::default().compare(&people[0], &people[1]),
+        Ordering::Equal
+    );
+
+    // 按年龄取最大的三个，年龄相同的先出现者优先
+    let oldest = top_k_by_key(make(), 3, |p| p.age);
+    let oldest: Vec<(&str, u32)> = oldest.iter().map(|p| (p.name.as_str(), p.age)).collect();
+    assert_eq!(oldest, [("Al", 60), ("Al", 30), ("Zoe", 25)]);
+}
+
+fn select_practice() {
+    let mut v = vec![9, 1, 8, 2, 7, 3, 6, 4, 5];
+    assert_eq!(*select_nth(&mut v, 4), 5);
+    assert!(v[..4].iter().all(|&x| x <= 5) && v[5..].iter().all(|&x| x >= 5));
+    assert_eq!(top_k(v, 3), [9, 8, 7]);
+    assert!(top_k(vec![1, 2, 3], 0).is_empty());
+}
+
+fn search_practice() {
+    let v = [1, 3, 3, 3, 5, 8];
+    assert_eq!((lower_bound(&v, &3), upper_bound(&v, &3)), (1, 4));
+    assert_eq!(equal_range(&v, &4), 4..4);
+    assert_eq!(partition_point(&v, |&x| x < 5), 4);
+    assert_eq!((find_first(&v, &3), find_last(&v, &3)), (Some(1), Some(3)));
+    assert_eq!(find_first(&v, &4), None);
+    assert_eq!(search_rotated(&[5, 8, 1, 3], &1), Some(2));
+    assert_eq!(search_rotated::<i32>(&[], &1), None);
+}
+
+pub fn sorting_practice() {
+    sort_practice();
+    people_practice();
+    select_practice();
+    search_practice();
+    println!("sorting: all checks passed");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hasher_bench::XorShift;
+
+    // 随机数组：长度在 0..max_len 之间，值域 range 越小重复越多
+    fn random_vec(rng: &mut XorShift, max_len: usize, range: u64) -> Vec<i64> {
+        let len = rng.next_u64() as usize % max_len;
+        (0..len)
+            .map(|_| (rng.next_u64() % range) as i64 - (range / 2) as i64)
+            .collect()
+    }
+
+    // 各种容易让快排退化的输入
+    fn adversarial(n: usize) -> Vec<(&'static str, Vec<i64>)> {
+        let n = n as i64;
+        vec![
+            ("sorted", (0..n).collect()),
+            ("reversed", (0..n).rev().collect()),
+            ("all equal", vec![7; n as usize]),
+            ("organ pipe", (0..n / 2).chain((0..n / 2).rev()).collect()),
+            ("sawtooth", (0..n).map(|i| i % 17).collect()),
+        ]
+    }
+
+    type SortFn = fn(&mut [i64]);
+
+    #[test]
+    fn sorts_match_std_sort() {
+        let mut rng = XorShift::new(48);
+        for round in 0..500 {
+            let range = [2, 10, 1000, u64::MAX / 4][round % 4];
+            let v = random_vec(&mut rng, 300, range);
+            let mut expected = v.clone();
+            expected.sort();
+            let sorts: [(&str, SortFn); 4] = [
+                ("merge", merge_sort),
+                ("quick", quick_sort),
+                ("heap", heap_sort),
+                ("radix", radix_sort),
+            ];
+            for (name, sort) in sorts {
+                let mut got = v.clone();
+                sort(&mut got);
+                assert_eq!(got, expected, "{} sort on {:?}", name, v);
+            }
+            // 降序比较函数
+            let mut desc = v.clone();
+            quick_sort_by(&mut desc, |a, b| b.cmp(a));
+            assert!(desc.iter().eq(expected.iter().rev()));
+        }
+    }
+
+    #[test]
+    fn stable_sorts_match_std_stable_sort() {
+        let mut rng = XorShift::new(480);
+        for _ in 0..500 {
+            let v = random_vec(&mut rng, 300, 1000);
+            // key 相同的元素保持原下标递增
+            let pairs: Vec<(i64, usize)> = v.iter().enumerate().map(|(i, &x)| (x % 5, i)).collect();
+            let mut std_stable = pairs.clone();
+            std_stable.sort_by_key(|p| p.0);
+            let mut merged = pairs.clone();
+            merge_sort_by_key(&mut merged, |p| p.0);
+            assert_eq!(merged, std_stable);
+            let mut radixed = pairs.clone();
+            radix_sort_by_key(&mut radixed, |p| p.0);
+            assert_eq!(radixed, std_stable);
+        }
+    }
+
+    #[test]
+    fn quick_sort_stays_n_log_n_on_adversarial_inputs() {
+        let n: usize = 20_000;
+        let bound = 4 * n * (usize::BITS - n.leading_zeros()) as usize;
+        for (name, v) in adversarial(n) {
+            let mut comparisons = 0;
+            let mut got = v.clone();
+            quick_sort_by(&mut got, |a, b| {
+                comparisons += 1;
+                a.cmp(b)
+            });
+            assert!(got.windows(2).all(|w| w[0] <= w[1]), "{}", name);
+            assert!(comparisons < bound, "{}: {} comparisons", name, comparisons);
+            let mut h = v.clone();
+            heap_sort(&mut h);
+            assert_eq!(h, got);
+        }
+    }
+
+    #[test]
+    fn select_nth_matches_std_select_nth_unstable() {
+        let mut rng = XorShift::new(4801);
+        for round in 0..300 {
+            let v = random_vec(&mut rng, 200, [3, 50, 1 << 20][round % 3]);
+            if v.is_empty() {
+                continue;
+            }
+            let k = rng.next_u64() as usize % v.len();
+            let mut work = v.clone();
+            let nth = *select_nth(&mut work, k);
+            assert!(work[..k].iter().all(|&x| x <= nth) && work[k + 1..].iter().all(|&x| x >= nth));
+            let mut std_work = v.clone();
+            assert_eq!(*std_work.select_nth_unstable(k).1, nth);
+        }
+        for (name, v) in adversarial(5_000) {
+            let mut work = v.clone();
+            let mut std_work = v;
+            assert_eq!(
+                *select_nth(&mut work, 2_500),
+                *std_work.select_nth_unstable(2_500).1,
+                "{}",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn top_k_matches_sorted_prefix() {
+        let mut rng = XorShift::new(4802);
+        for _ in 0..300 {
+            let v = random_vec(&mut rng, 200, 50);
+            let mut sorted = v.clone();
+            sorted.sort();
+            let count = rng.next_u64() as usize % (v.len() + 2);
+            let top = top_k(v.iter().copied(), count);
+            assert!(top.iter().eq(sorted.iter().rev().take(count)));
+        }
+    }
+
+    #[test]
+    fn bounds_match_std_binary_search() {
+        let mut rng = XorShift::new(4800);
+        for round in 0..300 {
+            let mut v = random_vec(&mut rng, 100, [4, 40, 400][round % 3]);
+            v.sort();
+            for x in -3..=3 {
+                let x = x * (round as i64 % 7);
+                let lower = v.partition_point(|&e| e < x);
+                let upper = v.partition_point(|&e| e <= x);
+                assert_eq!(lower_bound(&v, &x), lower);
+                assert_eq!(upper_bound(&v, &x), upper);
+                assert_eq!(equal_range(&v, &x), lower..upper);
+                assert_eq!(partition_point(&v, |&e| e < x), lower);
+                assert_eq!(find_first(&v, &x), v.iter().position(|&e| e == x));
+                assert_eq!(find_last(&v, &x), v.iter().rposition(|&e| e == x));
+                match v.binary_search(&x) {
+                    Ok(i) => assert!((lower..upper).contains(&i)),
+                    Err(i) => assert_eq!((i, lower), (upper, upper)),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn search_rotated_finds_every_element() {
+        let mut rng = XorShift::new(4803);
+        for round in 0..300 {
+            let mut v = random_vec(&mut rng, 100, 400);
+            v.sort();
+            v.dedup();
+            if v.is_empty() {
+                continue;
+            }
+            let mut rotated = v.clone();
+            rotated.rotate_left(round % v.len());
+            for (i, x) in rotated.iter().enumerate() {
+                assert_eq!(search_rotated(&rotated, x), Some(i));
+            }
+            assert_eq!(search_rotated(&rotated, &i64::MAX), None);
+        }
+    }
+}
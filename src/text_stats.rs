@@ -0,0 +1,597 @@
+//! 文本统计
+//! hash_map 中用 split_whitespace 统计了一句英文的词频，这里扩展为支持中文分词、n-gram 和 TF-IDF
+
+use std::cmp::Reverse;
+use std::collections::hash_map::RandomState;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::fs::File;
+use std::hash::BuildHasher;
+use std::io::{self, BufRead, BufReader};
+use std::time::{Duration, Instant};
+
+use crate::utf8;
+
+/*
+ * 中文没有空格分隔单词，需要根据词典分词
+ * 这里使用动态规划：在词典中能匹配到的切分方式里，选择词数最少的一种，
+ * 相同词数时选择单字最少的一种，比"正向最大匹配"更不容易切错，例如：
+ *   研究生命起源  正向最大匹配：研究生/命/起源  动态规划：研究/生命/起源
+ */
+
+// 内置的小词典，实际使用时可以通过 --dict 加载更完整的词典(每行一个词，空格后面的内容忽略)
+const BUILTIN_DICT: &str = "
+我们 你们 他们 她们 它们 自己 什么 怎么 为什么 这个 那个 这些 那些 这里 那里 现在 已经 因为 所以
+但是 如果 虽然 而且 或者 还是 就是 可以 可能 应该 需要 没有 不是 一个 一些 非常 特别 比较 一起
+喜欢 学习 研究 研究生 生命 起源 工作 生活 时间 问题 方法 世界 中国 中国人 人民 国家 社会 经济
+发展 技术 科学 计算机 程序 程序员 编程 语言 编程语言 代码 数据 数据库 结构 数据结构 算法 内存
+安全 并发 性能 系统 开发 开发者 软件 硬件 网络 服务 服务器 文件 字符串 字符 变量 函数 类型
+特征 泛型 生命周期 所有权 借用 引用 指针 智能指针 闭包 迭代器 线程 异步 宏 模块 测试 错误 处理
+微博 新闻 文章 评论 作者 用户 今天 明天 昨天 天气 北京 上海 大学 学生 老师 朋友 知识 书籍 阅读
+哈希 哈希表 词频 统计 分词 中文 英文 文本 分析 单词 句子 标点 符号 数字 结果 输出 输入 例子
+简单 容易 困难 重要 美好 快速 高效 正确 完成 开始 结束 使用 支持 实现 提供 通过 进行 成为
+";
+
+pub struct Segmenter {
+    dict: HashSet<String>,
+    max_len: usize,
+}
+
+impl Default for Segmenter {
+    fn default() -> Self {
+        Segmenter::with_words(BUILTIN_DICT.split_whitespace())
+    }
+}
+
+impl Segmenter {
+    pub fn with_words<'a>(words: impl IntoIterator<Item = &'a str>) -> Self {
+        let mut seg = Segmenter {
+            dict: HashSet::new(),
+            max_len: 1,
+        };
+        for w in words {
+            seg.add_word(w);
+        }
+        seg
+    }
+
+    pub fn add_word(&mut self, word: &str) {
+        self.max_len = self.max_len.max(word.chars().count());
+        self.dict.insert(word.to_string());
+    }
+
+    // 在内置词典的基础上加载词典文件
+    pub fn load(path: &str) -> io::Result<Self> {
+        let mut seg = Segmenter::default();
+        for line in BufReader::new(File::open(path)?).lines() {
+            if let Some(word) = line?.split_whitespace().next() {
+                seg.add_word(word);
+            }
+        }
+        Ok(seg)
+    }
+
+    // 对一段连续的汉字做动态规划分词
+    fn segment_cjk<'a>(&self, run: &'a str, out: &mut Vec<&'a str>) {
+        let bounds: Vec<usize> = run
+            .char_indices()
+            .map(|(i, _)| i)
+            .chain(std::iter::once(run.len()))
+            .collect();
+        let n = bounds.len() - 1;
+        // best[i] = 前 i 个字的最优切分代价 (词数, 单字数)，prev[i] 为最后一个词的起点
+        let mut best = vec![(usize::MAX, usize::MAX); n + 1];
+        let mut prev = vec![0; n + 1];
+        best[0] = (0, 0);
+        for end in 1..=n {
+            for start in end.saturating_sub(self.max_len)..end {
+                let len = end - start;
+                let word = &run[bounds[start]..bounds[end]];
+                if len > 1 && !self.dict.contains(word) {
+                    continue;
+                }
+                let (words, singles) = best[start];
+                let cost = (words + 1, singles + usize::from(len == 1));
+                if cost < best[end] {
+                    best[end] = cost;
+                    prev[end] = start;
+                }
+            }
+        }
+        let mut cuts = Vec::new();
+        let mut end = n;
+        while end > 0 {
+            let start = prev[end];
+            cuts.push(&run[bounds[start]..bounds[end]]);
+            end = start;
+        }
+        out.extend(cuts.into_iter().rev());
+    }
+
+    // 中英文混排分词，英文按 Unicode 单词边界切分，中文按词典切分，标点和空白被丢弃
+    pub fn segment<'a>(&self, text: &'a str) -> Vec<&'a str> {
+        let mut out = Vec::new();
+        let mut rest = text;
+        while let Some(c) = rest.chars().next() {
+            let cjk = utf8::is_ideographic(c);
+            let end = rest
+                .char_indices()
+                .find(|(_, c)| utf8::is_ideographic(*c) != cjk)
+                .map_or(rest.len(), |(i, _)| i);
+            let (run, tail) = rest.split_at(end);
+            if cjk {
+                self.segment_cjk(run, &mut out);
+            } else {
+                out.extend(utf8::words(run));
+            }
+            rest = tail;
+        }
+        out
+    }
+
+    // 分词后统一转为小写
+    pub fn tokens(&self, text: &str) -> Vec<String> {
+        self.segment(text)
+            .into_iter()
+            .map(|w| w.to_lowercase())
+            .collect()
+    }
+}
+
+/* ---------------------------- 计数 ---------------------------- */
+
+/*
+ * 哈希函数通过第三个泛型参数 S 选择：
+ *   std::collections::hash_map::RandomState  默认的 SipHash-1-3，可以抵抗哈希洪水攻击
+ *   ahash::RandomState                       更快，同样带有随机种子
+ */
+pub struct Counter<S = RandomState> {
+    counts: HashMap<String, u64, S>,
+    total: u64,
+}
+
+impl<S: BuildHasher + Default> Default for Counter<S> {
+    fn default() -> Self {
+        Counter {
+            counts: HashMap::default(),
+            total: 0,
+        }
+    }
+}
+
+impl<S: BuildHasher + Default> Counter<S> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, word: &str) {
+        self.total += 1;
+        // 已存在的词不再分配新的 String
+        match self.counts.get_mut(word) {
+            Some(c) => *c += 1,
+            None => {
+                self.counts.insert(word.to_string(), 1);
+            }
+        }
+    }
+
+    pub fn get(&self, word: &str) -> u64 {
+        self.counts.get(word).copied().unwrap_or(0)
+    }
+
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    pub fn distinct(&self) -> usize {
+        self.counts.len()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, u64)> {
+        self.counts.iter().map(|(k, v)| (k.as_str(), *v))
+    }
+
+    // 出现次数最多的 k 个词，次数相同时按字典序
+    pub fn top_k(&self, k: usize) -> Vec<(&str, u64)> {
+        top_k_by(self.iter(), k, |(w, c)| (*c, Reverse(*w)))
+    }
+}
+
+// 用大小为 k 的最小堆选出最大的 k 个元素，复杂度 O(n log k)
+pub fn top_k_by<T, K: Ord>(
+    items: impl Iterator<Item = T>,
+    k: usize,
+    key: impl Fn(&T) -> K,
+) -> Vec<T> {
+    if k == 0 {
+        return Vec::new();
+    }
+    struct Entry<K, T>(K, T);
+    impl<K: Ord, T> PartialEq for Entry<K, T> {
+        fn eq(&self, other: &Self) -> bool {
+            self.0 == other.0
+        }
+    }
+    impl<K: Ord, T> Eq for Entry<K, T> {}
+    impl<K: Ord, T> PartialOrd for Entry<K, T> {
+        fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+    impl<K: Ord, T> Ord for Entry<K, T> {
+        fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+            other.0.cmp(&self.0)
+        }
+    }
+
+    let mut heap = BinaryHeap::with_capacity(k + 1);
+    for item in items {
+        heap.push(Entry(key(&item), item));
+        if heap.len() > k {
+            heap.pop();
+        }
+    }
+    let mut out: Vec<Entry<K, T>> = heap.into_vec();
+    out.sort_by(|a, b| b.0.cmp(&a.0));
+    out.into_iter().map(|e| e.1).collect()
+}
+
+// 相邻 n 个词组成的片段
+pub fn ngrams(tokens: &[String], n: usize) -> impl Iterator<Item = String> + '_ {
+    tokens.windows(n.max(1)).map(|w| w.join(" "))
+}
+
+/* ---------------------------- TF-IDF ---------------------------- */
+
+pub struct Document<S = RandomState> {
+    pub name: String,
+    pub counts: Counter<S>,
+}
+
+/*
+ * TF-IDF 衡量一个词对某篇文档的重要程度：
+ *   tf  = 词在文档中的次数 / 文档总词数
+ *   idf = ln((1 + 文档数) / (1 + 包含该词的文档数)) + 1
+ * 在所有文档中都出现的词(比如"的")idf 最小
+ */
+pub fn tf_idf<S: BuildHasher + Default>(docs: &[Document<S>], k: usize) -> Vec<Vec<(String, f64)>> {
+    let mut df: HashMap<&str, usize, S> = HashMap::default();
+    for doc in docs {
+        for (word, _) in doc.counts.iter() {
+            *df.entry(word).or_insert(0) += 1;
+        }
+    }
+    let n = docs.len() as f64;
+    docs.iter()
+        .map(|doc| {
+            let total = doc.counts.total().max(1) as f64;
+            let scores = doc.counts.iter().map(|(word, c)| {
+                let idf = ((1.0 + n) / (1.0 + df[word] as f64)).ln() + 1.0;
+                (word, c as f64 / total * idf)
+            });
+            top_k_by(scores, k, |(w, s)| (OrdF64(*s), Reverse(*w)))
+                .into_iter()
+                .map(|(w, s)| (w.to_string(), s))
+                .collect()
+        })
+        .collect()
+}
+
+// f64 没有实现 Ord，分数不会是 NaN，用 total_cmp 比较
+struct OrdF64(f64);
+
+impl PartialEq for OrdF64 {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other).is_eq()
+    }
+}
+
+impl Eq for OrdF64 {}
+
+impl PartialOrd for OrdF64 {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for OrdF64 {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+/* ---------------------------- 命令行 ---------------------------- */
+
+struct Options {
+    top: usize,
+    ngram: usize,
+    hasher: String,
+    dict: Option<String>,
+    tfidf: bool,
+    bench: bool,
+    files: Vec<String>,
+}
+
+const USAGE: &str = "usage: wordfreq [--top K] [--ngram N] [--hasher sip|ahash] [--dict FILE] [--tfidf] [--bench] [FILE...]";
+
+fn parse_options(args: &[String]) -> Result<Options, String> {
+    let mut opts = Options {
+        top: 10,
+        ngram: 1,
+        hasher: "sip".to_string(),
+        dict: None,
+        tfidf: false,
+        bench: false,
+        files: Vec::new(),
+    };
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let mut value = |name: &str| {
+            iter.next()
+                .cloned()
+                .ok_or_else(|| format!("{} requires a value", name))
+        };
+        match arg.as_str() {
+            "--top" => {
+                opts.top = value("--top")?
+                    .parse()
+                    .map_err(|e| format!("--top: {}", e))?
+            }
+            "--ngram" => {
+                opts.ngram = value("--ngram")?
+                    .parse()
+                    .map_err(|e| format!("--ngram: {}", e))?
+            }
+            "--hasher" => opts.hasher = value("--hasher")?,
+            "--dict" => opts.dict = Some(value("--dict")?),
+            "--tfidf" => opts.tfidf = true,
+            "--bench" => opts.bench = true,
+            // 拼错的选项不能被当成文件名
+            flag if flag.starts_with("--") => {
+                return Err(format!("unknown option {}; {}", flag, USAGE))
+            }
+            _ => opts.files.push(arg.clone()),
+        }
+    }
+    Ok(opts)
+}
+
+// 按行流式读取，不把整个文件读入内存
+fn count_reader<S: BuildHasher + Default>(
+    reader: impl BufRead,
+    seg: &Segmenter,
+    n: usize,
+) -> io::Result<Counter<S>> {
+    let mut counter = Counter::new();
+    for line in reader.lines() {
+        let tokens = seg.tokens(&line?);
+        if n <= 1 {
+            tokens.iter().for_each(|t| counter.add(t));
+        } else {
+            ngrams(&tokens, n).for_each(|g| counter.add(&g));
+        }
+    }
+    Ok(counter)
+}
+
+fn run<S: BuildHasher + Default>(opts: &Options, seg: &Segmenter) -> io::Result<()> {
+    let mut docs: Vec<Document<S>> = Vec::new();
+    if opts.files.is_empty() {
+        let counts = count_reader(io::stdin().lock(), seg, opts.ngram)?;
+        docs.push(Document {
+            name: "<stdin>".to_string(),
+            counts,
+        });
+    }
+    for path in &opts.files {
+        let counts = count_reader(BufReader::new(File::open(path)?), seg, opts.ngram)?;
+        docs.push(Document {
+            name: path.clone(),
+            counts,
+        });
+    }
+
+    if opts.tfidf {
+        for (doc, terms) in docs.iter().zip(tf_idf(&docs, opts.top)) {
+            println!("== {} ==", doc.name);
+            for (term, score) in terms {
+                println!("{}{:.4}", utf8::pad_right(&term, 24), score);
+            }
+        }
+        return Ok(());
+    }
+
+    // 合并所有文档的词频
+    let mut all: Counter<S> = Counter::new();
+    for doc in &docs {
+        for (word, c) in doc.counts.iter() {
+            all.total += c;
+            *all.counts.entry(word.to_string()).or_insert(0) += c;
+        }
+    }
+    println!("total = {}, distinct = {}", all.total(), all.distinct());
+    for (word, c) in all.top_k(opts.top) {
+        println!("{}{}", utf8::pad_right(word, 24), c);
+    }
+    Ok(())
+}
+
+// cargo run --release -- wordfreq [--top K] [--ngram N] [--hasher sip|ahash] [--dict 词典] [--tfidf] [--bench] [文件...]
+pub fn wordfreq(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let opts = parse_options(args)?;
+    let seg = match &opts.dict {
+        Some(path) => Segmenter::load(path)?,
+        None => Segmenter::default(),
+    };
+    if opts.bench {
+        let tokens = if opts.files.is_empty() {
+            sample_corpus(&seg, 200_000)
+        } else {
+            let mut tokens = Vec::new();
+            for path in &opts.files {
+                for line in BufReader::new(File::open(path)?).lines() {
+                    tokens.extend(seg.tokens(&line?));
+                }
+            }
+            tokens
+        };
+        print_bench(&bench_hashers(&tokens, 5));
+        return Ok(());
+    }
+    match opts.hasher.as_str() {
+        "sip" => run::<RandomState>(&opts, &seg)?,
+        "ahash" => run::<ahash::RandomState>(&opts, &seg)?,
+        other => return Err(format!("unknown hasher: {} (sip|ahash)", other).into()),
+    }
+    Ok(())
+}
+
+/* ---------------------------- 基准测试 ---------------------------- */
+
+const SAMPLE_TEXT: &str = "hello world wonderful world 我们喜欢学习Rust编程语言 \
+    研究生命起源是非常重要的科学问题 Rust的所有权和借用让内存安全 \
+    the quick brown fox jumps over the lazy dog";
+
+// 用样例文本加上编号生成大量不同的词，模拟真实语料中长尾的低频词
+pub fn sample_corpus(seg: &Segmenter, len: usize) -> Vec<String> {
+    let base = seg.tokens(SAMPLE_TEXT);
+    (0..len)
+        .map(|i| {
+            let w = &base[i % base.len()];
+            if i % 3 == 0 {
+                format!("{}{}", w, i % 5000)
+            } else {
+                w.clone()
+            }
+        })
+        .collect()
+}
+
+pub struct BenchResult {
+    pub hasher: &'static str,
+    pub best: Duration,
+    pub distinct: usize,
+}
+
+fn bench_one<S: BuildHasher + Default>(
+    tokens: &[String],
+    rounds: usize,
+    hasher: &'static str,
+) -> BenchResult {
+    let mut best = Duration::MAX;
+    let mut distinct = 0;
+    for _ in 0..rounds.max(1) {
+        let start = Instant::now();
+        let mut counter: Counter<S> = Counter::new();
+        for t in tokens {
+            counter.add(t);
+        }
+        best = best.min(start.elapsed());
+        distinct = std::hint::black_box(&counter).distinct();
+    }
+    BenchResult {
+        hasher,
+        best,
+        distinct,
+    }
+}
+
+// 同样的词序列分别用 SipHash 和 ahash 计数，取多轮中最快的一次
+pub fn bench_hashers(tokens: &[String], rounds: usize) -> Vec<BenchResult> {
+    vec![
+        bench_one::<RandomState>(tokens, rounds, "SipHash (std)"),
+        bench_one::<ahash::RandomState>(tokens, rounds, "ahash"),
+    ]
+}
+
+fn print_bench(results: &[BenchResult]) {
+    let base = results[0].best.as_secs_f64();
+    println!(
+        "{:<16}{:>12}{:>10}{:>10}",
+        "hasher", "time(ms)", "speedup", "distinct"
+    );
+    for r in results {
+        println!(
+            "{:<16}{:>12.3}{:>9.2}x{:>10}",
+            r.hasher,
+            r.best.as_secs_f64() * 1000.0,
+            base / r.best.as_secs_f64(),
+            r.distinct
+        );
+    }
+}
+
+pub fn text_stats_practice() {
+    let seg = Segmenter::default();
+    assert_eq!(
+        seg.segment("我们喜欢学习Rust编程语言"),
+        ["我们", "喜欢", "学习", "Rust", "编程语言"]
+    );
+    // 动态规划避免了正向最大匹配的歧义
+    assert_eq!(seg.segment("研究生命起源"), ["研究", "生命", "起源"]);
+    assert_eq!(seg.segment("中国人民"), ["中国", "人民"]);
+    // 词典中没有的字单独成词
+    assert_eq!(seg.segment("你好，世界!"), ["你", "好", "世界"]);
+
+    // hash_map 中的例子
+    let mut counter: Counter = Counter::new();
+    for w in seg.tokens("hello world wonderful world") {
+        counter.add(&w);
+    }
+    assert_eq!(counter.get("world"), 2);
+    assert_eq!(counter.top_k(2), [("world", 2), ("hello", 1)]);
+
+    // 两种哈希函数的统计结果完全相同
+    let tokens = seg.tokens(SAMPLE_TEXT);
+    let mut sip: Counter<RandomState> = Counter::new();
+    let mut fast: Counter<ahash::RandomState> = Counter::new();
+    tokens.iter().for_each(|t| {
+        sip.add(t);
+        fast.add(t);
+    });
+    assert_eq!(sip.top_k(5), fast.top_k(5));
+    assert_eq!(sip.total(), fast.total());
+
+    let bigrams: Vec<String> = ngrams(&seg.tokens("学习Rust编程"), 2).collect();
+    assert_eq!(bigrams, ["学习 rust", "rust 编程"]);
+    assert_eq!(ngrams(&tokens[..1], 3).count(), 0);
+
+    // TF-IDF：每篇文档都有的 "rust" 得分低于各自独有的词，除非它出现得足够频繁
+    let docs: Vec<Document> = ["rust 内存 安全 rust", "rust 并发 线程", "rust 宏"]
+        .iter()
+        .enumerate()
+        .map(|(i, text)| {
+            let mut counts = Counter::new();
+            seg.tokens(text).iter().for_each(|t| counts.add(t));
+            Document {
+                name: i.to_string(),
+                counts,
+            }
+        })
+        .collect();
+    let scores = tf_idf(&docs, 3);
+    assert_eq!(scores[2][0].0, "宏");
+    assert_eq!(scores[1][2].0, "rust");
+    assert!(scores[1][2].1 < scores[1][0].1);
+    assert_eq!(scores[0][0].0, "rust");
+
+    print_bench(&bench_hashers(&sample_corpus(&seg, 20_000), 3));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(s: &str) -> Vec<String> {
+        s.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn parse_options_rejects_unknown_flags() {
+        let opts = parse_options(&args("--top 3 --tfidf a.txt b.txt")).unwrap();
+        assert_eq!((opts.top, opts.tfidf, opts.files.len()), (3, true, 2));
+        let err = parse_options(&args("--tpo 3 a.txt")).err().unwrap();
+        assert!(err.starts_with("unknown option --tpo; usage: wordfreq"));
+        assert!(parse_options(&args("--top")).is_err());
+        assert!(parse_options(&args("--ngram x")).is_err());
+    }
+}
@@ -0,0 +1,478 @@
+//! UTF-8 字符串切片
+//! string_unicode 中 &s[0..1] 切在 "中国人" 的字符中间会 panic，这里提供按字符、按字素切片的方法
+
+/*
+ * 三种"长度"：
+ * 字节    s.len()，UTF-8 编码的字节数，中文一般 3 个字节
+ * 字符    s.chars().count()，Unicode 标量值
+ * 字素    用户眼中的"一个字"，比如 "é" 可以是 e + 组合重音符两个字符，
+ *         "👨‍👩‍👧" 是由零宽连接符(ZWJ)连起来的 5 个字符
+ * 另外终端中中文、emoji 占两列，组合字符不占列，这是显示宽度
+ *
+ * 这里的字素和单词切分是 UAX #29 的简化版本，覆盖常见的组合字符、emoji 序列和中英文混排
+ */
+
+use std::ops::Range;
+
+// 按字符下标切片，越界返回 None，不会 panic
+pub fn char_slice(s: &str, range: Range<usize>) -> Option<&str> {
+    if range.start > range.end {
+        return None;
+    }
+    let start = char_to_byte(s, range.start)?;
+    let end = char_to_byte(s, range.end)?;
+    Some(&s[start..end])
+}
+
+// 第 n 个字符的字节偏移，n 可以等于字符数(指向末尾)
+pub fn char_to_byte(s: &str, n: usize) -> Option<usize> {
+    s.char_indices()
+        .map(|(i, _)| i)
+        .chain(std::iter::once(s.len()))
+        .nth(n)
+}
+
+pub fn char_len(s: &str) -> usize {
+    s.chars().count()
+}
+
+/* ---------------------------- 字素 ---------------------------- */
+
+const ZWJ: char = '\u{200D}';
+
+// 附着在前一个字符上、不能单独成为字素的字符
+fn is_extend(c: char) -> bool {
+    matches!(c as u32,
+        0x0300..=0x036F     // 组合附加符号
+        | 0x0483..=0x0489
+        | 0x0591..=0x05BD
+        | 0x0610..=0x061A
+        | 0x064B..=0x065F
+        | 0x0E31 | 0x0E34..=0x0E3A | 0x0E47..=0x0E4E   // 泰文
+        | 0x1AB0..=0x1AFF
+        | 0x1DC0..=0x1DFF
+        | 0x200C            // 零宽非连接符
+        | 0x20D0..=0x20FF   // 符号用组合符号
+        | 0x302A..=0x302F   // 汉字声调符号
+        | 0x3099..=0x309A   // 假名浊音符
+        | 0xFE00..=0xFE0F   // 变体选择符
+        | 0xFE20..=0xFE2F
+        | 0x1F3FB..=0x1F3FF // emoji 肤色修饰符
+        | 0xE0020..=0xE007F // emoji 标签序列
+        | 0xE0100..=0xE01EF
+    )
+}
+
+fn is_regional_indicator(c: char) -> bool {
+    matches!(c as u32, 0x1F1E6..=0x1F1FF)
+}
+
+// 下一个字素的字节长度
+fn next_grapheme_len(s: &str) -> usize {
+    let mut chars = s.char_indices().peekable();
+    let Some((_, first)) = chars.next() else {
+        return 0;
+    };
+    let mut end = first.len_utf8();
+    // \r\n 是一个字素
+    if first == '\r' {
+        if let Some(&(i, '\n')) = chars.peek() {
+            return i + 1;
+        }
+        return end;
+    }
+    if first.is_control() {
+        return end;
+    }
+    // 两个地区指示符组成一面国旗
+    if is_regional_indicator(first) {
+        if let Some(&(i, c)) = chars.peek() {
+            if is_regional_indicator(c) {
+                return i + c.len_utf8();
+            }
+        }
+        return end;
+    }
+    let mut after_zwj = false;
+    while let Some(&(i, c)) = chars.peek() {
+        if is_extend(c) || c == ZWJ || after_zwj {
+            after_zwj = c == ZWJ;
+            end = i + c.len_utf8();
+            chars.next();
+        } else {
+            break;
+        }
+    }
+    end
+}
+
+pub struct Graphemes<'a> {
+    rest: &'a str,
+}
+
+impl<'a> Iterator for Graphemes<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<&'a str> {
+        if self.rest.is_empty() {
+            return None;
+        }
+        let len = next_grapheme_len(self.rest);
+        let (g, rest) = self.rest.split_at(len);
+        self.rest = rest;
+        Some(g)
+    }
+}
+
+pub fn graphemes(s: &str) -> Graphemes<'_> {
+    Graphemes { rest: s }
+}
+
+// 按字素下标切片
+pub fn grapheme_slice(s: &str, range: Range<usize>) -> Option<&str> {
+    if range.start > range.end {
+        return None;
+    }
+    let mut offsets = graphemes(s)
+        .scan(0, |pos, g| {
+            let start = *pos;
+            *pos += g.len();
+            Some(start)
+        })
+        .chain(std::iter::once(s.len()));
+    let start = offsets.nth(range.start)?;
+    let end = if range.end == range.start {
+        start
+    } else {
+        offsets.nth(range.end - range.start - 1)?
+    };
+    Some(&s[start..end])
+}
+
+/* ---------------------------- 截断 ---------------------------- */
+
+// 截断到不超过 max_bytes 个字节，只在字符边界上截断
+pub fn truncate_bytes(s: &str, max_bytes: usize) -> &str {
+    if s.len() <= max_bytes {
+        return s;
+    }
+    let mut end = max_bytes;
+    while !s.is_char_boundary(end) {
+        end -= 1;
+    }
+    &s[..end]
+}
+
+// 截断到不超过 max_bytes 个字节，并且不拆开字素(比如 emoji 和它的肤色修饰符)
+pub fn truncate_graphemes(s: &str, max_bytes: usize) -> &str {
+    let mut end = 0;
+    for g in graphemes(s) {
+        if end + g.len() > max_bytes {
+            break;
+        }
+        end += g.len();
+    }
+    &s[..end]
+}
+
+/* ---------------------------- 显示宽度 ---------------------------- */
+
+// 东亚宽字符和 emoji 在终端中占两列
+fn is_wide(c: char) -> bool {
+    matches!(c as u32,
+        0x1100..=0x115F     // 谚文字母
+        | 0x231A..=0x231B | 0x23E9..=0x23EC | 0x23F0 | 0x23F3
+        | 0x25FD..=0x25FE
+        | 0x2614..=0x2615
+        | 0x2648..=0x2653
+        | 0x267F | 0x2693 | 0x26A1 | 0x26AA..=0x26AB | 0x26BD..=0x26BE
+        | 0x26C4..=0x26C5 | 0x26CE | 0x26D4 | 0x26EA | 0x26F2..=0x26F3
+        | 0x26F5 | 0x26FA | 0x26FD | 0x2705 | 0x270A..=0x270B | 0x2728
+        | 0x274C | 0x274E | 0x2753..=0x2755 | 0x2757 | 0x2795..=0x2797
+        | 0x27B0 | 0x27BF | 0x2B1B..=0x2B1C | 0x2B50 | 0x2B55
+        | 0x2E80..=0x303E   // 中日韩部首、标点
+        | 0x3041..=0x33FF   // 假名、注音、中日韩兼容
+        | 0x3400..=0x4DBF   // 扩展 A
+        | 0x4E00..=0x9FFF   // 中日韩统一表意文字
+        | 0xA000..=0xA4CF   // 彝文
+        | 0xAC00..=0xD7A3   // 谚文音节
+        | 0xF900..=0xFAFF   // 兼容表意文字
+        | 0xFE30..=0xFE4F   // 竖排标点
+        | 0xFF00..=0xFF60   // 全角字符
+        | 0xFFE0..=0xFFE6
+        | 0x1F004 | 0x1F0CF | 0x1F18E | 0x1F191..=0x1F19A
+        | 0x1F200..=0x1F251
+        | 0x1F300..=0x1F64F // emoji
+        | 0x1F680..=0x1F6FF
+        | 0x1F7E0..=0x1F7EB
+        | 0x1F900..=0x1F9FF
+        | 0x1FA70..=0x1FAFF
+        | 0x20000..=0x3FFFD // 扩展 B 之后
+    )
+}
+
+pub fn char_width(c: char) -> usize {
+    if c == ZWJ || is_extend(c) || c.is_control() || c == '\u{200B}' {
+        0
+    } else if is_wide(c) {
+        2
+    } else {
+        1
+    }
+}
+
+// 一个字素的宽度：以第一个字符为准，emoji 序列和国旗占两列
+fn grapheme_width(g: &str) -> usize {
+    let Some(first) = g.chars().next() else {
+        return 0;
+    };
+    // 文字样式的符号加上 U+FE0F 后按 emoji 显示
+    if is_regional_indicator(first) || g.contains('\u{FE0F}') {
+        return 2;
+    }
+    char_width(first)
+}
+
+pub fn display_width(s: &str) -> usize {
+    graphemes(s).map(grapheme_width).sum()
+}
+
+// 截断到不超过 max_width 列
+pub fn truncate_width(s: &str, max_width: usize) -> &str {
+    let mut width = 0;
+    let mut end = 0;
+    for g in graphemes(s) {
+        let w = grapheme_width(g);
+        if width + w > max_width {
+            break;
+        }
+        width += w;
+        end += g.len();
+    }
+    &s[..end]
+}
+
+// 超出宽度时截断并在末尾加上 …，结果的显示宽度不超过 max_width
+pub fn ellipsize(s: &str, max_width: usize) -> String {
+    if display_width(s) <= max_width {
+        return s.to_string();
+    }
+    if max_width == 0 {
+        return String::new();
+    }
+    format!("{}…", truncate_width(s, max_width - 1))
+}
+
+// 用空格补齐到指定的显示宽度，format! 的 {:<width$} 按字符计数，中文会对不齐
+pub fn pad_right(s: &str, width: usize) -> String {
+    let w = display_width(s);
+    format!("{}{}", s, " ".repeat(width.saturating_sub(w)))
+}
+
+/* ---------------------------- 单词 ---------------------------- */
+
+// 汉字、假名各自成词(UAX #29 中表意文字之间都是单词边界)
+pub fn is_ideographic(c: char) -> bool {
+    matches!(c as u32,
+        0x3040..=0x30FF | 0x3400..=0x4DBF | 0x4E00..=0x9FFF | 0xF900..=0xFAFF | 0x20000..=0x3FFFD
+    )
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+// 单词中间允许出现的标点：don't、3.14、e-mail 中的 ' . -
+fn is_mid_word(c: char) -> bool {
+    matches!(c, '\'' | '’' | '.' | '-' | ':')
+}
+
+// 按 Unicode 单词边界切分，返回所有片段(包括空白和标点)
+pub fn word_bounds(s: &str) -> Vec<&str> {
+    let mut out = Vec::new();
+    let mut rest = s;
+    while !rest.is_empty() {
+        let first = rest.chars().next().unwrap();
+        let mut end = next_grapheme_len(rest);
+        if is_ideographic(first) {
+            // 单个汉字成词
+        } else if is_word_char(first) {
+            loop {
+                let tail = &rest[end..];
+                let Some(c) = tail.chars().next() else {
+                    break;
+                };
+                if is_word_char(c) && !is_ideographic(c) {
+                    end += next_grapheme_len(tail);
+                } else if is_mid_word(c) {
+                    // 标点后面还是字母数字才算单词内部
+                    let after = &tail[c.len_utf8()..];
+                    match after.chars().next() {
+                        Some(n) if is_word_char(n) && !is_ideographic(n) => {
+                            end += c.len_utf8();
+                        }
+                        _ => break,
+                    }
+                } else {
+                    break;
+                }
+            }
+        } else if first.is_whitespace() {
+            while let Some(c) = rest[end..].chars().next() {
+                if !c.is_whitespace() {
+                    break;
+                }
+                end += c.len_utf8();
+            }
+        }
+        let (seg, tail) = rest.split_at(end);
+        out.push(seg);
+        rest = tail;
+    }
+    out
+}
+
+// 只保留单词，去掉空白和标点
+pub fn words(s: &str) -> impl Iterator<Item = &str> {
+    word_bounds(s)
+        .into_iter()
+        .filter(|w| w.chars().next().is_some_and(is_word_char))
+}
+
+// string_unicode 中的 first_word 只取了第一个字节，这里按单词边界取第一个单词
+pub fn first_word(s: &str) -> &str {
+    words(s).next().unwrap_or("")
+}
+
+pub fn utf8_practice() {
+    let s = "中国人";
+    // &s[0..1] 会 panic，按字符切片则不会
+    println!(
+        "{}: {} 字节 {} 字符，第 2 个字 {:?}，越界 {:?}",
+        s,
+        s.len(),
+        char_len(s),
+        char_slice(s, 1..2),
+        char_slice(s, 2..5)
+    );
+    let cafe = "cafe\u{301} ok";
+    println!(
+        "{}: 前 4 个字符 {:?}，前 4 个字素 {:?}",
+        cafe,
+        char_slice(cafe, 0..4),
+        grapheme_slice(cafe, 0..4)
+    );
+    println!(
+        "5 字节预算：按字符 {:?}，按字素 {:?}",
+        truncate_bytes("a👍🏽b", 5),
+        truncate_graphemes("a👍🏽b", 5)
+    );
+
+    let mixed = "Rust语言";
+    println!(
+        "|{}| 宽 {}，截到 7 列 {:?}，省略号 {:?}",
+        pad_right(mixed, 10),
+        display_width(mixed),
+        truncate_width(mixed, 7),
+        ellipsize(mixed, 7)
+    );
+    let text = "Hello, 世界! it's Rust2024 的 3.14 版本";
+    println!(
+        "{:?} -> 第一个词 {:?}，单词 {:?}，边界 {:?}",
+        text,
+        first_word(text),
+        words(text).collect::<Vec<_>>(),
+        word_bounds(text)
+    );
+
+    let emoji = "👨\u{200D}👩\u{200D}👧👍🏽🇨🇳";
+    for g in graphemes(emoji) {
+        println!(
+            "{} 字节 {} 字符 {} 宽 {}",
+            g,
+            g.len(),
+            char_len(g),
+            display_width(g)
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn char_slicing_never_splits_chars() {
+        let s = "中国人";
+        // &s[0..1] 会 panic，按字符切片则不会
+        assert_eq!(char_slice(s, 0..1), Some("中"));
+        assert_eq!(char_slice(s, 1..3), Some("国人"));
+        assert_eq!(char_slice(s, 2..5), None);
+        assert_eq!(char_len(s), 3);
+        assert_eq!(s.len(), 9);
+    }
+
+    #[test]
+    fn combining_marks_form_one_grapheme() {
+        // 组合字符：e + U+0301 是两个字符、一个字素
+        let cafe = "cafe\u{301} ok";
+        assert_eq!(char_len(cafe), 8);
+        assert_eq!(graphemes(cafe).count(), 7);
+        assert_eq!(grapheme_slice(cafe, 0..4), Some("cafe\u{301}"));
+        assert_eq!(char_slice(cafe, 0..4), Some("cafe"));
+    }
+
+    #[test]
+    fn emoji_sequences_are_single_graphemes() {
+        // emoji：家庭(ZWJ 序列)、肤色、国旗
+        let emoji = "👨\u{200D}👩\u{200D}👧👍🏽🇨🇳";
+        assert_eq!(
+            graphemes(emoji).collect::<Vec<_>>(),
+            ["👨\u{200D}👩\u{200D}👧", "👍🏽", "🇨🇳"]
+        );
+        assert_eq!(display_width(emoji), 6);
+    }
+
+    #[test]
+    fn truncation_respects_byte_budgets() {
+        // 按字节预算截断
+        let s = "中国人";
+        assert_eq!(truncate_bytes(s, 4), "中");
+        assert_eq!(truncate_bytes(s, 2), "");
+        assert_eq!(truncate_bytes("hello", 10), "hello");
+        assert_eq!(truncate_bytes("👍🏽", 5), "👍");
+        assert_eq!(truncate_graphemes("👍🏽", 5), "");
+        assert_eq!(truncate_graphemes("a👍🏽b", 9), "a👍🏽");
+    }
+
+    #[test]
+    fn display_width_of_mixed_text() {
+        // 显示宽度
+        let cafe = "cafe\u{301} ok";
+        let mixed = "Rust语言";
+        assert_eq!(display_width(mixed), 8);
+        assert_eq!(display_width("ｈｉ"), 4);
+        assert_eq!(display_width(cafe), 7);
+        assert_eq!(truncate_width(mixed, 7), "Rust语");
+        assert_eq!(ellipsize(mixed, 7), "Rust语…");
+        assert_eq!(ellipsize(mixed, 8), "Rust语言");
+        assert_eq!(pad_right("中", 4), "中  ");
+    }
+
+    #[test]
+    fn first_word_in_mixed_cjk_and_english() {
+        // 中英文混排的单词切分
+        let text = "Hello, 世界! it's Rust2024 的 3.14 版本";
+        assert_eq!(
+            words(text).collect::<Vec<_>>(),
+            ["Hello", "世", "界", "it's", "Rust2024", "的", "3.14", "版", "本"]
+        );
+        assert_eq!(first_word(text), "Hello");
+        assert_eq!(first_word("  中国人 hello"), "中");
+        assert_eq!(first_word("...wait"), "wait");
+        assert_eq!(first_word("「你好」"), "你");
+        assert_eq!(first_word(""), "");
+        assert_eq!(word_bounds("a b").concat(), "a b");
+        assert_eq!(word_bounds(text).concat(), text);
+    }
+}
@@ -0,0 +1,1178 @@
+//! 终端界面
+//! Draw 特征原来只用 println! 打印组件，这里让组件绘制到单元格缓冲区，Screen 负责布局、键盘焦点、事件分发和差量重绘
+
+use std::cell::RefCell;
+use std::io::{self, IsTerminal, Read, Write};
+use std::process::{Command, Stdio};
+use std::rc::Rc;
+
+use crate::frame::{BoxStyle, DOUBLE, SINGLE};
+use crate::utf8;
+use crate::{Button, Draw, Screen, SelectBox};
+
+/* ---------------------------- 缓冲区 ---------------------------- */
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Style {
+    pub bold: bool,
+    pub reverse: bool,
+}
+
+impl Style {
+    pub const PLAIN: Style = Style {
+        bold: false,
+        reverse: false,
+    };
+    pub const BOLD: Style = Style {
+        bold: true,
+        reverse: false,
+    };
+    pub const REVERSE: Style = Style {
+        bold: false,
+        reverse: true,
+    };
+
+    // 切换到该样式的 ANSI 转义序列(SGR)
+    fn sgr(self) -> String {
+        let mut s = String::from("\x1b[0");
+        if self.bold {
+            s.push_str(";1");
+        }
+        if self.reverse {
+            s.push_str(";7");
+        }
+        s.push('m');
+        s
+    }
+}
+
+/*
+ * 每个单元格保存一个字素，汉字和 emoji 占两列，第二列保存空字符串作为占位，
+ * 输出时跳过占位单元格，终端打印宽字符时光标会自动前进两列
+ */
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cell {
+    pub symbol: String,
+    pub style: Style,
+}
+
+impl Default for Cell {
+    fn default() -> Self {
+        Cell {
+            symbol: " ".to_string(),
+            style: Style::PLAIN,
+        }
+    }
+}
+
+impl Cell {
+    fn is_continuation(&self) -> bool {
+        self.symbol.is_empty()
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Rect {
+    pub x: u16,
+    pub y: u16,
+    pub width: u16,
+    pub height: u16,
+}
+
+impl Rect {
+    pub fn new(x: u16, y: u16, width: u16, height: u16) -> Self {
+        Rect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    pub fn right(&self) -> u16 {
+        self.x + self.width
+    }
+
+    pub fn bottom(&self) -> u16 {
+        self.y + self.height
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+
+    // 向内收缩 margin 格
+    pub fn inner(&self, margin: u16) -> Rect {
+        Rect {
+            x: self.x + margin,
+            y: self.y + margin,
+            width: self.width.saturating_sub(2 * margin),
+            height: self.height.saturating_sub(2 * margin),
+        }
+    }
+
+    pub fn contains(&self, x: u16, y: u16) -> bool {
+        x >= self.x && x < self.right() && y >= self.y && y < self.bottom()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Buffer {
+    pub width: u16,
+    pub height: u16,
+    cells: Vec<Cell>,
+}
+
+impl Buffer {
+    pub fn new(width: u16, height: u16) -> Self {
+        Buffer {
+            width,
+            height,
+            cells: vec![Cell::default(); width as usize * height as usize],
+        }
+    }
+
+    pub fn area(&self) -> Rect {
+        Rect::new(0, 0, self.width, self.height)
+    }
+
+    fn index(&self, x: u16, y: u16) -> usize {
+        y as usize * self.width as usize + x as usize
+    }
+
+    pub fn get(&self, x: u16, y: u16) -> &Cell {
+        &self.cells[self.index(x, y)]
+    }
+
+    // 写入一个字素，覆盖宽字符的一半时把另一半清成空格，返回占用的列数
+    fn set(&mut self, x: u16, y: u16, symbol: &str, style: Style) -> u16 {
+        let w = utf8::display_width(symbol) as u16;
+        if w == 0 || x + w > self.width || y >= self.height {
+            return 0;
+        }
+        for cx in x..x + w {
+            let i = self.index(cx, y);
+            if self.cells[i].is_continuation() && cx > 0 {
+                self.cells[i - 1].symbol = " ".to_string();
+            }
+            if cx + 1 < self.width && self.cells[i + 1].is_continuation() {
+                self.cells[i + 1].symbol = " ".to_string();
+            }
+        }
+        let i = self.index(x, y);
+        self.cells[i] = Cell {
+            symbol: symbol.to_string(),
+            style,
+        };
+        if w == 2 {
+            self.cells[i + 1] = Cell {
+                symbol: String::new(),
+                style,
+            };
+        }
+        w
+    }
+
+    // 从 (x, y) 开始写字符串，不超过 max_x 列，返回写完后的列号
+    pub fn put_str(&mut self, x: u16, y: u16, s: &str, max_x: u16, style: Style) -> u16 {
+        let max_x = max_x.min(self.width);
+        let mut x = x;
+        for g in utf8::graphemes(s) {
+            let w = utf8::display_width(g) as u16;
+            if x + w > max_x {
+                break;
+            }
+            x += self.set(x, y, g, style);
+        }
+        x
+    }
+
+    pub fn fill(&mut self, area: Rect, symbol: &str, style: Style) {
+        for y in area.y..area.bottom().min(self.height) {
+            for x in area.x..area.right().min(self.width) {
+                self.set(x, y, symbol, style);
+            }
+        }
+    }
+
+    pub fn draw_border(&mut self, area: Rect, border: &BoxStyle, style: Style) {
+        if area.width < 2 || area.height < 2 {
+            return;
+        }
+        let (r, b) = (area.right() - 1, area.bottom() - 1);
+        for x in area.x + 1..r {
+            self.set(x, area.y, border.horizontal, style);
+            self.set(x, b, border.horizontal, style);
+        }
+        for y in area.y + 1..b {
+            self.set(area.x, y, border.vertical, style);
+            self.set(r, y, border.vertical, style);
+        }
+        self.set(area.x, area.y, border.top_left, style);
+        self.set(r, area.y, border.top_right, style);
+        self.set(area.x, b, border.bottom_left, style);
+        self.set(r, b, border.bottom_right, style);
+    }
+
+    // 快照：每行的文本，去掉行尾空格，不含样式
+    pub fn to_lines(&self) -> Vec<String> {
+        (0..self.height)
+            .map(|y| {
+                let line: String = (0..self.width)
+                    .map(|x| self.get(x, y).symbol.as_str())
+                    .collect();
+                line.trim_end().to_string()
+            })
+            .collect()
+    }
+}
+
+/* ---------------------------- 终端输出 ---------------------------- */
+
+/*
+ * 差量重绘：保存上一帧的缓冲区，只输出发生变化的单元格，
+ * 每段连续变化的单元格前用 \x1b[行;列H 移动光标，样式变化时输出 SGR 序列
+ */
+pub struct Terminal<W: Write> {
+    out: W,
+    prev: Option<Buffer>,
+}
+
+impl<W: Write> Terminal<W> {
+    pub fn new(out: W) -> Self {
+        Terminal { out, prev: None }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+
+    // 返回本次输出的单元格数量，第一帧或尺寸变化时全部重绘
+    pub fn draw(&mut self, buf: &Buffer) -> io::Result<usize> {
+        let prev = self
+            .prev
+            .take()
+            .filter(|p| p.width == buf.width && p.height == buf.height);
+        let mut out = String::new();
+        if prev.is_none() {
+            out.push_str("\x1b[2J");
+        }
+        let mut written = 0;
+        let mut style = None;
+        let mut cursor = None;
+        for y in 0..buf.height {
+            let mut dirty: Vec<bool> = (0..buf.width)
+                .map(|x| prev.as_ref().is_none_or(|p| p.get(x, y) != buf.get(x, y)))
+                .collect();
+            // 宽字符的占位单元格变化时，需要重新输出它前面的字符
+            for x in (1..buf.width).rev() {
+                if dirty[x as usize] && buf.get(x, y).is_continuation() {
+                    dirty[x as usize - 1] = true;
+                }
+            }
+            for x in 0..buf.width {
+                let cell = buf.get(x, y);
+                if !dirty[x as usize] || cell.is_continuation() {
+                    continue;
+                }
+                if cursor != Some((x, y)) {
+                    out.push_str(&format!("\x1b[{};{}H", y + 1, x + 1));
+                }
+                if style != Some(cell.style) {
+                    out.push_str(&cell.style.sgr());
+                    style = Some(cell.style);
+                }
+                out.push_str(&cell.symbol);
+                cursor = Some((x + utf8::display_width(&cell.symbol) as u16, y));
+                written += 1;
+            }
+        }
+        if written > 0 {
+            out.push_str("\x1b[0m");
+        }
+        self.out.write_all(out.as_bytes())?;
+        self.out.flush()?;
+        self.prev = Some(buf.clone());
+        Ok(written)
+    }
+}
+
+/* ---------------------------- 事件 ---------------------------- */
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Key {
+    Tab,
+    BackTab,
+    Up,
+    Down,
+    Left,
+    Right,
+    Enter,
+    Esc,
+    Char(char),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MouseKind {
+    Down,
+    Up,
+    ScrollUp,
+    ScrollDown,
+}
+
+// 鼠标事件使用屏幕坐标，从 0 开始
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Mouse {
+    pub kind: MouseKind,
+    pub x: u16,
+    pub y: u16,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Event {
+    Key(Key),
+    Mouse(Mouse),
+}
+
+// 事件处理函数之间共享的状态，处理函数把 quit 设为 true 可以结束事件循环
+#[derive(Default)]
+pub struct Ctx {
+    pub quit: bool,
+}
+
+// 事件回调都是装箱的 FnMut 闭包，可以修改捕获的变量
+pub type ClickHandler = Box<dyn FnMut(&mut Ctx)>;
+pub type ChangeHandler = Box<dyn FnMut(&mut Ctx, usize, &str)>;
+pub type EventHandler = Box<dyn FnMut(&Event, &mut Ctx) -> bool>;
+
+// 读取转义序列中以 ; 分隔的数字，遇到其他字符时返回该字符
+fn read_params(chars: &mut impl Iterator<Item = char>) -> (Vec<u16>, Option<char>) {
+    let mut params = vec![0u16];
+    for c in chars.by_ref() {
+        match c {
+            '0'..='9' => {
+                let last = params.last_mut().unwrap();
+                *last = last
+                    .saturating_mul(10)
+                    .saturating_add(c as u16 - '0' as u16);
+            }
+            ';' => params.push(0),
+            c => return (params, Some(c)),
+        }
+    }
+    (params, None)
+}
+
+/*
+ * 把终端输入的字节解析为事件：
+ *   方向键是 ESC [ A 这样的转义序列，Shift+Tab 是 ESC [ Z
+ *   开启 SGR 鼠标模式后，鼠标事件是 ESC [ < 按键;列;行 M(按下) 或 m(松开)，坐标从 1 开始
+ */
+pub fn parse_events(bytes: &[u8]) -> Vec<Event> {
+    let text = String::from_utf8_lossy(bytes);
+    let mut events = Vec::new();
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        let key = match c {
+            '\t' => Key::Tab,
+            '\r' | '\n' => Key::Enter,
+            '\x1b' if chars.peek() == Some(&'[') => {
+                chars.next();
+                match chars.next() {
+                    Some('A') => Key::Up,
+                    Some('B') => Key::Down,
+                    Some('C') => Key::Right,
+                    Some('D') => Key::Left,
+                    Some('Z') => Key::BackTab,
+                    Some('<') => {
+                        let (p, end) = read_params(&mut chars);
+                        let (button, x, y) = match p[..] {
+                            [b, x, y] if x > 0 && y > 0 => (b, x - 1, y - 1),
+                            _ => continue,
+                        };
+                        let kind = match (button, end) {
+                            (0, Some('M')) => MouseKind::Down,
+                            (0, Some('m')) => MouseKind::Up,
+                            (64, Some('M')) => MouseKind::ScrollUp,
+                            (65, Some('M')) => MouseKind::ScrollDown,
+                            _ => continue,
+                        };
+                        events.push(Event::Mouse(Mouse { kind, x, y }));
+                        continue;
+                    }
+                    _ => continue,
+                }
+            }
+            '\x1b' => Key::Esc,
+            c if c.is_control() => continue,
+            c => Key::Char(c),
+        };
+        events.push(Event::Key(key));
+    }
+    events
+}
+
+/*
+ * 测试用的输入脚本，空白分隔：
+ *   tab backtab up down left right enter esc  按键
+ *   x                                        单个字符
+ *   click:列,行  scroll-up:列,行  scroll-down:列,行  鼠标(click 产生按下和松开两个事件)
+ */
+pub fn parse_script(script: &str) -> Result<Vec<Event>, String> {
+    let mut events = Vec::new();
+    for token in script.split_whitespace() {
+        let key = match token {
+            "tab" => Key::Tab,
+            "backtab" => Key::BackTab,
+            "up" => Key::Up,
+            "down" => Key::Down,
+            "left" => Key::Left,
+            "right" => Key::Right,
+            "enter" => Key::Enter,
+            "esc" => Key::Esc,
+            _ if token.chars().count() == 1 => Key::Char(token.chars().next().unwrap()),
+            _ => {
+                let (action, pos) = token
+                    .split_once(':')
+                    .ok_or_else(|| format!("unknown token `{}`", token))?;
+                let (x, y) = pos
+                    .split_once(',')
+                    .and_then(|(x, y)| Some((x.parse().ok()?, y.parse().ok()?)))
+                    .ok_or_else(|| format!("bad position in `{}`", token))?;
+                let mouse = |kind| Event::Mouse(Mouse { kind, x, y });
+                match action {
+                    "click" => events.extend([mouse(MouseKind::Down), mouse(MouseKind::Up)]),
+                    "scroll-up" => events.push(mouse(MouseKind::ScrollUp)),
+                    "scroll-down" => events.push(mouse(MouseKind::ScrollDown)),
+                    _ => return Err(format!("unknown action `{}`", action)),
+                }
+                continue;
+            }
+        };
+        events.push(Event::Key(key));
+    }
+    Ok(events)
+}
+
+/* ---------------------------- 组件 ---------------------------- */
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Layout {
+    // 从上到下
+    #[default]
+    Rows,
+    // 从左到右
+    Columns,
+}
+
+// Button 和 SelectBox 的 width/height 是 u32，终端坐标用 u16
+pub fn clamp_size(width: u32, height: u32) -> (u16, u16) {
+    (
+        width.min(u16::MAX as u32) as u16,
+        height.min(u16::MAX as u32) as u16,
+    )
+}
+
+// 拥有焦点的组件使用双线边框
+fn border_for(focused: bool) -> (&'static BoxStyle, Style) {
+    if focused {
+        (&DOUBLE, Style::BOLD)
+    } else {
+        (&SINGLE, Style::PLAIN)
+    }
+}
+
+// 带边框的按钮，标签在中间一行居中显示，获得焦点时反色
+pub fn render_button(buf: &mut Buffer, area: Rect, label: &str, focused: bool) {
+    let (border, style) = border_for(focused);
+    buf.draw_border(area, border, style);
+    let inner = area.inner(1);
+    if inner.is_empty() {
+        return;
+    }
+    let label = utf8::truncate_width(label, inner.width as usize);
+    let w = utf8::display_width(label) as u16;
+    let y = inner.y + (inner.height - 1) / 2;
+    let x = inner.x + (inner.width - w) / 2;
+    let label_style = if focused {
+        Style::REVERSE
+    } else {
+        Style::PLAIN
+    };
+    buf.put_str(x, y, label, inner.right(), label_style);
+}
+
+// 第一个可见选项的下标，保证选中的选项可见
+fn scroll_offset(selected: usize, visible: usize) -> usize {
+    (selected + 1).saturating_sub(visible)
+}
+
+/*
+ * 选择框：每行一个选项，选中的选项前面显示 >，获得焦点时选中行反色
+ * 选项比可见行数多时滚动，保证选中的选项可见
+ */
+pub fn render_select(
+    buf: &mut Buffer,
+    area: Rect,
+    options: &[String],
+    selected: usize,
+    focused: bool,
+) {
+    let (border, style) = border_for(focused);
+    buf.draw_border(area, border, style);
+    let inner = area.inner(1);
+    if inner.is_empty() {
+        return;
+    }
+    let visible = inner.height as usize;
+    let offset = scroll_offset(selected, visible);
+    for (row, (i, option)) in options
+        .iter()
+        .enumerate()
+        .skip(offset)
+        .take(visible)
+        .enumerate()
+    {
+        let y = inner.y + row as u16;
+        let line_style = match (i == selected, focused) {
+            (true, true) => Style::REVERSE,
+            (true, false) => Style::BOLD,
+            _ => Style::PLAIN,
+        };
+        if i == selected {
+            buf.fill(Rect::new(inner.x, y, inner.width, 1), " ", line_style);
+        }
+        let marker = if i == selected { "> " } else { "  " };
+        let x = buf.put_str(inner.x, y, marker, inner.right(), line_style);
+        buf.put_str(x, y, option, inner.right(), line_style);
+    }
+}
+
+// 回车，或者在 area 内按下鼠标
+pub fn is_activation(event: &Event, area: Rect) -> bool {
+    match event {
+        Event::Key(Key::Enter) => true,
+        Event::Mouse(m) => m.kind == MouseKind::Down && area.contains(m.x, m.y),
+        _ => false,
+    }
+}
+
+/*
+ * 选择框的事件处理，返回处理后选中的下标，None 表示不处理该事件
+ *   上下键循环切换，滚轮切换但不循环，点击选项直接选中
+ */
+pub fn select_event(selected: usize, len: usize, event: &Event, area: Rect) -> Option<usize> {
+    if len == 0 {
+        return None;
+    }
+    match event {
+        Event::Key(Key::Up) => Some((selected + len - 1) % len),
+        Event::Key(Key::Down) => Some((selected + 1) % len),
+        Event::Mouse(m) if area.contains(m.x, m.y) => match m.kind {
+            MouseKind::ScrollUp => Some(selected.saturating_sub(1)),
+            MouseKind::ScrollDown => Some((selected + 1).min(len - 1)),
+            MouseKind::Down => {
+                let inner = area.inner(1);
+                if !inner.contains(m.x, m.y) {
+                    return Some(selected);
+                }
+                let offset = scroll_offset(selected, inner.height as usize);
+                let i = offset + (m.y - inner.y) as usize;
+                Some(if i < len { i } else { selected })
+            }
+            MouseKind::Up => None,
+        },
+        _ => None,
+    }
+}
+
+/* ---------------------------- Screen ---------------------------- */
+
+impl Screen {
+    // 按 layout 依次排列组件，超出 area 的部分被裁剪
+    pub fn layout_areas(&self, area: Rect) -> Vec<Rect> {
+        let mut offset = 0u16;
+        self.components
+            .iter()
+            .map(|c| {
+                let (w, h) = c.size();
+                let rect = match self.layout {
+                    Layout::Rows => {
+                        let y = area.y + offset.min(area.height);
+                        Rect::new(area.x, y, w.min(area.width), h.min(area.bottom() - y))
+                    }
+                    Layout::Columns => {
+                        let x = area.x + offset.min(area.width);
+                        Rect::new(x, area.y, w.min(area.right() - x), h.min(area.height))
+                    }
+                };
+                offset = offset.saturating_add(match self.layout {
+                    Layout::Rows => h,
+                    Layout::Columns => w,
+                });
+                rect
+            })
+            .collect()
+    }
+
+    pub fn render(&self, buf: &mut Buffer) {
+        for (i, (c, area)) in self
+            .components
+            .iter()
+            .zip(self.layout_areas(buf.area()))
+            .enumerate()
+        {
+            if !area.is_empty() {
+                c.render(buf, area, self.focus == Some(i));
+            }
+        }
+    }
+
+    // 焦点在可获得焦点的组件之间循环移动，forward 为 false 时反向
+    pub fn move_focus(&mut self, forward: bool) {
+        let n = self.components.len();
+        if n == 0 {
+            return;
+        }
+        let start = match (self.focus, forward) {
+            (Some(i), true) => i + 1,
+            (Some(i), false) => i + n - 1,
+            (None, true) => 0,
+            (None, false) => n - 1,
+        };
+        for step in 0..n {
+            let i = if forward {
+                (start + step) % n
+            } else {
+                (start + n - step) % n
+            };
+            if self.components[i].focusable() {
+                self.focus = Some(i);
+                return;
+            }
+        }
+        self.focus = None;
+    }
+
+    /*
+     * 事件分发：
+     *   1. 键盘事件发给拥有焦点的组件，鼠标事件发给鼠标所在的组件，按下鼠标时该组件获得焦点
+     *   2. 组件没有处理时冒泡到 Screen，按注册顺序调用 handlers，某个返回 true 时停止
+     *   3. 仍然没有处理时执行默认行为：Tab/Shift+Tab 切换焦点
+     */
+    pub fn dispatch(&mut self, event: &Event, area: Rect, ctx: &mut Ctx) -> bool {
+        let areas = self.layout_areas(area);
+        let target = match event {
+            Event::Key(_) => self.focus,
+            Event::Mouse(m) => areas.iter().position(|a| a.contains(m.x, m.y)),
+        };
+        if let Some(i) = target {
+            if matches!(event, Event::Mouse(m) if m.kind == MouseKind::Down)
+                && self.components[i].focusable()
+            {
+                self.focus = Some(i);
+            }
+            if self.components[i].handle_event(event, areas[i], ctx) {
+                return true;
+            }
+        }
+        for handler in self.handlers.iter_mut() {
+            if handler(event, ctx) {
+                return true;
+            }
+        }
+        match event {
+            Event::Key(Key::Tab) => self.move_focus(true),
+            Event::Key(Key::BackTab) => self.move_focus(false),
+            _ => return false,
+        }
+        true
+    }
+
+    pub fn on_event(&mut self, handler: impl FnMut(&Event, &mut Ctx) -> bool + 'static) {
+        self.handlers.push(Box::new(handler));
+    }
+
+    pub fn snapshot(&self, width: u16, height: u16) -> Vec<String> {
+        let mut buf = Buffer::new(width, height);
+        self.render(&mut buf);
+        buf.to_lines()
+    }
+}
+
+/* ---------------------------- 事件循环 ---------------------------- */
+
+/*
+ * 事件循环：每处理一个事件重绘一次，输出到任意 Write，
+ * 测试时输出到 Vec<u8> 并用脚本代替真实的键盘和鼠标输入
+ */
+pub struct EventLoop<W: Write> {
+    pub screen: Screen,
+    pub ctx: Ctx,
+    term: Terminal<W>,
+    frame: Buffer,
+}
+
+impl<W: Write> EventLoop<W> {
+    pub fn new(screen: Screen, out: W, width: u16, height: u16) -> Self {
+        EventLoop {
+            screen,
+            ctx: Ctx::default(),
+            term: Terminal::new(out),
+            frame: Buffer::new(width, height),
+        }
+    }
+
+    // 重新绘制，返回输出的单元格数量
+    pub fn redraw(&mut self) -> io::Result<usize> {
+        self.frame = Buffer::new(self.frame.width, self.frame.height);
+        self.screen.render(&mut self.frame);
+        self.term.draw(&self.frame)
+    }
+
+    // 处理一个事件并重绘，返回 false 表示应该退出
+    pub fn step(&mut self, event: Event) -> io::Result<bool> {
+        let area = self.frame.area();
+        self.screen.dispatch(&event, area, &mut self.ctx);
+        self.redraw()?;
+        Ok(!self.ctx.quit)
+    }
+
+    // 依次处理所有事件，直到事件用完或者 ctx.quit 被设置
+    pub fn run(&mut self, events: impl IntoIterator<Item = Event>) -> io::Result<()> {
+        self.redraw()?;
+        for event in events {
+            if !self.step(event)? {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn snapshot(&self) -> Vec<String> {
+        self.frame.to_lines()
+    }
+
+    pub fn into_output(self) -> W {
+        self.term.into_inner()
+    }
+}
+
+/* ---------------------------- 示例 ---------------------------- */
+
+// 显示共享文本的标签，文本由其他组件的回调修改
+pub struct Label {
+    pub width: u16,
+    pub text: Rc<RefCell<String>>,
+}
+
+impl Draw for Label {
+    fn draw(&self) {
+        println!("{}", self.text.borrow());
+    }
+
+    fn size(&self) -> (u16, u16) {
+        (self.width, 1)
+    }
+
+    fn render(&self, buf: &mut Buffer, area: Rect, _focused: bool) {
+        buf.put_str(
+            area.x,
+            area.y,
+            &self.text.borrow(),
+            area.right(),
+            Style::PLAIN,
+        );
+    }
+}
+
+fn select_box(options: &[&str]) -> SelectBox {
+    SelectBox {
+        width: 14,
+        height: 5,
+        options: options.iter().map(|s| s.to_string()).collect(),
+        selected: 0,
+        on_change: None,
+    }
+}
+
+fn button(width: u32, label: &str) -> Button {
+    Button {
+        width,
+        height: 3,
+        label: label.to_string(),
+        on_click: None,
+    }
+}
+
+pub fn demo_screen() -> Screen {
+    let mut screen = Screen {
+        components: vec![
+            Box::new(select_box(&["Yes", "Maybe", "No", "不知道"])),
+            Box::new(button(8, "OK")),
+            Box::new(button(10, "取消")),
+        ],
+        layout: Layout::Columns,
+        ..Default::default()
+    };
+    screen.move_focus(true);
+    screen
+}
+
+/*
+ * 带回调的示例：选择框改变时更新状态，OK 显示选择结果，取消和 q/Esc 退出
+ * 回调之间通过 Rc<RefCell<String>> 共享状态，返回的 Rc 用来在外部查看
+ */
+pub fn demo_app() -> (Screen, Rc<RefCell<String>>) {
+    let status = Rc::new(RefCell::new(String::from("请选择")));
+    let choice = Rc::new(RefCell::new(String::from("Yes")));
+
+    let mut select = select_box(&["Yes", "Maybe", "No", "不知道"]);
+    let (s, c) = (Rc::clone(&status), Rc::clone(&choice));
+    select.on_change = Some(Box::new(move |_ctx, i, option| {
+        *s.borrow_mut() = format!("{}: {}", i, option);
+        *c.borrow_mut() = option.to_string();
+    }));
+
+    let mut ok = button(8, "OK");
+    let s = Rc::clone(&status);
+    ok.on_click = Some(Box::new(move |_ctx| {
+        *s.borrow_mut() = format!("选择了 {}", choice.borrow());
+    }));
+
+    let mut cancel = button(10, "取消");
+    cancel.on_click = Some(Box::new(|ctx| ctx.quit = true));
+
+    let mut screen = Screen {
+        components: vec![
+            Box::new(select),
+            Box::new(ok),
+            Box::new(cancel),
+            Box::new(Label {
+                width: 16,
+                text: Rc::clone(&status),
+            }),
+        ],
+        layout: Layout::Columns,
+        ..Default::default()
+    };
+    screen.on_event(|event, ctx| {
+        if matches!(event, Event::Key(Key::Esc | Key::Char('q'))) {
+            ctx.quit = true;
+            return true;
+        }
+        false
+    });
+    screen.move_focus(true);
+    (screen, status)
+}
+
+// 保存终端设置并切换到逐字符读取、不回显的模式
+fn stty(args: &[&str]) -> io::Result<String> {
+    let out = Command::new("stty")
+        .args(args)
+        .stdin(Stdio::inherit())
+        .output()?;
+    Ok(String::from_utf8_lossy(&out.stdout).trim().to_string())
+}
+
+const SCREEN_SIZE: (u16, u16) = (48, 6);
+
+// cargo run -- ui   Tab 切换焦点，上下键或鼠标选择，q 或 Esc 退出
+// cargo run -- ui --script "down tab enter"   按脚本操作后打印最终画面
+// 标准输入不是终端时，把输入当作按键序列，例如 printf '\t\x1b[B' | cargo run -- ui
+pub fn ui(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let (screen, _) = demo_app();
+    let (width, height) = SCREEN_SIZE;
+
+    let script = match args {
+        [flag, script] if flag == "--script" => Some(parse_script(script)?),
+        [] => None,
+        _ => return Err("usage: ui [--script \"tab down enter ...\"]".into()),
+    };
+    if script.is_some() || !io::stdin().is_terminal() {
+        let events = match script {
+            Some(events) => events,
+            None => {
+                let mut input = Vec::new();
+                io::stdin().read_to_end(&mut input)?;
+                parse_events(&input)
+            }
+        };
+        let mut lp = EventLoop::new(screen, io::sink(), width, height);
+        lp.run(events)?;
+        for line in lp.snapshot() {
+            println!("{}", line);
+        }
+        return Ok(());
+    }
+
+    let saved = stty(&["-g"])?;
+    stty(&["-icanon", "-echo", "min", "1"])?;
+    let mut out = io::stdout().lock();
+    // 开启鼠标上报(SGR 格式)，隐藏光标
+    write!(out, "\x1b[?1000h\x1b[?1006h\x1b[?25l")?;
+    let mut lp = EventLoop::new(screen, out, width, height);
+    let result = (|| -> io::Result<()> {
+        lp.redraw()?;
+        let mut bytes = [0u8; 64];
+        loop {
+            let n = io::stdin().read(&mut bytes)?;
+            if n == 0 {
+                return Ok(());
+            }
+            for event in parse_events(&bytes[..n]) {
+                if !lp.step(event)? {
+                    return Ok(());
+                }
+            }
+        }
+    })();
+    let mut out = lp.into_output();
+    write!(
+        out,
+        "\x1b[?1006l\x1b[?1000l\x1b[?25h\x1b[{};1H\r\n",
+        height + 1
+    )?;
+    stty(&[&saved])?;
+    Ok(result?)
+}
+
+// 回放一段脚本并打印界面，完整的快照比较见下方的测试
+pub fn widget_practice() {
+    let screen = demo_screen();
+    for line in screen.snapshot(40, 6) {
+        println!("{}", line);
+    }
+    let (screen, status) = demo_app();
+    let mut lp = EventLoop::new(screen, Vec::new(), SCREEN_SIZE.0, SCREEN_SIZE.1);
+    lp.run(parse_script("down down click:16,1").unwrap())
+        .unwrap();
+    for line in lp.snapshot() {
+        println!("{}", line);
+    }
+    assert_eq!(*status.borrow(), "选择了 No");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(screen: &mut Screen, key: Key) -> bool {
+        screen.dispatch(
+            &Event::Key(key),
+            Rect::new(0, 0, 40, 6),
+            &mut Ctx::default(),
+        )
+    }
+
+    #[test]
+    fn demo_screen_snapshot() {
+        let screen = demo_screen();
+        assert_eq!(screen.focus, Some(0));
+        assert_eq!(
+            screen.snapshot(40, 6),
+            [
+                "╔════════════╗┌──────┐┌────────┐",
+                "║> Yes       ║│  OK  ││  取消  │",
+                "║  Maybe     ║└──────┘└────────┘",
+                "║  No        ║",
+                "╚════════════╝",
+                "",
+            ]
+        );
+    }
+
+    #[test]
+    fn select_box_scrolls() {
+        // 选项超出可见行数时滚动
+        let mut screen = demo_screen();
+        press(&mut screen, Key::Up);
+        assert_eq!(
+            &screen.snapshot(40, 6)[1..4],
+            [
+                "║  Maybe     ║│  OK  ││  取消  │",
+                "║  No        ║└──────┘└────────┘",
+                "║> 不知道    ║"
+            ]
+        );
+    }
+
+    #[test]
+    fn tab_moves_focus() {
+        // Tab 移动焦点，焦点在最后一个组件之后回到第一个
+        let mut screen = demo_screen();
+        press(&mut screen, Key::Tab);
+        assert_eq!(screen.focus, Some(1));
+        let lines = screen.snapshot(40, 6);
+        assert_eq!(lines[0], "┌────────────┐╔══════╗┌────────┐");
+        let mut buf = Buffer::new(40, 6);
+        screen.render(&mut buf);
+        assert_eq!(buf.get(17, 1).style, Style::REVERSE);
+        assert_eq!(buf.get(3, 1).style, Style::BOLD);
+        press(&mut screen, Key::Tab);
+        press(&mut screen, Key::Tab);
+        assert_eq!(screen.focus, Some(0));
+        press(&mut screen, Key::BackTab);
+        assert_eq!(screen.focus, Some(2));
+        // 按钮不处理方向键
+        assert!(!press(&mut screen, Key::Down));
+    }
+
+    #[test]
+    fn rows_layout_clips() {
+        // 空间不够时裁剪，按行排列
+        let mut screen = demo_screen();
+        screen.layout = Layout::Rows;
+        assert_eq!(
+            screen.layout_areas(Rect::new(0, 0, 10, 6)),
+            [
+                Rect::new(0, 0, 10, 5),
+                Rect::new(0, 5, 8, 1),
+                Rect::new(0, 6, 10, 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn wide_chars_are_split() {
+        // 宽字符被覆盖一半时，另一半变为空格
+        let mut buf = Buffer::new(6, 1);
+        buf.put_str(0, 0, "中国人", 6, Style::PLAIN);
+        buf.put_str(1, 0, "a", 6, Style::PLAIN);
+        buf.put_str(4, 0, "b", 6, Style::PLAIN);
+        assert_eq!(buf.to_lines(), [" a国b"]);
+        assert_eq!(buf.put_str(0, 0, "文字", 3, Style::PLAIN), 2);
+    }
+
+    #[test]
+    fn terminal_draws_only_changes() {
+        // 差量重绘只输出变化的单元格
+        let mut screen = demo_screen();
+        let mut term = Terminal::new(Vec::new());
+        let mut buf = Buffer::new(40, 6);
+        screen.render(&mut buf);
+        assert_eq!(term.draw(&buf).unwrap(), 40 * 6 - 2);
+        assert_eq!(term.draw(&buf).unwrap(), 0);
+        press(&mut screen, Key::Down);
+        let mut next = Buffer::new(40, 6);
+        screen.render(&mut next);
+        // 两行选项各自的标记和样式发生变化
+        assert_eq!(term.draw(&next).unwrap(), 24);
+        let out = String::from_utf8(term.into_inner()).unwrap();
+        assert!(out.ends_with("\x1b[0m"));
+    }
+
+    #[test]
+    fn parse_input() {
+        assert_eq!(
+            parse_events(b"\t\x1b[A\x1b[Z\rq\x1b"),
+            [
+                Key::Tab,
+                Key::Up,
+                Key::BackTab,
+                Key::Enter,
+                Key::Char('q'),
+                Key::Esc
+            ]
+            .map(Event::Key)
+        );
+        assert_eq!(
+            parse_events(b"\x1b[<0;3;2M\x1b[<0;3;2m\x1b[<65;1;1M\x1b[<2;1;1M"),
+            [
+                (MouseKind::Down, 2, 1),
+                (MouseKind::Up, 2, 1),
+                (MouseKind::ScrollDown, 0, 0),
+            ]
+            .map(|(kind, x, y)| Event::Mouse(Mouse { kind, x, y }))
+        );
+        assert_eq!(parse_script("a click:1,2").unwrap().len(), 3);
+        assert!(parse_script("jump").is_err());
+    }
+
+    #[test]
+    fn callbacks_and_clicks() {
+        // 回调记录发生的事件
+        let (screen, status) = demo_app();
+        let mut lp = EventLoop::new(screen, Vec::new(), SCREEN_SIZE.0, SCREEN_SIZE.1);
+        lp.run(parse_script("down down").unwrap()).unwrap();
+        assert_eq!(*status.borrow(), "2: No");
+        assert_eq!(lp.snapshot()[0], "╔════════════╗┌──────┐┌────────┐2: No");
+
+        // 点击 OK 按钮：获得焦点并触发 on_click
+        lp.run(parse_script("click:16,1").unwrap()).unwrap();
+        assert_eq!(lp.screen.focus, Some(1));
+        assert_eq!(*status.borrow(), "选择了 No");
+        assert_eq!(
+            lp.snapshot(),
+            [
+                "┌────────────┐╔══════╗┌────────┐选择了 No",
+                "│  Yes       │║  OK  ║│  取消  │",
+                "│  Maybe     │╚══════╝└────────┘",
+                "│> No        │",
+                "└────────────┘",
+                "",
+            ]
+        );
+
+        // 点击选项、滚轮切换选项，点击边框只获得焦点
+        lp.run(parse_script("click:3,1 scroll-down:3,1 scroll-down:3,1 scroll-down:3,1").unwrap())
+            .unwrap();
+        assert_eq!(*status.borrow(), "3: 不知道");
+        lp.run(parse_script("click:0,0").unwrap()).unwrap();
+        assert_eq!(*status.borrow(), "3: 不知道");
+
+        // 没有被组件处理的按键冒泡到 Screen：q 退出，之后的事件不再处理
+        lp.run(parse_script("left q down").unwrap()).unwrap();
+        assert!(lp.ctx.quit);
+        assert_eq!(*status.borrow(), "3: 不知道");
+    }
+
+    #[test]
+    fn cancel_button_quits() {
+        let (screen, _) = demo_app();
+        let mut lp = EventLoop::new(screen, Vec::new(), SCREEN_SIZE.0, SCREEN_SIZE.1);
+        lp.run(parse_script("tab tab enter down").unwrap()).unwrap();
+        assert!(lp.ctx.quit);
+        assert_eq!(lp.screen.focus, Some(2));
+    }
+
+    #[test]
+    fn events_bubble_in_order() {
+        // 按注册顺序调用，返回 true 时停止传递
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let mut screen = demo_screen();
+        for (name, stop) in [("first", false), ("second", true), ("third", true)] {
+            let log = Rc::clone(&log);
+            screen.on_event(move |event, _ctx| {
+                log.borrow_mut().push(format!("{} {:?}", name, event));
+                stop
+            });
+        }
+        let area = Rect::new(0, 0, 40, 6);
+        let mut ctx = Ctx::default();
+        // 选择框处理了方向键，不会冒泡
+        assert!(screen.dispatch(&Event::Key(Key::Down), area, &mut ctx));
+        assert!(log.borrow().is_empty());
+        assert!(screen.dispatch(&Event::Key(Key::Char('x')), area, &mut ctx));
+        assert_eq!(
+            *log.borrow(),
+            ["first Key(Char('x'))", "second Key(Char('x'))"]
+        );
+        // Tab 被 second 拦截，焦点不变
+        screen.dispatch(&Event::Key(Key::Tab), area, &mut ctx);
+        assert_eq!(screen.focus, Some(0));
+    }
+
+    #[test]
+    fn replay_redraws_only_changes() {
+        // 脚本回放的输出只包含变化的部分
+        let (screen, _) = demo_app();
+        let mut lp = EventLoop::new(screen, Vec::new(), SCREEN_SIZE.0, SCREEN_SIZE.1);
+        lp.redraw().unwrap();
+        assert!(lp.step(Event::Key(Key::Char('z'))).unwrap());
+        assert_eq!(lp.redraw().unwrap(), 0);
+        let out = String::from_utf8(lp.into_output()).unwrap();
+        assert_eq!(out.matches("\x1b[2J").count(), 1);
+    }
+}